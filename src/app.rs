@@ -1,11 +1,28 @@
-use super::user_config::UserConfig;
+use super::user_config::{SequenceMatch, UserConfig};
+use crate::command_palette::CommandPalette;
+use crate::config::ClientConfig;
+use crate::discord;
+use crate::discovery::ConnectDevice;
+use crate::event::Key;
+use crate::hooks;
+use crate::library_finder::LibraryFinder;
+use crate::listenbrainz::{self, Listen, ListenType};
+use crate::lyrics::{LyricsQuery, LyricsStatus};
 use crate::network::IoEvent;
+use crate::notify;
+use crate::playlist_picker::PlaylistPicker;
+use crate::profile_menu::ProfileMenu;
+use crate::scrobbler::{ScrobbleType, Scrobbler};
+use crate::spotifyd;
+use crate::track_sort::{sort_playlists, sort_tracks, SortMenu, TrackSort};
+use crate::ui::util::create_artist_string;
+use crate::undo::UndoStack;
 use anyhow::anyhow;
 use rspotify::{
   model::{
     album::{FullAlbum, SavedAlbum, SimplifiedAlbum},
     artist::FullArtist,
-    audio::AudioAnalysis,
+    audio::{AudioAnalysis, AudioFeatures},
     context::CurrentlyPlaybackContext,
     device::DevicePayload,
     page::{CursorBasedPage, Page},
@@ -16,28 +33,47 @@ use rspotify::{
     user::PrivateUser,
     PlayingItem,
   },
-  senum::Country,
+  senum::{AlbumType, Country, TimeRange},
 };
 use std::str::FromStr;
 use std::sync::mpsc::Sender;
 use std::{
+  cell::RefCell,
   cmp::{max, min},
-  collections::HashSet,
-  time::{Instant, SystemTime},
+  collections::{HashMap, HashSet},
+  time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
+use tracing::{info, warn, Level};
 use tui::layout::Rect;
 
 use arboard::Clipboard;
 
-pub const LIBRARY_OPTIONS: [&str; 6] = [
+pub const LIBRARY_OPTIONS: [&str; 7] = [
   "Made For You",
   "Recently Played",
   "Liked Songs",
   "Albums",
   "Artists",
   "Podcasts",
+  "Top Items",
 ];
 
+// Where the selected track is in all tracks of the table's context, only a
+// page of them is loaded at a time
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TablePosition {
+  pub selected: usize,
+  // Of the first loaded track
+  pub offset: usize,
+  pub loaded: usize,
+  pub total: usize,
+}
+
+// How often the progress of the playback is redrawn without any other change
+const PROGRESS_REDRAW_INTERVAL: Duration = Duration::from_secs(1);
+// How long the toasts of e.g. `undo` stay
+const TOAST_DURATION: Duration = Duration::from_secs(3);
+
 const DEFAULT_ROUTE: Route = Route {
   id: RouteId::Home,
   active_block: ActiveBlock::Empty,
@@ -100,7 +136,7 @@ pub enum SearchResultBlock {
   Empty,
 }
 
-#[derive(PartialEq, Debug, Clone)]
+#[derive(PartialEq, Debug, Clone, Copy)]
 pub enum ArtistBlock {
   TopTracks,
   Albums,
@@ -117,6 +153,7 @@ pub enum DialogContext {
 #[derive(Clone, Copy, PartialEq, Debug)]
 pub enum ActiveBlock {
   Analysis,
+  Lyrics,
   PlayBar,
   AlbumTracks,
   AlbumList,
@@ -131,6 +168,7 @@ pub enum ActiveBlock {
   Podcasts,
   EpisodeTable,
   RecentlyPlayed,
+  TopItems,
   SearchResultBlock,
   SelectDevice,
   TrackTable,
@@ -143,6 +181,7 @@ pub enum ActiveBlock {
 #[derive(Clone, PartialEq, Debug)]
 pub enum RouteId {
   Analysis,
+  Lyrics,
   AlbumTracks,
   AlbumList,
   Artist,
@@ -150,6 +189,7 @@ pub enum RouteId {
   Error,
   Home,
   RecentlyPlayed,
+  TopItems,
   Search,
   SelectedDevice,
   TrackTable,
@@ -169,7 +209,7 @@ pub struct Route {
 }
 
 // Is it possible to compose enums?
-#[derive(PartialEq, Debug)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
 pub enum TrackTableContext {
   MyPlaylists,
   AlbumSearch,
@@ -186,6 +226,51 @@ pub enum AlbumTableContext {
   Full,
 }
 
+#[derive(Clone, PartialEq, Debug, Copy)]
+pub enum TopItemsKind {
+  Tracks,
+  Artists,
+}
+
+pub struct TopItems {
+  pub kind: TopItemsKind,
+  pub time_range: TimeRange,
+  pub tracks: Vec<FullTrack>,
+  pub artists: Vec<FullArtist>,
+  pub index: usize,
+}
+
+impl Default for TopItems {
+  fn default() -> Self {
+    TopItems {
+      kind: TopItemsKind::Tracks,
+      // What spotify uses if no time range is given
+      time_range: TimeRange::MediumTerm,
+      tracks: Vec::new(),
+      artists: Vec::new(),
+      index: 0,
+    }
+  }
+}
+
+impl TopItems {
+  pub fn len(&self) -> usize {
+    match self.kind {
+      TopItemsKind::Tracks => self.tracks.len(),
+      TopItemsKind::Artists => self.artists.len(),
+    }
+  }
+
+  // How spotify describes the time ranges
+  pub fn time_range_label(&self) -> &'static str {
+    match self.time_range {
+      TimeRange::ShortTerm => "last 4 weeks",
+      TimeRange::MediumTerm => "last 6 months",
+      TimeRange::LongTerm => "all time",
+    }
+  }
+}
+
 #[derive(Clone, PartialEq, Debug, Copy)]
 pub enum EpisodeTableContext {
   Simplified,
@@ -213,11 +298,204 @@ pub struct SearchResult {
   pub selected_block: SearchResultBlock,
 }
 
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ClickTarget {
+  // Clicking only focuses the block
+  Pane,
+  // Rows from `first_row` down, the first of them is item `offset`
+  Rows {
+    first_row: u16,
+    offset: usize,
+    count: usize,
+  },
+  // The progress bar of the playbar
+  Progress,
+}
+
+// Where a block was drawn, so mouse events can be mapped to it
+#[derive(Clone, Copy, Debug)]
+pub struct ClickArea {
+  pub rect: Rect,
+  pub block: ActiveBlock,
+  pub target: ClickTarget,
+}
+
 #[derive(Default)]
 pub struct TrackTable {
   pub tracks: Vec<FullTrack>,
   pub selected_index: usize,
   pub context: Option<TrackTableContext>,
+  // Uris of the marked tracks, so marks on other pages are kept
+  pub marked: Vec<String>,
+  // Where the visual selection started, it ends at the selected track
+  pub visual_anchor: Option<usize>,
+  // The tracks in the order spotify sent them, `tracks` is sorted from these
+  pub unsorted: Vec<FullTrack>,
+  // When each track was added by uri, for sorting by date added
+  pub added_at: HashMap<String, i64>,
+  pub sorts: HashMap<TrackTableContext, TrackSort>,
+  // Only tracks with this in their title, artists or album are shown
+  pub filter: String,
+  // Whether keys are typed into the filter
+  pub filtering: bool,
+  // The offset of the next page while it's being loaded for these tracks
+  pub prefetch: Option<u32>,
+}
+
+impl TrackTable {
+  fn in_visual_range(&self, index: usize) -> bool {
+    self.visual_anchor.is_some_and(|anchor| {
+      let (start, end) = if anchor <= self.selected_index {
+        (anchor, self.selected_index)
+      } else {
+        (self.selected_index, anchor)
+      };
+      (start..=end).contains(&index)
+    })
+  }
+
+  pub fn is_marked(&self, index: usize) -> bool {
+    self.in_visual_range(index)
+      || self
+        .tracks
+        .get(index)
+        .is_some_and(|t| self.marked.contains(&t.uri))
+  }
+
+  // The marked tracks of this table in its order, then the ones of other pages
+  pub fn selection(&self) -> Vec<String> {
+    let mut uris = self
+      .tracks
+      .iter()
+      .enumerate()
+      .filter(|(i, _)| self.is_marked(*i))
+      .map(|(_, t)| t.uri.clone())
+      .collect::<Vec<String>>();
+    for uri in &self.marked {
+      if !uris.contains(uri) {
+        uris.push(uri.clone());
+      }
+    }
+    uris
+  }
+
+  pub fn toggle_mark(&mut self) {
+    if let Some(track) = self.tracks.get(self.selected_index) {
+      match self.marked.iter().position(|uri| *uri == track.uri) {
+        Some(i) => {
+          self.marked.remove(i);
+        }
+        None => self.marked.push(track.uri.clone()),
+      }
+    }
+  }
+
+  // Ending the visual selection keeps its tracks marked
+  pub fn toggle_visual(&mut self) {
+    if self.visual_anchor.is_some() {
+      self.marked = self.selection();
+      self.visual_anchor = None;
+    } else if !self.tracks.is_empty() {
+      self.visual_anchor = Some(self.selected_index);
+    }
+  }
+
+  pub fn clear_marks(&mut self) {
+    self.marked.clear();
+    self.visual_anchor = None;
+  }
+
+  pub fn set_tracks(&mut self, tracks: Vec<FullTrack>, added_at: HashMap<String, i64>) {
+    self.unsorted = tracks;
+    self.added_at = added_at;
+    self.prefetch = None;
+    self.refresh();
+  }
+
+  // The next page of the same list, a sort can move the selected track but it
+  // stays selected
+  pub fn append_tracks(&mut self, tracks: Vec<FullTrack>, added_at: HashMap<String, i64>) {
+    let selected = self
+      .tracks
+      .get(self.selected_index)
+      .map(|track| track.uri.clone());
+    self.unsorted.extend(tracks);
+    self.added_at.extend(added_at);
+    self.prefetch = None;
+    self.refresh();
+    if let Some(index) =
+      selected.and_then(|uri| self.tracks.iter().position(|track| track.uri == uri))
+    {
+      self.selected_index = index;
+    }
+  }
+
+  // A newly opened list isn't filtered
+  pub fn set_context(&mut self, context: Option<TrackTableContext>) {
+    self.context = context;
+    self.filter.clear();
+    self.filtering = false;
+    self.refresh();
+  }
+
+  pub fn set_filter(&mut self, filter: String) {
+    self.filter = filter;
+    self.selected_index = 0;
+    self.refresh();
+  }
+
+  pub fn is_filtered(&self) -> bool {
+    !self.filter.is_empty()
+  }
+
+  // How the tracks of the current context are sorted
+  pub fn sort(&self) -> Option<TrackSort> {
+    self.context.and_then(|c| self.sorts.get(&c).copied())
+  }
+
+  // Keeps the hovered track selected
+  pub fn set_sort(&mut self, sort: Option<TrackSort>) {
+    let context = match self.context {
+      Some(context) => context,
+      None => return,
+    };
+    match sort {
+      Some(sort) => self.sorts.insert(context, sort),
+      None => self.sorts.remove(&context),
+    };
+    let hovered = self.tracks.get(self.selected_index).map(|t| t.uri.clone());
+    self.refresh();
+    if let Some(uri) = hovered {
+      self.selected_index = self.tracks.iter().position(|t| t.uri == uri).unwrap_or(0);
+    }
+  }
+
+  fn refresh(&mut self) {
+    let filter = self.filter.to_lowercase();
+    let matches = |track: &FullTrack| {
+      track.name.to_lowercase().contains(&filter)
+        || track.album.name.to_lowercase().contains(&filter)
+        || track
+          .artists
+          .iter()
+          .any(|a| a.name.to_lowercase().contains(&filter))
+    };
+    self.tracks = self
+      .unsorted
+      .iter()
+      .filter(|track| matches(track))
+      .cloned()
+      .collect();
+    if let Some(sort) = self.sort() {
+      sort_tracks(&mut self.tracks, sort, &self.added_at);
+    }
+  }
+}
+
+// The popup with a track's audio features, `None` while they're loading
+pub struct TrackFeatures {
+  pub name: String,
+  pub features: Option<AudioFeatures>,
 }
 
 #[derive(Clone)]
@@ -245,8 +523,11 @@ pub struct SelectedFullAlbum {
 
 #[derive(Clone)]
 pub struct Artist {
+  pub artist_id: String,
   pub artist_name: String,
   pub albums: Page<SimplifiedAlbum>,
+  // Which albums are listed, `None` for all of them
+  pub album_group: Option<AlbumType>,
   pub related_artists: Vec<FullArtist>,
   pub top_tracks: Vec<FullTrack>,
   pub selected_album_index: usize,
@@ -269,6 +550,8 @@ pub struct App {
   pub api_error: String,
   pub current_playback_context: Option<CurrentlyPlaybackContext>,
   pub devices: Option<DevicePayload>,
+  // Connect devices found on the network, listed after `devices`
+  pub discovered_devices: Vec<ConnectDevice>,
   // Inputs:
   // input is the string for input;
   // input_idx is the index of the cursor in terms of character;
@@ -279,6 +562,8 @@ pub struct App {
   pub input_idx: usize,
   pub input_cursor_position: u16,
   pub liked_song_ids_set: HashSet<String>,
+  // The tracks spotify was asked about, liked or not, they aren't checked again
+  pub liked_song_ids_checked: HashSet<String>,
   pub followed_artist_ids_set: HashSet<String>,
   pub saved_album_ids_set: HashSet<String>,
   pub saved_show_ids_set: HashSet<String>,
@@ -287,6 +572,9 @@ pub struct App {
   pub playlist_offset: u32,
   pub made_for_you_offset: u32,
   pub playlist_tracks: Option<Page<PlaylistTrack>>,
+  // The playlist of `playlist_tracks`, and the offset of the page after the
+  // loaded ones while it's being fetched in the background
+  pub playlist_tracks_id: Option<String>,
   pub made_for_you_tracks: Option<Page<PlaylistTrack>>,
   pub playlists: Option<Page<SimplifiedPlaylist>>,
   pub recently_played: SpotifyResultAndSelectedIndex<Option<CursorBasedPage<PlayHistory>>>,
@@ -307,6 +595,7 @@ pub struct App {
   pub episode_table_context: EpisodeTableContext,
   pub selected_show_simplified: Option<SelectedShow>,
   pub selected_show_full: Option<SelectedFullShow>,
+  pub top_items: TopItems,
   pub user: Option<PrivateUser>,
   pub album_list_index: usize,
   pub made_for_you_index: usize,
@@ -324,6 +613,58 @@ pub struct App {
   pub spotify_token_expiry: SystemTime,
   pub dialog: Option<String>,
   pub confirm: bool,
+  // The start of a multi-key binding and when it was pressed
+  pending_keys: Vec<Key>,
+  pending_keys_since: Option<Instant>,
+  // `seek_to_percent` was pressed and waits for its digit
+  pub seeking_to_percent: bool,
+  // Uri of the item the last notification was about
+  notified_uri: Option<String>,
+  // The playback the hooks last saw
+  hook_state: Option<hooks::PlaybackState>,
+  // The track that's submitted to ListenBrainz and Last.fm once it has been
+  // listened to
+  listen: Option<Listen>,
+  scrobbler: Option<Scrobbler>,
+  // Set while the spotifyd the TUI started hasn't shown up in the devices
+  pub device_claim: Option<spotifyd::DeviceClaim>,
+  // Discord Rich Presence, the thread is only started once it's first enabled
+  pub discord_presence: bool,
+  discord_tx: Option<Sender<Option<discord::Activity>>>,
+  discord_activity: Option<discord::Activity>,
+  // Lyrics by track uri, kept for the whole session
+  pub lyrics: HashMap<String, LyricsStatus>,
+  // How far plain lyrics were scrolled, and for which track
+  pub lyrics_scroll: u16,
+  lyrics_uri: Option<String>,
+  // Shown on top of every view while open
+  pub command_palette: Option<CommandPalette>,
+  // Browsing what's cached, without reaching spotify
+  pub offline: bool,
+  // A short message about what the app is doing in the background
+  pub toast: Option<String>,
+  // When a toast of the UI itself goes away again
+  pub toast_expires: Option<Instant>,
+  pub library_finder: Option<LibraryFinder>,
+  pub playlist_picker: Option<PlaylistPicker>,
+  pub sort_menu: Option<SortMenu>,
+  // How the sidebar playlists are sorted, `None` is the order spotify sent
+  pub playlist_sort: Option<TrackSort>,
+  // The playlists in that order, `playlists` is sorted from these
+  pub unsorted_playlists: Vec<SimplifiedPlaylist>,
+  // The account profile in use, `None` is the default one
+  pub profile: Option<String>,
+  pub profile_menu: Option<ProfileMenu>,
+  pub sidebar_collapsed: bool,
+  // Set by the network thread when it changed something, the UI is only
+  // drawn again on changes
+  pub needs_redraw: bool,
+  pub undo_stack: UndoStack,
+  pub track_features: Option<TrackFeatures>,
+  // Filled while drawing, later areas are drawn on top of earlier ones
+  pub click_areas: RefCell<Vec<ClickArea>>,
+  // Playlists tracks were added to in this session, the last one first
+  pub recent_playlist_ids: Vec<String>,
 }
 
 impl Default for App {
@@ -355,6 +696,7 @@ impl Default for App {
         selected_index: 0,
       },
       liked_song_ids_set: HashSet::new(),
+      liked_song_ids_checked: HashSet::new(),
       followed_artist_ids_set: HashSet::new(),
       saved_album_ids_set: HashSet::new(),
       saved_show_ids_set: HashSet::new(),
@@ -364,12 +706,14 @@ impl Default for App {
       api_error: String::new(),
       current_playback_context: None,
       devices: None,
+      discovered_devices: Vec::new(),
       input: vec![],
       input_idx: 0,
       input_cursor_position: 0,
       playlist_offset: 0,
       made_for_you_offset: 0,
       playlist_tracks: None,
+      playlist_tracks_id: None,
       made_for_you_tracks: None,
       playlists: None,
       recommended_tracks: vec![],
@@ -398,6 +742,7 @@ impl Default for App {
       episode_table_context: EpisodeTableContext::Full,
       selected_show_simplified: None,
       selected_show_full: None,
+      top_items: TopItems::default(),
       user: None,
       instant_since_last_current_playback_poll: Instant::now(),
       clipboard: Clipboard::new().ok(),
@@ -411,6 +756,37 @@ impl Default for App {
       spotify_token_expiry: SystemTime::now(),
       dialog: None,
       confirm: false,
+      pending_keys: Vec::new(),
+      pending_keys_since: None,
+      seeking_to_percent: false,
+      notified_uri: None,
+      hook_state: None,
+      listen: None,
+      scrobbler: None,
+      device_claim: None,
+      discord_presence: false,
+      discord_tx: None,
+      discord_activity: None,
+      lyrics: HashMap::new(),
+      lyrics_scroll: 0,
+      lyrics_uri: None,
+      command_palette: None,
+      offline: false,
+      toast: None,
+      toast_expires: None,
+      library_finder: None,
+      playlist_picker: None,
+      sort_menu: None,
+      playlist_sort: None,
+      unsorted_playlists: vec![],
+      profile: None,
+      profile_menu: None,
+      sidebar_collapsed: false,
+      needs_redraw: false,
+      undo_stack: UndoStack::default(),
+      track_features: None,
+      click_areas: RefCell::new(Vec::new()),
+      recent_playlist_ids: Vec::new(),
     }
   }
 }
@@ -423,12 +799,61 @@ impl App {
   ) -> App {
     App {
       io_tx: Some(io_tx),
+      discord_presence: user_config.behavior.enable_discord_presence,
+      sidebar_collapsed: user_config.layout.sidebar_collapsed.unwrap_or(false),
+      scrobbler: Scrobbler::from_config(&user_config.behavior),
       user_config,
       spotify_token_expiry,
       ..App::default()
     }
   }
 
+  // Returns the keys to handle after `key` was pressed: none while it could
+  // still be part of a sequence, the sequence once it's complete, or the
+  // pressed keys one by one if they don't make up a sequence after all
+  pub fn resolve_key(&mut self, key: Key) -> Vec<Key> {
+    if self.user_config.keys.sequences.is_empty() {
+      return vec![key];
+    }
+    self.pending_keys.push(key);
+    match self.user_config.keys.match_sequence(&self.pending_keys) {
+      SequenceMatch::Complete(sequence) => {
+        self.pending_keys.clear();
+        self.pending_keys_since = None;
+        vec![sequence]
+      }
+      SequenceMatch::Prefix => {
+        self.pending_keys_since = Some(Instant::now());
+        Vec::new()
+      }
+      SequenceMatch::None => {
+        self.pending_keys_since = None;
+        let mut keys = std::mem::take(&mut self.pending_keys);
+        let last = keys.pop().unwrap();
+        if keys.is_empty() {
+          vec![last]
+        } else {
+          // The last key might start another sequence
+          keys.extend(self.resolve_key(last));
+          keys
+        }
+      }
+    }
+  }
+
+  // Gives up on a started sequence after the timeout
+  pub fn take_expired_keys(&mut self) -> Vec<Key> {
+    let timeout =
+      Duration::from_millis(self.user_config.behavior.key_sequence_timeout_milliseconds);
+    match self.pending_keys_since {
+      Some(since) if since.elapsed() >= timeout => {
+        self.pending_keys_since = None;
+        std::mem::take(&mut self.pending_keys)
+      }
+      _ => Vec::new(),
+    }
+  }
+
   // Send a network event to the network thread
   pub fn dispatch(&mut self, action: IoEvent) {
     // `is_loading` will be set to false again after the async action has finished in network.rs
@@ -483,6 +908,40 @@ impl App {
 
   pub fn update_on_tick(&mut self) {
     self.poll_current_playback();
+    if self.user_config.behavior.show_notifications {
+      self.notify_on_item_change();
+    }
+    if !self.user_config.hooks.is_empty()
+      || self.user_config.behavior.listenbrainz_token.is_some()
+      || self.scrobbler.is_some()
+      || tracing::enabled!(Level::INFO)
+    {
+      self.run_hooks();
+    }
+    if let Some(claim) = &mut self.device_claim {
+      match claim.poll(Instant::now()) {
+        Some(true) => self.dispatch(IoEvent::ClaimSpotifydDevice),
+        Some(false) => {}
+        None => self.device_claim = None,
+      }
+    }
+    if self.discord_presence {
+      self.update_discord_presence();
+    }
+    if self.get_current_route().id == RouteId::Lyrics {
+      self.fetch_lyrics();
+    }
+    if self.get_current_route().id == RouteId::TrackTable {
+      self.prefetch_track_table();
+    }
+    if self
+      .toast_expires
+      .is_some_and(|expires| expires <= Instant::now())
+    {
+      self.toast = None;
+      self.toast_expires = None;
+      self.needs_redraw = true;
+    }
     if let Some(CurrentlyPlaybackContext {
       item: Some(item),
       progress_ms: Some(progress_ms),
@@ -514,6 +973,179 @@ impl App {
     }
   }
 
+  // While something plays, its progress moves on without any event. The
+  // visualizer and synced lyrics follow it every tick, the rest every second
+  pub fn progress_redraw_due(&self, since_draw: Duration) -> bool {
+    match &self.current_playback_context {
+      Some(CurrentlyPlaybackContext {
+        is_playing: true, ..
+      }) => match self.get_current_route().active_block {
+        ActiveBlock::Analysis | ActiveBlock::Lyrics => true,
+        _ => since_draw >= PROGRESS_REDRAW_INTERVAL,
+      },
+      _ => false,
+    }
+  }
+
+  // Uri, title and a description of the playing item
+  fn playing_item_summary(&self) -> Option<(&String, &String, String)> {
+    match &self.current_playback_context {
+      Some(CurrentlyPlaybackContext {
+        item: Some(PlayingItem::Track(track)),
+        ..
+      }) => Some((
+        &track.uri,
+        &track.name,
+        format!(
+          "{} — {}",
+          create_artist_string(&track.artists),
+          track.album.name
+        ),
+      )),
+      Some(CurrentlyPlaybackContext {
+        item: Some(PlayingItem::Episode(episode)),
+        ..
+      }) => Some((&episode.uri, &episode.name, episode.show.name.clone())),
+      _ => None,
+    }
+  }
+
+  // Only changes are announced, not the item that was playing on startup
+  fn notify_on_item_change(&mut self) {
+    let (uri, title, body) = match self.playing_item_summary() {
+      Some(summary) => summary,
+      None => return,
+    };
+    if self.notified_uri.as_ref() == Some(uri) {
+      return;
+    }
+    if self.notified_uri.is_some() {
+      notify::send(title, &body);
+    }
+    self.notified_uri = Some(uri.clone());
+  }
+
+  // Like notifications, the playback on startup doesn't run any hooks
+  fn run_hooks(&mut self) {
+    let context = match &self.current_playback_context {
+      Some(context) => context,
+      None => return,
+    };
+    let state = hooks::PlaybackState::from_context(context);
+    let mut track_changed = false;
+    if let Some(previous) = &self.hook_state {
+      for event in previous.events(&state) {
+        info!(event = ?event, "playback changed");
+        hooks::run(&self.user_config.hooks, event, context);
+        track_changed |= event == hooks::HookEvent::TrackChange;
+      }
+    }
+    self.hook_state = Some(state);
+    if self.user_config.behavior.listenbrainz_token.is_some() || self.scrobbler.is_some() {
+      self.submit_listens(track_changed);
+    }
+  }
+
+  // A track is submitted once the next one starts, if enough of it was played.
+  // The track playing on startup counts, but isn't announced as playing now
+  fn submit_listens(&mut self, track_changed: bool) {
+    let context = match &self.current_playback_context {
+      Some(context) => context,
+      None => return,
+    };
+    if let Some(listen) = self.listen.as_mut().filter(|_| !track_changed) {
+      listen.update(context);
+      return;
+    }
+    if let Some(previous) = self.listen.take().filter(Listen::is_complete) {
+      info!(uri = %previous.uri, "submitting listen");
+      self.submit_listen(&previous, ListenType::Single, ScrobbleType::Scrobble);
+    }
+    self.listen = Listen::from_context(context);
+    if let Some(listen) = self
+      .listen
+      .clone()
+      .filter(|_| track_changed && context.is_playing)
+    {
+      self.submit_listen(&listen, ListenType::PlayingNow, ScrobbleType::NowPlaying);
+    }
+  }
+
+  fn submit_listen(&self, listen: &Listen, listen_type: ListenType, scrobble_type: ScrobbleType) {
+    let behavior = &self.user_config.behavior;
+    if let Some(token) = &behavior.listenbrainz_token {
+      listenbrainz::submit(&behavior.listenbrainz_url, token, listen_type, listen);
+    }
+    if let Some(scrobbler) = &self.scrobbler {
+      scrobbler.submit(scrobble_type, listen);
+    }
+  }
+
+  // Nothing is shown while paused, and Discord is only told about changes
+  fn update_discord_presence(&mut self) {
+    let activity = match (&self.current_playback_context, self.playing_item_summary()) {
+      (
+        Some(CurrentlyPlaybackContext {
+          is_playing: true,
+          item: Some(item),
+          ..
+        }),
+        Some((_, title, body)),
+      ) => {
+        let duration_ms = match item {
+          PlayingItem::Track(track) => track.duration_ms,
+          PlayingItem::Episode(episode) => episode.duration_ms,
+        };
+        let now_ms = SystemTime::now()
+          .duration_since(UNIX_EPOCH)
+          .map(|d| d.as_millis() as u64)
+          .unwrap_or(0);
+        let start_ms = now_ms.saturating_sub(self.song_progress_ms as u64);
+        Some(discord::Activity {
+          details: title.clone(),
+          state: body,
+          start_ms,
+          end_ms: start_ms + u64::from(duration_ms),
+        })
+      }
+      _ => None,
+    };
+    let unchanged = match (&activity, &self.discord_activity) {
+      (Some(new), Some(old)) => new.same_as(old),
+      (None, None) => true,
+      _ => false,
+    };
+    // The first update clears whatever a previous session may have left
+    if !unchanged || self.discord_tx.is_none() {
+      self.send_discord_activity(activity);
+    }
+  }
+
+  fn send_discord_activity(&mut self, activity: Option<discord::Activity>) {
+    let client_id = match &self.user_config.behavior.discord_client_id {
+      Some(client_id) => client_id.clone(),
+      None => return,
+    };
+    let tx = self
+      .discord_tx
+      .get_or_insert_with(|| discord::spawn(client_id));
+    let _ = tx.send(activity.clone());
+    self.discord_activity = activity;
+  }
+
+  pub fn toggle_discord_presence(&mut self) {
+    if self.user_config.behavior.discord_client_id.is_none() {
+      self.handle_error(anyhow!(
+        "set `discord_client_id` in the behavior config to use Discord Rich Presence"
+      ));
+      return;
+    }
+    self.discord_presence = !self.discord_presence;
+    if !self.discord_presence && self.discord_tx.is_some() {
+      self.send_discord_activity(None);
+    }
+  }
+
   pub fn seek_forwards(&mut self) {
     if let Some(CurrentlyPlaybackContext {
       item: Some(item), ..
@@ -538,6 +1170,20 @@ impl App {
     }
   }
 
+  // Seeks to a point of the playing item, from 0.0 (the start) to 1.0
+  pub fn seek_to_ratio(&mut self, ratio: f64) {
+    if let Some(CurrentlyPlaybackContext {
+      item: Some(item), ..
+    }) = &self.current_playback_context
+    {
+      let duration_ms = match item {
+        PlayingItem::Track(track) => track.duration_ms,
+        PlayingItem::Episode(episode) => episode.duration_ms,
+      };
+      self.seek_ms = Some((f64::from(duration_ms) * ratio.clamp(0.0, 1.0)) as u128);
+    }
+  }
+
   pub fn seek_backwards(&mut self) {
     let old_progress = match self.seek_ms {
       Some(seek_ms) => seek_ms,
@@ -599,7 +1245,41 @@ impl App {
     }
   }
 
+  // The devices found on the network that spotify doesn't list (yet)
+  pub fn unlisted_devices(&self) -> Vec<&ConnectDevice> {
+    let listed = |name: &str| {
+      self
+        .devices
+        .as_ref()
+        .is_some_and(|p| p.devices.iter().any(|d| d.name == name))
+    };
+    self
+      .discovered_devices
+      .iter()
+      .filter(|d| !listed(&d.name))
+      .collect()
+  }
+
+  // What the device selection shows, the listed devices come first
+  pub fn device_names(&self) -> Vec<String> {
+    let behavior = &self.user_config.behavior;
+    let listed = self.devices.iter().flat_map(|p| &p.devices);
+    listed
+      .map(|d| match behavior.device_alias(&d.name, &d.id) {
+        Some(alias) => format!("{} ({})", alias, d.name),
+        None => d.name.clone(),
+      })
+      .chain(
+        self
+          .unlisted_devices()
+          .into_iter()
+          .map(|d| format!("{} (on the network)", d.name)),
+      )
+      .collect()
+  }
+
   pub fn handle_error(&mut self, e: anyhow::Error) {
+    warn!(error = %e, "error");
     self.push_navigation_stack(RouteId::Error, ActiveBlock::Error);
     self.api_error = e.to_string();
   }
@@ -627,6 +1307,7 @@ impl App {
   // The navigation_stack actually only controls the large block to the right of `library` and
   // `playlists`
   pub fn push_navigation_stack(&mut self, next_route_id: RouteId, next_active_block: ActiveBlock) {
+    info!(route = ?next_route_id, block = ?next_active_block, "navigate");
     if !self
       .navigation_stack
       .last()
@@ -642,6 +1323,7 @@ impl App {
   }
 
   pub fn pop_navigation_stack(&mut self) -> Option<Route> {
+    info!("navigate back");
     if self.navigation_stack.len() == 1 {
       None
     } else {
@@ -786,6 +1468,43 @@ impl App {
     }
   }
 
+  pub fn track_table_position(&self) -> TablePosition {
+    let selected = self.track_table.selected_index;
+    let loaded = self.track_table.tracks.len();
+    let page = match self.track_table.context {
+      // A filter only shows some of the loaded tracks
+      _ if self.track_table.is_filtered() => None,
+      Some(TrackTableContext::MyPlaylists) | Some(TrackTableContext::PlaylistSearch) => self
+        .playlist_tracks
+        .as_ref()
+        .map(|p| (self.playlist_offset, p.total)),
+      Some(TrackTableContext::MadeForYou) => self
+        .made_for_you_tracks
+        .as_ref()
+        .map(|p| (self.made_for_you_offset, p.total)),
+      Some(TrackTableContext::SavedTracks) => self
+        .library
+        .saved_tracks
+        .get_results(None)
+        .map(|p| (p.offset, p.total)),
+      _ => None,
+    };
+    let (offset, total) = page
+      .map(|(offset, total)| {
+        (
+          offset as usize,
+          (total as usize).max(offset as usize + loaded),
+        )
+      })
+      .unwrap_or((0, loaded));
+    TablePosition {
+      selected: offset + selected,
+      offset,
+      loaded,
+      total,
+    }
+  }
+
   pub fn get_current_user_saved_tracks_next(&mut self) {
     // Before fetching the next tracks, check if we have already fetched them
     match self
@@ -800,7 +1519,8 @@ impl App {
       }
       None => {
         if let Some(saved_tracks) = &self.library.saved_tracks.get_results(None) {
-          let offset = Some(saved_tracks.offset + saved_tracks.limit);
+          // After all the tracks that were loaded while scrolling
+          let offset = Some(saved_tracks.offset + saved_tracks.items.len() as u32);
           self.dispatch(IoEvent::GetCurrentSavedTracks(offset));
         }
       }
@@ -904,6 +1624,90 @@ impl App {
     }
   }
 
+  // The album of the hovered track or album, or of the playing track elsewhere
+  // (the recently played tracks don't come with their album)
+  fn selected_album_id(&self) -> Option<String> {
+    let route = self.get_current_route();
+    let selected = match route.active_block {
+      ActiveBlock::TrackTable => self
+        .track_table
+        .tracks
+        .get(self.track_table.selected_index)
+        .and_then(|t| t.album.id.clone()),
+      ActiveBlock::AlbumTracks => match self.album_table_context {
+        AlbumTableContext::Full => self
+          .selected_album_full
+          .as_ref()
+          .map(|selected| selected.album.id.clone()),
+        AlbumTableContext::Simplified => self
+          .selected_album_simplified
+          .as_ref()
+          .and_then(|selected| selected.album.id.clone()),
+      },
+      ActiveBlock::AlbumList => self
+        .library
+        .saved_albums
+        .get_results(None)
+        .and_then(|albums| albums.items.get(self.album_list_index))
+        .map(|saved| saved.album.id.clone()),
+      ActiveBlock::TopItems if self.top_items.kind == TopItemsKind::Tracks => self
+        .top_items
+        .tracks
+        .get(self.top_items.index)
+        .and_then(|t| t.album.id.clone()),
+      ActiveBlock::ArtistBlock => {
+        self
+          .artist
+          .as_ref()
+          .and_then(|artist| match artist.artist_selected_block {
+            ArtistBlock::TopTracks => artist
+              .top_tracks
+              .get(artist.selected_top_track_index)
+              .and_then(|t| t.album.id.clone()),
+            ArtistBlock::Albums => artist
+              .albums
+              .items
+              .get(artist.selected_album_index)
+              .and_then(|a| a.id.clone()),
+            _ => None,
+          })
+      }
+      ActiveBlock::SearchResultBlock => match self.search_results.selected_block {
+        SearchResultBlock::SongSearch => {
+          let tracks = self.search_results.tracks.as_ref();
+          self
+            .search_results
+            .selected_tracks_index
+            .and_then(|index| tracks.and_then(|t| t.items.get(index)))
+            .and_then(|t| t.album.id.clone())
+        }
+        SearchResultBlock::AlbumSearch => {
+          let albums = self.search_results.albums.as_ref();
+          self
+            .search_results
+            .selected_album_index
+            .and_then(|index| albums.and_then(|a| a.items.get(index)))
+            .and_then(|a| a.id.clone())
+        }
+        _ => None,
+      },
+      _ => None,
+    };
+    selected.or_else(|| match &self.current_playback_context {
+      Some(CurrentlyPlaybackContext {
+        item: Some(PlayingItem::Track(track)),
+        ..
+      }) => track.album.id.clone(),
+      _ => None,
+    })
+  }
+
+  pub fn toggle_save_album(&mut self) {
+    if let Some(album_id) = self.selected_album_id() {
+      self.dispatch(IoEvent::ToggleSaveAlbum(album_id));
+    }
+  }
+
   pub fn get_current_user_saved_shows_next(&mut self) {
     match self
       .library
@@ -950,55 +1754,50 @@ impl App {
     }
   }
 
-  pub fn user_unfollow_artists(&mut self, block: ActiveBlock) {
+  // The artist hovered in `block`, in the artist view it's the related artist
+  // when that list is selected and the artist of the view otherwise
+  fn selected_artist_id(&self, block: ActiveBlock) -> Option<String> {
     match block {
       ActiveBlock::SearchResultBlock => {
-        if let Some(artists) = &self.search_results.artists {
-          if let Some(selected_index) = self.search_results.selected_artists_index {
-            let selected_artist: &FullArtist = &artists.items[selected_index];
-            let artist_id = selected_artist.id.clone();
-            self.dispatch(IoEvent::UserUnfollowArtists(vec![artist_id]));
-          }
-        }
+        let artists = self.search_results.artists.as_ref()?;
+        let selected_index = self.search_results.selected_artists_index?;
+        artists.items.get(selected_index).map(|a| a.id.clone())
       }
       ActiveBlock::AlbumList => {
-        if let Some(artists) = self.library.saved_artists.get_results(None) {
-          if let Some(selected_artist) = artists.items.get(self.artists_list_index) {
-            let artist_id = selected_artist.id.clone();
-            self.dispatch(IoEvent::UserUnfollowArtists(vec![artist_id]));
-          }
-        }
+        let artists = self.library.saved_artists.get_results(None)?;
+        artists
+          .items
+          .get(self.artists_list_index)
+          .map(|a| a.id.clone())
       }
       ActiveBlock::ArtistBlock => {
-        if let Some(artist) = &self.artist {
-          let selected_artis = &artist.related_artists[artist.selected_related_artist_index];
-          let artist_id = selected_artis.id.clone();
-          self.dispatch(IoEvent::UserUnfollowArtists(vec![artist_id]));
+        let artist = self.artist.as_ref()?;
+        match artist.artist_selected_block {
+          ArtistBlock::RelatedArtists => artist
+            .related_artists
+            .get(artist.selected_related_artist_index)
+            .map(|a| a.id.clone()),
+          _ => Some(artist.artist_id.clone()),
         }
       }
-      _ => (),
-    };
+      ActiveBlock::TopItems if self.top_items.kind == TopItemsKind::Artists => self
+        .top_items
+        .artists
+        .get(self.top_items.index)
+        .map(|a| a.id.clone()),
+      _ => None,
+    }
+  }
+
+  pub fn user_unfollow_artists(&mut self, block: ActiveBlock) {
+    if let Some(artist_id) = self.selected_artist_id(block) {
+      self.dispatch(IoEvent::UserUnfollowArtists(vec![artist_id]));
+    }
   }
 
   pub fn user_follow_artists(&mut self, block: ActiveBlock) {
-    match block {
-      ActiveBlock::SearchResultBlock => {
-        if let Some(artists) = &self.search_results.artists {
-          if let Some(selected_index) = self.search_results.selected_artists_index {
-            let selected_artist: &FullArtist = &artists.items[selected_index];
-            let artist_id = selected_artist.id.clone();
-            self.dispatch(IoEvent::UserFollowArtists(vec![artist_id]));
-          }
-        }
-      }
-      ActiveBlock::ArtistBlock => {
-        if let Some(artist) = &self.artist {
-          let selected_artis = &artist.related_artists[artist.selected_related_artist_index];
-          let artist_id = selected_artis.id.clone();
-          self.dispatch(IoEvent::UserFollowArtists(vec![artist_id]));
-        }
-      }
-      _ => (),
+    if let Some(artist_id) = self.selected_artist_id(block) {
+      self.dispatch(IoEvent::UserFollowArtists(vec![artist_id]));
     }
   }
 
@@ -1021,6 +1820,103 @@ impl App {
     }
   }
 
+  // Moves the hovered track of the open playlist one position, the table is
+  // updated right away and the network thread tells spotify
+  pub fn move_playlist_track(&mut self, down: bool) {
+    // The table positions aren't the playlist ones while sorted or filtered
+    if self.track_table.sort().is_some() || self.track_table.is_filtered() {
+      return;
+    }
+    let (playlists, selected_index, user, page) = match (
+      &self.playlists,
+      self.selected_playlist_index,
+      &self.user,
+      &mut self.playlist_tracks,
+    ) {
+      (Some(playlists), Some(selected_index), Some(user), Some(page)) => {
+        (playlists, selected_index, user, page)
+      }
+      _ => return,
+    };
+    let playlist = match playlists.items.get(selected_index) {
+      Some(playlist) if playlist.collaborative || playlist.owner.id == user.id => playlist,
+      _ => return,
+    };
+    // Unavailable tracks aren't in the table but still take a position
+    let from = match page
+      .items
+      .iter()
+      .enumerate()
+      .filter(|(_, item)| item.track.is_some())
+      .nth(self.track_table.selected_index)
+    {
+      Some((from, _)) => from,
+      None => return,
+    };
+    let to = if down {
+      from + 1
+    } else {
+      match from.checked_sub(1) {
+        Some(to) => to,
+        None => return,
+      }
+    };
+    // Tracks of other pages can't be swapped with
+    if to >= page.items.len() {
+      return;
+    }
+
+    page.items.swap(from, to);
+    let position = self.playlist_offset + from as u32;
+    // Moving down inserts before the track after the next one
+    let insert_before = if down { position + 2 } else { position - 1 };
+    let playlist_id = playlist.id.clone();
+    let added_at = std::mem::take(&mut self.track_table.added_at);
+    self.track_table.set_tracks(
+      page
+        .items
+        .iter()
+        .filter_map(|item| item.track.clone())
+        .collect(),
+      added_at,
+    );
+    self.track_table.selected_index = page.items[..to]
+      .iter()
+      .filter(|item| item.track.is_some())
+      .count();
+    self.dispatch(IoEvent::ReorderPlaylistTracks(
+      playlist_id,
+      position,
+      insert_before,
+    ));
+  }
+
+  // Shown for a few seconds
+  pub fn show_toast(&mut self, toast: String) {
+    self.toast = Some(toast);
+    self.toast_expires = Some(Instant::now() + TOAST_DURATION);
+  }
+
+  // Takes back the latest unlike, unfollow or deletion
+  pub fn undo(&mut self) {
+    match self.undo_stack.pop() {
+      Some(action) => {
+        self.dispatch(action.reverse());
+        self.show_toast(action.description());
+      }
+      None => self.show_toast("Nothing to undo".to_string()),
+    }
+  }
+
+  // The owner and name of a playlist in the sidebar or the search results
+  pub fn find_playlist(&self, playlist_id: &str) -> Option<(String, String)> {
+    let listed = self.playlists.iter().chain(&self.search_results.playlists);
+    listed
+      .flat_map(|page| &page.items)
+      .find(|playlist| playlist.id == playlist_id)
+      .map(|playlist| (playlist.owner.id.clone(), playlist.name.clone()))
+  }
+
   pub fn user_unfollow_playlist(&mut self) {
     if let (Some(playlists), Some(selected_index), Some(user)) =
       (&self.playlists, self.selected_playlist_index, &self.user)
@@ -1110,6 +2006,50 @@ impl App {
     }
   }
 
+  pub fn get_top_items(&mut self) {
+    let time_range = self.top_items.time_range;
+    self.dispatch(match self.top_items.kind {
+      TopItemsKind::Tracks => IoEvent::GetTopTracks(time_range),
+      TopItemsKind::Artists => IoEvent::GetTopArtists(time_range),
+    });
+  }
+
+  pub fn toggle_top_items_kind(&mut self) {
+    self.top_items.kind = match self.top_items.kind {
+      TopItemsKind::Tracks => TopItemsKind::Artists,
+      TopItemsKind::Artists => TopItemsKind::Tracks,
+    };
+    self.top_items.index = 0;
+    self.get_top_items();
+  }
+
+  pub fn cycle_top_items_time_range(&mut self) {
+    self.top_items.time_range = match self.top_items.time_range {
+      TimeRange::ShortTerm => TimeRange::MediumTerm,
+      TimeRange::MediumTerm => TimeRange::LongTerm,
+      TimeRange::LongTerm => TimeRange::ShortTerm,
+    };
+    self.top_items.index = 0;
+    self.get_top_items();
+  }
+
+  // Every group comes after all of them, then it starts over
+  pub fn cycle_artist_album_group(&mut self) {
+    let (artist_id, group) = match &self.artist {
+      Some(artist) => (artist.artist_id.clone(), artist.album_group),
+      None => return,
+    };
+    let group = match group {
+      None => Some(AlbumType::Album),
+      Some(AlbumType::Album) => Some(AlbumType::Single),
+      Some(AlbumType::Single) => Some(AlbumType::Compilation),
+      Some(AlbumType::Compilation) => Some(AlbumType::AppearsOn),
+      Some(AlbumType::AppearsOn) => None,
+    };
+    let country = self.get_user_country();
+    self.dispatch(IoEvent::GetArtistAlbums(artist_id, group, country));
+  }
+
   pub fn get_made_for_you(&mut self) {
     // TODO: replace searches when relevant endpoint is added
     const DISCOVER_WEEKLY: &str = "Discover Weekly";
@@ -1137,6 +2077,225 @@ impl App {
     ));
   }
 
+  pub fn toggle_save_playing_item(&mut self) {
+    if let Some(CurrentlyPlaybackContext {
+      item: Some(item), ..
+    }) = self.current_playback_context.to_owned()
+    {
+      match item {
+        PlayingItem::Track(track) => {
+          if let Some(track_id) = track.id {
+            self.dispatch(IoEvent::ToggleSaveTrack(track_id));
+          }
+        }
+        PlayingItem::Episode(episode) => {
+          self.dispatch(IoEvent::ToggleSaveTrack(episode.id));
+        }
+      };
+    };
+  }
+
+  // Liked songs aren't fetched here since that would also replace the track table
+  pub fn open_library_finder(&mut self) {
+    if self.library.saved_albums.pages.is_empty() {
+      self.dispatch(IoEvent::GetCurrentUserSavedAlbums(None));
+    }
+    if self.library.saved_artists.pages.is_empty() {
+      self.dispatch(IoEvent::GetFollowedArtists(None));
+    }
+    self.library_finder = Some(LibraryFinder::default());
+  }
+
+  pub fn open_profile_menu(&mut self) {
+    self.profile_menu = Some(ProfileMenu::new(ClientConfig::list_profiles()));
+  }
+
+  // Starts over with nothing of the previous account, so none of its library or
+  // playback shows up for the new one. Only what belongs to the terminal stays
+  pub fn switch_profile(&mut self, profile: Option<String>, spotify_token_expiry: SystemTime) {
+    let previous = std::mem::take(self);
+    *self = App {
+      io_tx: previous.io_tx,
+      user_config: previous.user_config,
+      spotify_token_expiry,
+      profile,
+      size: previous.size,
+      large_search_limit: previous.large_search_limit,
+      small_search_limit: previous.small_search_limit,
+      help_docs_size: previous.help_docs_size,
+      help_menu_max_lines: previous.help_menu_max_lines,
+      discord_presence: previous.discord_presence,
+      discord_tx: previous.discord_tx,
+      sidebar_collapsed: previous.sidebar_collapsed,
+      ..App::default()
+    };
+    self.dispatch(IoEvent::GetPlaylists);
+    self.dispatch(IoEvent::GetUser);
+    self.dispatch(IoEvent::GetCurrentPlayback);
+  }
+
+  pub fn open_playlist_picker(&mut self, uris: Vec<String>) {
+    if uris.is_empty() {
+      return;
+    }
+    if self.playlists.is_none() {
+      self.dispatch(IoEvent::GetPlaylists);
+    }
+    self.playlist_picker = Some(PlaylistPicker::new(uris));
+  }
+
+  pub fn show_track_features(&mut self, uri: &str, name: String) {
+    if let Some(id) = uri.strip_prefix("spotify:track:") {
+      self.dispatch(IoEvent::GetAudioFeatures(id.to_string()));
+      self.track_features = Some(TrackFeatures {
+        name,
+        features: None,
+      });
+    }
+  }
+
+  pub fn set_playlists(&mut self, playlists: Page<SimplifiedPlaylist>) {
+    let ids = self.selected_playlist_ids();
+    self.unsorted_playlists = playlists.items.clone();
+    self.playlists = Some(playlists);
+    self.refresh_playlists(ids);
+  }
+
+  pub fn set_playlist_sort(&mut self, sort: Option<TrackSort>) {
+    let ids = self.selected_playlist_ids();
+    self.playlist_sort = sort;
+    self.refresh_playlists(ids);
+  }
+
+  // The ids of the selected and the open playlist
+  fn selected_playlist_ids(&self) -> (Option<String>, Option<String>) {
+    let id_at = |index: Option<usize>| {
+      let playlists = self.playlists.as_ref()?;
+      Some(playlists.items.get(index?)?.id.clone())
+    };
+    (
+      id_at(self.selected_playlist_index),
+      id_at(self.active_playlist_index),
+    )
+  }
+
+  // The selected and the open playlist stay the same when the order changes
+  fn refresh_playlists(&mut self, (selected, active): (Option<String>, Option<String>)) {
+    let playlists = match &mut self.playlists {
+      Some(playlists) => playlists,
+      None => return,
+    };
+    playlists.items = self.unsorted_playlists.clone();
+    if let Some(sort) = self.playlist_sort {
+      sort_playlists(&mut playlists.items, sort);
+    }
+    let index_of =
+      |id: Option<String>| id.and_then(|id| playlists.items.iter().position(|p| p.id == id));
+    if let Some(index) = index_of(selected) {
+      self.selected_playlist_index = Some(index);
+    }
+    if let Some(index) = index_of(active) {
+      self.active_playlist_index = Some(index);
+    }
+  }
+
+  pub fn show_lyrics(&mut self) {
+    if self.get_current_route().id != RouteId::Lyrics {
+      self.push_navigation_stack(RouteId::Lyrics, ActiveBlock::Lyrics);
+      self.fetch_lyrics();
+    }
+  }
+
+  // Asks the provider once per track, the view follows track changes
+  // Loads the next page of a playlist or the liked songs while the selection
+  // is still a page away from the end of the loaded tracks, so scrolling down
+  // doesn't stop
+  fn prefetch_track_table(&mut self) {
+    // A filter hides how close the end is
+    if self.track_table.is_filtered() || self.track_table.prefetch.is_some() {
+      return;
+    }
+    let next = match self.track_table.context {
+      Some(TrackTableContext::MyPlaylists) | Some(TrackTableContext::PlaylistSearch) => {
+        match (&self.playlist_tracks_id, &self.playlist_tracks) {
+          (Some(playlist_id), Some(page)) => Some((
+            Some(playlist_id.clone()),
+            self.playlist_offset + page.items.len() as u32,
+            page.total,
+          )),
+          _ => None,
+        }
+      }
+      Some(TrackTableContext::MadeForYou) => {
+        let playlist_id = self
+          .library
+          .made_for_you_playlists
+          .get_results(Some(0))
+          .and_then(|playlists| playlists.items.get(self.made_for_you_index))
+          .map(|playlist| playlist.id.clone());
+        match (playlist_id, &self.made_for_you_tracks) {
+          (Some(playlist_id), Some(page)) => Some((
+            Some(playlist_id),
+            self.made_for_you_offset + page.items.len() as u32,
+            page.total,
+          )),
+          _ => None,
+        }
+      }
+      // Only the last page grows, the ones after it were loaded on their own
+      Some(TrackTableContext::SavedTracks)
+        if self.library.saved_tracks.index + 1 == self.library.saved_tracks.pages.len() =>
+      {
+        self
+          .library
+          .saved_tracks
+          .get_results(None)
+          .map(|page| (None, page.offset + page.items.len() as u32, page.total))
+      }
+      _ => None,
+    };
+    let (playlist_id, next_offset, total) = match next {
+      Some(next) => next,
+      None => return,
+    };
+    if next_offset >= total {
+      return;
+    }
+    let remaining = self
+      .track_table
+      .tracks
+      .len()
+      .saturating_sub(self.track_table.selected_index);
+    if remaining <= self.large_search_limit as usize {
+      self.track_table.prefetch = Some(next_offset);
+      match playlist_id {
+        Some(playlist_id) => self.dispatch(IoEvent::AppendPlaylistTracks(playlist_id, next_offset)),
+        None => self.dispatch(IoEvent::AppendSavedTracks(next_offset)),
+      }
+    }
+  }
+
+  fn fetch_lyrics(&mut self) {
+    let query = match &self.current_playback_context {
+      Some(CurrentlyPlaybackContext {
+        item: Some(PlayingItem::Track(track)),
+        ..
+      }) => LyricsQuery::from_track(track),
+      _ => return,
+    };
+    if self.lyrics_uri.as_ref() != Some(&query.uri) {
+      self.lyrics_scroll = 0;
+      self.lyrics_uri = Some(query.uri.clone());
+    }
+    if !self.lyrics.contains_key(&query.uri) {
+      self.lyrics.insert(query.uri.clone(), LyricsStatus::Loading);
+      self.dispatch(IoEvent::GetLyrics(
+        self.user_config.behavior.lyrics_provider,
+        query,
+      ));
+    }
+  }
+
   pub fn get_audio_analysis(&mut self) {
     if let Some(CurrentlyPlaybackContext {
       item: Some(item), ..
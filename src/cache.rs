@@ -0,0 +1,176 @@
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+  fs,
+  path::PathBuf,
+  time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+// How long a response stays fresh depends on how often it changes
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CacheKind {
+  // The user's playlists, liked songs, saved albums and followed artists
+  Library,
+  // Albums and artists, which hardly ever change
+  Catalog,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Entry {
+  stored_at: u64,
+  value: Value,
+}
+
+// Responses stored as one json file per key, a ttl of 0 turns a kind off
+#[derive(Clone, Debug)]
+pub struct ResponseCache {
+  dir: PathBuf,
+  library_ttl: Duration,
+  catalog_ttl: Duration,
+}
+
+impl ResponseCache {
+  pub fn new(dir: PathBuf, library_ttl_seconds: u64, catalog_ttl_seconds: u64) -> Self {
+    ResponseCache {
+      dir,
+      library_ttl: Duration::from_secs(library_ttl_seconds),
+      catalog_ttl: Duration::from_secs(catalog_ttl_seconds),
+    }
+  }
+
+  fn ttl(&self, kind: CacheKind) -> Duration {
+    match kind {
+      CacheKind::Library => self.library_ttl,
+      CacheKind::Catalog => self.catalog_ttl,
+    }
+  }
+
+  // Keys are made of ids and numbers, anything else is replaced to keep them
+  // valid file names
+  fn path(&self, key: &str) -> PathBuf {
+    let name = key
+      .chars()
+      .map(|c| match c {
+        'a'..='z' | 'A'..='Z' | '0'..='9' | '-' | '_' => c,
+        _ => '_',
+      })
+      .collect::<String>();
+    self.dir.join(format!("{}.json", name))
+  }
+
+  fn now() -> u64 {
+    SystemTime::now()
+      .duration_since(UNIX_EPOCH)
+      .map(|d| d.as_secs())
+      .unwrap_or(0)
+  }
+
+  fn read(&self, key: &str) -> Option<Entry> {
+    serde_json::from_slice(&fs::read(self.path(key)).ok()?).ok()
+  }
+
+  // Missing, expired and unreadable entries all mean asking the API again
+  pub fn get<T: DeserializeOwned>(&self, kind: CacheKind, key: &str) -> Option<T> {
+    let ttl = self.ttl(kind).as_secs();
+    if ttl == 0 {
+      return None;
+    }
+    let entry = self.read(key)?;
+    if Self::now().saturating_sub(entry.stored_at) >= ttl {
+      return None;
+    }
+    serde_json::from_value(entry.value).ok()
+  }
+
+  // However old the entry is, for when the API can't be reached
+  pub fn get_any<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+    serde_json::from_value(self.read(key)?.value).ok()
+  }
+
+  // A cache that can't be written only costs the next start its speed
+  pub fn put<T: Serialize>(&self, kind: CacheKind, key: &str, value: &T) {
+    if self.ttl(kind).as_secs() == 0 {
+      return;
+    }
+    let entry = match serde_json::to_value(value) {
+      Ok(value) => Entry {
+        stored_at: Self::now(),
+        value,
+      },
+      Err(_) => return,
+    };
+    if fs::create_dir_all(&self.dir).is_ok() {
+      if let Ok(json) = serde_json::to_vec(&entry) {
+        let _ = fs::write(self.path(key), json);
+      }
+    }
+  }
+
+  // Drops every entry whose key starts with `prefix`, after the user changed
+  // what it holds
+  pub fn invalidate(&self, prefix: &str) {
+    let prefix = self.path(prefix);
+    let prefix = prefix
+      .file_stem()
+      .map(|s| s.to_string_lossy().to_string())
+      .unwrap_or_default();
+    if let Ok(files) = fs::read_dir(&self.dir) {
+      for file in files.flatten() {
+        if file.file_name().to_string_lossy().starts_with(&prefix) {
+          let _ = fs::remove_file(file.path());
+        }
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn cache_test() {
+    let dir = std::env::temp_dir().join(format!("spt-cache-test-{}", std::process::id()));
+    let cache = ResponseCache::new(dir.clone(), 60, 0);
+
+    assert_eq!(
+      cache.get::<Vec<u32>>(CacheKind::Library, "saved_tracks-0"),
+      None
+    );
+    cache.put(CacheKind::Library, "saved_tracks-0", &vec![1, 2]);
+    cache.put(CacheKind::Library, "saved_tracks-20", &vec![3]);
+    cache.put(CacheKind::Library, "playlists", &vec![4]);
+    assert_eq!(
+      cache.get::<Vec<u32>>(CacheKind::Library, "saved_tracks-0"),
+      Some(vec![1, 2])
+    );
+
+    // Catalog responses have a ttl of 0 here
+    cache.put(CacheKind::Catalog, "album-1", &vec![5]);
+    assert_eq!(cache.get::<Vec<u32>>(CacheKind::Catalog, "album-1"), None);
+
+    cache.invalidate("saved_tracks");
+    assert_eq!(
+      cache.get::<Vec<u32>>(CacheKind::Library, "saved_tracks-0"),
+      None
+    );
+    assert_eq!(
+      cache.get::<Vec<u32>>(CacheKind::Library, "saved_tracks-20"),
+      None
+    );
+    assert_eq!(
+      cache.get::<Vec<u32>>(CacheKind::Library, "playlists"),
+      Some(vec![4])
+    );
+
+    let expired = ResponseCache::new(dir.clone(), 1, 1);
+    fs::write(expired.path("playlists"), r#"{"stored_at":0,"value":[4]}"#).unwrap();
+    assert_eq!(
+      expired.get::<Vec<u32>>(CacheKind::Library, "playlists"),
+      None
+    );
+    assert_eq!(expired.get_any::<Vec<u32>>("playlists"), Some(vec![4]));
+
+    fs::remove_dir_all(dir).unwrap();
+  }
+}
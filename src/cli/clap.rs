@@ -1,4 +1,4 @@
-use clap::{App, Arg, ArgGroup, SubCommand};
+use clap::{App, AppSettings, Arg, ArgGroup, SubCommand};
 
 fn device_arg() -> Arg<'static, 'static> {
   Arg::with_name("device")
@@ -7,6 +7,26 @@ fn device_arg() -> Arg<'static, 'static> {
     .takes_value(true)
     .value_name("DEVICE")
     .help("Specifies the spotify device to use")
+    .long_help(
+      "Specifies the spotify device to use by name or id. The name doesn't need to be exact, \
+it also matches ignoring case, by a part of it or fuzzily (like `desk` for `DESKTOP-3AB2F1`) as \
+long as only one device matches. Aliases from `device_aliases` in the config work too. If it's not given, the device is taken \
+from $SPOTIFY_TUI_DEVICE, then the last used device (if `remember_device` is enabled), then \
+`default_device` from the config and finally the active or any available device.",
+    )
+}
+
+fn wait_for_device_arg() -> Arg<'static, 'static> {
+  Arg::with_name("wait-for-device")
+    .long("wait-for-device")
+    .takes_value(true)
+    .value_name("SECONDS")
+    .help("Waits up to SECONDS for the device to show up")
+    .long_help(
+      "Polls the device list until the device from `--device` (or $SPOTIFY_TUI_DEVICE) is \
+available, or any device if none is given. Useful right after waking up a speaker or \
+unlocking a phone. Fails if the device doesn't show up within SECONDS.",
+    )
 }
 
 fn format_arg() -> Arg<'static, 'static> {
@@ -18,11 +38,61 @@ fn format_arg() -> Arg<'static, 'static> {
     .help("Specifies the output format")
     .long_help(
       "There are multiple format specifiers you can use: %a: artist, %b: album, %p: playlist, \
-%t: track, %h: show, %f: flags (shuffle, repeat, like), %s: playback status, %v: volume, %d: current device. \
+%t: track, %h: show, %f: flags (shuffle, repeat, like), %s: playback status, %v: volume, %d: current device, \
+%r: progress, %pct: progress in percent (0-100), \
+%dur_ctx: time left in the playlist or album, which fetches all of its tracks (one request per \
+100 tracks, so it's slow on large playlists) and ignores shuffle and the queue. \
+%k: musical key (e.g. C#m), %bpm: tempo. %k and %bpm need an additional request to the audio-features \
+endpoint (which requires the scopes granted on login) and expand to nothing if it fails. \
+%l: record label, %cr: copyright. %l and %cr need an additional request for the full album, \
+the label is taken from the ℗ copyright of the album, both expand to nothing if they're unavailable. \
+%disc: disc number, only for tracks of albums with multiple discs (also needs the full album). \
+%avail: number of markets a track or album is available in, %avail?CC: `yes` or `no` for the \
+market with the country code CC (e.g. %avail?DE), both are empty if spotify left the markets out. \
+%src: why the current item can't be fully controlled, a comma-separated list of `local-file`, \
+`unplayable`, `restricted` (the device doesn't accept commands) and `no-ACTION` for every action \
+spotify reports as disallowed (e.g. `no-skipping-next` during an ad, `no-seeking`), empty if \
+nothing is restricted. \
+%len: track length, %e: elapsed time of the playback, %y: release year, %n: track number, \
+%i: popularity (0-100), %c: ISRC. \
+Put a width between the `%` and the specifier to pad it, with zeros if the width starts with 0, \
+e.g. %{03}n for `007`. \
+Wrap specifiers in %j{...} to JSON-escape their output, e.g. '{\"track\": \"%j{%t}\"}'. \
+%(x?...) only prints what's inside if the specifier %x isn't empty (and for %s, if it's \
+playing), e.g. '%t%(f? [%f])' leaves out the brackets when no flag is set. \
 Example: spt pb -s -f 'playing on %d at %v%'",
     )
 }
 
+fn table_output_arg() -> Arg<'static, 'static> {
+  Arg::with_name("output")
+    .short("o")
+    .long("output")
+    .takes_value(true)
+    .value_name("OUTPUT")
+    .possible_values(&["text", "tsv", "csv"])
+    .default_value("text")
+    .help("Prints the results as formatted text or as a table")
+    .long_help(
+      "With `tsv` or `csv`, every format specifier in `--format` becomes a column and all \
+other text of the format is left out. A header row with the column names is printed \
+if stdout is a terminal, use `--header` or `--no-header` to override this.",
+    )
+}
+
+fn header_arg() -> Arg<'static, 'static> {
+  Arg::with_name("header")
+    .long("header")
+    .conflicts_with("no-header")
+    .help("Prints a header row in the tsv and csv outputs (default on a terminal)")
+}
+
+fn no_header_arg() -> Arg<'static, 'static> {
+  Arg::with_name("no-header")
+    .long("no-header")
+    .help("Omits the header row in the tsv and csv outputs (default when piped)")
+}
+
 pub fn playback_subcommand() -> App<'static, 'static> {
   SubCommand::with_name("playback")
     .version(env!("CARGO_PKG_VERSION"))
@@ -44,6 +114,7 @@ can be used together
     )
     .visible_alias("pb")
     .arg(device_arg())
+    .arg(wait_for_device_arg())
     .arg(
       format_arg()
         .default_value("%f %s %t - %a")
@@ -53,6 +124,35 @@ can be used together
           ("transfer", None, "%f %s %t - %a on %d"),
         ]),
     )
+    .arg(
+      Arg::with_name("output")
+        .short("o")
+        .long("output")
+        .takes_value(true)
+        .value_name("OUTPUT")
+        .possible_values(&["text", "waybar", "polybar", "i3blocks"])
+        .default_value("text")
+        .help("Prints the status as a module for a status bar")
+        .long_help(
+          "Prints the status from `--format` the way the bar's custom script modules expect it. \
+`waybar` prints JSON with the class `playing`, `paused` or `stopped` (use `\"return-type\": \
+\"json\"`), `polybar` and `i3blocks` dim the text with `bar_paused_color` from the config \
+while paused. If nothing is playing the module is empty. Overrides `--json`.",
+        ),
+    )
+    .arg(
+      Arg::with_name("follow")
+        .long("follow")
+        .visible_alias("watch")
+        .conflicts_with_all(&["single", "jumps", "from-stdin"])
+        .help("Keeps running and prints a new line whenever the status changes")
+        .long_help(
+          "Checks the playback every two seconds and prints the status again if it changed, \
+right when the track should end and every ten seconds while nothing is playing. This is meant \
+for status bars that read lines from a running script (waybar with `exec` and no `interval`, \
+polybar with `tail = true`, i3blocks with `interval=persist`). Other actions run once first.",
+        ),
+    )
     .arg(
       Arg::with_name("toggle")
         .short("t")
@@ -80,7 +180,22 @@ can be used together
         .long("transfer")
         .takes_value(true)
         .value_name("DEVICE")
-        .help("Transfers the playback to new DEVICE"),
+        .help("Transfers the playback to new DEVICE")
+        .long_help(
+          "Transfers the playback to new DEVICE, which is matched like the one from `--device`.",
+        ),
+    )
+    .arg(
+      Arg::with_name("restore-volume")
+        .long("restore-volume")
+        .requires("transfer")
+        .conflicts_with("volume")
+        .help("Sets the new device to the volume it was last used with")
+        .long_help(
+          "With `remember_device_volume: true` in the config, `--volume` saves the volume \
+for each device. `--restore-volume` sets the device from `--transfer` back to that volume and \
+leaves it unchanged if none was saved yet.",
+        ),
     )
     .arg(
       Arg::with_name("like")
@@ -92,6 +207,18 @@ can be used together
         .long("dislike")
         .help("Dislikes the current song if possible"),
     )
+    .arg(
+      Arg::with_name("from-stdin")
+        .long("from-stdin")
+        .requires("likes")
+        .conflicts_with_all(&["shuffle", "repeat", "seek", "single", "jumps", "actions"])
+        .help("Likes or dislikes the tracks read from stdin instead of the current song")
+        .long_help(
+          "Reads one track per line (uri, link or id) from stdin and likes (or with `--dislike` \
+removes) all of them, 50 per request: `cat uris.txt | spt pb --like --from-stdin`. Invalid \
+lines are reported on stderr and skipped.",
+        ),
+    )
     .arg(
       Arg::with_name("shuffle")
         .long("shuffle")
@@ -100,7 +227,15 @@ can be used together
     .arg(
       Arg::with_name("repeat")
         .long("repeat")
-        .help("Switches between repeat modes"),
+        .takes_value(true)
+        .min_values(0)
+        .value_name("MODE")
+        .possible_values(&["off", "track", "context"])
+        .help("Sets the repeat mode, or switches to the next one without MODE")
+        .long_help(
+          "Without a value, `--repeat` cycles from off to context to track. Scripts should give \
+the mode instead (`off`, `track` or `context`), which doesn't depend on the current one.",
+        ),
     )
     .arg(
       Arg::with_name("next")
@@ -129,12 +264,13 @@ two songs back, you can use `spt pb -ppp` and so on.",
       Arg::with_name("seek")
         .long("seek")
         .takes_value(true)
-        .value_name("±SECONDS")
+        .value_name("[±]POSITION")
         .allow_hyphen_values(true)
-        .help("Jumps SECONDS forwards (+) or backwards (-)")
+        .help("Jumps to POSITION, or by POSITION forwards (+) or backwards (-)")
         .long_help(
           "For example: `spt pb --seek +10` jumps ten second forwards, `spt pb --seek -10` ten \
-seconds backwards and `spt pb --seek 10` to the tenth second of the track.",
+seconds backwards and `spt pb --seek 10` to the tenth second of the track. POSITION can also be \
+a timestamp (`--seek 1:02:03` or `--seek -1:30`) or a percentage of the track (`--seek 50%`).",
         ),
     )
     .arg(
@@ -142,8 +278,24 @@ seconds backwards and `spt pb --seek 10` to the tenth second of the track.",
         .short("v")
         .long("volume")
         .takes_value(true)
-        .value_name("VOLUME")
-        .help("Sets the volume of a device to VOLUME (1 - 100)"),
+        .value_name("[±]VOLUME")
+        .allow_hyphen_values(true)
+        .help("Sets the volume of a device to VOLUME (0 - 100), or changes it by +/-VOLUME")
+        .long_help(
+          "For example: `spt pb -v 40` sets the volume to 40%, `spt pb -v +10` raises it by ten \
+and `spt pb -v -10` lowers it by ten (relative volumes stop at 0 and 100).",
+        ),
+    )
+    .arg(
+      Arg::with_name("force")
+        .long("force")
+        .help("Tries to control the device even if it is restricted")
+        .long_help(
+          "Some devices (e.g. TVs or cast targets) are restricted and can't be controlled by spt. \
+`--transfer` and `--volume` will refuse to work with them unless `--force` is used. \
+Spotify also reports actions it won't allow for the current item (e.g. skipping during an ad), \
+`--next`, `--previous`, `--seek` and `--toggle` fail early for those unless `--force` is used.",
+        ),
     )
     .group(
       ArgGroup::with_name("jumps")
@@ -182,23 +334,40 @@ pub fn play_subcommand() -> App<'static, 'static> {
     .author(env!("CARGO_PKG_AUTHORS"))
     .about("Plays a uri or another spotify item by name")
     .long_about(
-      "If you specify a uri, the type can be inferred. If you want to play something by \
-name, you have to specify the type: `--track`, `--album`, `--artist`, `--playlist` \
-or `--show`. The first item which was found will be played without confirmation. \
+      "If you specify a uri, the type can be inferred. `spt play <URI_OR_NAME>` guesses \
+whether it got a uri or a name. If you want to play something with `--name`, you have to \
+specify the type: `--track`, `--album`, `--artist`, `--playlist` \
+or `--show`. The first item which was found will be played without confirmation, \
+unless `--pick` is used to choose one of the results. \
 To add a track to the queue, use `--queue`. To play a random song from a playlist, \
-use `--random`. Again, with `--format` you can specify how the output will look. \
-The same function as found in `playback` will be called.",
+use `--random`. To start an album, playlist or show at a random position but play it in \
+order from there, use `--start-random-track`. To continue an episode (or the last \
+unfinished episode of a show) where you left off, use `--resume`. Again, with `--format` you can specify how \
+the output will look. The same function as found in `playback` will be called.",
     )
     .visible_alias("p")
     .arg(device_arg())
+    .arg(wait_for_device_arg())
     .arg(format_arg().default_value("%f %s %t - %a"))
+    .arg(
+      Arg::with_name("item")
+        .index(1)
+        .value_name("URI_OR_NAME")
+        .help("Plays a uri or link, or the first match of a name")
+        .long_help(
+          "Uris (`spotify:album:...`) and links (`https://open.spotify.com/album/...`) are \
+played directly, anything else is searched for. Without `--album`, `--artist`, `--playlist` or \
+`--show` the first matching track is played. Use `--uri` or `--name` to avoid the guessing \
+in scripts.",
+        ),
+    )
     .arg(
       Arg::with_name("uri")
         .short("u")
         .long("uri")
         .takes_value(true)
         .value_name("URI")
-        .help("Plays the URI"),
+        .help("Plays the URI or open.spotify.com link"),
     )
     .arg(
       Arg::with_name("name")
@@ -225,6 +394,54 @@ The same function as found in `playback` will be called.",
         .conflicts_with_all(&["track", "album", "artist", "show"])
         .help("Plays a random track (only works with playlists)"),
     )
+    .arg(
+      Arg::with_name("start-random-track")
+        .long("start-random-track")
+        // Only works with contexts
+        .conflicts_with_all(&["track", "artist", "random", "queue"])
+        .help("Starts an album, playlist or show at a random position")
+        .long_help(
+          "Starts the album, playlist or show at a random position and keeps playing it in \
+order from there. In contrast, `--random` picks a random track of a playlist and `spt pb \
+--shuffle` toggles shuffle mode, which randomizes the order of all following tracks.",
+        ),
+    )
+    .arg(
+      Arg::with_name("resume")
+        .long("resume")
+        // Only works with episodes and shows
+        .conflicts_with_all(&[
+          "track",
+          "album",
+          "artist",
+          "playlist",
+          "queue",
+          "random",
+          "start-random-track",
+        ])
+        .help("Continues an episode or show at the last played position"),
+    )
+    .arg(
+      Arg::with_name("pick")
+        .long("pick")
+        .conflicts_with("uri")
+        .help("Lets you choose which of the results to play")
+        .long_help(
+          "Lists the first 20 results of the search instead of playing the first one. Type to \
+filter them, pick one with its number (1-9) or move to it and press enter.",
+        ),
+    )
+    .arg(
+      Arg::with_name("verify")
+        .long("verify")
+        .conflicts_with("queue")
+        .help("Checks that the playback started on the selected device")
+        .long_help(
+          "Re-fetches the playback for a few seconds after starting it and warns on stderr \
+if it isn't playing on the device from `--device` (or the last used one). Spotify sometimes \
+accepts the request but starts the playback on another device.",
+        ),
+    )
     .arg(
       Arg::with_name("album")
         .short("b")
@@ -262,7 +479,7 @@ The same function as found in `playback` will be called.",
     )
     .group(
       ArgGroup::with_name("actions")
-        .args(&["uri", "name"])
+        .args(&["uri", "name", "item"])
         .multiple(false)
         .required(true),
     )
@@ -272,10 +489,12 @@ pub fn list_subcommand() -> App<'static, 'static> {
   SubCommand::with_name("list")
     .version(env!("CARGO_PKG_VERSION"))
     .author(env!("CARGO_PKG_AUTHORS"))
-    .about("Lists devices, liked songs and playlists")
+    .about("Lists devices, liked songs, playlists, followed artists, saved albums and shows")
     .long_about(
-      "This will list devices, liked songs or playlists. With the `--limit` flag you are \
-able to specify the amount of results (between 1 and 50). Here, the `--format` is \
+      "This will list devices, liked songs, playlists, followed artists, saved albums or \
+saved shows. With the `--limit` flag you are \
+able to specify the amount of results (between 1 and 10000), more than 50 are fetched \
+in several requests. `--all` fetches everything. Here, the `--format` is \
 even more awesome, get your output exactly the way you want. The format option will \
 be applied to every item found.",
     )
@@ -284,7 +503,13 @@ be applied to every item found.",
       ("devices", None, "%v% %d"),
       ("liked", None, "%t - %a (%u)"),
       ("playlists", None, "%p (%u)"),
+      ("artists", None, "%a (%u)"),
+      ("albums", None, "%b - %a (%u)"),
+      ("shows", None, "%h - %a (%u)"),
     ]))
+    .arg(table_output_arg())
+    .arg(header_arg())
+    .arg(no_header_arg())
     .arg(
       Arg::with_name("devices")
         .short("d")
@@ -302,15 +527,59 @@ be applied to every item found.",
         .long("liked")
         .help("Lists liked songs"),
     )
+    .arg(
+      Arg::with_name("artists")
+        .short("a")
+        .long("artists")
+        .help("Lists followed artists"),
+    )
+    .arg(
+      Arg::with_name("albums")
+        .short("b")
+        .long("albums")
+        .help("Lists saved albums"),
+    )
+    .arg(
+      Arg::with_name("shows")
+        .long("shows")
+        .help("Lists saved shows"),
+    )
+    .arg(
+      Arg::with_name("sort")
+        .long("sort")
+        .takes_value(true)
+        .value_name("SORT")
+        .possible_values(&["name", "popularity"])
+        .requires("artists")
+        .help("Sorts the followed artists by name or popularity")
+        .long_help(
+          "Sorts the followed artists alphabetically (`name`) or with the most popular first \
+(`popularity`). Without it, the order of the API is kept. Spotify doesn't tell when an artist \
+was followed, so the artists can't be sorted by that.",
+        ),
+    )
+    .arg(
+      Arg::with_name("all")
+        .long("all")
+        .conflicts_with("limit")
+        .help("Fetches all results instead of only the first `--limit`"),
+    )
     .arg(
       Arg::with_name("limit")
         .long("limit")
         .takes_value(true)
-        .help("Specifies the maximum number of results (1 - 50)"),
+        .help("Specifies the maximum number of results (1 - 10000)"),
     )
     .group(
       ArgGroup::with_name("listable")
-        .args(&["devices", "playlists", "liked"])
+        .args(&[
+          "devices",
+          "playlists",
+          "liked",
+          "artists",
+          "albums",
+          "shows",
+        ])
         .required(true)
         .multiple(false),
     )
@@ -324,10 +593,23 @@ pub fn search_subcommand() -> App<'static, 'static> {
     .long_about(
       "This will search for something on spotify and displays you the items. The output \
 format can be changed with the `--format` flag and the limit can be changed with \
-the `--limit` flag (between 1 and 50). The type can't be inferred, so you have to \
-specify it.",
+the `--limit` flag (between 1 and 10000, spotify stops after 1000 results). The type can't be inferred, so you have to \
+specify it. With `--interactive`, the results are shown in a pager where the highlighted \
+item can be played (enter), queued (a) or opened as a link (o).",
     )
     .visible_alias("s")
+    .arg(
+      Arg::with_name("interactive")
+        .short("i")
+        .long("interactive")
+        .conflicts_with_all(&["output", "header", "no-header"])
+        .help("Pages the results to pick one to play, queue or open")
+        .long_help(
+          "Shows the results in a pager: move with the arrow keys (or j and k), press \
+enter to play the highlighted item, `a` to add a track to the queue, `o` to print its link \
+and `q` or escape to quit. If stdout isn't a terminal, the results are printed as usual.",
+        ),
+    )
     .arg(format_arg().default_value_ifs(&[
       ("tracks", None, "%t - %a (%u)"),
       ("playlists", None, "%p (%u)"),
@@ -335,6 +617,9 @@ specify it.",
       ("albums", None, "%b - %a (%u)"),
       ("shows", None, "%h - %a (%u)"),
     ]))
+    .arg(table_output_arg())
+    .arg(header_arg())
+    .arg(no_header_arg())
     .arg(
       Arg::with_name("search")
         .required(true)
@@ -376,7 +661,54 @@ specify it.",
       Arg::with_name("limit")
         .long("limit")
         .takes_value(true)
-        .help("Specifies the maximum number of results (1 - 50)"),
+        .help("Specifies the maximum number of results (1 - 10000)"),
+    )
+    .arg(
+      Arg::with_name("all")
+        .long("all")
+        .conflicts_with("limit")
+        .help("Fetches all results spotify returns (up to 1000)"),
+    )
+    .arg(
+      Arg::with_name("min-duration")
+        .long("min-duration")
+        .takes_value(true)
+        .value_name("DURATION")
+        .requires("tracks")
+        .help("Only shows tracks that are at least DURATION long (mm:ss or seconds)"),
+    )
+    .arg(
+      Arg::with_name("max-duration")
+        .long("max-duration")
+        .takes_value(true)
+        .value_name("DURATION")
+        .requires("tracks")
+        .help("Only shows tracks that are at most DURATION long (mm:ss or seconds)")
+        .long_help(
+          "Filters the found tracks by their length. Both `--min-duration` and \
+`--max-duration` accept either `mm:ss` or plain seconds and can be combined: \
+`spt s -t 'interlude' --max-duration 1:30`. The number of tracks that were \
+filtered out is reported on stderr.",
+        ),
+    )
+    .arg(
+      Arg::with_name("sort")
+        .long("sort")
+        .takes_value(true)
+        .value_name("SORT")
+        .possible_values(&["release"])
+        .requires("release-sortable")
+        .help("Sorts albums or tracks by release date (newest first)")
+        .long_help(
+          "Sorts the found albums or tracks by the release date of their album, newest \
+first. Items without a release date come last. Combine it with spotify's field filters to \
+see what an artist released recently: `spt s -b 'artist:Radiohead' --sort release`.",
+        ),
+    )
+    .group(
+      ArgGroup::with_name("release-sortable")
+        .args(&["albums", "tracks"])
+        .multiple(false),
     )
     .group(
       ArgGroup::with_name("searchable")
@@ -385,3 +717,402 @@ specify it.",
         .multiple(false),
     )
 }
+
+fn playlist_arg() -> Arg<'static, 'static> {
+  Arg::with_name("playlist")
+    .required(true)
+    .value_name("PLAYLIST")
+    .help("The playlist as uri, link or id")
+}
+
+pub fn playlist_subcommand() -> App<'static, 'static> {
+  SubCommand::with_name("playlist")
+    .version(env!("CARGO_PKG_VERSION"))
+    .author(env!("CARGO_PKG_AUTHORS"))
+    .about("Creates, renames and deletes playlists and changes their tracks")
+    .long_about(
+      "Manages your playlists. Playlists and tracks can be given as uris, links or ids, use \
+`spt list --playlists --format '%p %u'` to find the uri of a playlist. Example: \
+spt playlist add spotify:playlist:37i9dQZF1DXcBWIGoYBM5M spotify:track:4uLU6hMCjMI75M1A2tKUQC",
+    )
+    .visible_alias("pl")
+    .setting(AppSettings::SubcommandRequiredElseHelp)
+    .subcommand(
+      SubCommand::with_name("create")
+        .about("Creates a new playlist")
+        .arg(
+          Arg::with_name("name")
+            .required(true)
+            .value_name("NAME")
+            .help("The name of the new playlist"),
+        )
+        .arg(
+          Arg::with_name("public")
+            .long("public")
+            .help("Makes the playlist public (it's private by default)"),
+        )
+        .arg(
+          Arg::with_name("description")
+            .long("description")
+            .takes_value(true)
+            .value_name("DESCRIPTION")
+            .help("Sets the description of the playlist"),
+        ),
+    )
+    .subcommand(
+      SubCommand::with_name("delete")
+        .about("Deletes a playlist")
+        .long_about(
+          "Deletes a playlist. Spotify doesn't really delete playlists, they're unfollowed \
+and can be recovered on the account page of the spotify website.",
+        )
+        .arg(playlist_arg()),
+    )
+    .subcommand(
+      SubCommand::with_name("rename")
+        .about("Renames a playlist")
+        .arg(playlist_arg())
+        .arg(
+          Arg::with_name("name")
+            .required(true)
+            .value_name("NAME")
+            .help("The new name of the playlist"),
+        ),
+    )
+    .subcommand(
+      SubCommand::with_name("export")
+        .about("Writes all tracks of a playlist to a JSON, M3U8 or XSPF file")
+        .long_about(
+          "Exports every track of a playlist with its uri, name, artists, album, duration, ISRC \
+and the time it was added. JSON keeps all of it, M3U8 only has the uris, durations, artists and \
+names and XSPF everything but who added the tracks. Prints the export if `--out` isn't given.",
+        )
+        .arg(playlist_arg())
+        .arg(
+          Arg::with_name("format")
+            .short("f")
+            .long("format")
+            .takes_value(true)
+            .value_name("FORMAT")
+            .possible_values(&["json", "m3u8", "xspf"])
+            .default_value("json")
+            .help("Specifies the file format"),
+        )
+        .arg(
+          Arg::with_name("out")
+            .short("o")
+            .long("out")
+            .takes_value(true)
+            .value_name("FILE")
+            .help("Writes the export to FILE instead of printing it"),
+        ),
+    )
+    .subcommand(
+      SubCommand::with_name("import")
+        .about("Creates a playlist from the tracks in a CSV or JSON file")
+        .long_about(
+          "Creates a playlist from a CSV file with title and artist columns (found by their \
+header, or the first two columns if there's none) or a JSON array of objects with `title` and \
+`artist` fields, like the files of `spt playlist export --format json`. Rows with a spotify uri \
+are added as is, the others are searched by ISRC if the file has one and then by title and \
+artist. Rows without a good match are listed at the end.",
+        )
+        .arg(
+          Arg::with_name("file")
+            .required(true)
+            .value_name("FILE")
+            .help("The CSV or JSON file"),
+        )
+        .arg(
+          Arg::with_name("name")
+            .short("n")
+            .long("name")
+            .required(true)
+            .takes_value(true)
+            .value_name("NAME")
+            .help("The name of the new playlist"),
+        )
+        .arg(
+          Arg::with_name("public")
+            .long("public")
+            .help("Makes the playlist public (it's private by default)"),
+        ),
+    )
+    .subcommand(
+      SubCommand::with_name("add")
+        .about("Adds tracks to the end of a playlist")
+        .arg(playlist_arg())
+        .arg(
+          Arg::with_name("tracks")
+            .required(true)
+            .multiple(true)
+            .value_name("TRACK")
+            .help("The tracks as uris, links or ids"),
+        ),
+    )
+    .subcommand(
+      SubCommand::with_name("remove")
+        .about("Removes every occurrence of tracks from a playlist")
+        .arg(playlist_arg())
+        .arg(
+          Arg::with_name("tracks")
+            .required(true)
+            .multiple(true)
+            .value_name("TRACK")
+            .help("The tracks as uris, links or ids"),
+        ),
+    )
+}
+
+pub fn queue_subcommand() -> App<'static, 'static> {
+  SubCommand::with_name("queue")
+    .version(env!("CARGO_PKG_VERSION"))
+    .author(env!("CARGO_PKG_AUTHORS"))
+    .about("Adds tracks and episodes to the queue")
+    .long_about(
+      "Adds one or more tracks or episodes (uris or links) to the end of the queue, in the \
+given order. Use `--from-stdin` to queue uris piped into spt (one per line), e.g. the output of \
+`spt search --tracks --format %u`. The web api spt uses can't read or clear the queue, so \
+there's no way to list it here yet.",
+    )
+    .arg(device_arg())
+    .arg(wait_for_device_arg())
+    .arg(
+      Arg::with_name("add")
+        .short("a")
+        .long("add")
+        .takes_value(true)
+        .multiple(true)
+        .value_name("URI")
+        .help("Adds the tracks or episodes with URI to the queue"),
+    )
+    .arg(
+      Arg::with_name("from-stdin")
+        .long("from-stdin")
+        .help("Reads track or episode uris from stdin (one per line)"),
+    )
+    .group(
+      ArgGroup::with_name("items")
+        .args(&["add", "from-stdin"])
+        .required(true)
+        .multiple(false),
+    )
+}
+
+pub fn analyze_subcommand() -> App<'static, 'static> {
+  SubCommand::with_name("analyze")
+    .version(env!("CARGO_PKG_VERSION"))
+    .author(env!("CARGO_PKG_AUTHORS"))
+    .about("Shows the audio features of a track")
+    .long_about(
+      "Prints the audio features (danceability, energy, valence, tempo, key, mode, loudness \
+and time signature) of the current track or of the track specified with `--uri`. To analyze \
+multiple tracks at once, pipe their URIs into spt with `--from-stdin` (one per line). Use \
+`--format` with the specifiers %dance, %energy, %valence, %bpm, %k, %mode, %loud and %ts \
+or `--output json` to get all features as JSON.",
+    )
+    .visible_alias("an")
+    .arg(format_arg().default_value(concat!(
+      "%t - %a: %bpm bpm, %k, %ts/4, %loud dB, ",
+      "danceability %dance, energy %energy, valence %valence"
+    )))
+    .arg(
+      Arg::with_name("uri")
+        .short("u")
+        .long("uri")
+        .takes_value(true)
+        .value_name("URI")
+        .help("Analyzes the track with URI instead of the current one"),
+    )
+    .arg(
+      Arg::with_name("from-stdin")
+        .long("from-stdin")
+        .conflicts_with("uri")
+        .help("Reads track URIs from stdin (one per line)"),
+    )
+    .arg(
+      Arg::with_name("output")
+        .short("o")
+        .long("output")
+        .takes_value(true)
+        .value_name("OUTPUT")
+        .possible_values(&["text", "json"])
+        .default_value("text")
+        .help("Prints the features as formatted text or as JSON"),
+    )
+}
+
+pub fn lyrics_subcommand() -> App<'static, 'static> {
+  SubCommand::with_name("lyrics")
+    .version(env!("CARGO_PKG_VERSION"))
+    .author(env!("CARGO_PKG_AUTHORS"))
+    .about("Prints the lyrics of a track")
+    .long_about(
+      "Prints the lyrics of the current track or of the track specified with `--uri`, from the \
+`lyrics_provider` in the config (lrclib by default, fetched with curl). With `--synced` the \
+lyrics are printed as LRC, with a `[mm:ss.xx]` timestamp in front of every line, which fails if \
+only unsynced lyrics exist.",
+    )
+    .arg(
+      Arg::with_name("uri")
+        .short("u")
+        .long("uri")
+        .takes_value(true)
+        .value_name("URI")
+        .help("Prints the lyrics of the track with URI instead of the current one"),
+    )
+    .arg(
+      Arg::with_name("synced")
+        .long("synced")
+        .help("Prints the lyrics as LRC with a timestamp for every line"),
+    )
+}
+
+pub fn top_subcommand() -> App<'static, 'static> {
+  SubCommand::with_name("top")
+    .version(env!("CARGO_PKG_VERSION"))
+    .author(env!("CARGO_PKG_AUTHORS"))
+    .about("Lists your top tracks or artists")
+    .long_about(
+      "Lists the tracks or artists you listened to the most, with the most listened first. \
+`--range` picks the time they are computed over: `short` is about the last 4 weeks, `medium` \
+the last 6 months and `long` several years. The format and output options are the same as for \
+`list`.",
+    )
+    .arg(format_arg().default_value_ifs(&[
+      ("tracks", None, "%t - %a (%u)"),
+      ("artists", None, "%a (%u)"),
+    ]))
+    .arg(table_output_arg())
+    .arg(header_arg())
+    .arg(no_header_arg())
+    .arg(
+      Arg::with_name("tracks")
+        .short("t")
+        .long("tracks")
+        .help("Lists your top tracks"),
+    )
+    .arg(
+      Arg::with_name("artists")
+        .short("a")
+        .long("artists")
+        .help("Lists your top artists"),
+    )
+    .arg(
+      Arg::with_name("range")
+        .short("r")
+        .long("range")
+        .takes_value(true)
+        .value_name("RANGE")
+        .possible_values(&["short", "medium", "long"])
+        .default_value("medium")
+        .help("Specifies the time range: about 4 weeks, 6 months or several years"),
+    )
+    .arg(
+      Arg::with_name("limit")
+        .long("limit")
+        .takes_value(true)
+        .default_value("20")
+        .help("Specifies the maximum number of results (1 - 50)"),
+    )
+    .group(
+      ArgGroup::with_name("top")
+        .args(&["tracks", "artists"])
+        .required(true)
+        .multiple(false),
+    )
+}
+
+pub fn recommend_subcommand() -> App<'static, 'static> {
+  SubCommand::with_name("recommend")
+    .version(env!("CARGO_PKG_VERSION"))
+    .author(env!("CARGO_PKG_AUTHORS"))
+    .about("Lists, plays or saves recommendations based on seed tracks, artists or genres")
+    .long_about(
+      "Asks spotify for tracks similar to up to 5 seeds (tracks, artists and genres combined). \
+Without `--play` or `--save-as` the tracks are listed, with the same format and output options as \
+`list`. `--play` starts a radio of the tracks and `--save-as` saves them to a new private \
+playlist. Example: spt recommend --seed-track spotify:track:4uLU6hMCjMI75M1A2tKUQC --limit 30 \
+--play",
+    )
+    .visible_alias("rec")
+    .arg(device_arg())
+    .arg(wait_for_device_arg())
+    .arg(format_arg().default_value("%t - %a (%u)"))
+    .arg(table_output_arg())
+    .arg(header_arg())
+    .arg(no_header_arg())
+    .arg(
+      Arg::with_name("seed-track")
+        .long("seed-track")
+        .takes_value(true)
+        .multiple(true)
+        .number_of_values(1)
+        .value_name("TRACK")
+        .help("Uses a track (uri, link or id) as seed"),
+    )
+    .arg(
+      Arg::with_name("seed-artist")
+        .long("seed-artist")
+        .takes_value(true)
+        .multiple(true)
+        .number_of_values(1)
+        .value_name("ARTIST")
+        .help("Uses an artist (uri, link or id) as seed"),
+    )
+    .arg(
+      Arg::with_name("seed-genre")
+        .long("seed-genre")
+        .takes_value(true)
+        .multiple(true)
+        .number_of_values(1)
+        .value_name("GENRE")
+        .help("Uses a genre (like 'rock' or 'hip-hop') as seed"),
+    )
+    .arg(
+      Arg::with_name("limit")
+        .long("limit")
+        .takes_value(true)
+        .default_value("30")
+        .help("Specifies the number of tracks (1 - 100)"),
+    )
+    .arg(
+      Arg::with_name("play")
+        .long("play")
+        .help("Plays the recommended tracks"),
+    )
+    .arg(
+      Arg::with_name("save-as")
+        .long("save-as")
+        .takes_value(true)
+        .value_name("NAME")
+        .help("Saves the recommended tracks to a new private playlist called NAME"),
+    )
+    .group(
+      ArgGroup::with_name("seeds")
+        .args(&["seed-track", "seed-artist", "seed-genre"])
+        .required(true)
+        .multiple(true),
+    )
+}
+
+pub fn daemon_subcommand() -> App<'static, 'static> {
+  SubCommand::with_name("daemon")
+    .version(env!("CARGO_PKG_VERSION"))
+    .author(env!("CARGO_PKG_AUTHORS"))
+    .about("Runs commands sent over a local socket, without the startup cost")
+    .long_about(
+      "Keeps the login fresh and listens on a unix socket for commands. Each connection sends \
+one line with the arguments of a spt command (like `pb --next` or `play --name \"Some Song\" \
+--track`) and gets its output back, or a line starting with `error:`. For example: \
+`echo 'pb --next' | socat - UNIX-CONNECT:$HOME/.config/spotify-tui/spt.sock`. Reading from \
+stdin, `--interactive` and `--follow` aren't available through the daemon. Stop it with ctrl-c.",
+    )
+    .arg(
+      Arg::with_name("socket")
+        .long("socket")
+        .takes_value(true)
+        .value_name("PATH")
+        .help("Listens on PATH instead of spt.sock in the config directory"),
+    )
+}
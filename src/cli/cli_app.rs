@@ -1,15 +1,50 @@
-use crate::network::{IoEvent, Network};
+use crate::network::{
+  all_items, IoEvent, Network, ALBUMS_LIMIT, AUDIO_FEATURES_LIMIT, PLAYLIST_TRACKS_CHANGE_LIMIT,
+  SAVED_TRACKS_CHANGE_LIMIT, TRACKS_LIMIT,
+};
 use crate::user_config::UserConfig;
 
-use super::util::{Flag, Format, FormatType, JumpDirection, Type};
+use super::export::{self, ExportFormat, ExportedTrack};
+use super::import::{best_match, parse_rows, ImportRow};
+use super::util::{
+  describe_event, disallowed_message, find_device, fresh_progress, parse_id, parse_limit,
+  parse_track_id, queueable_uri, remaining_in_context, render_header, render_json, render_row,
+  resolve_device, resume_position, seek_position, sort_by_release, volume_level, ArtistSort,
+  DeviceChoice, DurationRange, Flag, FollowInterval, JumpDirection, OutputMode, Retry, SearchSort,
+  StatusBar, Type, MAX_QUERY_LIMIT,
+};
+use super::volumes::DeviceVolumes;
+use crate::format::{join_artists, release_year, render, Format, FormatType};
+use crate::lyrics::{LyricsQuery, LyricsStatus};
 
 use anyhow::{anyhow, Result};
 use rand::{thread_rng, Rng};
-use rspotify::model::{context::CurrentlyPlaybackContext, PlayingItem};
+use rspotify::model::search::SearchResult;
+use rspotify::model::{
+  album::FullAlbum, artist::FullArtist, audio::AudioFeatures, context::CurrentlyPlaybackContext,
+  track::FullTrack, PlayingItem,
+};
+use rspotify::senum::{CurrentlyPlayingType, DisallowKey, SearchType, TimeRange};
+use std::{
+  collections::HashMap,
+  time::{Duration, Instant},
+};
 
 pub struct CliApp<'a> {
   pub net: Network<'a>,
   pub config: UserConfig,
+  pub output: OutputMode,
+  pub header: bool,
+  pub dry_run: bool,
+  pub verbose: bool,
+  // Cache for %k and %bpm, keyed by track id
+  audio_features: HashMap<String, AudioFeatures>,
+  albums: HashMap<String, FullAlbum>,
+  // Uris and durations of the tracks of a playlist or album
+  context_tracks: HashMap<String, Vec<(String, u32)>>,
+  // Set once a device was picked or the playback was transferred,
+  // only then transient device errors are retried
+  device_changed: bool,
 }
 
 // Non-concurrent functions
@@ -18,7 +53,18 @@ pub struct CliApp<'a> {
 // by calling network.handle_network_event
 impl<'a> CliApp<'a> {
   pub fn new(net: Network<'a>, config: UserConfig) -> Self {
-    Self { net, config }
+    Self {
+      net,
+      config,
+      output: OutputMode::Text,
+      header: false,
+      dry_run: false,
+      verbose: false,
+      audio_features: HashMap::new(),
+      albums: HashMap::new(),
+      context_tracks: HashMap::new(),
+      device_changed: false,
+    }
   }
 
   async fn is_a_saved_track(&mut self, id: &str) -> bool {
@@ -32,30 +78,167 @@ impl<'a> CliApp<'a> {
     self.net.app.lock().await.liked_song_ids_set.contains(id)
   }
 
-  pub fn format_output(&self, mut format: String, values: Vec<Format>) -> String {
-    for val in values {
-      format = format.replace(val.get_placeholder(), &val.inner(self.config.clone()));
+  pub fn format_output(&self, format: String, values: Vec<Format>) -> String {
+    match self.output {
+      OutputMode::Text => render(&format, &values, &self.config).trim().to_string(),
+      OutputMode::Json => render_json(&values),
+      mode => render_row(&format, &values, &self.config, mode),
+    }
+  }
+
+  // Prepends the column names in the tabular output modes
+  pub fn with_header(&self, format: &str, body: String) -> String {
+    if matches!(self.output, OutputMode::Text | OutputMode::Json) || !self.header {
+      body
+    } else {
+      format!("{}\n{}", render_header(format, self.output), body)
+    }
+  }
+
+  // Sends an event which changes the playback or the library,
+  // with `--dry-run` it's only printed
+  async fn mutate(&mut self, event: IoEvent) {
+    if self.dry_run {
+      println!("[dry-run] would {}", describe_event(&event));
+      return;
     }
-    // Replace unsupported flags with 'None'
-    for p in &["%a", "%b", "%t", "%p", "%h", "%u", "%d", "%v", "%f", "%s"] {
-      format = format.replace(p, "None");
+
+    // Don't retry if an earlier step already failed or no device was
+    // switched to, since the errors can't be transient then
+    if !self.device_changed || !self.net.app.lock().await.api_error.is_empty() {
+      self.net.handle_network_event(event).await;
+      return;
+    }
+
+    let retry = Retry::from_config(&self.config);
+    let verbose = self.verbose;
+    let result = retry
+      .run(self, verbose, |cli| Box::pin(cli.try_event(event.clone())))
+      .await;
+    if let Err(e) = result {
+      self.net.app.lock().await.handle_error(e);
     }
-    format.trim().to_string()
+  }
+
+  // Sends the event and returns the error it caused
+  async fn try_event(&mut self, event: IoEvent) -> Result<()> {
+    self.net.handle_network_event(event).await;
+    let mut app = self.net.app.lock().await;
+    if app.api_error.is_empty() {
+      Ok(())
+    } else {
+      Err(anyhow!(std::mem::take(&mut app.api_error)))
+    }
+  }
+
+  // Only calls the audio-features endpoint if %k or %bpm are used
+  async fn fetch_audio_features(&mut self, format: &str, ids: Vec<String>) {
+    if Format::needs_audio_features(format) {
+      self.load_audio_features(ids).await;
+    }
+  }
+
+  async fn load_audio_features(&mut self, ids: Vec<String>) {
+    let missing = ids
+      .into_iter()
+      .filter(|id| !self.audio_features.contains_key(id))
+      .collect::<Vec<String>>();
+
+    for chunk in missing.chunks(AUDIO_FEATURES_LIMIT) {
+      // Errors are ignored, the specifiers will just be empty
+      if let Ok(Some(payload)) = self.net.spotify.audios_features(chunk).await {
+        for f in payload.audio_features {
+          self.audio_features.insert(f.id.clone(), f);
+        }
+      }
+    }
+  }
+
+  // Only fetches the full albums if %l or %cr are used
+  async fn fetch_albums(&mut self, format: &str, ids: Vec<String>) {
+    if !Format::needs_album(format) {
+      return;
+    }
+    let mut missing = ids
+      .into_iter()
+      .filter(|id| !self.albums.contains_key(id))
+      .collect::<Vec<String>>();
+    missing.sort();
+    missing.dedup();
+
+    for chunk in missing.chunks(ALBUMS_LIMIT) {
+      // Errors are ignored, the specifiers will just be empty
+      if let Ok(result) = self.net.spotify.albums(chunk.to_vec()).await {
+        for a in result.albums {
+          self.albums.insert(a.id.clone(), a);
+        }
+      }
+    }
+  }
+
+  // Fetches every track of a playlist or album (one request per 100 or 50 tracks)
+  async fn load_context_tracks(&mut self, uri: &str) -> Option<&Vec<(String, u32)>> {
+    if !self.context_tracks.contains_key(uri) {
+      let id = uri.rsplit(':').next()?;
+      let spotify = &self.net.spotify;
+      let tracks = if uri.contains("spotify:playlist:") {
+        all_items(100, |limit, offset| {
+          spotify.user_playlist_tracks("spotify", id, None, limit, offset, None)
+        })
+        .await
+        .ok()?
+        .into_iter()
+        // Local or unavailable tracks still take a slot
+        .map(|i| match i.track {
+          Some(t) => (t.uri, t.duration_ms),
+          None => (String::new(), 0),
+        })
+        .collect()
+      } else if uri.contains("spotify:album:") {
+        all_items(50, |limit, offset| spotify.album_track(id, limit, offset))
+          .await
+          .ok()?
+          .into_iter()
+          .map(|t| (t.uri, t.duration_ms))
+          .collect()
+      } else {
+        return None;
+      };
+      self.context_tracks.insert(uri.to_string(), tracks);
+    }
+    self.context_tracks.get(uri)
+  }
+
+  fn album_ids(tracks: &[FullTrack]) -> Vec<String> {
+    tracks.iter().filter_map(|t| t.album.id.clone()).collect()
+  }
+
+  fn format_track(&self, format: &str, track: &FullTrack) -> String {
+    let mut values = Format::from_type(FormatType::Track(Box::new(track.clone())));
+    if Format::needs_audio_features(format) {
+      let features = track.id.as_ref().and_then(|id| self.audio_features.get(id));
+      values.extend(Format::from_audio_features(features));
+    }
+    if Format::needs_album(format) {
+      let album = track.album.id.as_ref().and_then(|id| self.albums.get(id));
+      values.extend(Format::from_album(album, Some(track)));
+    }
+    self.format_output(format.to_string(), values)
   }
 
   // spt playback -t
-  pub async fn toggle_playback(&mut self) {
+  pub async fn toggle_playback(&mut self, force: bool) -> Result<()> {
     let context = self.net.app.lock().await.current_playback_context.clone();
     if let Some(c) = context {
       if c.is_playing {
-        self.net.handle_network_event(IoEvent::PausePlayback).await;
-        return;
+        self.check_allowed(DisallowKey::Pausing, force).await?;
+        self.mutate(IoEvent::PausePlayback).await;
+        return Ok(());
       }
     }
-    self
-      .net
-      .handle_network_event(IoEvent::StartPlayback(None, None, None))
-      .await;
+    self.check_allowed(DisallowKey::Resuming, force).await?;
+    self.mutate(IoEvent::StartPlayback(None, None, None)).await;
+    Ok(())
   }
 
   // spt pb --share-track (share the current playing song)
@@ -108,40 +291,106 @@ impl<'a> CliApp<'a> {
     }
   }
 
-  // spt ... -d ... (specify device to control)
-  pub async fn set_device(&mut self, name: String) -> Result<()> {
-    // Change the device if specified by user
-    let mut app = self.net.app.lock().await;
-    let mut device_index = 0;
-    if let Some(dp) = &app.devices {
-      for (i, d) in dp.devices.iter().enumerate() {
-        if d.name == name {
-          device_index = i;
-          // Save the id of the device
-          self
-            .net
-            .client_config
-            .set_device_id(d.id.clone())
-            .map_err(|_e| anyhow!("failed to use device with name '{}'", d.name))?;
+  // spt ... --wait-for-device SECONDS
+  // Polls the devices until `target` (or any device) is available
+  pub async fn wait_for_device(&mut self, target: Option<&str>, timeout: Duration) -> Result<()> {
+    let start = Instant::now();
+    loop {
+      let target = target.map(|t| self.config.behavior.unalias_device(t));
+      let found = match &self.net.app.lock().await.devices {
+        Some(p) => match target {
+          Some(t) => find_device(&p.devices, t).is_ok(),
+          None => !p.devices.is_empty(),
+        },
+        None => false,
+      };
+      if found {
+        return Ok(());
+      }
+      if start.elapsed() >= timeout {
+        return Err(match target {
+          Some(t) => anyhow!(
+            "device '{}' didn't show up within {}s",
+            t,
+            timeout.as_secs()
+          ),
+          None => anyhow!("no device showed up within {}s", timeout.as_secs()),
+        });
+      }
+      tokio::time::sleep(Duration::from_secs(1)).await;
+      self.net.handle_network_event(IoEvent::GetDevices).await;
+    }
+  }
+
+  // spt play --verify
+  // Spotify sometimes accepts a play request but starts it somewhere else
+  pub async fn verify_device(&mut self, attempts: u32) {
+    let target = match &self.net.client_config.device_id {
+      Some(id) if !self.dry_run => id.clone(),
+      _ => return,
+    };
+    let mut playing_on = None;
+    for _ in 0..attempts {
+      tokio::time::sleep(Duration::from_secs(1)).await;
+      self.net.app.lock().await.current_playback_context = None;
+      self
+        .net
+        .handle_network_event(IoEvent::GetCurrentPlayback)
+        .await;
+      if let Some(context) = &self.net.app.lock().await.current_playback_context {
+        if context.device.id == target {
+          return;
         }
+        playing_on = Some(context.device.name.clone());
       }
-    } else {
-      // Error out if no device is available
-      return Err(anyhow!("no device available"));
     }
-    app.selected_device_index = Some(device_index);
-    Ok(())
+    match playing_on {
+      Some(name) => eprintln!(
+        "Warning: playback didn't start on the selected device, it's playing on '{}'",
+        name
+      ),
+      None => eprintln!("Warning: couldn't confirm that playback started on the selected device"),
+    }
   }
 
-  // spt query ... --limit LIMIT (set max search limit)
-  pub async fn update_query_limits(&mut self, max: String) -> Result<()> {
-    let num = max
-      .parse::<u32>()
-      .map_err(|_e| anyhow!("limit must be between 1 and 50"))?;
+  // spt ... -d ... (specify device to control)
+  // The device is picked in the order described by `resolve_device`
+  pub async fn select_device(&mut self, flag: Option<&str>, env: Option<&str>) -> Result<()> {
+    let devices = match &self.net.app.lock().await.devices {
+      Some(p) => p.devices.clone(),
+      None => Vec::new(),
+    };
+    let behavior = &self.config.behavior;
+    let last_used = self.net.client_config.device_id.clone();
+    let choice = DeviceChoice {
+      flag: flag.map(|d| behavior.unalias_device(d)),
+      env: env.map(|d| behavior.unalias_device(d)),
+      last_used: last_used.as_deref().filter(|_| behavior.remember_device),
+      default: behavior
+        .default_device
+        .as_deref()
+        .map(|d| behavior.unalias_device(d)),
+    };
 
-    // 50 seems to be the maximum limit
-    if num > 50 || num == 0 {
-      return Err(anyhow!("limit must be between 1 and 50"));
+    if let Some(id) = resolve_device(&choice, &devices)? {
+      self.device_changed = true;
+      // Only the last used device is saved, and only if it's enabled
+      if behavior.remember_device && !self.dry_run && last_used.as_ref() != Some(&id) {
+        self.net.client_config.set_device_id(id)?;
+      } else {
+        self.net.client_config.device_id = Some(id);
+      }
+    }
+    Ok(())
+  }
+
+  // spt query ... --limit LIMIT (set max search limit), `--all` fetches
+  // every page there is
+  pub async fn update_query_limits(&mut self, max: Option<&str>, all: bool) -> Result<()> {
+    let num = match max {
+      _ if all => u32::MAX,
+      Some(max) => parse_limit(max, MAX_QUERY_LIMIT)?,
+      None => return Ok(()),
     };
 
     self
@@ -151,29 +400,129 @@ impl<'a> CliApp<'a> {
     Ok(())
   }
 
-  pub async fn volume(&mut self, vol: String) -> Result<()> {
-    let num = vol
-      .parse::<u32>()
-      .map_err(|_e| anyhow!("volume must be between 0 and 100"))?;
+  // Restricted devices (e.g. some TVs or cast targets) don't accept any
+  // commands from the web api, so fail early with a clear message
+  async fn check_restricted(&self, id: &str) -> Result<()> {
+    if let Some(devices) = &self.net.app.lock().await.devices {
+      if let Some(d) = devices.devices.iter().find(|d| d.id == id) {
+        if d.is_restricted {
+          return Err(anyhow!(
+            "device '{}' is restricted and can't be controlled (use --force to try anyway)",
+            d.name
+          ));
+        }
+      }
+    }
+    Ok(())
+  }
+
+  // Spotify reports which actions it would reject for the current item
+  // (e.g. skipping during an ad), so don't bother sending those
+  async fn check_allowed(&self, key: DisallowKey, force: bool) -> Result<()> {
+    if force {
+      return Ok(());
+    }
+    if let Some(c) = &self.net.app.lock().await.current_playback_context {
+      if c.actions.disallows.get(&key) == Some(&true) {
+        let during_ad = c.currently_playing_type == CurrentlyPlayingType::Advertisement;
+        return Err(anyhow!(disallowed_message(key, during_ad)));
+      }
+    }
+    Ok(())
+  }
 
-    // Check if it's in range
-    if num > 100 {
-      return Err(anyhow!("volume must be between 0 and 100"));
+  pub async fn volume(&mut self, vol: String, force: bool) -> Result<()> {
+    // Relative volumes need the current one, which may have changed since
+    // the playback was fetched
+    let current = if vol.starts_with(['+', '-']) {
+      self.net.app.lock().await.current_playback_context = None;
+      self
+        .net
+        .handle_network_event(IoEvent::GetCurrentPlayback)
+        .await;
+      match &self.net.app.lock().await.current_playback_context {
+        Some(context) => context.device.volume_percent,
+        None => return Err(anyhow!("no context available")),
+      }
+    } else {
+      0
     };
+    let num = volume_level(&vol, current)?;
 
-    self
+    if !force {
+      if let Some(id) = self.net.client_config.device_id.clone() {
+        self.check_restricted(&id).await?;
+      }
+    }
+
+    self.mutate(IoEvent::ChangeVolume(num as u8)).await;
+    if self.config.behavior.remember_device_volume && !self.dry_run {
+      if let Some(id) = self.current_device_id().await {
+        let path = self
+          .net
+          .client_config
+          .get_or_build_paths()?
+          .device_volumes_path;
+        let mut volumes = DeviceVolumes::load(&path);
+        volumes.set(&id, num as u8);
+        volumes.save(&path)?;
+      }
+    }
+    Ok(())
+  }
+
+  // spt playback --transfer DEVICE --restore-volume
+  // Devices without a saved volume are left as they are
+  pub async fn restore_volume(&mut self) -> Result<()> {
+    if !self.config.behavior.remember_device_volume {
+      return Err(anyhow!(
+        "--restore-volume needs `remember_device_volume: true` in the config"
+      ));
+    }
+    let id = match self.current_device_id().await {
+      Some(id) => id,
+      None => return Ok(()),
+    };
+    let path = self
       .net
-      .handle_network_event(IoEvent::ChangeVolume(num as u8))
-      .await;
+      .client_config
+      .get_or_build_paths()?
+      .device_volumes_path;
+    match DeviceVolumes::load(&path).get(&id) {
+      Some(volume) => self.mutate(IoEvent::ChangeVolume(volume)).await,
+      None => eprintln!("No saved volume for this device, leaving it unchanged"),
+    }
     Ok(())
   }
 
+  // The selected device, or the one that's currently playing
+  async fn current_device_id(&self) -> Option<String> {
+    match &self.net.client_config.device_id {
+      Some(id) => Some(id.clone()),
+      None => self
+        .net
+        .app
+        .lock()
+        .await
+        .current_playback_context
+        .as_ref()
+        .map(|c| c.device.id.clone()),
+    }
+  }
+
   // spt playback --next / --previous
-  pub async fn jump(&mut self, d: &JumpDirection) {
+  pub async fn jump(&mut self, d: &JumpDirection, force: bool) -> Result<()> {
     match d {
-      JumpDirection::Next => self.net.handle_network_event(IoEvent::NextTrack).await,
-      JumpDirection::Previous => self.net.handle_network_event(IoEvent::PreviousTrack).await,
+      JumpDirection::Next => {
+        self.check_allowed(DisallowKey::SkippingNext, force).await?;
+        self.mutate(IoEvent::NextTrack).await
+      }
+      JumpDirection::Previous => {
+        self.check_allowed(DisallowKey::SkippingPrev, force).await?;
+        self.mutate(IoEvent::PreviousTrack).await
+      }
     }
+    Ok(())
   }
 
   // spt query -l ...
@@ -222,20 +571,14 @@ impl<'a> CliApp<'a> {
           .net
           .handle_network_event(IoEvent::GetCurrentSavedTracks(None))
           .await;
-        let liked_songs = self
-          .net
-          .app
-          .lock()
-          .await
-          .track_table
-          .tracks
+        let tracks = self.net.app.lock().await.track_table.tracks.clone();
+        self
+          .fetch_audio_features(format, tracks.iter().filter_map(|t| t.id.clone()).collect())
+          .await;
+        self.fetch_albums(format, Self::album_ids(&tracks)).await;
+        let liked_songs = tracks
           .iter()
-          .map(|t| {
-            self.format_output(
-              format.to_string(),
-              Format::from_type(FormatType::Track(Box::new(t.clone()))),
-            )
-          })
+          .map(|t| self.format_track(format, t))
           .collect::<Vec<String>>();
         // Check if there are any liked songs
         if liked_songs.is_empty() {
@@ -244,97 +587,751 @@ impl<'a> CliApp<'a> {
           liked_songs.join("\n")
         }
       }
+      Type::Album => {
+        self
+          .net
+          .handle_network_event(IoEvent::GetCurrentUserSavedAlbums(None))
+          .await;
+        let albums = match self
+          .net
+          .app
+          .lock()
+          .await
+          .library
+          .saved_albums
+          .get_results(None)
+        {
+          Some(page) => page.items.iter().map(|s| s.album.clone()).collect(),
+          None => Vec::new(),
+        };
+        if albums.is_empty() {
+          "No saved albums found".to_string()
+        } else {
+          albums
+            .into_iter()
+            .map(|a: FullAlbum| {
+              self.format_output(
+                format.to_string(),
+                vec![
+                  Format::Year(release_year(&a.release_date)),
+                  Format::Album(a.name),
+                  Format::Artist(join_artists(a.artists)),
+                  Format::Uri(a.uri),
+                ],
+              )
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+        }
+      }
+      Type::Show => {
+        self
+          .net
+          .handle_network_event(IoEvent::GetCurrentUserSavedShows(None))
+          .await;
+        let shows = match self
+          .net
+          .app
+          .lock()
+          .await
+          .library
+          .saved_shows
+          .get_results(None)
+        {
+          Some(page) => page.items.iter().map(|s| s.show.clone()).collect(),
+          None => Vec::new(),
+        };
+        if shows.is_empty() {
+          "No saved shows found".to_string()
+        } else {
+          shows
+            .into_iter()
+            .map(|s| {
+              self.format_output(
+                format.to_string(),
+                Format::from_type(FormatType::Show(Box::new(s))),
+              )
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+        }
+      }
       // Enforced by clap
       _ => unreachable!(),
     }
   }
 
-  // spt playback --transfer DEVICE
-  pub async fn transfer_playback(&mut self, device: &str) -> Result<()> {
-    // Get the device id by name
-    let mut id = String::new();
-    if let Some(devices) = &self.net.app.lock().await.devices {
-      for d in &devices.devices {
-        if d.name == device {
-          id.push_str(d.id.as_str());
-          break;
+  // spt list --artists
+  pub async fn list_artists(&mut self, format: &str, sort: ArtistSort) -> String {
+    self
+      .net
+      .handle_network_event(IoEvent::GetFollowedArtists(None))
+      .await;
+    // The endpoint uses cursors, so the pages have to be fetched one by one
+    loop {
+      let (after, pages) = {
+        let app = self.net.app.lock().await;
+        let pages = &app.library.saved_artists.pages;
+        let fetched = pages.iter().map(|p| p.items.len()).sum::<usize>();
+        let after = match pages.last() {
+          Some(page) if page.next.is_some() && fetched < self.net.large_search_limit as usize => {
+            page.cursors.after.clone()
+          }
+          _ => None,
+        };
+        (after, pages.len())
+      };
+      match after {
+        Some(after) => {
+          self
+            .net
+            .handle_network_event(IoEvent::GetFollowedArtists(Some(after)))
+            .await
         }
+        None => break,
       }
-    };
+      // Stop if the request failed
+      if self.net.app.lock().await.library.saved_artists.pages.len() == pages {
+        break;
+      }
+    }
 
-    if id.is_empty() {
-      Err(anyhow!("no device with name '{}'", device))
+    let mut artists = self
+      .net
+      .app
+      .lock()
+      .await
+      .library
+      .saved_artists
+      .pages
+      .iter()
+      .flat_map(|p| p.items.clone())
+      .collect::<Vec<FullArtist>>();
+    // The last page can go past the limit
+    artists.truncate(self.net.large_search_limit as usize);
+    sort.sort(&mut artists);
+
+    if artists.is_empty() {
+      "No followed artists found".to_string()
     } else {
-      self
+      artists
+        .iter()
+        .map(|a| {
+          self.format_output(
+            format.to_string(),
+            Format::from_type(FormatType::Artist(Box::new(a.clone()))),
+          )
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+    }
+  }
+
+  // spt top --tracks / --artists
+  pub async fn top(
+    &mut self,
+    item: Type,
+    range: TimeRange,
+    limit: u32,
+    format: &str,
+  ) -> Result<String> {
+    let lines = if matches!(item, Type::Artist) {
+      let artists = self
         .net
-        .handle_network_event(IoEvent::TransferPlaybackToDevice(id.to_string()))
+        .spotify
+        .current_user_top_artists(limit, 0, range)
+        .await
+        .map_err(|e| anyhow!(e))?
+        .items;
+      artists
+        .into_iter()
+        .map(|a| {
+          self.format_output(
+            format.to_string(),
+            Format::from_type(FormatType::Artist(Box::new(a))),
+          )
+        })
+        .collect::<Vec<String>>()
+    } else {
+      let tracks = self
+        .net
+        .spotify
+        .current_user_top_tracks(limit, 0, range)
+        .await
+        .map_err(|e| anyhow!(e))?
+        .items;
+      self
+        .fetch_audio_features(format, tracks.iter().filter_map(|t| t.id.clone()).collect())
         .await;
-      Ok(())
+      self.fetch_albums(format, Self::album_ids(&tracks)).await;
+      tracks
+        .iter()
+        .map(|t| self.format_track(format, t))
+        .collect::<Vec<String>>()
+    };
+    if lines.is_empty() {
+      Err(anyhow!(
+        "spotify doesn't have enough listening history for this time range yet"
+      ))
+    } else {
+      Ok(self.with_header(format, lines.join("\n")))
     }
   }
 
-  pub async fn seek(&mut self, seconds_str: String) -> Result<()> {
-    let seconds = match seconds_str.parse::<i32>() {
-      Ok(s) => s.abs() as u32,
-      Err(_) => return Err(anyhow!("failed to convert seconds to i32")),
-    };
+  // spt recommend --seed-track / --seed-artist / --seed-genre
+  pub async fn recommendations(
+    &mut self,
+    artists: Vec<String>,
+    tracks: Vec<String>,
+    genres: Vec<String>,
+    limit: u32,
+  ) -> Result<Vec<FullTrack>> {
+    let mut artist_ids = Vec::new();
+    for artist in &artists {
+      match parse_id(artist, "artist") {
+        Some(id) => artist_ids.push(id),
+        None => return Err(anyhow!("'{}' isn't an artist uri, link or id", artist)),
+      }
+    }
+    let mut track_ids = Vec::new();
+    for track in &tracks {
+      match parse_track_id(track) {
+        Some(id) => track_ids.push(id),
+        None => return Err(anyhow!("'{}' isn't a track uri, link or id", track)),
+      }
+    }
+    if artist_ids.len() + track_ids.len() + genres.len() > 5 {
+      return Err(anyhow!("spotify accepts at most 5 seeds"));
+    }
+
+    let some = |v: Vec<String>| if v.is_empty() { None } else { Some(v) };
+    let recommendations = self
+      .net
+      .spotify
+      .recommendations(
+        some(artist_ids),
+        some(genres),
+        some(track_ids),
+        limit,
+        None,
+        &serde_json::Map::new(),
+      )
+      .await
+      .map_err(|e| anyhow!("failed to get recommendations: {}", e))?;
+
+    // The recommendations only have simplified tracks
+    let ids = recommendations
+      .tracks
+      .into_iter()
+      .filter_map(|t| t.id)
+      .collect::<Vec<String>>();
+    let mut full_tracks = Vec::new();
+    for chunk in ids.chunks(TRACKS_LIMIT) {
+      let page = self
+        .net
+        .spotify
+        .tracks(chunk.iter().map(|id| id.as_str()).collect(), None)
+        .await
+        .map_err(|e| anyhow!("failed to get the recommended tracks: {}", e))?;
+      full_tracks.extend(page.tracks);
+    }
+    if full_tracks.is_empty() {
+      return Err(anyhow!("spotify has no recommendations for these seeds"));
+    }
+    Ok(full_tracks)
+  }
+
+  pub async fn format_tracks(&mut self, tracks: &[FullTrack], format: &str) -> String {
+    self
+      .fetch_audio_features(format, tracks.iter().filter_map(|t| t.id.clone()).collect())
+      .await;
+    self.fetch_albums(format, Self::album_ids(tracks)).await;
+    let lines = tracks
+      .iter()
+      .map(|t| self.format_track(format, t))
+      .collect::<Vec<String>>();
+    self.with_header(format, lines.join("\n"))
+  }
+
+  // spt recommend --play
+  pub async fn play_tracks(&mut self, tracks: &[FullTrack]) -> String {
+    let uris = tracks
+      .iter()
+      .map(|t| t.uri.clone())
+      .collect::<Vec<String>>();
+    let count = uris.len();
+    if self.dry_run {
+      return format!(
+        "[dry-run] would play {} recommended tracks (PUT /me/player/play)",
+        count
+      );
+    }
+    self
+      .mutate(IoEvent::StartPlayback(None, Some(uris), Some(0)))
+      .await;
+    format!("Playing {} recommended tracks", count)
+  }
 
-    let (current_pos, duration) = {
+  // spt recommend --save-as NAME
+  pub async fn save_as_playlist(&mut self, name: &str, tracks: &[FullTrack]) -> Result<String> {
+    if self.dry_run {
+      return Ok(format!(
+        "[dry-run] would create the playlist '{}' with {} tracks (POST /users/{{user_id}}/playlists)",
+        name,
+        tracks.len()
+      ));
+    }
+    let user_id = self.user_id().await?;
+    let playlist = self
+      .net
+      .spotify
+      .user_playlist_create(&user_id, name, false, None)
+      .await
+      .map_err(|e| anyhow!("failed to create the playlist: {}", e))?;
+    let ids = tracks
+      .iter()
+      .filter_map(|t| t.id.clone())
+      .collect::<Vec<String>>();
+    for chunk in ids.chunks(PLAYLIST_TRACKS_CHANGE_LIMIT) {
       self
         .net
-        .handle_network_event(IoEvent::GetCurrentPlayback)
-        .await;
-      let app = self.net.app.lock().await;
-      if let Some(CurrentlyPlaybackContext {
-        progress_ms: Some(ms),
-        item: Some(item),
-        ..
-      }) = &app.current_playback_context
-      {
-        let duration = match item {
-          PlayingItem::Track(track) => track.duration_ms,
-          PlayingItem::Episode(episode) => episode.duration_ms,
-        };
+        .spotify
+        .user_playlist_add_tracks(&user_id, &playlist.id, chunk, None)
+        .await
+        .map_err(|e| {
+          anyhow!(
+            "created {} but failed to add the tracks: {}",
+            playlist.uri,
+            e
+          )
+        })?;
+    }
+    Ok(format!(
+      "Created {} ({}) with {} tracks",
+      playlist.name,
+      playlist.uri,
+      ids.len()
+    ))
+  }
 
-        (*ms as u32, duration)
-      } else {
-        return Err(anyhow!("no context available"));
-      }
+  // spt playback --transfer DEVICE
+  pub async fn transfer_playback(&mut self, device: &str, force: bool) -> Result<()> {
+    let device = self.config.behavior.unalias_device(device);
+    let id = match &self.net.app.lock().await.devices {
+      Some(p) => find_device(&p.devices, device)?.id.clone(),
+      None => return Err(anyhow!("no device with name '{}' available", device)),
     };
+    if !force {
+      self.check_restricted(&id).await?;
+    }
+    self.device_changed = true;
+    self.mutate(IoEvent::TransferPlaybackToDevice(id)).await;
+    Ok(())
+  }
 
-    // Convert secs to ms
-    let ms = seconds * 1000;
-    // Calculate new positon
-    let position_to_seek = if seconds_str.starts_with('+') {
-      current_pos + ms
-    } else if seconds_str.starts_with('-') {
-      // Jump to the beginning if the position_to_seek would be
-      // negative, must be checked before the calculation to avoid
-      // an 'underflow'
-      if ms > current_pos {
-        0u32
-      } else {
-        current_pos - ms
-      }
-    } else {
-      // Absolute value of the track
-      seconds * 1000
-    };
+  pub async fn seek(&mut self, seconds_str: String, force: bool) -> Result<()> {
+    let app = self.net.app;
+    let (current_pos, duration) = fresh_progress(
+      app,
+      self.net.handle_network_event(IoEvent::GetCurrentPlayback),
+    )
+    .await?;
+
+    let position_to_seek = seek_position(&seconds_str, current_pos, duration)?;
 
     // Check if position_to_seek is greater than duration (next track)
     if position_to_seek > duration {
-      self.jump(&JumpDirection::Next).await;
+      self.jump(&JumpDirection::Next, force).await?;
     } else {
       // This seeks to a position in the current song
-      self
-        .net
-        .handle_network_event(IoEvent::Seek(position_to_seek))
-        .await;
+      self.check_allowed(DisallowKey::Seeking, force).await?;
+      self.mutate(IoEvent::Seek(position_to_seek)).await;
     }
 
     Ok(())
   }
 
+  // spt queue --add URI... / --from-stdin
+  pub async fn add_to_queue(&mut self, items: Vec<String>) -> Result<String> {
+    let mut uris = Vec::new();
+    for item in &items {
+      match queueable_uri(item) {
+        Some(uri) => uris.push(uri),
+        None => {
+          return Err(anyhow!(
+            "'{}' isn't a track or episode, only those can be queued",
+            item
+          ))
+        }
+      }
+    }
+
+    if self.dry_run {
+      return Ok(format!(
+        "[dry-run] would add {} items to the queue (POST /me/player/queue)",
+        uris.len()
+      ));
+    }
+
+    // Queued in the given order, stop at the first error
+    for (done, uri) in uris.iter().enumerate() {
+      self.mutate(IoEvent::AddItemToQueue(uri.clone())).await;
+      let error = std::mem::take(&mut self.net.app.lock().await.api_error);
+      if !error.is_empty() {
+        return Err(anyhow!(
+          "added {} of {} items before an error occurred: {}",
+          done,
+          uris.len(),
+          error
+        ));
+      }
+    }
+    Ok(format!("Added {} items to the queue", uris.len()))
+  }
+
+  // The playlist endpoints need the id of the current user
+  async fn user_id(&self) -> Result<String> {
+    self
+      .net
+      .spotify
+      .current_user()
+      .await
+      .map(|u| u.id)
+      .map_err(|e| anyhow!("failed to get the current user: {}", e))
+  }
+
+  // spt playlist create NAME
+  pub async fn create_playlist(
+    &mut self,
+    name: &str,
+    public: bool,
+    description: Option<String>,
+  ) -> Result<String> {
+    if self.dry_run {
+      return Ok(format!(
+        "[dry-run] would create the playlist '{}' (POST /users/{{user_id}}/playlists)",
+        name
+      ));
+    }
+    let user_id = self.user_id().await?;
+    let playlist = self
+      .net
+      .spotify
+      .user_playlist_create(&user_id, name, public, description)
+      .await
+      .map_err(|e| anyhow!("failed to create the playlist: {}", e))?;
+    Ok(format!("Created {} ({})", playlist.name, playlist.uri))
+  }
+
+  // spt playlist delete PLAYLIST
+  // Spotify doesn't delete playlists, the owner just unfollows them
+  pub async fn delete_playlist(&mut self, playlist: &str) -> Result<String> {
+    let id = parse_id(playlist, "playlist")
+      .ok_or_else(|| anyhow!("'{}' isn't a playlist uri, link or id", playlist))?;
+    if self.dry_run {
+      return Ok(format!(
+        "[dry-run] would delete spotify:playlist:{} (DELETE /playlists/{{id}}/followers)",
+        id
+      ));
+    }
+    let user_id = self.user_id().await?;
+    self
+      .net
+      .spotify
+      .user_playlist_unfollow(&user_id, &id)
+      .await
+      .map_err(|e| anyhow!("failed to delete the playlist: {}", e))?;
+    Ok(format!("Deleted spotify:playlist:{}", id))
+  }
+
+  // spt playlist rename PLAYLIST NAME
+  pub async fn rename_playlist(&mut self, playlist: &str, name: &str) -> Result<String> {
+    let id = parse_id(playlist, "playlist")
+      .ok_or_else(|| anyhow!("'{}' isn't a playlist uri, link or id", playlist))?;
+    if self.dry_run {
+      return Ok(format!(
+        "[dry-run] would rename spotify:playlist:{} to '{}' (PUT /playlists/{{id}})",
+        id, name
+      ));
+    }
+    let user_id = self.user_id().await?;
+    self
+      .net
+      .spotify
+      .user_playlist_change_detail(&user_id, &id, Some(name), None, None, None)
+      .await
+      .map_err(|e| anyhow!("failed to rename the playlist: {}", e))?;
+    Ok(format!("Renamed spotify:playlist:{} to '{}'", id, name))
+  }
+
+  // spt playlist export PLAYLIST --format FORMAT [--out FILE]
+  pub async fn export_playlist(
+    &mut self,
+    playlist: &str,
+    format: ExportFormat,
+    out: Option<&str>,
+  ) -> Result<String> {
+    let id = parse_id(playlist, "playlist")
+      .ok_or_else(|| anyhow!("'{}' isn't a playlist uri, link or id", playlist))?;
+    let details = self
+      .net
+      .spotify
+      .playlist(&id, None, None)
+      .await
+      .map_err(|e| anyhow!("failed to get the playlist: {}", e))?;
+
+    let spotify = &self.net.spotify;
+    let items = all_items(100, |limit, offset| {
+      spotify.user_playlist_tracks("spotify", &id, None, limit, offset, None)
+    })
+    .await
+    .map_err(|e| {
+      anyhow!(
+        "failed to get the {} tracks of the playlist: {}",
+        details.tracks.total,
+        e
+      )
+    })?;
+    let tracks = items
+      .iter()
+      .filter_map(ExportedTrack::from_playlist_track)
+      .collect::<Vec<ExportedTrack>>();
+    let rendered = export::render(format, &details, &tracks);
+
+    match out {
+      Some(path) => {
+        std::fs::write(path, rendered + "\n")
+          .map_err(|e| anyhow!("failed to write {}: {}", path, e))?;
+        let mut message = format!(
+          "Exported {} tracks of '{}' to {}",
+          tracks.len(),
+          details.name,
+          path
+        );
+        // Tracks that were removed from spotify can't be exported
+        if tracks.len() < items.len() {
+          message.push_str(&format!(
+            ", skipped {} unavailable tracks",
+            items.len() - tracks.len()
+          ));
+        }
+        Ok(message)
+      }
+      None => Ok(rendered),
+    }
+  }
+
+  // The id of the best search result for a row of an imported file
+  async fn find_import_match(&self, row: &ImportRow) -> Result<Option<String>> {
+    for query in row.queries() {
+      let tracks = match self
+        .net
+        .spotify
+        .search(&query, SearchType::Track, 10, 0, None, None)
+        .await
+        .map_err(|e| anyhow!("the search failed: {}", e))?
+      {
+        SearchResult::Tracks(page) => page.items,
+        _ => Vec::new(),
+      };
+      // An isrc identifies the recording, so its first result is taken as is
+      let found = if query.starts_with("isrc:") {
+        tracks.first()
+      } else {
+        best_match(row, &tracks)
+      };
+      if let Some(id) = found.and_then(|t| t.id.clone()) {
+        return Ok(Some(id));
+      }
+    }
+    Ok(None)
+  }
+
+  // spt playlist import FILE --name NAME
+  pub async fn import_playlist(&mut self, path: &str, name: &str, public: bool) -> Result<String> {
+    let contents =
+      std::fs::read_to_string(path).map_err(|e| anyhow!("failed to read {}: {}", path, e))?;
+    let rows = parse_rows(&contents)?;
+
+    let mut ids = Vec::new();
+    let mut unmatched = Vec::new();
+    for row in &rows {
+      let id = match &row.id {
+        Some(id) => Ok(Some(id.clone())),
+        None => self.find_import_match(row).await,
+      };
+      // One failed search doesn't lose the rest of the file
+      match id {
+        Ok(Some(id)) => ids.push(id),
+        Ok(None) => unmatched.push(format!("  {}", row.describe())),
+        Err(e) => unmatched.push(format!("  {} ({})", row.describe(), e)),
+      }
+    }
+    if ids.is_empty() {
+      return Err(anyhow!("none of the {} tracks were found", rows.len()));
+    }
+
+    let mut message = if self.dry_run {
+      format!(
+        "[dry-run] would create the playlist '{}' with {} of {} tracks",
+        name,
+        ids.len(),
+        rows.len()
+      )
+    } else {
+      let user_id = self.user_id().await?;
+      let playlist = self
+        .net
+        .spotify
+        .user_playlist_create(&user_id, name, public, None)
+        .await
+        .map_err(|e| anyhow!("failed to create the playlist: {}", e))?;
+      for (done, chunk) in ids.chunks(PLAYLIST_TRACKS_CHANGE_LIMIT).enumerate() {
+        self
+          .net
+          .spotify
+          .user_playlist_add_tracks(&user_id, &playlist.id, chunk, None)
+          .await
+          .map_err(|e| {
+            anyhow!(
+              "created {} but only added {} of {} tracks: {}",
+              playlist.uri,
+              done * PLAYLIST_TRACKS_CHANGE_LIMIT,
+              ids.len(),
+              e
+            )
+          })?;
+      }
+      format!(
+        "Created {} ({}) with {} of {} tracks",
+        playlist.name,
+        playlist.uri,
+        ids.len(),
+        rows.len()
+      )
+    };
+    if !unmatched.is_empty() {
+      message.push_str(&format!("\nNo match for:\n{}", unmatched.join("\n")));
+    }
+    Ok(message)
+  }
+
+  // spt playlist add / remove PLAYLIST TRACK...
+  pub async fn change_playlist_tracks(
+    &mut self,
+    playlist: &str,
+    tracks: Vec<String>,
+    add: bool,
+  ) -> Result<String> {
+    let id = parse_id(playlist, "playlist")
+      .ok_or_else(|| anyhow!("'{}' isn't a playlist uri, link or id", playlist))?;
+    let mut ids = Vec::new();
+    for track in &tracks {
+      match parse_track_id(track) {
+        Some(id) => ids.push(id),
+        None => return Err(anyhow!("'{}' isn't a track uri, link or id", track)),
+      }
+    }
+
+    let verb = if add { "Added" } else { "Removed" };
+    if self.dry_run {
+      let action = if add {
+        "add (POST /playlists/{id}/tracks)"
+      } else {
+        "remove (DELETE /playlists/{id}/tracks)"
+      };
+      return Ok(format!(
+        "[dry-run] would {} {} tracks of spotify:playlist:{}",
+        action,
+        ids.len(),
+        id
+      ));
+    }
+
+    let user_id = self.user_id().await?;
+    let mut done = 0;
+    for chunk in ids.chunks(PLAYLIST_TRACKS_CHANGE_LIMIT) {
+      let result = if add {
+        self
+          .net
+          .spotify
+          .user_playlist_add_tracks(&user_id, &id, chunk, None)
+          .await
+      } else {
+        self
+          .net
+          .spotify
+          .user_playlist_remove_all_occurrences_of_tracks(&user_id, &id, chunk, None)
+          .await
+      };
+      if let Err(e) = result {
+        return Err(anyhow!(
+          "{} {} of {} tracks before an error occurred: {}",
+          verb,
+          done,
+          ids.len(),
+          e
+        ));
+      }
+      done += chunk.len();
+    }
+    Ok(format!("{} {} tracks", verb, done))
+  }
+
+  // spt playback --like / --dislike --from-stdin
+  pub async fn like_tracks(&mut self, lines: Vec<String>, like: bool) -> Result<String> {
+    let mut ids = Vec::new();
+    let mut invalid = 0;
+    for line in &lines {
+      match parse_track_id(line) {
+        Some(id) => ids.push(id),
+        None => {
+          eprintln!("Skipping invalid track '{}'", line);
+          invalid += 1;
+        }
+      }
+    }
+
+    let verb = if like { "Liked" } else { "Removed" };
+    if self.dry_run {
+      let action = if like {
+        "like (PUT /me/tracks)"
+      } else {
+        "remove (DELETE /me/tracks)"
+      };
+      return Ok(format!("[dry-run] would {} {} tracks", action, ids.len()));
+    }
+
+    let mut done = 0;
+    for chunk in ids.chunks(SAVED_TRACKS_CHANGE_LIMIT) {
+      let result = if like {
+        self.net.spotify.current_user_saved_tracks_add(chunk).await
+      } else {
+        self
+          .net
+          .spotify
+          .current_user_saved_tracks_delete(chunk)
+          .await
+      };
+      if let Err(e) = result {
+        return Err(anyhow!(
+          "{} {} of {} tracks before an error occurred: {}",
+          verb,
+          done,
+          ids.len(),
+          e
+        ));
+      }
+      done += chunk.len();
+    }
+
+    let mut output = format!("{} {} tracks", verb, done);
+    if invalid > 0 {
+      output.push_str(&format!(", skipped {} invalid", invalid));
+    }
+    Ok(output)
+  }
+
   // spt playback --like / --dislike / --shuffle / --repeat
   pub async fn mark(&mut self, flag: Flag) -> Result<()> {
     let c = {
@@ -359,30 +1356,19 @@ impl<'a> CliApp<'a> {
         // Want to like but is already liked -> do nothing
         // Want to like and is not liked yet -> like
         if s && !self.is_a_saved_track(&id).await {
-          self
-            .net
-            .handle_network_event(IoEvent::ToggleSaveTrack(id))
-            .await;
+          self.mutate(IoEvent::ToggleSaveTrack(id)).await;
         // Want to dislike but is already disliked -> do nothing
         // Want to dislike and is liked currently -> remove like
         } else if !s && self.is_a_saved_track(&id).await {
-          self
-            .net
-            .handle_network_event(IoEvent::ToggleSaveTrack(id))
-            .await;
+          self.mutate(IoEvent::ToggleSaveTrack(id)).await;
         }
       }
-      Flag::Shuffle => {
-        self
-          .net
-          .handle_network_event(IoEvent::Shuffle(c.shuffle_state))
-          .await
+      Flag::Shuffle => self.mutate(IoEvent::Shuffle(c.shuffle_state)).await,
+      Flag::Repeat(None) => {
+        self.mutate(IoEvent::Repeat(c.repeat_state)).await;
       }
-      Flag::Repeat => {
-        self
-          .net
-          .handle_network_event(IoEvent::Repeat(c.repeat_state))
-          .await;
+      Flag::Repeat(Some(state)) => {
+        self.mutate(IoEvent::SetRepeat(state)).await;
       }
     }
 
@@ -410,66 +1396,211 @@ impl<'a> CliApp<'a> {
       .clone()
       .ok_or_else(|| anyhow!("no context available"))?;
 
-    let playing_item = context.item.ok_or_else(|| anyhow!("no track playing"))?;
-
-    let mut hs = match playing_item {
+    let playing_item = context
+      .item
+      .clone()
+      .ok_or_else(|| anyhow!("no track playing"))?;
+    let liked = match &playing_item {
       PlayingItem::Track(track) => {
-        let id = track.id.clone().unwrap_or_default();
-        let mut hs = Format::from_type(FormatType::Track(Box::new(track.clone())));
-        if let Some(ms) = context.progress_ms {
-          hs.push(Format::Position((ms, track.duration_ms)))
-        }
-        hs.push(Format::Flags((
-          context.repeat_state,
-          context.shuffle_state,
-          self.is_a_saved_track(&id).await,
-        )));
-        hs
-      }
-      PlayingItem::Episode(episode) => {
-        let mut hs = Format::from_type(FormatType::Episode(Box::new(episode.clone())));
-        if let Some(ms) = context.progress_ms {
-          hs.push(Format::Position((ms, episode.duration_ms)))
-        }
-        hs.push(Format::Flags((
-          context.repeat_state,
-          context.shuffle_state,
-          false,
-        )));
-        hs
+        self
+          .is_a_saved_track(&track.id.clone().unwrap_or_default())
+          .await
       }
+      PlayingItem::Episode(_) => false,
     };
 
-    hs.push(Format::Device(context.device.name));
-    hs.push(Format::Volume(context.device.volume_percent));
-    hs.push(Format::Playing(context.is_playing));
+    let mut hs = Format::from_playback(&context, context.progress_ms, liked);
+    if let PlayingItem::Track(track) = playing_item {
+      let id = track.id.clone().unwrap_or_default();
+      if Format::needs_audio_features(&format) {
+        self.fetch_audio_features(&format, vec![id.clone()]).await;
+        hs.extend(Format::from_audio_features(self.audio_features.get(&id)));
+      }
+      if let Some(album_id) = track.album.id.clone() {
+        if Format::needs_album(&format) {
+          self.fetch_albums(&format, vec![album_id.clone()]).await;
+          hs.extend(Format::from_album(self.albums.get(&album_id), Some(&track)));
+        }
+      }
+      if format.contains("%dur_ctx") {
+        let progress = context.progress_ms.unwrap_or(0);
+        let remaining = match &context.context {
+          Some(c) => self
+            .load_context_tracks(&c.uri)
+            .await
+            .and_then(|t| remaining_in_context(t, &track.uri, progress)),
+          None => None,
+        };
+        hs.push(Format::ContextRemaining(remaining));
+      }
+    }
 
     Ok(self.format_output(format, hs))
   }
 
+  // `spt playback --output BAR`, nothing playing isn't an error for a bar
+  pub async fn get_bar_status(&mut self, format: String, bar: StatusBar) -> String {
+    let paused_color = self.config.behavior.bar_paused_color.clone();
+    match self.get_status(format).await {
+      Ok(text) => {
+        let playing = match &self.net.app.lock().await.current_playback_context {
+          Some(context) => context.is_playing,
+          None => false,
+        };
+        bar.render(&text, Some(playing), &paused_color)
+      }
+      Err(_) => bar.render("", None, &paused_color),
+    }
+  }
+
+  // `spt playback --follow`, prints the status whenever it changes until spt is killed
+  pub async fn follow_status(
+    &mut self,
+    format: String,
+    bar: Option<StatusBar>,
+    interval: FollowInterval,
+  ) -> Result<String> {
+    let mut last = None;
+    loop {
+      let status = match bar {
+        Some(bar) => Ok(self.get_bar_status(format.clone(), bar).await),
+        None => self
+          .get_status(format.clone())
+          .await
+          .map_err(|e| e.to_string()),
+      };
+      if last.as_ref() != Some(&status) {
+        match &status {
+          Ok(line) => println!("{}", line),
+          Err(e) => eprintln!("Error: {}", e),
+        }
+        last = Some(status);
+      }
+      let (is_playing, remaining_ms) = match &self.net.app.lock().await.current_playback_context {
+        Some(context) => {
+          let duration = match &context.item {
+            Some(PlayingItem::Track(track)) => Some(track.duration_ms),
+            Some(PlayingItem::Episode(episode)) => Some(episode.duration_ms),
+            None => None,
+          };
+          let remaining = duration
+            .zip(context.progress_ms)
+            .map(|(duration, progress)| duration.saturating_sub(progress));
+          (context.is_playing, remaining)
+        }
+        None => (false, None),
+      };
+      tokio::time::sleep(interval.next(is_playing, remaining_ms)).await;
+    }
+  }
+
+  // Picks a random position in an album, playlist or show
+  async fn random_offset(&mut self, uri: &str) -> Result<usize> {
+    let id = uri.rsplit(':').next().unwrap();
+    let total = if uri.contains("spotify:playlist:") {
+      self
+        .net
+        .spotify
+        .playlist(id, None, None)
+        .await
+        .map(|p| p.tracks.total)
+    } else if uri.contains("spotify:album:") {
+      self.net.spotify.album(id).await.map(|a| a.tracks.total)
+    } else if uri.contains("spotify:show:") {
+      self
+        .net
+        .spotify
+        .get_a_show(id.to_string(), None)
+        .await
+        .map(|s| s.episodes.total)
+    } else {
+      return Err(anyhow!(
+        "a random start is only possible for albums, playlists and shows"
+      ));
+    }
+    .map_err(|e| anyhow!(e.to_string()))?;
+
+    if total == 0 {
+      return Err(anyhow!("'{}' has no items to play", uri));
+    }
+    Ok(thread_rng().gen_range(0..total) as usize)
+  }
+
+  // Finds the episode and position to continue with for `--resume`, for shows
+  // this is the latest episode which was started but not finished
+  async fn resume_point(&mut self, uri: &str) -> Result<Option<(String, String, u32)>> {
+    let id = uri.rsplit(':').next().unwrap().to_string();
+    if uri.contains("spotify:episode:") {
+      let episode = self
+        .net
+        .spotify
+        .get_an_episode(id, None)
+        .await
+        .map_err(|e| anyhow!(e.to_string()))?;
+      Ok(resume_position(episode.resume_point.as_ref()).map(|ms| (episode.uri, episode.name, ms)))
+    } else if uri.contains("spotify:show:") {
+      let episodes = self
+        .net
+        .spotify
+        .get_shows_episodes(id, 50, 0, None)
+        .await
+        .map_err(|e| anyhow!(e.to_string()))?;
+      Ok(
+        episodes
+          .items
+          .into_iter()
+          .find_map(|e| resume_position(e.resume_point.as_ref()).map(|ms| (e.uri, e.name, ms))),
+      )
+    } else {
+      Err(anyhow!("--resume only works with episodes and shows"))
+    }
+  }
+
   // spt play -u URI
-  pub async fn play_uri(&mut self, uri: String, queue: bool, random: bool) {
-    let offset = if random {
-      // Only works with playlists for now
-      if uri.contains("spotify:playlist:") {
-        let id = uri.split(':').last().unwrap();
-        match self.net.spotify.playlist(id, None, None).await {
-          Ok(p) => {
-            let num = p.tracks.total;
-            Some(thread_rng().gen_range(0..num) as usize)
-          }
-          Err(e) => {
-            self
-              .net
-              .app
-              .lock()
-              .await
-              .handle_error(anyhow!(e.to_string()));
-            return;
-          }
+  pub async fn play_uri(
+    &mut self,
+    uri: String,
+    queue: bool,
+    random: bool,
+    start_random: bool,
+    resume: bool,
+  ) {
+    if resume {
+      match self.resume_point(&uri).await {
+        Ok(Some((episode, name, ms))) => {
+          self
+            .mutate(IoEvent::StartPlaybackAt(
+              None,
+              Some(vec![episode]),
+              Some(0),
+              ms,
+            ))
+            .await;
+          // Report on stderr to keep the output parsable
+          eprintln!(
+            "Resuming '{}' at {}",
+            name,
+            crate::ui::util::millis_to_minutes(ms as u128)
+          );
+          return;
+        }
+        // Nothing to resume, so it's played from the beginning
+        Ok(None) => (),
+        Err(e) => {
+          self.net.app.lock().await.handle_error(e);
+          return;
+        }
+      }
+    }
+
+    // `--random` only works with playlists, `--start-random-track` with every context
+    let offset = if start_random || (random && uri.contains("spotify:playlist:")) {
+      match self.random_offset(&uri).await {
+        Ok(o) => Some(o),
+        Err(e) => {
+          self.net.app.lock().await.handle_error(e);
+          return;
         }
-      } else {
-        None
       }
     } else {
       None
@@ -477,14 +1608,10 @@ impl<'a> CliApp<'a> {
 
     if uri.contains("spotify:track:") {
       if queue {
-        self
-          .net
-          .handle_network_event(IoEvent::AddItemToQueue(uri))
-          .await;
+        self.mutate(IoEvent::AddItemToQueue(uri)).await;
       } else {
         self
-          .net
-          .handle_network_event(IoEvent::StartPlayback(
+          .mutate(IoEvent::StartPlayback(
             None,
             Some(vec![uri.clone()]),
             Some(0),
@@ -493,77 +1620,112 @@ impl<'a> CliApp<'a> {
       }
     } else {
       self
-        .net
-        .handle_network_event(IoEvent::StartPlayback(Some(uri.clone()), None, offset))
+        .mutate(IoEvent::StartPlayback(Some(uri.clone()), None, offset))
         .await;
     }
   }
 
   // spt play -n NAME ...
-  pub async fn play(&mut self, name: String, item: Type, queue: bool, random: bool) -> Result<()> {
-    self
-      .net
-      .handle_network_event(IoEvent::GetSearchResults(name.clone(), None))
-      .await;
-    // Get the uri of the first found
-    // item + the offset or return an error message
-    let uri = {
-      let results = &self.net.app.lock().await.search_results;
-      match item {
-        Type::Track => {
-          if let Some(r) = &results.tracks {
-            r.items[0].uri.clone()
-          } else {
-            return Err(anyhow!("no tracks with name '{}'", name));
-          }
-        }
-        Type::Album => {
-          if let Some(r) = &results.albums {
-            let album = &r.items[0];
-            if let Some(uri) = &album.uri {
-              uri.clone()
-            } else {
-              return Err(anyhow!("album {} has no uri", album.name));
-            }
-          } else {
-            return Err(anyhow!("no albums with name '{}'", name));
-          }
-        }
-        Type::Artist => {
-          if let Some(r) = &results.artists {
-            r.items[0].uri.clone()
-          } else {
-            return Err(anyhow!("no artists with name '{}'", name));
-          }
-        }
-        Type::Show => {
-          if let Some(r) = &results.shows {
-            r.items[0].uri.clone()
-          } else {
-            return Err(anyhow!("no shows with name '{}'", name));
-          }
-        }
-        Type::Playlist => {
-          if let Some(r) = &results.playlists {
-            let p = &r.items[0];
-            // For a random song, create a random offset
-            p.uri.clone()
-          } else {
-            return Err(anyhow!("no playlists with name '{}'", name));
-          }
-        }
-        _ => unreachable!(),
-      }
+  pub async fn play(
+    &mut self,
+    name: String,
+    item: Type,
+    queue: bool,
+    random: bool,
+    start_random: bool,
+    resume: bool,
+  ) -> Result<()> {
+    let uri = match self
+      .candidates(&name, &item, None)
+      .await?
+      .into_iter()
+      .next()
+    {
+      Some((_, uri)) => uri,
+      None => return Err(anyhow!("no {} with name '{}'", item.plural(), name)),
     };
 
     // Play or queue the uri
-    self.play_uri(uri, queue, random).await;
+    self
+      .play_uri(uri, queue, random, start_random, resume)
+      .await;
 
     Ok(())
   }
 
+  // The search results of the `item` type as a name to show and the uri,
+  // `limit` changes how many are fetched
+  pub async fn candidates(
+    &mut self,
+    name: &str,
+    item: &Type,
+    limit: Option<u32>,
+  ) -> Result<Vec<(String, String)>> {
+    if let Some(limit) = limit {
+      self
+        .net
+        .handle_network_event(IoEvent::UpdateSearchLimits(limit, limit))
+        .await;
+    }
+    self
+      .net
+      .handle_network_event(IoEvent::GetSearchResults(name.to_string(), None))
+      .await;
+    let results = &self.net.app.lock().await.search_results;
+    let candidates = match item {
+      Type::Track => results
+        .tracks
+        .iter()
+        .flat_map(|r| &r.items)
+        .map(|t| {
+          let label = format!("{} - {}", t.name, join_artists(t.artists.clone()));
+          (label, t.uri.clone())
+        })
+        .collect(),
+      Type::Album => results
+        .albums
+        .iter()
+        .flat_map(|r| &r.items)
+        .filter_map(|a| {
+          let label = format!("{} - {}", a.name, join_artists(a.artists.clone()));
+          a.uri.clone().map(|uri| (label, uri))
+        })
+        .collect(),
+      Type::Artist => results
+        .artists
+        .iter()
+        .flat_map(|r| &r.items)
+        .map(|a| (a.name.clone(), a.uri.clone()))
+        .collect(),
+      Type::Show => results
+        .shows
+        .iter()
+        .flat_map(|r| &r.items)
+        .map(|s| (format!("{} - {}", s.name, s.publisher), s.uri.clone()))
+        .collect(),
+      Type::Playlist => results
+        .playlists
+        .iter()
+        .flat_map(|r| &r.items)
+        .map(|p| {
+          let owner = p.owner.display_name.as_deref().unwrap_or(&p.owner.id);
+          (format!("{} ({})", p.name, owner), p.uri.clone())
+        })
+        .collect(),
+      _ => unreachable!(),
+    };
+    Ok(candidates)
+  }
+
   // spt query -s SEARCH ...
-  pub async fn query(&mut self, search: String, format: String, item: Type) -> String {
+  pub async fn query(
+    &mut self,
+    search: String,
+    format: String,
+    item: Type,
+    range: DurationRange,
+    sort: SearchSort,
+  ) -> String {
     self
       .net
       .handle_network_event(IoEvent::GetSearchResults(search.clone(), None))
@@ -589,16 +1751,35 @@ impl<'a> CliApp<'a> {
         }
       }
       Type::Track => {
-        if let Some(results) = &app.search_results.tracks {
-          results
+        if let Some(results) = app.search_results.tracks.clone() {
+          // The lock has to be released to fetch the audio features
+          drop(app);
+          let found = results.items.len();
+          let mut tracks = results
             .items
+            .into_iter()
+            .filter(|r| range.contains(r.duration_ms))
+            .collect::<Vec<FullTrack>>();
+          if sort == SearchSort::Release {
+            sort_by_release(&mut tracks, |t| t.album.release_date.as_deref());
+          }
+
+          // Report on stderr to keep the output parsable
+          let filtered = found - tracks.len();
+          if filtered > 0 {
+            eprintln!("{} tracks were filtered out by duration", filtered);
+          }
+
+          self
+            .fetch_audio_features(
+              &format,
+              tracks.iter().filter_map(|t| t.id.clone()).collect(),
+            )
+            .await;
+          self.fetch_albums(&format, Self::album_ids(&tracks)).await;
+          tracks
             .iter()
-            .map(|r| {
-              self.format_output(
-                format.clone(),
-                Format::from_type(FormatType::Track(Box::new(r.clone()))),
-              )
-            })
+            .map(|r| self.format_track(&format, r))
             .collect::<Vec<String>>()
             .join("\n")
         } else {
@@ -640,15 +1821,28 @@ impl<'a> CliApp<'a> {
         }
       }
       Type::Album => {
-        if let Some(results) = &app.search_results.albums {
-          results
-            .items
+        if let Some(results) = app.search_results.albums.clone() {
+          // The lock has to be released to fetch the full albums
+          drop(app);
+          let mut albums = results.items;
+          if sort == SearchSort::Release {
+            sort_by_release(&mut albums, |a| a.release_date.as_deref());
+          }
+          self
+            .fetch_albums(
+              &format,
+              albums.iter().filter_map(|r| r.id.clone()).collect(),
+            )
+            .await;
+          albums
             .iter()
             .map(|r| {
-              self.format_output(
-                format.clone(),
-                Format::from_type(FormatType::Album(Box::new(r.clone()))),
-              )
+              let mut values = Format::from_type(FormatType::Album(Box::new(r.clone())));
+              if Format::needs_album(&format) {
+                let album = r.id.as_ref().and_then(|id| self.albums.get(id));
+                values.extend(Format::from_album(album, None));
+              }
+              self.format_output(format.clone(), values)
             })
             .collect::<Vec<String>>()
             .join("\n")
@@ -660,4 +1854,120 @@ impl<'a> CliApp<'a> {
       _ => unreachable!(),
     }
   }
+
+  // Like `query`, but keeps the uri of every result (spt search --interactive)
+  pub async fn query_with_uris(
+    &mut self,
+    search: String,
+    format: String,
+    item: Type,
+    range: DurationRange,
+    sort: SearchSort,
+  ) -> Result<Vec<(String, String)>> {
+    // The uri is appended behind a separator which won't be part of any name
+    const SEPARATOR: char = '\u{1f}';
+    let output = self
+      .query(
+        search,
+        format!("{}{}%u", format, SEPARATOR),
+        item,
+        range,
+        sort,
+      )
+      .await;
+    let items = output
+      .lines()
+      .filter_map(|l| l.rsplit_once(SEPARATOR))
+      .map(|(line, uri)| (line.to_string(), uri.to_string()))
+      .collect::<Vec<(String, String)>>();
+    if items.is_empty() {
+      // The output is an error message like "no tracks with name ..."
+      Err(anyhow!(output))
+    } else {
+      Ok(items)
+    }
+  }
+
+  // spt lyrics
+  pub async fn lyrics(&mut self, uri: Option<&str>, synced: bool) -> Result<String> {
+    let track = match uri {
+      Some(uri) => {
+        let id = parse_track_id(uri).ok_or_else(|| anyhow!("'{}' isn't a track", uri))?;
+        self.net.spotify.track(&id).await.map_err(|e| anyhow!(e))?
+      }
+      None => {
+        let context = self.net.app.lock().await.current_playback_context.clone();
+        match context.and_then(|c| c.item) {
+          Some(PlayingItem::Track(track)) => track,
+          Some(PlayingItem::Episode(_)) => {
+            return Err(anyhow!("lyrics are only available for tracks"))
+          }
+          None => return Err(anyhow!("no track playing")),
+        }
+      }
+    };
+
+    let provider = self.config.behavior.lyrics_provider;
+    match provider.fetch(&LyricsQuery::from_track(&track)).await? {
+      LyricsStatus::Found(lyrics) if synced => lyrics
+        .to_lrc()
+        .ok_or_else(|| anyhow!("only unsynced lyrics were found for '{}'", track.name)),
+      LyricsStatus::Found(lyrics) => Ok(lyrics.lines().join("\n")),
+      _ => Err(anyhow!("no lyrics found for '{}'", track.name)),
+    }
+  }
+
+  // spt analyze ...
+  pub async fn analyze(&mut self, uris: Vec<String>, format: String, json: bool) -> Result<String> {
+    let batch = uris.len() > 1;
+    let tracks = if uris.is_empty() {
+      let context = self.net.app.lock().await.current_playback_context.clone();
+      match context.and_then(|c| c.item) {
+        Some(PlayingItem::Track(track)) => vec![track],
+        Some(PlayingItem::Episode(_)) => {
+          return Err(anyhow!("audio features are only available for tracks"))
+        }
+        None => return Err(anyhow!("no track playing")),
+      }
+    } else {
+      let mut tracks = Vec::new();
+      for chunk in uris.chunks(TRACKS_LIMIT) {
+        let ids = chunk.iter().map(|u| u.as_str()).collect::<Vec<&str>>();
+        let result = self
+          .net
+          .spotify
+          .tracks(ids, None)
+          .await
+          .map_err(|e| anyhow!(e))?;
+        tracks.extend(result.tracks);
+      }
+      tracks
+    };
+
+    self
+      .load_audio_features(tracks.iter().filter_map(|t| t.id.clone()).collect())
+      .await;
+    self.fetch_albums(&format, Self::album_ids(&tracks)).await;
+
+    if json {
+      let features = tracks
+        .iter()
+        .filter_map(|t| t.id.as_ref().and_then(|id| self.audio_features.get(id)))
+        .collect::<Vec<&AudioFeatures>>();
+      let output = match features.first() {
+        Some(f) if !batch => serde_json::to_string_pretty(f)?,
+        Some(_) => serde_json::to_string_pretty(&features)?,
+        None => return Err(anyhow!("no audio features found")),
+      };
+      Ok(output)
+    } else {
+      Ok(
+        tracks
+          .iter()
+          .map(|t| self.format_track(&format, t))
+          .collect::<Vec<String>>()
+          .join("\n"),
+      )
+    }
+  }
 }
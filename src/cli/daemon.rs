@@ -0,0 +1,229 @@
+use super::handle_matches;
+use crate::app::App;
+use crate::config::ClientConfig;
+use crate::network::{get_spotify, Network};
+use crate::user_config::UserConfig;
+
+use anyhow::{anyhow, Result};
+use clap::{App as ClapApp, ErrorKind};
+use rspotify::{client::Spotify, oauth2::SpotifyOAuth, util::get_token};
+use std::{
+  iter,
+  path::Path,
+  sync::Arc,
+  time::{Duration, SystemTime},
+};
+use tokio::{
+  io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader},
+  sync::Mutex,
+};
+
+// How often the login is checked while no commands come in
+const REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+// Commands are answered one after another, so a client that doesn't send its
+// line or a command that hangs can't keep the others waiting for long
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+const SERVE_TIMEOUT: Duration = Duration::from_secs(120);
+
+// Splits a command line into arguments like a shell, with single and double
+// quotes and backslash escapes
+fn split_args(line: &str) -> Result<Vec<String>> {
+  let mut args = Vec::new();
+  let mut current = String::new();
+  let mut in_arg = false;
+  let mut quote = None;
+  let mut chars = line.chars();
+  while let Some(c) = chars.next() {
+    match (quote, c) {
+      (Some(q), c) if c == q => quote = None,
+      (Some('\''), c) => current.push(c),
+      (_, '\\') => {
+        current.push(chars.next().ok_or_else(|| anyhow!("trailing backslash"))?);
+        in_arg = true;
+      }
+      (Some(_), c) => current.push(c),
+      (None, '\'' | '"') => {
+        quote = Some(c);
+        in_arg = true;
+      }
+      (None, c) if c.is_whitespace() => {
+        if in_arg {
+          args.push(std::mem::take(&mut current));
+          in_arg = false;
+        }
+      }
+      (None, c) => {
+        current.push(c);
+        in_arg = true;
+      }
+    }
+  }
+  if quote.is_some() {
+    return Err(anyhow!("unterminated quote"));
+  }
+  if in_arg {
+    args.push(current);
+  }
+  Ok(args)
+}
+
+// `spt daemon`, runs the commands sent to its socket with one login
+pub struct Daemon<'a> {
+  pub clap_app: ClapApp<'static, 'static>,
+  pub oauth: SpotifyOAuth,
+  pub spotify: Spotify,
+  pub token_expiry: SystemTime,
+  pub client_config: ClientConfig,
+  pub user_config: UserConfig,
+  pub app: &'a Arc<Mutex<App>>,
+}
+
+impl<'a> Daemon<'a> {
+  async fn refresh_token(&mut self) {
+    if SystemTime::now() < self.token_expiry {
+      return;
+    }
+    match get_token(&mut self.oauth).await {
+      Some(token_info) => {
+        let (spotify, token_expiry) = get_spotify(token_info);
+        self.spotify = spotify;
+        self.token_expiry = token_expiry;
+      }
+      None => eprintln!("Failed to refresh authentication token"),
+    }
+  }
+
+  async fn execute(&mut self, line: &str) -> Result<String> {
+    let args = split_args(line)?;
+    let matches = match self
+      .clap_app
+      .clone()
+      .get_matches_from_safe(iter::once("spt".to_string()).chain(args))
+    {
+      Ok(matches) => matches,
+      // `--help` and `--version` are errors to clap
+      Err(e)
+        if matches!(
+          e.kind,
+          ErrorKind::HelpDisplayed | ErrorKind::VersionDisplayed
+        ) =>
+      {
+        return Ok(e.message)
+      }
+      Err(e) => return Err(anyhow!(e.message)),
+    };
+    let (cmd, m) = match matches.subcommand() {
+      ("daemon", _) | (_, None) => return Err(anyhow!("expected a command like `pb --next`")),
+      (cmd, Some(m)) => (cmd, m),
+    };
+    // The daemon's stdin and terminal aren't the client's
+    if m.is_present("from-stdin") || m.is_present("interactive") || m.is_present("follow") {
+      return Err(anyhow!(
+        "`--from-stdin`, `--interactive` and `--follow` can't be used through the daemon"
+      ));
+    }
+
+    self.refresh_token().await;
+    // Picks up the device saved by earlier commands
+    let mut client_config = self.client_config.clone();
+    if client_config
+      .get_or_build_paths()?
+      .config_file_path
+      .exists()
+    {
+      client_config.load_config()?;
+    }
+    let net = Network::new(
+      self.oauth.clone(),
+      self.spotify.clone(),
+      client_config,
+      self.app,
+    );
+    handle_matches(m, cmd.to_string(), net, self.user_config.clone()).await
+  }
+
+  // Reads one command line and answers with its output
+  async fn serve<S: AsyncRead + AsyncWrite>(&mut self, stream: S) -> Result<()> {
+    let (read, mut write) = tokio::io::split(stream);
+    let mut line = String::new();
+    tokio::time::timeout(READ_TIMEOUT, BufReader::new(read).read_line(&mut line))
+      .await
+      .map_err(|_| anyhow!("no command within {}s", READ_TIMEOUT.as_secs()))??;
+    let reply = match self.execute(&line).await {
+      Ok(output) => output,
+      Err(e) => format!("error: {}", e),
+    };
+    write.write_all(format!("{}\n", reply).as_bytes()).await?;
+    write.shutdown().await?;
+    Ok(())
+  }
+
+  #[cfg(unix)]
+  pub async fn run(mut self, socket: &Path) -> Result<()> {
+    use std::{fs, os::unix::fs::PermissionsExt};
+    use tokio::net::{UnixListener, UnixStream};
+
+    // A socket left behind by a daemon that was killed is replaced
+    if socket.exists() {
+      if UnixStream::connect(socket).await.is_ok() {
+        return Err(anyhow!(
+          "another daemon is listening on {}",
+          socket.display()
+        ));
+      }
+      fs::remove_file(socket)?;
+    }
+    let listener = UnixListener::bind(socket)?;
+    // Anyone who can connect controls the playback
+    fs::set_permissions(socket, fs::Permissions::from_mode(0o600))?;
+    eprintln!("Listening on {}", socket.display());
+
+    let mut refresh = tokio::time::interval(REFRESH_INTERVAL);
+    loop {
+      tokio::select! {
+        accepted = listener.accept() => match accepted {
+          Ok((stream, _)) => {
+            match tokio::time::timeout(SERVE_TIMEOUT, self.serve(stream)).await {
+              Ok(Ok(())) => {}
+              Ok(Err(e)) => eprintln!("Failed to answer a command: {}", e),
+              Err(_) => eprintln!(
+                "Gave up on a command after {}s",
+                SERVE_TIMEOUT.as_secs()
+              ),
+            }
+          }
+          Err(e) => eprintln!("Failed to accept a connection: {}", e),
+        },
+        _ = refresh.tick() => self.refresh_token().await,
+        _ = tokio::signal::ctrl_c() => break,
+      }
+    }
+    fs::remove_file(socket)?;
+    Ok(())
+  }
+
+  #[cfg(not(unix))]
+  pub async fn run(self, _socket: &Path) -> Result<()> {
+    Err(anyhow!("`spt daemon` is only supported on unix systems"))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn split_args_test() {
+    assert_eq!(split_args("pb --next\n").unwrap(), vec!["pb", "--next"]);
+    assert_eq!(
+      split_args(r#"play --name "Some Song" -t"#).unwrap(),
+      vec!["play", "--name", "Some Song", "-t"]
+    );
+    assert_eq!(
+      split_args(r#"search 'it''s' a\ b "" "say \"hi\"""#).unwrap(),
+      vec!["search", "its", "a b", "", "say \"hi\""]
+    );
+    assert!(split_args("play --name \"Song").is_err());
+    assert!(split_args("pb \\").is_err());
+  }
+}
@@ -0,0 +1,170 @@
+use anyhow::{anyhow, Result};
+use rspotify::model::playlist::{FullPlaylist, PlaylistTrack};
+use serde::Serialize;
+use serde_json::json;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ExportFormat {
+  Json,
+  M3u8,
+  Xspf,
+}
+
+impl ExportFormat {
+  pub fn from_name(name: &str) -> Result<Self> {
+    match name {
+      "json" => Ok(Self::Json),
+      "m3u8" => Ok(Self::M3u8),
+      "xspf" => Ok(Self::Xspf),
+      _ => Err(anyhow!("unknown export format '{}'", name)),
+    }
+  }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ExportedTrack {
+  pub uri: String,
+  pub name: String,
+  pub artists: Vec<String>,
+  pub album: String,
+  pub duration_ms: u32,
+  pub isrc: Option<String>,
+  pub added_at: String,
+  pub added_by: Option<String>,
+}
+
+impl ExportedTrack {
+  // Tracks that were removed from spotify have no track object left
+  pub fn from_playlist_track(item: &PlaylistTrack) -> Option<Self> {
+    let track = item.track.as_ref()?;
+    Some(ExportedTrack {
+      uri: track.uri.clone(),
+      name: track.name.clone(),
+      artists: track.artists.iter().map(|a| a.name.clone()).collect(),
+      album: track.album.name.clone(),
+      duration_ms: track.duration_ms,
+      isrc: track.external_ids.get("isrc").cloned(),
+      added_at: item.added_at.to_rfc3339(),
+      added_by: item.added_by.as_ref().map(|u| u.id.clone()),
+    })
+  }
+}
+
+pub fn render(format: ExportFormat, playlist: &FullPlaylist, tracks: &[ExportedTrack]) -> String {
+  match format {
+    ExportFormat::Json => render_json(playlist, tracks),
+    ExportFormat::M3u8 => render_m3u8(&playlist.name, tracks),
+    ExportFormat::Xspf => render_xspf(&playlist.name, &playlist.uri, tracks),
+  }
+}
+
+fn render_json(playlist: &FullPlaylist, tracks: &[ExportedTrack]) -> String {
+  let export = json!({
+    "name": playlist.name,
+    "uri": playlist.uri,
+    "description": playlist.description,
+    "owner": playlist.owner.id,
+    "snapshot_id": playlist.snapshot_id,
+    "tracks": tracks,
+  });
+  // Serializing a `Value` can't fail
+  serde_json::to_string_pretty(&export).unwrap_or_default()
+}
+
+// Players can't resolve spotify uris, but the extended info keeps the file
+// readable and importable by tools that search by artist and title
+fn render_m3u8(name: &str, tracks: &[ExportedTrack]) -> String {
+  let mut lines = vec!["#EXTM3U".to_string(), format!("#PLAYLIST:{}", name)];
+  for track in tracks {
+    lines.push(format!(
+      "#EXTINF:{},{} - {}",
+      track.duration_ms / 1000,
+      track.artists.join(", "),
+      track.name
+    ));
+    lines.push(track.uri.clone());
+  }
+  lines.join("\n")
+}
+
+fn render_xspf(name: &str, uri: &str, tracks: &[ExportedTrack]) -> String {
+  let mut xml = vec![
+    r#"<?xml version="1.0" encoding="UTF-8"?>"#.to_string(),
+    r#"<playlist version="1" xmlns="http://xspf.org/ns/0/">"#.to_string(),
+    format!("  <title>{}</title>", escape_xml(name)),
+    format!("  <identifier>{}</identifier>", escape_xml(uri)),
+    "  <trackList>".to_string(),
+  ];
+  for track in tracks {
+    xml.push("    <track>".to_string());
+    xml.push(format!(
+      "      <location>{}</location>",
+      escape_xml(&track.uri)
+    ));
+    if let Some(isrc) = &track.isrc {
+      xml.push(format!(
+        "      <identifier>isrc:{}</identifier>",
+        escape_xml(isrc)
+      ));
+    }
+    xml.push(format!("      <title>{}</title>", escape_xml(&track.name)));
+    xml.push(format!(
+      "      <creator>{}</creator>",
+      escape_xml(&track.artists.join(", "))
+    ));
+    xml.push(format!("      <album>{}</album>", escape_xml(&track.album)));
+    xml.push(format!("      <duration>{}</duration>", track.duration_ms));
+    xml.push(format!(
+      r#"      <meta rel="https://open.spotify.com/added_at">{}</meta>"#,
+      escape_xml(&track.added_at)
+    ));
+    xml.push("    </track>".to_string());
+  }
+  xml.push("  </trackList>".to_string());
+  xml.push("</playlist>".to_string());
+  xml.join("\n")
+}
+
+fn escape_xml(s: &str) -> String {
+  s.replace('&', "&amp;")
+    .replace('<', "&lt;")
+    .replace('>', "&gt;")
+    .replace('"', "&quot;")
+    .replace('\'', "&apos;")
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn track() -> ExportedTrack {
+    ExportedTrack {
+      uri: "spotify:track:4uLU6hMCjMI75M1A2tKUQC".to_string(),
+      name: "Rock & Roll".to_string(),
+      artists: vec!["Led Zeppelin".to_string()],
+      album: "Led Zeppelin IV".to_string(),
+      duration_ms: 220_000,
+      isrc: Some("USAT29900609".to_string()),
+      added_at: "2020-01-01T00:00:00+00:00".to_string(),
+      added_by: None,
+    }
+  }
+
+  #[test]
+  fn render_m3u8_test() {
+    assert_eq!(
+      render_m3u8("Mix", &[track()]),
+      "#EXTM3U\n#PLAYLIST:Mix\n#EXTINF:220,Led Zeppelin - Rock & Roll\nspotify:track:4uLU6hMCjMI75M1A2tKUQC"
+    );
+  }
+
+  #[test]
+  fn render_xspf_test() {
+    let xml = render_xspf("<Mix>", "spotify:playlist:1", &[track()]);
+    assert!(xml.contains("<title>&lt;Mix&gt;</title>"));
+    assert!(xml.contains("<title>Rock &amp; Roll</title>"));
+    assert!(xml.contains("<identifier>isrc:USAT29900609</identifier>"));
+    assert!(xml.contains("<duration>220000</duration>"));
+    assert!(xml.ends_with("  </trackList>\n</playlist>"));
+  }
+}
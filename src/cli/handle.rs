@@ -2,12 +2,49 @@ use crate::network::{IoEvent, Network};
 use crate::user_config::UserConfig;
 
 use super::{
-  util::{Flag, JumpDirection, Type},
+  export::ExportFormat,
+  pager::{self, Selection},
+  util::{
+    link_to_uri, parse_limit, parse_track_id, time_range_from_name, uri_to_url, ArtistSort,
+    DurationRange, Flag, FollowInterval, JumpDirection, OutputMode, PlayInput, SearchSort,
+    StatusBar, Type,
+  },
   CliApp,
 };
 
 use anyhow::{anyhow, Result};
 use clap::ArgMatches;
+use std::{
+  env,
+  io::{self, BufRead, IsTerminal},
+  time::Duration,
+};
+
+// Used if `--device` isn't given
+const DEVICE_ENV_VAR: &str = "SPOTIFY_TUI_DEVICE";
+
+// How many times `play --verify` checks the playback, one second apart
+const VERIFY_ATTEMPTS: u32 = 5;
+
+// How many search results `play --pick` lets you choose from
+const PICK_LIMIT: u32 = 20;
+
+// How often `playback --follow` checks for changes, less often while paused
+const FOLLOW_INTERVAL: Duration = Duration::from_secs(2);
+const FOLLOW_IDLE_INTERVAL: Duration = Duration::from_secs(10);
+
+// Reads every non-empty line from stdin
+fn read_stdin_lines() -> Result<Vec<String>> {
+  let mut lines = Vec::new();
+  for line in io::stdin().lock().lines() {
+    let line = line?;
+    let trimmed = line.trim();
+    if !trimmed.is_empty() {
+      lines.push(trimmed.to_string());
+    }
+  }
+  Ok(lines)
+}
 
 // Handle the different subcommands
 pub async fn handle_matches(
@@ -17,6 +54,9 @@ pub async fn handle_matches(
   config: UserConfig,
 ) -> Result<String> {
   let mut cli = CliApp::new(net, config);
+  cli.dry_run = matches.is_present("dry-run");
+  cli.verbose = matches.is_present("verbose");
+  cli.output = OutputMode::from_matches(matches);
 
   cli.net.handle_network_event(IoEvent::GetDevices).await;
   cli
@@ -24,27 +64,19 @@ pub async fn handle_matches(
     .handle_network_event(IoEvent::GetCurrentPlayback)
     .await;
 
-  let devices_list = match &cli.net.app.lock().await.devices {
-    Some(p) => p
-      .devices
-      .iter()
-      .map(|d| d.id.clone())
-      .collect::<Vec<String>>(),
-    None => Vec::new(),
-  };
-
-  // If the device_id is not specified, select the first available device
-  let device_id = cli.net.client_config.device_id.clone();
-  if device_id.is_none() || !devices_list.contains(&device_id.unwrap()) {
-    // Select the first device available
-    if let Some(d) = devices_list.get(0) {
-      cli.net.client_config.set_device_id(d.clone())?;
-    }
-  }
-
-  if let Some(d) = matches.value_of("device") {
-    cli.set_device(d.to_string()).await?;
+  let env_device = env::var(DEVICE_ENV_VAR).ok();
+  if let Some(secs) = matches.value_of("wait-for-device") {
+    let secs = secs
+      .parse::<u64>()
+      .map_err(|_| anyhow!("--wait-for-device expects a number of seconds"))?;
+    let target = matches.value_of("device").or(env_device.as_deref());
+    cli
+      .wait_for_device(target, Duration::from_secs(secs))
+      .await?;
   }
+  cli
+    .select_device(matches.value_of("device"), env_device.as_deref())
+    .await?;
 
   // Evalute the subcommand
   let output = match cmd.as_str() {
@@ -58,13 +90,22 @@ pub async fn handle_matches(
         return cli.share_album_or_show().await;
       }
 
+      if matches.is_present("from-stdin") {
+        let like = matches.is_present("like");
+        return cli.like_tracks(read_stdin_lines()?, like).await;
+      }
+
       // Run the action, and print out the status
       // No 'else if's because multiple different commands are possible
+      let force = matches.is_present("force");
       if matches.is_present("toggle") {
-        cli.toggle_playback().await;
+        cli.toggle_playback(force).await?;
       }
       if let Some(d) = matches.value_of("transfer") {
-        cli.transfer_playback(d).await?;
+        cli.transfer_playback(d, force).await?;
+        if matches.is_present("restore-volume") {
+          cli.restore_volume().await?;
+        }
       }
       // Multiple flags are possible
       if matches.is_present("flags") {
@@ -76,29 +117,115 @@ pub async fn handle_matches(
       if matches.is_present("jumps") {
         let (direction, amount) = JumpDirection::from_matches(matches);
         for _ in 0..amount {
-          cli.jump(&direction).await;
+          cli.jump(&direction, force).await?;
         }
       }
       if let Some(vol) = matches.value_of("volume") {
-        cli.volume(vol.to_string()).await?;
+        cli.volume(vol.to_string(), force).await?;
       }
       if let Some(secs) = matches.value_of("seek") {
-        cli.seek(secs.to_string()).await?;
+        cli.seek(secs.to_string(), force).await?;
+      }
+
+      let bar = StatusBar::from_matches(matches);
+      if bar.is_some() {
+        cli.output = OutputMode::Text;
+      }
+      if matches.is_present("follow") {
+        return cli
+          .follow_status(
+            format.to_string(),
+            bar,
+            FollowInterval {
+              playing: FOLLOW_INTERVAL,
+              idle: FOLLOW_IDLE_INTERVAL,
+            },
+          )
+          .await;
       }
 
       // Print out the status if no errors were found
-      cli.get_status(format.to_string()).await
+      match bar {
+        Some(bar) => Ok(cli.get_bar_status(format.to_string(), bar).await),
+        None => cli.get_status(format.to_string()).await,
+      }
     }
     "play" => {
       let queue = matches.is_present("queue");
       let random = matches.is_present("random");
+      let start_random = matches.is_present("start-random-track");
+      let resume = matches.is_present("resume");
       let format = matches.value_of("format").unwrap();
 
-      if let Some(uri) = matches.value_of("uri") {
-        cli.play_uri(uri.to_string(), queue, random).await;
+      let input = if let Some(uri) = matches.value_of("uri") {
+        PlayInput::Uri(link_to_uri(uri).unwrap_or_else(|| uri.to_string()))
       } else if let Some(name) = matches.value_of("name") {
-        let category = Type::play_from_matches(matches);
-        cli.play(name.to_string(), category, queue, random).await?;
+        PlayInput::Name(name.to_string())
+      } else {
+        // Clap makes sure that one of them is given
+        let item = matches.value_of("item").unwrap();
+        let input = PlayInput::detect(item);
+        if input == PlayInput::Name(item.to_string()) && parse_track_id(item).is_some() {
+          eprintln!(
+            "Searching for '{}' by name, use `--uri spotify:track:{}` to play it as an id",
+            item, item
+          );
+        }
+        input
+      };
+
+      match input {
+        PlayInput::Uri(uri) => {
+          // With a name, clap makes sure that `--resume` is used with `--show`
+          if resume && !(uri.contains("spotify:episode:") || uri.contains("spotify:show:")) {
+            return Err(anyhow!("--resume only works with episodes and shows"));
+          }
+          cli.play_uri(uri, queue, random, start_random, resume).await;
+        }
+        PlayInput::Name(name) => {
+          // Only the positional argument can be used without a type
+          let category = if matches.is_present("contexts") {
+            Type::play_from_matches(matches)
+          } else if resume {
+            Type::Show
+          } else if matches.is_present("pick") {
+            Type::Track
+          } else {
+            eprintln!(
+              "Playing the first track named '{}', use --album, --artist, --playlist or --show \
+to look for something else",
+              name
+            );
+            Type::Track
+          };
+          if matches.is_present("pick") {
+            if !io::stdout().is_terminal() {
+              return Err(anyhow!("--pick needs a terminal"));
+            }
+            let candidates = cli.candidates(&name, &category, Some(PICK_LIMIT)).await?;
+            if candidates.is_empty() {
+              return Err(anyhow!("no {} with name '{}'", category.plural(), name));
+            }
+            let lines = candidates
+              .iter()
+              .map(|(l, _)| l.clone())
+              .collect::<Vec<_>>();
+            match pager::pick(&lines)? {
+              Some(i) => {
+                let uri = candidates[i].1.clone();
+                cli.play_uri(uri, queue, random, start_random, resume).await;
+              }
+              None => return Ok(String::new()),
+            }
+          } else {
+            cli
+              .play(name, category, queue, random, start_random, resume)
+              .await?;
+          }
+        }
+      }
+      if matches.is_present("verify") {
+        cli.verify_device(VERIFY_ATTEMPTS).await;
       }
 
       cli.get_status(format.to_string()).await
@@ -109,12 +236,21 @@ pub async fn handle_matches(
       // Update the limits for the list and search functions
       // I think the small and big search limits are very confusing
       // so I just set them both to max, is this okay?
-      if let Some(max) = matches.value_of("limit") {
-        cli.update_query_limits(max.to_string()).await?;
-      }
+      cli
+        .update_query_limits(matches.value_of("limit"), matches.is_present("all"))
+        .await?;
+
+      cli.output = OutputMode::from_matches(matches);
+      cli.header = OutputMode::header_from_matches(matches);
 
-      let category = Type::list_from_matches(matches);
-      Ok(cli.list(category, &format).await)
+      let output = match Type::list_from_matches(matches) {
+        Type::Artist => {
+          let sort = ArtistSort::from_matches(matches);
+          cli.list_artists(&format, sort).await
+        }
+        category => cli.list(category, &format).await,
+      };
+      Ok(cli.with_header(&format, output))
     }
     "search" => {
       let format = matches.value_of("format").unwrap().to_string();
@@ -122,20 +258,172 @@ pub async fn handle_matches(
       // Update the limits for the list and search functions
       // I think the small and big search limits are very confusing
       // so I just set them both to max, is this okay?
-      if let Some(max) = matches.value_of("limit") {
-        cli.update_query_limits(max.to_string()).await?;
-      }
+      cli
+        .update_query_limits(matches.value_of("limit"), matches.is_present("all"))
+        .await?;
+
+      cli.output = OutputMode::from_matches(matches);
+      cli.header = OutputMode::header_from_matches(matches);
 
       let category = Type::search_from_matches(matches);
-      Ok(
+      let range = DurationRange::from_matches(matches)?;
+      let sort = SearchSort::from_matches(matches);
+      let search = matches.value_of("search").unwrap().to_string();
+
+      // Without a terminal the results are just printed
+      if matches.is_present("interactive")
+        && io::stdout().is_terminal()
+        && cli.output != OutputMode::Json
+      {
+        let items = cli
+          .query_with_uris(search, format, category, range, sort)
+          .await?;
+        let lines = items.iter().map(|(l, _)| l.clone()).collect::<Vec<_>>();
+        return match pager::run(&lines)? {
+          Selection::Play(i) => {
+            cli
+              .play_uri(items[i].1.clone(), false, false, false, false)
+              .await;
+            cli.get_status("%f %s %t - %a".to_string()).await
+          }
+          Selection::Queue(i) if items[i].1.contains("spotify:track:") => {
+            cli
+              .play_uri(items[i].1.clone(), true, false, false, false)
+              .await;
+            Ok(format!("Added {} to the queue", lines[i]))
+          }
+          Selection::Queue(_) => Err(anyhow!("only tracks can be added to the queue")),
+          Selection::Open(i) => {
+            uri_to_url(&items[i].1).ok_or_else(|| anyhow!("no link available for '{}'", items[i].1))
+          }
+          Selection::Quit => Ok(String::new()),
+        };
+      }
+
+      let output = cli
+        .query(search, format.clone(), category, range, sort)
+        .await;
+      Ok(cli.with_header(&format, output))
+    }
+    "analyze" => {
+      let format = matches.value_of("format").unwrap().to_string();
+      let json = matches.value_of("output") == Some("json") || matches.is_present("json");
+
+      let uris = if matches.is_present("from-stdin") {
+        read_stdin_lines()?
+      } else if let Some(uri) = matches.value_of("uri") {
+        vec![uri.to_string()]
+      } else {
+        // The current track
+        Vec::new()
+      };
+
+      cli.analyze(uris, format, json).await
+    }
+    "top" => {
+      let format = matches.value_of("format").unwrap();
+      let range = time_range_from_name(matches.value_of("range").unwrap());
+      let limit = parse_limit(matches.value_of("limit").unwrap(), 50)?;
+      let item = if matches.is_present("artists") {
+        Type::Artist
+      } else {
+        Type::Track
+      };
+      cli.output = OutputMode::from_matches(matches);
+      cli.header = OutputMode::header_from_matches(matches);
+      cli.top(item, range, limit, format).await
+    }
+    "recommend" => {
+      let format = matches.value_of("format").unwrap();
+      let limit = parse_limit(matches.value_of("limit").unwrap(), 100)?;
+      let values = |name: &str| {
+        matches
+          .values_of(name)
+          .map(|v| v.map(|s| s.to_string()).collect())
+          .unwrap_or_default()
+      };
+      let tracks = cli
+        .recommendations(
+          values("seed-artist"),
+          values("seed-track"),
+          values("seed-genre"),
+          limit,
+        )
+        .await?;
+
+      let mut messages = Vec::new();
+      if let Some(name) = matches.value_of("save-as") {
+        messages.push(cli.save_as_playlist(name, &tracks).await?);
+      }
+      if matches.is_present("play") {
+        messages.push(cli.play_tracks(&tracks).await);
+      }
+      if messages.is_empty() {
+        cli.output = OutputMode::from_matches(matches);
+        cli.header = OutputMode::header_from_matches(matches);
+        Ok(cli.format_tracks(&tracks, format).await)
+      } else {
+        Ok(messages.join("\n"))
+      }
+    }
+    "lyrics" => {
+      cli
+        .lyrics(matches.value_of("uri"), matches.is_present("synced"))
+        .await
+    }
+    "playlist" => match matches.subcommand() {
+      ("create", Some(m)) => {
+        let name = m.value_of("name").unwrap();
+        let description = m.value_of("description").map(|s| s.to_string());
         cli
-          .query(
-            matches.value_of("search").unwrap().to_string(),
-            format,
-            category,
-          )
-          .await,
-      )
+          .create_playlist(name, m.is_present("public"), description)
+          .await
+      }
+      ("delete", Some(m)) => cli.delete_playlist(m.value_of("playlist").unwrap()).await,
+      ("import", Some(m)) => {
+        let file = m.value_of("file").unwrap();
+        cli
+          .import_playlist(file, m.value_of("name").unwrap(), m.is_present("public"))
+          .await
+      }
+      ("export", Some(m)) => {
+        let format = ExportFormat::from_name(m.value_of("format").unwrap())?;
+        cli
+          .export_playlist(m.value_of("playlist").unwrap(), format, m.value_of("out"))
+          .await
+      }
+      ("rename", Some(m)) => {
+        let playlist = m.value_of("playlist").unwrap();
+        cli
+          .rename_playlist(playlist, m.value_of("name").unwrap())
+          .await
+      }
+      (action @ ("add" | "remove"), Some(m)) => {
+        let playlist = m.value_of("playlist").unwrap();
+        let tracks = m
+          .values_of("tracks")
+          .unwrap()
+          .map(|s| s.to_string())
+          .collect();
+        cli
+          .change_playlist_tracks(playlist, tracks, action == "add")
+          .await
+      }
+      // Clap requires one of the actions
+      _ => unreachable!(),
+    },
+    "queue" => {
+      let items = if matches.is_present("from-stdin") {
+        read_stdin_lines()?
+      } else {
+        // Clap makes sure that one of them is given
+        matches
+          .values_of("add")
+          .unwrap()
+          .map(|s| s.to_string())
+          .collect()
+      };
+      cli.add_to_queue(items).await
     }
     // Clap enforces that one of the things above is specified
     _ => unreachable!(),
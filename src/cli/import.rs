@@ -0,0 +1,269 @@
+use super::util::parse_track_id;
+use anyhow::{anyhow, Result};
+use rspotify::model::track::FullTrack;
+use serde_json::Value;
+
+// One entry of the imported file, tracks with an id or uri aren't searched
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ImportRow {
+  pub title: String,
+  pub artist: String,
+  pub id: Option<String>,
+  pub isrc: Option<String>,
+}
+
+impl ImportRow {
+  // What to search for, the most exact first
+  pub fn queries(&self) -> Vec<String> {
+    let mut queries = Vec::new();
+    if let Some(isrc) = &self.isrc {
+      queries.push(format!("isrc:{}", isrc));
+    }
+    let quoted = |s: &str| s.replace('"', "");
+    if self.artist.is_empty() {
+      queries.push(format!("track:\"{}\"", quoted(&self.title)));
+    } else {
+      queries.push(format!(
+        "track:\"{}\" artist:\"{}\"",
+        quoted(&self.title),
+        quoted(&self.artist)
+      ));
+    }
+    queries
+  }
+
+  pub fn describe(&self) -> String {
+    if self.artist.is_empty() {
+      self.title.clone()
+    } else {
+      format!("{} - {}", self.title, self.artist)
+    }
+  }
+}
+
+// JSON files are an array of tracks or an object with a `tracks` array (like
+// `spt playlist export`), anything else is read as CSV
+pub fn parse_rows(contents: &str) -> Result<Vec<ImportRow>> {
+  let trimmed = contents.trim_start();
+  let rows = if trimmed.starts_with('[') || trimmed.starts_with('{') {
+    parse_json(trimmed)?
+  } else {
+    parse_csv(contents)
+  };
+  if rows.is_empty() {
+    return Err(anyhow!("the file doesn't contain any tracks"));
+  }
+  Ok(rows)
+}
+
+fn parse_json(contents: &str) -> Result<Vec<ImportRow>> {
+  let json: Value =
+    serde_json::from_str(contents).map_err(|e| anyhow!("the file isn't valid JSON: {}", e))?;
+  let items = match json.get("tracks").unwrap_or(&json) {
+    Value::Array(items) => items.clone(),
+    _ => return Err(anyhow!("expected an array of tracks")),
+  };
+  let text = |item: &Value, keys: &[&str]| {
+    keys.iter().find_map(|key| match &item[key] {
+      Value::String(s) => Some(s.trim().to_string()),
+      // e.g. the `artists` of an export
+      Value::Array(values) => Some(
+        values
+          .iter()
+          .filter_map(|v| v.as_str())
+          .collect::<Vec<&str>>()
+          .join(", "),
+      ),
+      _ => None,
+    })
+  };
+  Ok(
+    items
+      .iter()
+      .map(|item| ImportRow {
+        title: text(item, &["title", "name", "track"]).unwrap_or_default(),
+        artist: text(item, &["artist", "artists"]).unwrap_or_default(),
+        id: text(item, &["uri", "id"]).and_then(|uri| parse_track_id(&uri)),
+        isrc: text(item, &["isrc"]).filter(|s| !s.is_empty()),
+      })
+      .filter(|row| !row.title.is_empty() || row.id.is_some())
+      .collect(),
+  )
+}
+
+// Uses the header to find the columns if it has one, otherwise the first
+// column is the title and the second the artist
+fn parse_csv(contents: &str) -> Vec<ImportRow> {
+  let mut lines = contents
+    .lines()
+    .filter(|l| !l.trim().is_empty())
+    .map(parse_csv_line)
+    .peekable();
+  let column = |header: &[String], names: &[&str]| {
+    header
+      .iter()
+      .position(|h| names.contains(&h.trim().to_lowercase().as_str()))
+  };
+  let (title, artist, uri, isrc) = match lines.peek() {
+    Some(header) if column(header, &["title", "name", "track", "track name"]).is_some() => {
+      let columns = (
+        column(header, &["title", "name", "track", "track name"]),
+        column(
+          header,
+          &["artist", "artists", "artist name", "artist name(s)"],
+        ),
+        column(header, &["uri", "spotify uri", "track uri", "id"]),
+        column(header, &["isrc"]),
+      );
+      lines.next();
+      columns
+    }
+    _ => (Some(0), Some(1), None, None),
+  };
+  let get = |fields: &[String], index: Option<usize>| {
+    index
+      .and_then(|i| fields.get(i))
+      .map(|s| s.trim().to_string())
+      .filter(|s| !s.is_empty())
+  };
+  lines
+    .map(|fields| ImportRow {
+      title: get(&fields, title).unwrap_or_default(),
+      artist: get(&fields, artist).unwrap_or_default(),
+      id: get(&fields, uri).and_then(|uri| parse_track_id(&uri)),
+      isrc: get(&fields, isrc),
+    })
+    .filter(|row| !row.title.is_empty() || row.id.is_some())
+    .collect()
+}
+
+// Fields can be quoted, with `""` for a quote inside them
+fn parse_csv_line(line: &str) -> Vec<String> {
+  let mut fields = Vec::new();
+  let mut current = String::new();
+  let mut quoted = false;
+  let mut chars = line.chars().peekable();
+  while let Some(c) = chars.next() {
+    match c {
+      '"' if quoted && chars.peek() == Some(&'"') => {
+        current.push('"');
+        chars.next();
+      }
+      '"' => quoted = !quoted,
+      ',' if !quoted => fields.push(std::mem::take(&mut current)),
+      c => current.push(c),
+    }
+  }
+  fields.push(current);
+  fields
+}
+
+// Lowercase words without the `(feat. ...)`, `[Live]` or ` - Remastered` parts
+// that services add to titles differently
+fn normalize(s: &str) -> String {
+  let mut out = String::new();
+  let mut depth = 0u32;
+  for c in s.split(" - ").next().unwrap_or_default().chars() {
+    match c {
+      '(' | '[' => depth += 1,
+      ')' | ']' => depth = depth.saturating_sub(1),
+      c if depth == 0 && c.is_alphanumeric() => out.extend(c.to_lowercase()),
+      _ if depth == 0 => out.push(' '),
+      _ => {}
+    }
+  }
+  out.split_whitespace().collect::<Vec<&str>>().join(" ")
+}
+
+// How well a search result fits the row, `None` if it's another track
+pub fn match_score(row: &ImportRow, track: &FullTrack) -> Option<u32> {
+  let wanted = normalize(&row.title);
+  let found = normalize(&track.name);
+  // An empty name is contained in every other one
+  if wanted.is_empty() || found.is_empty() {
+    return None;
+  }
+  let mut score = if wanted == found {
+    3
+  } else if found.contains(&wanted) || wanted.contains(&found) {
+    1
+  } else {
+    return None;
+  };
+  if !row.artist.is_empty() {
+    let artist = normalize(&row.artist);
+    if artist.is_empty() {
+      return None;
+    }
+    if !track.artists.iter().any(|a| {
+      let name = normalize(&a.name);
+      !name.is_empty() && (artist.contains(&name) || name.contains(&artist))
+    }) {
+      return None;
+    }
+    score += 2;
+  }
+  Some(score)
+}
+
+// The best result, ties go to the one spotify ranked higher
+pub fn best_match<'a>(row: &ImportRow, tracks: &'a [FullTrack]) -> Option<&'a FullTrack> {
+  let mut best: Option<(u32, &FullTrack)> = None;
+  for track in tracks {
+    if let Some(score) = match_score(row, track) {
+      if best.map(|(s, _)| score > s).unwrap_or(true) {
+        best = Some((score, track));
+      }
+    }
+  }
+  best.map(|(_, track)| track)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn parse_rows_test() {
+    let csv = "Track Name,Artist Name(s),ISRC\n\"Hello, Goodbye\",The Beatles,GBAYE0601648\n\"Say \"\"Hi\"\"\",Someone,\n\n";
+    assert_eq!(
+      parse_rows(csv).unwrap(),
+      vec![
+        ImportRow {
+          title: "Hello, Goodbye".to_string(),
+          artist: "The Beatles".to_string(),
+          id: None,
+          isrc: Some("GBAYE0601648".to_string()),
+        },
+        ImportRow {
+          title: "Say \"Hi\"".to_string(),
+          artist: "Someone".to_string(),
+          ..Default::default()
+        },
+      ]
+    );
+    assert_eq!(parse_rows("Song,Band").unwrap()[0].artist, "Band");
+
+    let json = r#"{"tracks": [{"name": "Song", "artists": ["A", "B"], "uri": "spotify:track:4uLU6hMCjMI75M1A2tKUQC"}]}"#;
+    assert_eq!(
+      parse_rows(json).unwrap(),
+      vec![ImportRow {
+        title: "Song".to_string(),
+        artist: "A, B".to_string(),
+        id: Some("4uLU6hMCjMI75M1A2tKUQC".to_string()),
+        isrc: None,
+      }]
+    );
+    assert!(parse_rows("[]").is_err());
+    assert!(parse_rows("{ broken").is_err());
+  }
+
+  #[test]
+  fn normalize_test() {
+    assert_eq!(
+      normalize("Help! - Remastered 2009"),
+      normalize("help (feat. Someone)")
+    );
+    assert_eq!(normalize("  Don't   Stop [Live] "), "don t stop");
+  }
+}
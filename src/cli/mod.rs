@@ -1,8 +1,19 @@
 mod clap;
 mod cli_app;
+mod daemon;
+mod export;
 mod handle;
+mod import;
+mod pager;
 mod util;
+mod volumes;
 
-pub use self::clap::{list_subcommand, play_subcommand, playback_subcommand, search_subcommand};
+pub use self::clap::{
+  analyze_subcommand, daemon_subcommand, list_subcommand, lyrics_subcommand, play_subcommand,
+  playback_subcommand, playlist_subcommand, queue_subcommand, recommend_subcommand,
+  search_subcommand, top_subcommand,
+};
 use cli_app::CliApp;
+pub use daemon::Daemon;
 pub use handle::handle_matches;
+pub use util::link_to_uri;
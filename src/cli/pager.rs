@@ -0,0 +1,235 @@
+use crate::command_palette::fuzzy_score;
+use anyhow::Result;
+use crossterm::{
+  cursor::{Hide, MoveTo, Show},
+  event::{read, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+  execute, queue,
+  style::{Attribute, Print, SetAttribute},
+  terminal::{
+    self, disable_raw_mode, enable_raw_mode, Clear, ClearType, EnterAlternateScreen,
+    LeaveAlternateScreen,
+  },
+};
+use std::io::{self, Write};
+
+const HELP: &str = "↑/k ↓/j: move  enter: play  a: queue  o: open  q/esc: quit";
+// Typing is the filter, so only keys that can't be part of it move
+const PICK_HELP: &str = "↑↓: move  enter: play  1-9: play that one  esc: quit";
+
+// What the user picked in `spt search --interactive`
+#[derive(Debug, PartialEq)]
+pub enum Selection {
+  Play(usize),
+  Queue(usize),
+  Open(usize),
+  Quit,
+}
+
+// Restores the terminal when the pager is left, even on errors
+struct Screen;
+
+impl Screen {
+  fn enter() -> Result<Self> {
+    enable_raw_mode()?;
+    execute!(io::stdout(), EnterAlternateScreen, Hide)?;
+    Ok(Self)
+  }
+}
+
+impl Drop for Screen {
+  fn drop(&mut self) {
+    let _ = execute!(io::stdout(), Show, LeaveAlternateScreen);
+    let _ = disable_raw_mode();
+  }
+}
+
+// Keeps the selected line visible in a window of `height` lines
+pub fn scroll_offset(selected: usize, offset: usize, height: usize) -> usize {
+  if height == 0 || selected < offset {
+    selected
+  } else if selected >= offset + height {
+    selected + 1 - height
+  } else {
+    offset
+  }
+}
+
+fn draw(lines: &[String], selected: usize, offset: usize, help: &str) -> Result<()> {
+  let mut stdout = io::stdout();
+  let (width, height) = terminal::size()?;
+  // The last line is used for the help
+  let height = height.saturating_sub(1) as usize;
+
+  queue!(stdout, Clear(ClearType::All))?;
+  for (row, line) in lines.iter().enumerate().skip(offset).take(height) {
+    let text = line.chars().take(width as usize).collect::<String>();
+    queue!(stdout, MoveTo(0, (row - offset) as u16))?;
+    if row == selected {
+      queue!(
+        stdout,
+        SetAttribute(Attribute::Reverse),
+        Print(text),
+        SetAttribute(Attribute::Reset)
+      )?;
+    } else {
+      queue!(stdout, Print(text))?;
+    }
+  }
+  let help = help.chars().take(width as usize).collect::<String>();
+  queue!(
+    stdout,
+    MoveTo(0, height as u16),
+    SetAttribute(Attribute::Dim),
+    Print(help),
+    SetAttribute(Attribute::Reset)
+  )?;
+  stdout.flush()?;
+  Ok(())
+}
+
+// Shows `lines` until one is picked or the pager is closed
+pub fn run(lines: &[String]) -> Result<Selection> {
+  if lines.is_empty() {
+    return Ok(Selection::Quit);
+  }
+
+  let _screen = Screen::enter()?;
+  let mut selected = 0;
+  let mut offset = 0;
+
+  loop {
+    let height = terminal::size()?.1.saturating_sub(1) as usize;
+    offset = scroll_offset(selected, offset, height);
+    draw(lines, selected, offset, HELP)?;
+
+    match read()? {
+      Event::Key(KeyEvent {
+        code,
+        modifiers,
+        kind: KeyEventKind::Press,
+        ..
+      }) => match code {
+        KeyCode::Up | KeyCode::Char('k') => selected = selected.saturating_sub(1),
+        KeyCode::Down | KeyCode::Char('j') => selected = (selected + 1).min(lines.len() - 1),
+        KeyCode::PageUp => selected = selected.saturating_sub(height),
+        KeyCode::PageDown => selected = (selected + height).min(lines.len() - 1),
+        KeyCode::Home | KeyCode::Char('g') => selected = 0,
+        KeyCode::End | KeyCode::Char('G') => selected = lines.len() - 1,
+        KeyCode::Enter => return Ok(Selection::Play(selected)),
+        KeyCode::Char('a') => return Ok(Selection::Queue(selected)),
+        KeyCode::Char('o') => return Ok(Selection::Open(selected)),
+        KeyCode::Char('c') if modifiers.contains(KeyModifiers::CONTROL) => {
+          return Ok(Selection::Quit)
+        }
+        KeyCode::Char('q') | KeyCode::Esc => return Ok(Selection::Quit),
+        _ => (),
+      },
+      // The next draw picks up the new size
+      Event::Resize(_, _) => (),
+      _ => (),
+    }
+  }
+}
+
+// The lines matching `query`, best matches first
+fn filter(lines: &[String], query: &str) -> Vec<usize> {
+  let mut scored = lines
+    .iter()
+    .enumerate()
+    .filter_map(|(i, line)| fuzzy_score(query, line).map(|score| (score, i)))
+    .collect::<Vec<_>>();
+  scored.sort_by_key(|(score, _)| -score);
+  scored.into_iter().map(|(_, i)| i).collect()
+}
+
+// Lets the user choose one of `lines` for `spt play --pick`, by typing to filter
+// them or with the number in front of them. Returns the index in `lines`
+pub fn pick(lines: &[String]) -> Result<Option<usize>> {
+  if lines.is_empty() {
+    return Ok(None);
+  }
+
+  let _screen = Screen::enter()?;
+  let mut query = String::new();
+  let mut selected = 0;
+  let mut offset = 0;
+
+  loop {
+    let matches = filter(lines, &query);
+    selected = selected.min(matches.len().saturating_sub(1));
+    let height = terminal::size()?.1.saturating_sub(1) as usize;
+    offset = scroll_offset(selected, offset, height);
+    let shown = matches
+      .iter()
+      .enumerate()
+      .map(|(n, &i)| format!("{:>2}. {}", n + 1, lines[i]))
+      .collect::<Vec<String>>();
+    let help = if query.is_empty() {
+      PICK_HELP.to_string()
+    } else {
+      format!("filter: {}  {}", query, PICK_HELP)
+    };
+    draw(&shown, selected, offset, &help)?;
+
+    match read()? {
+      Event::Key(KeyEvent {
+        code,
+        modifiers,
+        kind: KeyEventKind::Press,
+        ..
+      }) => match code {
+        KeyCode::Up => selected = selected.saturating_sub(1),
+        KeyCode::Down => selected = (selected + 1).min(matches.len().saturating_sub(1)),
+        KeyCode::Enter => return Ok(matches.get(selected).copied()),
+        KeyCode::Char('c') if modifiers.contains(KeyModifiers::CONTROL) => return Ok(None),
+        KeyCode::Esc => return Ok(None),
+        KeyCode::Backspace => {
+          query.pop();
+        }
+        // Numbers only pick before filtering, titles can contain them
+        KeyCode::Char(c @ '1'..='9') if query.is_empty() => {
+          if let Some(&i) = matches.get(c as usize - '1' as usize) {
+            return Ok(Some(i));
+          }
+        }
+        KeyCode::Char(c) => {
+          query.push(c);
+          selected = 0;
+        }
+        _ => (),
+      },
+      Event::Resize(_, _) => (),
+      _ => (),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn filter_test() {
+    let lines = vec![
+      "Hello - Adele".to_string(),
+      "Hello - Lionel Richie".to_string(),
+      "Hello, Goodbye - The Beatles".to_string(),
+    ];
+    assert_eq!(filter(&lines, ""), vec![0, 1, 2]);
+    assert_eq!(filter(&lines, "lionel"), vec![1]);
+    assert_eq!(filter(&lines, "beat")[0], 2);
+    assert!(filter(&lines, "zzz").is_empty());
+  }
+
+  #[test]
+  fn scroll_offset_test() {
+    // Selection inside of the window
+    assert_eq!(scroll_offset(3, 0, 10), 0);
+    // Moving below the window
+    assert_eq!(scroll_offset(10, 0, 10), 1);
+    // Moving above the window
+    assert_eq!(scroll_offset(2, 5, 10), 2);
+    // The window shrunk after a resize
+    assert_eq!(scroll_offset(8, 0, 5), 4);
+  }
+}
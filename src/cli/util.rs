@@ -1,13 +1,26 @@
 use clap::ArgMatches;
 use rspotify::{
   model::{
-    album::SimplifiedAlbum, artist::FullArtist, artist::SimplifiedArtist,
-    playlist::SimplifiedPlaylist, show::FullEpisode, show::SimplifiedShow, track::FullTrack,
+    artist::FullArtist, context::CurrentlyPlaybackContext, device::Device, show::ResumePoint,
+    PlayingItem,
   },
-  senum::RepeatState,
+  senum::{DisallowKey, RepeatState, TimeRange},
 };
 
+use crate::app::App;
+use crate::command_palette::fuzzy_score;
+use crate::format::{match_token, render, split_padding, Format};
+use crate::network::IoEvent;
 use crate::user_config::UserConfig;
+use anyhow::{anyhow, Result};
+use serde_json::{json, Map, Value};
+use std::{
+  future::Future,
+  io::{self, IsTerminal},
+  pin::Pin,
+  time::Duration,
+};
+use tokio::sync::Mutex;
 
 // Possible types to list or search
 #[derive(Debug)]
@@ -22,6 +35,19 @@ pub enum Type {
 }
 
 impl Type {
+  // How the items are called in messages
+  pub fn plural(&self) -> &'static str {
+    match self {
+      Self::Playlist => "playlists",
+      Self::Track => "tracks",
+      Self::Artist => "artists",
+      Self::Album => "albums",
+      Self::Show => "shows",
+      Self::Device => "devices",
+      Self::Liked => "liked songs",
+    }
+  }
+
   pub fn play_from_matches(m: &ArgMatches<'_>) -> Self {
     if m.is_present("playlist") {
       Self::Playlist
@@ -65,6 +91,12 @@ impl Type {
       Self::Device
     } else if m.is_present("liked") {
       Self::Liked
+    } else if m.is_present("artists") {
+      Self::Artist
+    } else if m.is_present("albums") {
+      Self::Album
+    } else if m.is_present("shows") {
+      Self::Show
     }
     // Enforced by clap
     else {
@@ -83,7 +115,8 @@ pub enum Flag {
   // * User chooses dislike -> Flag::Like(false)
   Like(bool),
   Shuffle,
-  Repeat,
+  // Cycles to the next mode without one
+  Repeat(Option<RepeatState>),
 }
 
 impl Flag {
@@ -102,7 +135,9 @@ impl Flag {
       flags.push(Self::Shuffle);
     }
     if m.is_present("repeat") {
-      flags.push(Self::Repeat);
+      // Validated by clap
+      let mode = m.value_of("repeat").and_then(|v| v.parse().ok());
+      flags.push(Self::Repeat(mode));
     }
     flags
   }
@@ -127,147 +162,1260 @@ impl JumpDirection {
   }
 }
 
-// For fomatting (-f / --format flag)
+// Accepts 'h:mm:ss', 'mm:ss' or plain seconds
+pub fn parse_duration(s: &str) -> Result<u32> {
+  let err = || anyhow!("invalid duration '{}', expected [h:]mm:ss or seconds", s);
+  let parts = s
+    .split(':')
+    .map(|part| part.trim().parse::<u32>().map_err(|_e| err()))
+    .collect::<Result<Vec<u32>>>()?;
+  // Only the first part can be 60 or more
+  if parts.len() > 3 || parts.iter().skip(1).any(|part| *part >= 60) {
+    return Err(err());
+  }
+  let secs = parts
+    .iter()
+    .try_fold(0u32, |secs, part| secs.checked_mul(60)?.checked_add(*part));
+  // Return milliseconds to be comparable with `duration_ms`
+  secs
+    .and_then(|secs| secs.checked_mul(1000))
+    .ok_or_else(|| anyhow!("duration '{}' is too long", s))
+}
 
-// Types to create a Format enum from
-// Boxing was proposed by cargo clippy
-// to reduce the size of this enum
-pub enum FormatType {
-  Album(Box<SimplifiedAlbum>),
-  Artist(Box<FullArtist>),
-  Playlist(Box<SimplifiedPlaylist>),
-  Track(Box<FullTrack>),
-  Episode(Box<FullEpisode>),
-  Show(Box<SimplifiedShow>),
+// spt search ... --min-duration / --max-duration
+pub struct DurationRange {
+  pub min: Option<u32>,
+  pub max: Option<u32>,
 }
 
-// Types that can be formatted
-#[derive(Clone)]
-pub enum Format {
-  Album(String),
-  Artist(String),
-  Playlist(String),
-  Track(String),
-  Show(String),
+impl DurationRange {
+  pub fn from_matches(m: &ArgMatches<'_>) -> Result<Self> {
+    let min = m.value_of("min-duration").map(parse_duration).transpose()?;
+    let max = m.value_of("max-duration").map(parse_duration).transpose()?;
+    if let (Some(min), Some(max)) = (min, max) {
+      if min > max {
+        return Err(anyhow!(
+          "min-duration must not be greater than max-duration"
+        ));
+      }
+    }
+    Ok(Self { min, max })
+  }
+
+  pub fn contains(&self, duration_ms: u32) -> bool {
+    !matches!(self.min, Some(min) if duration_ms < min)
+      && !matches!(self.max, Some(max) if duration_ms > max)
+  }
+}
+
+// Sums up the rest of the current track and all tracks after it, `tracks` are pairs
+// of uri and duration in the order of the context. Summed as u64 since about 50
+// days of tracks don't fit in u32 milliseconds, those end up at u32::MAX
+pub fn remaining_in_context(
+  tracks: &[(String, u32)],
+  current: &str,
+  progress_ms: u32,
+) -> Option<u32> {
+  let index = tracks.iter().position(|(uri, _)| uri == current)?;
+  let left_in_current = u64::from(tracks[index].1.saturating_sub(progress_ms));
+  let after = tracks[index + 1..]
+    .iter()
+    .map(|(_, d)| u64::from(*d))
+    .sum::<u64>();
+  Some((left_in_current + after).min(u64::from(u32::MAX)) as u32)
+}
+
+// Used instead of sending a request that spotify would reject with a 403
+pub fn disallowed_message(key: DisallowKey, during_ad: bool) -> String {
+  let action = match key {
+    DisallowKey::SkippingNext | DisallowKey::SkippingPrev => "skipping",
+    DisallowKey::Seeking => "seeking",
+    DisallowKey::Pausing => "pausing",
+    DisallowKey::Resuming => "resuming",
+    DisallowKey::TogglingShuffle => "toggling shuffle",
+    DisallowKey::TogglingRepeatContext | DisallowKey::TogglingRepeatTrack => "changing repeat",
+    DisallowKey::TransferringPlayback => "transferring the playback",
+    DisallowKey::InterruptingPlayback => "interrupting the playback",
+  };
+  let when = if during_ad {
+    "during an ad"
+  } else {
+    "right now"
+  };
+  format!(
+    "{} is not allowed {} (use --force to try anyway)",
+    action, when
+  )
+}
+
+// The positional argument of `spt play`
+#[derive(Debug, PartialEq)]
+pub enum PlayInput {
   Uri(String),
-  Device(String),
-  Volume(u32),
-  // Current position, duration
-  Position((u32, u32)),
-  // This is a bit long, should it be splitted up?
-  Flags((RepeatState, bool, bool)),
-  Playing(bool),
-}
-
-pub fn join_artists(a: Vec<SimplifiedArtist>) -> String {
-  a.iter()
-    .map(|l| l.name.clone())
+  Name(String),
+}
+
+impl PlayInput {
+  // Uris and links are played directly, everything else is searched for
+  pub fn detect(s: &str) -> Self {
+    match link_to_uri(s) {
+      Some(uri) => Self::Uri(uri),
+      None => Self::Name(s.trim().to_string()),
+    }
+  }
+}
+
+// Uris are kept, `https://open.spotify.com/KIND/ID` links become `spotify:KIND:ID`
+pub fn link_to_uri(s: &str) -> Option<String> {
+  let s = s.trim();
+  if s.starts_with("spotify:") {
+    return Some(s.to_string());
+  }
+  let path = s.split("open.spotify.com/").nth(1)?;
+  // Links can contain a locale (`intl-de/`) and a query (`?si=...`)
+  let parts = path
+    .split(['?', '#'])
+    .next()
+    .unwrap_or_default()
+    .split('/')
+    .filter(|p| !p.is_empty() && !p.starts_with("intl-"))
+    .collect::<Vec<&str>>();
+  match parts[..] {
+    [kind, id] => Some(format!("spotify:{}:{}", kind, id)),
+    _ => None,
+  }
+}
+
+// Only tracks and episodes can be added to the queue
+pub fn queueable_uri(s: &str) -> Option<String> {
+  match PlayInput::detect(s) {
+    PlayInput::Uri(uri)
+      if uri.starts_with("spotify:track:") || uri.starts_with("spotify:episode:") =>
+    {
+      Some(uri)
+    }
+    _ => None,
+  }
+}
+
+// Accepts `spotify:track:ID`, `https://open.spotify.com/track/ID?si=...` or a plain ID
+pub fn parse_track_id(s: &str) -> Option<String> {
+  parse_id(s, "track")
+}
+
+// Same as `parse_track_id`, for any `kind` of item (e.g. `playlist`)
+pub fn parse_id(s: &str, kind: &str) -> Option<String> {
+  let uri = link_to_uri(s);
+  let id = match &uri {
+    Some(uri) => uri.strip_prefix(&format!("spotify:{}:", kind))?,
+    None => s.trim(),
+  };
+  // Spotify ids are 22 base62 characters
+  if id.len() == 22 && id.chars().all(|c| c.is_ascii_alphanumeric()) {
+    Some(id.to_string())
+  } else {
+    None
+  }
+}
+
+// Converts e.g. `spotify:track:ID` to `https://open.spotify.com/track/ID`
+pub fn uri_to_url(uri: &str) -> Option<String> {
+  match uri.split(':').collect::<Vec<&str>>()[..] {
+    ["spotify", kind, id] => Some(format!("https://open.spotify.com/{}/{}", kind, id)),
+    _ => None,
+  }
+}
+
+// The sources of the device to use, the device ids saved in the client
+// config are only used if `remember_device` is enabled
+pub struct DeviceChoice<'a> {
+  pub flag: Option<&'a str>,
+  pub env: Option<&'a str>,
+  pub last_used: Option<&'a str>,
+  pub default: Option<&'a str>,
+}
+
+fn device_names(devices: &[&Device]) -> String {
+  devices
+    .iter()
+    .map(|d| format!("'{}'", d.name))
     .collect::<Vec<String>>()
     .join(", ")
 }
 
-impl Format {
-  // Extract important information from types
-  pub fn from_type(t: FormatType) -> Vec<Self> {
-    match t {
-      FormatType::Album(a) => {
-        let joined_artists = join_artists(a.artists.clone());
-        let mut vec = vec![Self::Album(a.name), Self::Artist(joined_artists)];
-        if let Some(uri) = a.uri {
-          vec.push(Self::Uri(uri));
+// Finds a device by its id or name. The name can also be matched ignoring
+// case, by a part of it or fuzzily, in that order, as long as only one device
+// matches
+pub fn find_device<'a>(devices: &'a [Device], query: &str) -> Result<&'a Device> {
+  let lower = query.to_lowercase();
+  let matchers: [&dyn Fn(&Device) -> bool; 4] = [
+    &|d| d.id == query || d.name == query,
+    &|d| d.name.to_lowercase() == lower,
+    &|d| d.name.to_lowercase().contains(&lower),
+    &|d| fuzzy_score(query, &d.name).is_some(),
+  ];
+  for matches in matchers.iter() {
+    let found = devices
+      .iter()
+      .filter(|d| matches(d))
+      .collect::<Vec<&Device>>();
+    match found[..] {
+      [] => continue,
+      [device] => return Ok(device),
+      _ => {
+        return Err(anyhow!(
+          "'{}' matches more than one device: {}",
+          query,
+          device_names(&found)
+        ))
+      }
+    }
+  }
+  if devices.is_empty() {
+    Err(anyhow!("no device with name '{}' available", query))
+  } else {
+    Err(anyhow!(
+      "no device with name '{}' available, there are {}",
+      query,
+      device_names(&devices.iter().collect::<Vec<&Device>>())
+    ))
+  }
+}
+
+// Returns the id of the first available device of:
+// `--device` > $SPOTIFY_TUI_DEVICE > last used > `default_device` > active device > any device
+pub fn resolve_device(choice: &DeviceChoice<'_>, devices: &[Device]) -> Result<Option<String>> {
+  // Explicitly requested devices have to exist
+  if let Some(requested) = choice.flag.or(choice.env) {
+    return find_device(devices, requested).map(|d| Some(d.id.clone()));
+  }
+
+  let device = [choice.last_used, choice.default]
+    .iter()
+    .flatten()
+    .find_map(|d| find_device(devices, d).ok())
+    .or_else(|| devices.iter().find(|d| d.is_active))
+    .or_else(|| devices.first());
+  Ok(device.map(|d| d.id.clone()))
+}
+
+// Order of `spt search` (--sort)
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum SearchSort {
+  Relevance,
+  Release,
+}
+
+impl SearchSort {
+  pub fn from_matches(m: &ArgMatches<'_>) -> Self {
+    match m.value_of("sort") {
+      Some("release") => Self::Release,
+      _ => Self::Relevance,
+    }
+  }
+}
+
+// Release dates can be `YYYY`, `YYYY-MM` or `YYYY-MM-DD`, missing parts are
+// treated as 0 so they come after more precise dates of the same year
+pub fn release_key(date: &str) -> Option<(u32, u32, u32)> {
+  let mut parts = date.splitn(3, '-').map(|p| p.parse::<u32>());
+  let year = parts.next()?.ok()?;
+  let month = parts.next().unwrap_or(Ok(0)).ok()?;
+  let day = parts.next().unwrap_or(Ok(0)).ok()?;
+  Some((year, month, day))
+}
+
+// Newest first, items without a (valid) release date come last
+pub fn sort_by_release<T>(items: &mut [T], date: impl Fn(&T) -> Option<&str>) {
+  items.sort_by_key(|i| std::cmp::Reverse(date(i).and_then(release_key)));
+}
+
+// Order of `spt list --artists` (--sort)
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ArtistSort {
+  Api,
+  Name,
+  Popularity,
+}
+
+impl ArtistSort {
+  pub fn from_matches(m: &ArgMatches<'_>) -> Self {
+    match m.value_of("sort") {
+      Some("name") => Self::Name,
+      Some("popularity") => Self::Popularity,
+      _ => Self::Api,
+    }
+  }
+
+  pub fn sort(self, artists: &mut [FullArtist]) {
+    match self {
+      Self::Api => (),
+      Self::Name => artists.sort_by_key(|a| a.name.to_lowercase()),
+      Self::Popularity => artists.sort_by_key(|a| std::cmp::Reverse(a.popularity)),
+    }
+  }
+}
+
+// Right after a transfer, spotify sometimes answers with 403 or 404
+// because the state of the device hasn't settled yet
+// A 403 for a restriction or a missing premium account is final though
+pub fn is_transient_device_error(e: &str) -> bool {
+  let e = e.to_lowercase();
+  let settled = e.contains("restriction violated") || e.contains("premium required");
+  (e.contains("error code 403") || e.contains("error code 404")) && !settled
+}
+
+// The limits spotify accepts for one page of results
+// Most endpoints return at most 50 items, recommendations up to 100
+// `list` and `search` fetch bigger limits page by page
+pub const MAX_QUERY_LIMIT: u32 = 10_000;
+
+pub fn parse_limit(limit: &str, max: u32) -> Result<u32> {
+  match limit.parse::<u32>() {
+    Ok(num) if (1..=max).contains(&num) => Ok(num),
+    _ => Err(anyhow!("limit must be between 1 and {}", max)),
+  }
+}
+
+// The names `spt top --range` uses for spotify's time ranges
+pub fn time_range_from_name(name: &str) -> TimeRange {
+  match name {
+    "short" => TimeRange::ShortTerm,
+    "long" => TimeRange::LongTerm,
+    _ => TimeRange::MediumTerm,
+  }
+}
+
+pub struct Retry {
+  pub count: u8,
+  pub delay: Duration,
+}
+
+impl Retry {
+  pub fn from_config(conf: &UserConfig) -> Self {
+    Self {
+      count: conf.behavior.device_retry_count,
+      delay: Duration::from_millis(conf.behavior.device_retry_delay_milliseconds),
+    }
+  }
+
+  // Repeats `attempt` as long as it fails with a transient device error
+  pub async fn run<S, F>(&self, state: &mut S, verbose: bool, mut attempt: F) -> Result<()>
+  where
+    F: for<'s> FnMut(&'s mut S) -> Pin<Box<dyn Future<Output = Result<()>> + 's>>,
+  {
+    let mut retries = 0;
+    loop {
+      match attempt(state).await {
+        Err(e) if retries < self.count && is_transient_device_error(&e.to_string()) => {
+          retries += 1;
+          if verbose {
+            eprintln!("Retrying ({}/{}) after: {}", retries, self.count, e);
+          }
+          tokio::time::sleep(self.delay).await;
         }
-        vec
+        result => return result,
+      }
+    }
+  }
+}
+
+// Describes what a mutating event would do for `--dry-run`
+pub fn describe_event(event: &IoEvent) -> String {
+  match event {
+    IoEvent::StartPlayback(context, uris, offset) => {
+      let mut d = String::from("start playback (PUT /me/player/play)");
+      if let Some(c) = context {
+        d.push_str(&format!(" of {}", c));
       }
-      FormatType::Artist(a) => vec![Self::Artist(a.name), Self::Uri(a.uri)],
-      FormatType::Playlist(p) => vec![Self::Playlist(p.name), Self::Uri(p.uri)],
-      FormatType::Track(t) => {
-        let joined_artists = join_artists(t.artists.clone());
-        vec![
-          Self::Album(t.album.name),
-          Self::Artist(joined_artists),
-          Self::Track(t.name),
-          Self::Uri(t.uri),
-        ]
+      if let Some(u) = uris {
+        d.push_str(&format!(" of {}", u.join(", ")));
       }
-      FormatType::Show(r) => vec![
-        Self::Artist(r.publisher),
-        Self::Show(r.name),
-        Self::Uri(r.uri),
-      ],
-      FormatType::Episode(e) => vec![
-        Self::Show(e.show.name),
-        Self::Artist(e.show.publisher),
-        Self::Track(e.name),
-        Self::Uri(e.uri),
-      ],
-    }
-  }
-
-  // Is there a better way?
-  pub fn inner(&self, conf: UserConfig) -> String {
+      if let (Some(o), Some(_)) = (offset, context) {
+        d.push_str(&format!(" at position {}", o));
+      }
+      d
+    }
+    IoEvent::PausePlayback => "pause playback (PUT /me/player/pause)".to_string(),
+    IoEvent::NextTrack => "skip to the next track (POST /me/player/next)".to_string(),
+    IoEvent::PreviousTrack => "skip to the previous track (POST /me/player/previous)".to_string(),
+    IoEvent::Seek(ms) => format!(
+      "seek to {} (PUT /me/player/seek)",
+      crate::ui::util::millis_to_minutes(*ms as u128)
+    ),
+    IoEvent::Shuffle(current) => format!(
+      "turn shuffle {} (PUT /me/player/shuffle)",
+      if *current { "off" } else { "on" }
+    ),
+    IoEvent::Repeat(current) => {
+      let next = match current {
+        RepeatState::Off => "context",
+        RepeatState::Context => "track",
+        RepeatState::Track => "off",
+      };
+      format!("set repeat to {} (PUT /me/player/repeat)", next)
+    }
+    IoEvent::SetRepeat(state) => {
+      format!("set repeat to {} (PUT /me/player/repeat)", state.as_str())
+    }
+    IoEvent::ChangeVolume(v) => format!("set the volume to {}% (PUT /me/player/volume)", v),
+    IoEvent::ToggleSaveTrack(id) => format!(
+      "toggle the like of spotify:track:{} (PUT or DELETE /me/tracks)",
+      id
+    ),
+    IoEvent::TransferPlaybackToDevice(id) => {
+      format!("transfer playback to device {} (PUT /me/player)", id)
+    }
+    IoEvent::AddItemToQueue(uri) => format!("add {} to the queue (POST /me/player/queue)", uri),
+    e => format!("send {:?}", e),
+  }
+}
+
+// The progress and duration of the playing item as `fetch` leaves them. The old
+// context is dropped first, so that a failed fetch can't leave us seeking
+// relative to a stale position (e.g. when the seek key is spammed)
+pub async fn fresh_progress(
+  app: &Mutex<App>,
+  fetch: impl Future<Output = ()>,
+) -> Result<(u32, u32)> {
+  app.lock().await.current_playback_context = None;
+  fetch.await;
+  match &app.lock().await.current_playback_context {
+    Some(CurrentlyPlaybackContext {
+      progress_ms: Some(ms),
+      item: Some(item),
+      ..
+    }) => {
+      let duration = match item {
+        PlayingItem::Track(track) => track.duration_ms,
+        PlayingItem::Episode(episode) => episode.duration_ms,
+      };
+      Ok((*ms, duration))
+    }
+    _ => Err(anyhow!("no context available")),
+  }
+}
+
+// Calculates the new position (ms) for `--seek` from seconds, a timestamp (`1:02:03`)
+// or a percentage of `duration` (`50%`). Relative positions (`+10`, `-1:30`, `+10%`)
+// are based on `current_pos`
+pub fn seek_position(seconds_str: &str, current_pos: u32, duration: u32) -> Result<u32> {
+  let amount = seconds_str.strip_prefix(['+', '-']).unwrap_or(seconds_str);
+  let ms = match amount.strip_suffix('%') {
+    Some(percent) => match percent.parse::<f64>() {
+      Ok(p) if (0.0..=100.0).contains(&p) => (f64::from(duration) * p / 100.0) as u32,
+      _ => return Err(anyhow!("percentage must be between 0% and 100%")),
+    },
+    None => parse_duration(amount)?,
+  };
+
+  let position = if seconds_str.starts_with('+') {
+    // Past the end skips to the next track, however far
+    current_pos.saturating_add(ms)
+  } else if seconds_str.starts_with('-') {
+    // Jump to the beginning instead of underflowing
+    current_pos.saturating_sub(ms)
+  } else {
+    // Absolute value of the track
+    ms
+  };
+  Ok(position)
+}
+
+// The volume for `--volume`, relative volumes (`+10`, `-10`) are based on `current`
+// and stop at 0 and 100
+pub fn volume_level(volume_str: &str, current: u32) -> Result<u32> {
+  let out_of_range = || anyhow!("volume must be between 0 and 100, or +N / -N");
+  let num = volume_str
+    .strip_prefix(['+', '-'])
+    .unwrap_or(volume_str)
+    .parse::<u32>()
+    .map_err(|_| out_of_range())?;
+  if volume_str.starts_with('+') {
+    Ok(current.saturating_add(num).min(100))
+  } else if volume_str.starts_with('-') {
+    Ok(current.saturating_sub(num))
+  } else if num > 100 {
+    Err(out_of_range())
+  } else {
+    Ok(num)
+  }
+}
+
+// How long `playback --follow` waits before the next check
+pub struct FollowInterval {
+  pub playing: Duration,
+  pub idle: Duration,
+}
+
+impl FollowInterval {
+  // Checks right after the track should have ended, so the next one shows up in time
+  pub fn next(&self, is_playing: bool, remaining_ms: Option<u32>) -> Duration {
+    match remaining_ms {
+      Some(ms) if is_playing => self.playing.min(Duration::from_millis(u64::from(ms) + 500)),
+      _ => self.idle,
+    }
+  }
+}
+
+// The position to continue an episode at, `None` if it wasn't started or is finished
+pub fn resume_position(point: Option<&ResumePoint>) -> Option<u32> {
+  match point {
+    Some(p) if !p.fully_played && p.resume_position_ms > 0 => Some(p.resume_position_ms),
+    _ => None,
+  }
+}
+
+// How `list` and `search` print their results (--output)
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum OutputMode {
+  Text,
+  Tsv,
+  Csv,
+  // One object per line, with the global `--json`
+  Json,
+}
+
+impl OutputMode {
+  pub fn from_matches(m: &ArgMatches<'_>) -> Self {
+    if m.is_present("json") {
+      return Self::Json;
+    }
+    match m.value_of("output") {
+      Some("tsv") => Self::Tsv,
+      Some("csv") => Self::Csv,
+      _ => Self::Text,
+    }
+  }
+
+  // The header row is printed by default if a human is reading (stdout is a tty)
+  pub fn header_from_matches(m: &ArgMatches<'_>) -> bool {
+    if m.is_present("header") {
+      true
+    } else if m.is_present("no-header") {
+      false
+    } else {
+      io::stdout().is_terminal()
+    }
+  }
+}
+
+// The status bars `spt playback --output` can print a module for
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum StatusBar {
+  Waybar,
+  Polybar,
+  I3blocks,
+}
+
+impl StatusBar {
+  pub fn from_matches(m: &ArgMatches<'_>) -> Option<Self> {
+    match m.value_of("output") {
+      Some("waybar") => Some(Self::Waybar),
+      Some("polybar") => Some(Self::Polybar),
+      Some("i3blocks") => Some(Self::I3blocks),
+      _ => None,
+    }
+  }
+
+  // `playing` is None if nothing is playing, which the bars show as an empty module
+  pub fn render(self, text: &str, playing: Option<bool>, paused_color: &str) -> String {
+    let class = match playing {
+      Some(true) => "playing",
+      Some(false) => "paused",
+      None => "stopped",
+    };
+    let dimmed = playing == Some(false);
     match self {
-      Self::Album(s) => s.clone(),
-      Self::Artist(s) => s.clone(),
-      Self::Playlist(s) => s.clone(),
-      Self::Track(s) => s.clone(),
-      Self::Show(s) => s.clone(),
-      Self::Uri(s) => s.clone(),
-      Self::Device(s) => s.clone(),
-      // Because this match statements
-      // needs to return a &String, I have to do it this way
-      Self::Volume(s) => s.to_string(),
-      Self::Position((curr, duration)) => {
-        crate::ui::util::display_track_progress(*curr as u128, *duration)
+      // Waybar parses the text as pango markup
+      Self::Waybar => {
+        let text = text
+          .replace('&', "&amp;")
+          .replace('<', "&lt;")
+          .replace('>', "&gt;");
+        json!({ "text": text, "tooltip": text, "class": class, "alt": class }).to_string()
       }
-      Self::Flags((r, s, l)) => {
-        let like = if *l {
-          conf.behavior.liked_icon
-        } else {
-          String::new()
-        };
-        let shuffle = if *s {
-          conf.behavior.shuffle_icon
-        } else {
-          String::new()
-        };
-        let repeat = match r {
-          RepeatState::Off => String::new(),
-          RepeatState::Track => conf.behavior.repeat_track_icon,
-          RepeatState::Context => conf.behavior.repeat_context_icon,
-        };
-
-        // Add them together (only those that aren't empty)
-        [shuffle, repeat, like]
-          .iter()
-          .filter(|a| !a.is_empty())
-          // Convert &String to String to join them
-          .map(|s| s.to_string())
-          .collect::<Vec<String>>()
-          .join(" ")
+      Self::Polybar if dimmed => format!("%{{F{}}}{}%{{F-}}", paused_color, text),
+      Self::Polybar => text.to_string(),
+      // Full text, short text and color, each on a line
+      Self::I3blocks if dimmed => format!("{}\n{}\n{}", text, text, paused_color),
+      Self::I3blocks => format!("{}\n{}", text, text),
+    }
+  }
+}
+
+// Every placeholder in `format` becomes one column in the tabular output modes
+pub fn columns(format: &str) -> Vec<&str> {
+  let mut columns = Vec::new();
+  let mut rest = format;
+  while let Some(i) = rest.find('%') {
+    rest = &rest[i..];
+    match match_token(rest) {
+      Some(p) => {
+        columns.push(p);
+        rest = &rest[p.len()..];
       }
-      Self::Playing(s) => {
-        if *s {
-          conf.behavior.playing_icon
-        } else {
-          conf.behavior.paused_icon
-        }
+      None => rest = &rest[1..],
+    }
+  }
+  columns
+}
+
+pub fn column_name(placeholder: &str) -> String {
+  if let Some(market) = placeholder.strip_prefix("%avail?") {
+    return format!("available_{}", market.to_uppercase());
+  }
+  let (_, placeholder) = split_padding(placeholder);
+  let name = match placeholder.as_str() {
+    "%a" => "artist",
+    "%b" => "album",
+    "%t" => "track",
+    "%p" => "playlist",
+    "%h" => "show",
+    "%u" => "uri",
+    "%d" => "device",
+    "%v" => "volume",
+    "%r" => "progress",
+    "%pct" => "percent",
+    "%dur_ctx" => "context_remaining",
+    "%f" => "flags",
+    "%s" => "status",
+    "%k" => "key",
+    "%bpm" => "tempo",
+    "%dance" => "danceability",
+    "%energy" => "energy",
+    "%valence" => "valence",
+    "%loud" => "loudness",
+    "%mode" => "mode",
+    "%ts" => "time_signature",
+    "%l" => "label",
+    "%cr" => "copyright",
+    "%disc" => "disc",
+    "%src" => "source",
+    "%avail" => "markets",
+    "%len" => "length",
+    "%e" => "elapsed",
+    "%y" => "year",
+    "%n" => "track_number",
+    "%i" => "popularity",
+    "%c" => "isrc",
+    _ => "",
+  };
+  name.to_string()
+}
+
+fn escape_cell(cell: &str, mode: OutputMode) -> String {
+  match mode {
+    OutputMode::Tsv => cell.replace(['\t', '\n', '\r'], " "),
+    OutputMode::Csv => {
+      if cell.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", cell.replace('"', "\"\""))
+      } else {
+        cell.to_string()
       }
     }
+    OutputMode::Text | OutputMode::Json => cell.to_string(),
   }
+}
 
-  pub fn get_placeholder(&self) -> &str {
-    match self {
-      Self::Album(_) => "%b",
-      Self::Artist(_) => "%a",
-      Self::Playlist(_) => "%p",
-      Self::Track(_) => "%t",
-      Self::Show(_) => "%h",
-      Self::Uri(_) => "%u",
-      Self::Device(_) => "%d",
-      Self::Volume(_) => "%v",
-      Self::Position(_) => "%r",
-      Self::Flags(_) => "%f",
-      Self::Playing(_) => "%s",
+fn separator(mode: OutputMode) -> &'static str {
+  match mode {
+    OutputMode::Csv => ",",
+    _ => "\t",
+  }
+}
+
+// `--json` prints every value that is known about an item, not just those in the format
+pub fn render_json(values: &[Format]) -> String {
+  let mut object = Map::new();
+  for value in values {
+    for (key, field) in value.json_fields() {
+      object.insert(key.to_string(), field);
+    }
+  }
+  Value::Object(object).to_string()
+}
+
+pub fn render_row(format: &str, values: &[Format], conf: &UserConfig, mode: OutputMode) -> String {
+  columns(format)
+    .iter()
+    .map(|p| escape_cell(&render(p, values, conf), mode))
+    .collect::<Vec<String>>()
+    .join(separator(mode))
+}
+
+pub fn render_header(format: &str, mode: OutputMode) -> String {
+  columns(format)
+    .iter()
+    .map(|p| column_name(p))
+    .collect::<Vec<String>>()
+    .join(separator(mode))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn parse_limit_test() {
+    assert_eq!(parse_limit("1", 50).unwrap(), 1);
+    assert_eq!(parse_limit("50", 50).unwrap(), 50);
+    assert!(parse_limit("0", 50).is_err());
+    assert!(parse_limit("51", 50).is_err());
+    assert_eq!(parse_limit("100", 100).unwrap(), 100);
+    assert!(parse_limit("ten", 50).is_err());
+  }
+
+  #[test]
+  fn status_bar_test() {
+    assert_eq!(
+      StatusBar::Waybar.render("▶ Rock & Roll", Some(true), "#888888"),
+      r#"{"alt":"playing","class":"playing","text":"▶ Rock &amp; Roll","tooltip":"▶ Rock &amp; Roll"}"#
+    );
+    assert_eq!(
+      StatusBar::Waybar.render("", None, "#888888"),
+      r#"{"alt":"stopped","class":"stopped","text":"","tooltip":""}"#
+    );
+    assert_eq!(
+      StatusBar::Polybar.render("Song", Some(false), "#888888"),
+      "%{F#888888}Song%{F-}"
+    );
+    assert_eq!(
+      StatusBar::Polybar.render("Song", Some(true), "#888888"),
+      "Song"
+    );
+    assert_eq!(
+      StatusBar::I3blocks.render("Song", Some(false), "#888888"),
+      "Song\nSong\n#888888"
+    );
+  }
+
+  #[test]
+  fn parse_duration_test() {
+    assert_eq!(parse_duration("90").unwrap(), 90 * 1000);
+    assert_eq!(parse_duration("1:30").unwrap(), 90 * 1000);
+    assert_eq!(parse_duration("0:05").unwrap(), 5 * 1000);
+    assert!(parse_duration("1:60").is_err());
+    assert_eq!(parse_duration("1:02:03").unwrap(), 3723 * 1000);
+    assert!(parse_duration("1:2:3:4").is_err());
+    assert!(parse_duration("abc").is_err());
+    assert!(parse_duration("99999999:00").is_err());
+    assert!(parse_duration("4294968").is_err());
+  }
+
+  #[test]
+  fn duration_range_test() {
+    let range = DurationRange {
+      min: Some(60 * 1000),
+      max: Some(120 * 1000),
+    };
+    assert!(range.contains(90 * 1000));
+    assert!(!range.contains(30 * 1000));
+    assert!(!range.contains(150 * 1000));
+
+    let open = DurationRange {
+      min: None,
+      max: None,
+    };
+    assert!(open.contains(0));
+  }
+
+  #[test]
+  fn resume_position_test() {
+    let point = |fully_played, resume_position_ms| ResumePoint {
+      fully_played,
+      resume_position_ms,
+    };
+    assert_eq!(resume_position(Some(&point(false, 90000))), Some(90000));
+    assert_eq!(resume_position(Some(&point(true, 90000))), None);
+    assert_eq!(resume_position(Some(&point(false, 0))), None);
+    assert_eq!(resume_position(None), None);
+  }
+
+  #[test]
+  fn remaining_in_context_test() {
+    let tracks = vec![
+      ("spotify:track:1".to_string(), 60000),
+      ("spotify:track:2".to_string(), 120000),
+      ("spotify:track:3".to_string(), 30000),
+    ];
+    assert_eq!(
+      remaining_in_context(&tracks, "spotify:track:2", 20000),
+      Some(130000)
+    );
+    assert_eq!(
+      remaining_in_context(&tracks, "spotify:track:3", 30000),
+      Some(0)
+    );
+    assert_eq!(remaining_in_context(&tracks, "spotify:track:4", 0), None);
+
+    // Longer than u32 milliseconds
+    let long = (0..20)
+      .map(|i| (format!("spotify:track:{}", i), u32::MAX / 10))
+      .collect::<Vec<(String, u32)>>();
+    assert_eq!(
+      remaining_in_context(&long, "spotify:track:0", 0),
+      Some(u32::MAX)
+    );
+  }
+
+  #[test]
+  fn disallowed_message_test() {
+    assert_eq!(
+      disallowed_message(DisallowKey::SkippingNext, true),
+      "skipping is not allowed during an ad (use --force to try anyway)"
+    );
+    assert_eq!(
+      disallowed_message(DisallowKey::Seeking, false),
+      "seeking is not allowed right now (use --force to try anyway)"
+    );
+  }
+
+  #[test]
+  fn play_input_test() {
+    assert_eq!(
+      PlayInput::detect("spotify:album:abc"),
+      PlayInput::Uri("spotify:album:abc".to_string())
+    );
+    assert_eq!(
+      PlayInput::detect("https://open.spotify.com/intl-de/playlist/abc?si=123"),
+      PlayInput::Uri("spotify:playlist:abc".to_string())
+    );
+    assert_eq!(
+      PlayInput::detect("open.spotify.com/track/abc"),
+      PlayInput::Uri("spotify:track:abc".to_string())
+    );
+    assert_eq!(link_to_uri("https://open.spotify.com/intl-de/artist"), None);
+    assert_eq!(
+      PlayInput::detect(" Bohemian Rhapsody "),
+      PlayInput::Name("Bohemian Rhapsody".to_string())
+    );
+  }
+
+  #[test]
+  fn parse_track_id_test() {
+    let id = "4uLU6hMCjMI75M1A2tKUQC";
+    assert_eq!(
+      parse_track_id(&format!("spotify:track:{}", id)).unwrap(),
+      id
+    );
+    assert_eq!(
+      parse_track_id(&format!("https://open.spotify.com/track/{}?si=abc", id)).unwrap(),
+      id
+    );
+    assert_eq!(parse_track_id(id).unwrap(), id);
+    assert_eq!(parse_track_id("spotify:album:4uLU6hMCjMI75M1A2tKUQC"), None);
+    assert_eq!(parse_track_id("spotify:track:short"), None);
+    assert_eq!(
+      parse_id(&format!("spotify:playlist:{}", id), "playlist").unwrap(),
+      id
+    );
+    assert_eq!(parse_id(&format!("spotify:track:{}", id), "playlist"), None);
+    assert_eq!(
+      parse_id(
+        &format!("https://open.spotify.com/intl-de/playlist/{}?si=abc", id),
+        "playlist"
+      )
+      .unwrap(),
+      id
+    );
+  }
+
+  #[test]
+  fn queueable_uri_test() {
+    assert_eq!(
+      queueable_uri("spotify:track:abc").unwrap(),
+      "spotify:track:abc"
+    );
+    assert_eq!(
+      queueable_uri("https://open.spotify.com/episode/abc?si=x").unwrap(),
+      "spotify:episode:abc"
+    );
+    assert_eq!(queueable_uri("spotify:album:abc"), None);
+    assert_eq!(queueable_uri("some song"), None);
+  }
+
+  #[test]
+  fn uri_to_url_test() {
+    assert_eq!(
+      uri_to_url("spotify:album:abc").unwrap(),
+      "https://open.spotify.com/album/abc"
+    );
+    assert_eq!(uri_to_url("https://example.com"), None);
+  }
+
+  #[test]
+  fn sort_by_release_test() {
+    assert_eq!(release_key("2020-05-01"), Some((2020, 5, 1)));
+    assert_eq!(release_key("2020-05"), Some((2020, 5, 0)));
+    assert_eq!(release_key("2020"), Some((2020, 0, 0)));
+    assert_eq!(release_key("0000-xx"), None);
+
+    let mut dates = vec![
+      Some("2019-12-31"),
+      None,
+      Some("2020"),
+      Some("2020-02"),
+      Some("2018-01-01"),
+    ];
+    sort_by_release(&mut dates, |d| *d);
+    assert_eq!(
+      dates,
+      vec![
+        Some("2020-02"),
+        Some("2020"),
+        Some("2019-12-31"),
+        Some("2018-01-01"),
+        None
+      ]
+    );
+  }
+
+  #[test]
+  fn resolve_device_test() {
+    let device = |id: &str, name: &str, is_active: bool| -> Device {
+      serde_json::from_value(serde_json::json!({
+        "id": id,
+        "is_active": is_active,
+        "is_restricted": false,
+        "name": name,
+        "type": "Computer",
+        "volume_percent": 50,
+      }))
+      .unwrap()
+    };
+    let devices = vec![
+      device("1", "Laptop", false),
+      device("2", "Phone", true),
+      device("3", "Speaker", false),
+    ];
+    let choice = |flag, env, last_used, default| DeviceChoice {
+      flag,
+      env,
+      last_used,
+      default,
+    };
+    let resolve = |c: DeviceChoice<'_>| resolve_device(&c, &devices).unwrap();
+
+    assert_eq!(
+      resolve(choice(Some("Speaker"), Some("Laptop"), Some("2"), None)),
+      Some("3".to_string())
+    );
+    assert_eq!(
+      resolve(choice(None, Some("Laptop"), Some("2"), None)),
+      Some("1".to_string())
+    );
+    assert_eq!(
+      resolve(choice(None, None, Some("3"), Some("Laptop"))),
+      Some("3".to_string())
+    );
+    // The last used device is gone
+    assert_eq!(
+      resolve(choice(None, None, Some("4"), Some("Laptop"))),
+      Some("1".to_string())
+    );
+    // The default device is unavailable, so the active one is used
+    assert_eq!(
+      resolve(choice(None, None, None, Some("TV"))),
+      Some("2".to_string())
+    );
+    assert_eq!(
+      resolve(choice(Some("speak"), None, None, None)),
+      Some("3".to_string())
+    );
+    assert!(resolve_device(&choice(Some("TV"), None, None, None), &devices).is_err());
+    assert_eq!(
+      resolve_device(&choice(None, None, None, None), &[]).unwrap(),
+      None
+    );
+  }
+
+  #[test]
+  fn find_device_test() {
+    let device = |id: &str, name: &str| -> Device {
+      serde_json::from_value(serde_json::json!({
+        "id": id,
+        "is_active": false,
+        "is_restricted": false,
+        "name": name,
+        "type": "Computer",
+        "volume_percent": 50,
+      }))
+      .unwrap()
+    };
+    let devices = vec![
+      device("1", "DESKTOP-3AB2F1"),
+      device("2", "Desk Speaker"),
+      device("3", "Kitchen"),
+      device("4", "Kitchen Speaker"),
+    ];
+    let id = |query: &str| find_device(&devices, query).map(|d| d.id.as_str());
+
+    assert_eq!(id("2").unwrap(), "2");
+    assert_eq!(id("kitchen").unwrap(), "3");
+    assert_eq!(id("desktop").unwrap(), "1");
+    assert_eq!(id("dsk3ab").unwrap(), "1");
+    assert_eq!(
+      id("desk").unwrap_err().to_string(),
+      "'desk' matches more than one device: 'DESKTOP-3AB2F1', 'Desk Speaker'"
+    );
+    assert_eq!(
+      id("TV").unwrap_err().to_string(),
+      "no device with name 'TV' available, there are 'DESKTOP-3AB2F1', 'Desk Speaker', 'Kitchen', 'Kitchen Speaker'"
+    );
+  }
+
+  #[test]
+  fn artist_sort_test() {
+    let artist = |name: &str, popularity: u32| -> FullArtist {
+      serde_json::from_value(serde_json::json!({
+        "external_urls": {},
+        "followers": {},
+        "genres": [],
+        "href": "",
+        "id": name,
+        "images": [],
+        "name": name,
+        "popularity": popularity,
+        "type": "artist",
+        "uri": format!("spotify:artist:{}", name),
+      }))
+      .unwrap()
+    };
+    let names = |artists: &[FullArtist]| {
+      artists
+        .iter()
+        .map(|a| a.name.clone())
+        .collect::<Vec<String>>()
+    };
+    let mut artists = vec![artist("beta", 10), artist("Alpha", 5), artist("gamma", 50)];
+
+    ArtistSort::Api.sort(&mut artists);
+    assert_eq!(names(&artists), vec!["beta", "Alpha", "gamma"]);
+    ArtistSort::Name.sort(&mut artists);
+    assert_eq!(names(&artists), vec!["Alpha", "beta", "gamma"]);
+    ArtistSort::Popularity.sort(&mut artists);
+    assert_eq!(names(&artists), vec!["gamma", "beta", "Alpha"]);
+  }
+
+  #[tokio::test]
+  async fn retry_test() {
+    let retry = Retry {
+      count: 2,
+      delay: Duration::ZERO,
+    };
+    let transient = || anyhow!("Spotify API error code 404: Device not found");
+
+    // Fails once, then succeeds
+    let mut results = vec![Ok(()), Err(transient())];
+    fn attempt(r: &mut Vec<Result<()>>) -> Pin<Box<dyn Future<Output = Result<()>> + '_>> {
+      let result = r.pop().unwrap();
+      Box::pin(async move { result })
     }
+    assert!(retry.run(&mut results, false, attempt).await.is_ok());
+    assert!(results.is_empty());
+
+    // Gives up after `count` retries
+    let mut results = vec![Err(transient()), Err(transient()), Err(transient())];
+    assert!(retry.run(&mut results, false, attempt).await.is_err());
+    assert!(results.is_empty());
+
+    // Other errors aren't retried
+    let mut results = vec![Ok(()), Err(anyhow!("Unauthorized request to API"))];
+    assert!(retry.run(&mut results, false, attempt).await.is_err());
+    assert_eq!(results.len(), 1);
+
+    // Neither are 403s which don't depend on the device
+    let restricted =
+      anyhow!("Spotify API error code 403: Player command failed: Restriction violated");
+    let mut results = vec![Ok(()), Err(restricted)];
+    assert!(retry.run(&mut results, false, attempt).await.is_err());
+    assert_eq!(results.len(), 1);
+  }
+
+  #[test]
+  fn describe_event_test() {
+    assert_eq!(
+      describe_event(&IoEvent::StartPlayback(
+        Some("spotify:album:1".to_string()),
+        None,
+        Some(3)
+      )),
+      "start playback (PUT /me/player/play) of spotify:album:1 at position 3"
+    );
+    assert_eq!(
+      describe_event(&IoEvent::Shuffle(false)),
+      "turn shuffle on (PUT /me/player/shuffle)"
+    );
+    assert_eq!(
+      describe_event(&IoEvent::Repeat(RepeatState::Track)),
+      "set repeat to off (PUT /me/player/repeat)"
+    );
+    assert_eq!(
+      describe_event(&IoEvent::SetRepeat(RepeatState::Track)),
+      "set repeat to track (PUT /me/player/repeat)"
+    );
+    assert_eq!(
+      describe_event(&IoEvent::ChangeVolume(40)),
+      "set the volume to 40% (PUT /me/player/volume)"
+    );
+  }
+
+  #[tokio::test]
+  async fn fresh_progress_test() {
+    use rspotify::model::context::Actions;
+    use rspotify::senum::{CurrentlyPlayingType, DeviceType};
+
+    let context = |progress_ms: u32| CurrentlyPlaybackContext {
+      device: Device {
+        id: "1".to_string(),
+        is_active: true,
+        is_restricted: false,
+        name: "Speaker".to_string(),
+        _type: DeviceType::Speaker,
+        volume_percent: 50,
+      },
+      repeat_state: RepeatState::Off,
+      shuffle_state: false,
+      context: None,
+      timestamp: 0,
+      progress_ms: Some(progress_ms),
+      is_playing: true,
+      item: Some(PlayingItem::Track(crate::test_util::track(
+        "1",
+        json!({ "duration_ms": 200_000 }),
+      ))),
+      currently_playing_type: CurrentlyPlayingType::Track,
+      actions: Actions {
+        disallows: Default::default(),
+      },
+    };
+    let app = Mutex::new(App::default());
+
+    // The stale progress isn't used, but what the fetch brought
+    app.lock().await.current_playback_context = Some(context(60_000));
+    let fetch = async {
+      app.lock().await.current_playback_context = Some(context(5_000));
+    };
+    assert_eq!(fresh_progress(&app, fetch).await.unwrap(), (5_000, 200_000));
+
+    // Nor is it after a failed fetch
+    app.lock().await.current_playback_context = Some(context(60_000));
+    assert!(fresh_progress(&app, async {}).await.is_err());
+  }
+
+  #[test]
+  fn seek_position_test() {
+    let duration = 3_600_000;
+    assert_eq!(seek_position("+10", 5000, duration).unwrap(), 15000);
+    assert_eq!(seek_position("+10", 60000, duration).unwrap(), 70000);
+    assert_eq!(seek_position("-10", 60000, duration).unwrap(), 50000);
+    assert_eq!(seek_position("-10", 5000, duration).unwrap(), 0);
+    assert_eq!(seek_position("10", 60000, duration).unwrap(), 10000);
+    assert!(seek_position("ten", 0, duration).is_err());
+
+    assert_eq!(seek_position("1:30", 0, duration).unwrap(), 90000);
+    assert_eq!(seek_position("1:02:03", 0, duration).unwrap(), 3_723_000);
+    assert_eq!(seek_position("-1:00", 90000, duration).unwrap(), 30000);
+    assert_eq!(seek_position("50%", 0, duration).unwrap(), 1_800_000);
+    assert_eq!(seek_position("+10%", 60000, duration).unwrap(), 420_000);
+    assert!(seek_position("150%", 0, duration).is_err());
+    assert!(seek_position("1:60", 0, duration).is_err());
+    assert_eq!(
+      seek_position("+4294967", 60000, duration).unwrap(),
+      u32::MAX
+    );
+  }
+
+  #[test]
+  fn follow_interval_test() {
+    let interval = FollowInterval {
+      playing: Duration::from_secs(2),
+      idle: Duration::from_secs(10),
+    };
+    assert_eq!(interval.next(true, Some(60_000)), Duration::from_secs(2));
+    assert_eq!(interval.next(true, Some(1000)), Duration::from_millis(1500));
+    assert_eq!(interval.next(false, Some(1000)), Duration::from_secs(10));
+    assert_eq!(interval.next(true, None), Duration::from_secs(10));
+  }
+
+  #[test]
+  fn volume_level_test() {
+    assert_eq!(volume_level("40", 70).unwrap(), 40);
+    assert_eq!(volume_level("+10", 70).unwrap(), 80);
+    assert_eq!(volume_level("+50", 70).unwrap(), 100);
+    assert_eq!(volume_level("+4294967295", 70).unwrap(), 100);
+    assert_eq!(volume_level("-10", 70).unwrap(), 60);
+    assert_eq!(volume_level("-90", 70).unwrap(), 0);
+    assert!(volume_level("101", 0).is_err());
+    assert!(volume_level("+-1", 0).is_err());
+    assert!(volume_level("loud", 0).is_err());
+  }
+
+  #[test]
+  fn render_markets_test() {
+    let conf = UserConfig::new();
+    let values = vec![Format::Markets(Some(vec![
+      "DE".to_string(),
+      "US".to_string(),
+    ]))];
+    assert_eq!(
+      render("%avail: %avail?de %avail?FR", &values, &conf),
+      "2: yes no"
+    );
+    assert_eq!(render("%avail?", &values, &conf), "2?");
+    assert_eq!(render("[%avail?US]", &[Format::Markets(None)], &conf), "[]");
+    assert_eq!(
+      render_header("%t %avail %avail?us", OutputMode::Csv),
+      "track,markets,available_US"
+    );
+  }
+
+  #[test]
+  fn render_json_test() {
+    let values = vec![
+      Format::Track("Hello, \"World\"".to_string()),
+      Format::Uri("spotify:track:1".to_string()),
+      Format::Position((1000, 2000)),
+      Format::Flags((RepeatState::Track, true, false)),
+      Format::Disc(None),
+    ];
+    let json: Value = serde_json::from_str(&render_json(&values)).unwrap();
+    assert_eq!(
+      json,
+      json!({
+        "track": "Hello, \"World\"",
+        "uri": "spotify:track:1",
+        "progress_ms": 1000,
+        "duration_ms": 2000,
+        "shuffle": true,
+        "repeat": "track",
+        "liked": false,
+        "disc": null,
+      })
+    );
+  }
+
+  #[test]
+  fn render_row_test() {
+    let conf = UserConfig::new();
+    let values = vec![
+      Format::Track("Hello, World".to_string()),
+      Format::Artist("Say \"Hi\"".to_string()),
+      Format::Uri("spotify:track:1".to_string()),
+    ];
+    assert_eq!(columns("%t - %a (%u)"), vec!["%t", "%a", "%u"]);
+    assert_eq!(
+      render_header("%t - %a (%u)", OutputMode::Csv),
+      "track,artist,uri"
+    );
+    assert_eq!(
+      render_row("%t - %a (%u)", &values, &conf, OutputMode::Csv),
+      "\"Hello, World\",\"Say \"\"Hi\"\"\",spotify:track:1"
+    );
+    assert_eq!(
+      render_row("%t %u", &values, &conf, OutputMode::Tsv),
+      "Hello, World\tspotify:track:1"
+    );
   }
 }
@@ -0,0 +1,73 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::{fs, path::Path};
+
+// Old devices are dropped so the file can't grow forever
+const MAX_DEVICES: usize = 32;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct DeviceVolume {
+  id: String,
+  volume: u8,
+}
+
+// The last volume set on each device, most recently used first
+#[derive(Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DeviceVolumes {
+  devices: Vec<DeviceVolume>,
+}
+
+impl DeviceVolumes {
+  // A missing or broken file is treated as empty
+  pub fn load(path: &Path) -> Self {
+    fs::read_to_string(path)
+      .ok()
+      .and_then(|s| serde_yaml::from_str(&s).ok())
+      .unwrap_or_default()
+  }
+
+  pub fn save(&self, path: &Path) -> Result<()> {
+    fs::write(path, serde_yaml::to_string(self)?)?;
+    Ok(())
+  }
+
+  pub fn get(&self, id: &str) -> Option<u8> {
+    self.devices.iter().find(|d| d.id == id).map(|d| d.volume)
+  }
+
+  pub fn set(&mut self, id: &str, volume: u8) {
+    self.devices.retain(|d| d.id != id);
+    self.devices.insert(
+      0,
+      DeviceVolume {
+        id: id.to_string(),
+        volume,
+      },
+    );
+    self.devices.truncate(MAX_DEVICES);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn device_volumes_test() {
+    let mut volumes = DeviceVolumes::default();
+    assert_eq!(volumes.get("speaker"), None);
+
+    volumes.set("speaker", 80);
+    volumes.set("headphones", 30);
+    volumes.set("speaker", 70);
+    assert_eq!(volumes.get("speaker"), Some(70));
+    assert_eq!(volumes.get("headphones"), Some(30));
+
+    // The least recently used devices are dropped first
+    for i in 0..MAX_DEVICES {
+      volumes.set(&i.to_string(), 50);
+    }
+    assert_eq!(volumes.get("speaker"), None);
+    assert_eq!(volumes.devices.len(), MAX_DEVICES);
+  }
+}
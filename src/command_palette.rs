@@ -0,0 +1,185 @@
+use crate::event::Key;
+use crate::user_config::KeyBindings;
+use rspotify::senum::RepeatState;
+
+pub enum Action {
+  // Does whatever the key is bound to, so the palette always matches the config
+  Key(fn(&KeyBindings) -> Key),
+  // Opens an entry of `LIBRARY_OPTIONS`
+  Library(usize),
+  SaveTrack,
+  SaveAlbum,
+  Repeat(RepeatState),
+}
+
+pub struct Command {
+  pub name: &'static str,
+  pub action: Action,
+}
+
+macro_rules! key_command {
+  ($name: expr, $key: ident) => {
+    Command {
+      name: $name,
+      action: Action::Key(|keys| keys.$key),
+    }
+  };
+}
+
+pub const COMMANDS: [Command; 42] = [
+  key_command!("Play/pause", toggle_playback),
+  key_command!("Next track", next_track),
+  key_command!("Previous track", previous_track),
+  key_command!("Seek forwards", seek_forwards),
+  key_command!("Seek backwards", seek_backwards),
+  key_command!("Increase volume", increase_volume),
+  key_command!("Decrease volume", decrease_volume),
+  key_command!("Toggle shuffle", shuffle),
+  key_command!("Cycle repeat mode", repeat),
+  Command {
+    name: "Repeat off",
+    action: Action::Repeat(RepeatState::Off),
+  },
+  Command {
+    name: "Repeat the track",
+    action: Action::Repeat(RepeatState::Track),
+  },
+  Command {
+    name: "Repeat the playlist or album",
+    action: Action::Repeat(RepeatState::Context),
+  },
+  Command {
+    name: "Like/unlike the current track",
+    action: Action::SaveTrack,
+  },
+  Command {
+    name: "Save the album of the current track",
+    action: Action::SaveAlbum,
+  },
+  key_command!("Search", search),
+  key_command!("Find in your library", library_finder),
+  key_command!("Transfer playback to another device", manage_devices),
+  key_command!("Switch to another account profile", switch_profile),
+  key_command!("Hide or show the library and playlists", toggle_sidebar),
+  key_command!("Undo the last unlike, unfollow or deletion", undo),
+  key_command!("Add the selected item to the queue", add_item_to_queue),
+  key_command!("Add the selected track to a playlist", add_to_playlist),
+  key_command!("Sort the track table", sort_tracks),
+  key_command!("Filter the track table", filter),
+  key_command!(
+    "Show the audio features of the selected track",
+    audio_features
+  ),
+  key_command!(
+    "Save or unsave the album of the selected track",
+    toggle_save_album
+  ),
+  key_command!("Go to the album of the current track", jump_to_album),
+  key_command!(
+    "Go to the artist of the current track",
+    jump_to_artist_album
+  ),
+  key_command!("Go to the context of the current track", jump_to_context),
+  Command {
+    name: "Go to Made For You",
+    action: Action::Library(0),
+  },
+  Command {
+    name: "Go to Recently Played",
+    action: Action::Library(1),
+  },
+  Command {
+    name: "Go to Liked Songs",
+    action: Action::Library(2),
+  },
+  Command {
+    name: "Go to Albums",
+    action: Action::Library(3),
+  },
+  Command {
+    name: "Go to Artists",
+    action: Action::Library(4),
+  },
+  Command {
+    name: "Go to Podcasts",
+    action: Action::Library(5),
+  },
+  Command {
+    name: "Go to your Top Tracks and Artists",
+    action: Action::Library(6),
+  },
+  key_command!("Show lyrics", lyrics),
+  key_command!("Show audio analysis", audio_analysis),
+  key_command!("Show basic view", basic_view),
+  key_command!("Copy the url of the current song", copy_song_url),
+  key_command!("Copy the url of the current album", copy_album_url),
+  key_command!("Show help", help),
+];
+
+#[derive(Default)]
+pub struct CommandPalette {
+  pub query: String,
+  pub selected: usize,
+}
+
+impl CommandPalette {
+  // Best matches first, ties stay in the order of `COMMANDS`
+  pub fn matches(&self) -> Vec<&'static Command> {
+    let mut scored = COMMANDS
+      .iter()
+      .filter_map(|c| fuzzy_score(&self.query, c.name).map(|score| (score, c)))
+      .collect::<Vec<_>>();
+    scored.sort_by_key(|(score, _)| -score);
+    scored.into_iter().map(|(_, c)| c).collect()
+  }
+}
+
+// Every character of the query has to appear in order, matches at the start of
+// a word and runs of consecutive characters score higher
+pub fn fuzzy_score(query: &str, candidate: &str) -> Option<i32> {
+  let candidate = candidate.to_lowercase().chars().collect::<Vec<char>>();
+  let mut score = 0;
+  let mut position = 0;
+  let mut previous = None;
+  for q in query.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+    let found = position + candidate[position..].iter().position(|c| *c == q)?;
+    score += 1;
+    if found == 0 || candidate[found - 1] == ' ' || candidate[found - 1] == '/' {
+      score += 3;
+    }
+    if previous.map(|p| p + 1 == found).unwrap_or(false) {
+      score += 2;
+    }
+    previous = Some(found);
+    position = found + 1;
+  }
+  Some(score)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn fuzzy_score_test() {
+    assert_eq!(fuzzy_score("", "Next track"), Some(0));
+    assert!(fuzzy_score("nt", "Next track").is_some());
+    assert!(fuzzy_score("tn", "Next track").is_none());
+    assert!(fuzzy_score("NEXT", "Next track") > fuzzy_score("next", "Go to the context"));
+  }
+
+  #[test]
+  fn matches_test() {
+    let mut palette = CommandPalette::default();
+    assert_eq!(palette.matches().len(), COMMANDS.len());
+
+    palette.query = "shuf".to_string();
+    assert_eq!(palette.matches()[0].name, "Toggle shuffle");
+
+    palette.query = "liked".to_string();
+    assert_eq!(palette.matches()[0].name, "Go to Liked Songs");
+
+    palette.query = "zzz".to_string();
+    assert!(palette.matches().is_empty());
+  }
+}
@@ -1,5 +1,7 @@
 use super::banner::BANNER;
+use crate::keyring::{Keyring, CLIENT_SECRET};
 use anyhow::{anyhow, Error, Result};
+use rspotify::oauth2::SpotifyOAuth;
 use serde::{Deserialize, Serialize};
 use std::{
   fs,
@@ -12,6 +14,58 @@ const FILE_NAME: &str = "client.yml";
 const CONFIG_DIR: &str = ".config";
 const APP_CONFIG_DIR: &str = "spotify-tui";
 const TOKEN_CACHE_FILE: &str = ".spotify_token_cache.json";
+const DEVICE_VOLUMES_FILE: &str = "device_volumes.yml";
+const DAEMON_SOCKET_FILE: &str = "spt.sock";
+const CACHE_DIR: &str = "cache";
+// Every profile has its own directory in here with the files above, except
+// for `config.yml` which they share
+const PROFILES_DIR: &str = "profiles";
+const DEFAULT_PROFILE: &str = "default";
+
+const SCOPES: [&str; 15] = [
+  "playlist-read-collaborative",
+  "playlist-read-private",
+  "playlist-modify-private",
+  "playlist-modify-public",
+  "user-follow-read",
+  "user-follow-modify",
+  "user-library-modify",
+  "user-library-read",
+  "user-modify-playback-state",
+  "user-read-currently-playing",
+  "user-read-playback-state",
+  "user-read-playback-position",
+  "user-read-private",
+  "user-read-recently-played",
+  "user-top-read",
+];
+
+// The profile from `--profile` or $SPOTIFY_TUI_PROFILE, `None` is the default
+// one that lives right in the config directory
+pub fn parse_profile(name: &str) -> Result<Option<String>> {
+  if name == DEFAULT_PROFILE {
+    return Ok(None);
+  }
+  let valid = |c: char| c.is_ascii_alphanumeric() || c == '-' || c == '_';
+  if name.is_empty() || !name.chars().all(valid) {
+    return Err(anyhow!(
+      "invalid profile name '{}', use letters, digits, '-' and '_'",
+      name
+    ));
+  }
+  Ok(Some(name.to_string()))
+}
+
+// Whether a token was given every permission spt asks for, it needs a new
+// login otherwise
+pub fn covers_scopes(scope: &str) -> bool {
+  let granted = scope.split_whitespace().collect::<Vec<&str>>();
+  SCOPES.iter().all(|s| granted.contains(s))
+}
+
+pub fn profile_name(profile: Option<&str>) -> &str {
+  profile.unwrap_or(DEFAULT_PROFILE)
+}
 
 #[derive(Default, Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct ClientConfig {
@@ -20,11 +74,19 @@ pub struct ClientConfig {
   pub device_id: Option<String>,
   // FIXME: port should be defined in `user_config` not in here
   pub port: Option<u16>,
+  #[serde(skip)]
+  pub profile: Option<String>,
+  // The client secret and the login are in the keyring, see `use_keyring`
+  #[serde(skip)]
+  pub use_keyring: bool,
 }
 
 pub struct ConfigPaths {
   pub config_file_path: PathBuf,
   pub token_cache_path: PathBuf,
+  pub device_volumes_path: PathBuf,
+  pub daemon_socket_path: PathBuf,
+  pub cache_dir: PathBuf,
 }
 
 impl ClientConfig {
@@ -34,9 +96,53 @@ impl ClientConfig {
       client_secret: "".to_string(),
       device_id: None,
       port: None,
+      profile: None,
+      use_keyring: false,
     }
   }
 
+  pub fn for_profile(profile: Option<String>) -> ClientConfig {
+    ClientConfig {
+      profile,
+      ..ClientConfig::new()
+    }
+  }
+
+  pub fn oauth(&self, paths: &ConfigPaths) -> SpotifyOAuth {
+    SpotifyOAuth::default()
+      .client_id(&self.client_id)
+      .client_secret(&self.client_secret)
+      .redirect_uri(&self.get_redirect_uri())
+      .cache_path(paths.token_cache_path.clone())
+      .scope(&SCOPES.join(" "))
+      .build()
+  }
+
+  // The default profile and every profile that was set up, for switching
+  pub fn list_profiles() -> Vec<Option<String>> {
+    let mut profiles = match dirs::home_dir() {
+      Some(home) => fs::read_dir(
+        home
+          .join(CONFIG_DIR)
+          .join(APP_CONFIG_DIR)
+          .join(PROFILES_DIR),
+      )
+      .map(|entries| {
+        entries
+          .flatten()
+          .filter(|entry| entry.path().join(FILE_NAME).exists())
+          .filter_map(|entry| entry.file_name().into_string().ok())
+          .collect::<Vec<String>>()
+      })
+      .unwrap_or_default(),
+      None => Vec::new(),
+    };
+    profiles.sort();
+    std::iter::once(None)
+      .chain(profiles.into_iter().map(Some))
+      .collect()
+  }
+
   pub fn get_redirect_uri(&self) -> String {
     format!("http://localhost:{}/callback", self.get_port())
   }
@@ -60,12 +166,27 @@ impl ClientConfig {
           fs::create_dir(&app_config_dir)?;
         }
 
+        let app_config_dir = match &self.profile {
+          Some(profile) => {
+            let profile_dir = app_config_dir.join(PROFILES_DIR).join(profile);
+            fs::create_dir_all(&profile_dir)?;
+            profile_dir
+          }
+          None => app_config_dir,
+        };
+
         let config_file_path = &app_config_dir.join(FILE_NAME);
         let token_cache_path = &app_config_dir.join(TOKEN_CACHE_FILE);
+        let device_volumes_path = &app_config_dir.join(DEVICE_VOLUMES_FILE);
+        let daemon_socket_path = &app_config_dir.join(DAEMON_SOCKET_FILE);
+        let cache_dir = &app_config_dir.join(CACHE_DIR);
 
         let paths = ConfigPaths {
           config_file_path: config_file_path.to_path_buf(),
           token_cache_path: token_cache_path.to_path_buf(),
+          device_volumes_path: device_volumes_path.to_path_buf(),
+          daemon_socket_path: daemon_socket_path.to_path_buf(),
+          cache_dir: cache_dir.to_path_buf(),
         };
 
         Ok(paths)
@@ -92,7 +213,27 @@ impl ClientConfig {
     let paths = self.get_or_build_paths()?;
     if paths.config_file_path.exists() {
       let config_string = fs::read_to_string(&paths.config_file_path)?;
-      let config_yml: ClientConfig = serde_yaml::from_str(&config_string)?;
+      let mut config_yml: ClientConfig = serde_yaml::from_str(&config_string)?;
+
+      if self.use_keyring {
+        let keyring = Keyring::new(self.profile.as_deref());
+        if config_yml.client_secret.is_empty() {
+          config_yml.client_secret = keyring.get(CLIENT_SECRET)?.ok_or_else(|| {
+            anyhow!(
+              "there's no client secret in the keyring, add it to {} and it's moved there",
+              paths.config_file_path.display()
+            )
+          })?;
+        } else {
+          // Saved before `use_keyring` was enabled
+          keyring.set(CLIENT_SECRET, &config_yml.client_secret)?;
+          let file_yml = ClientConfig {
+            client_secret: String::new(),
+            ..config_yml.clone()
+          };
+          fs::write(&paths.config_file_path, serde_yaml::to_string(&file_yml)?)?;
+        }
+      }
 
       self.client_id = config_yml.client_id;
       self.client_secret = config_yml.client_secret;
@@ -140,9 +281,19 @@ impl ClientConfig {
         client_secret,
         device_id: None,
         port: Some(port),
+        profile: None,
+        use_keyring: false,
       };
 
-      let content_yml = serde_yaml::to_string(&config_yml)?;
+      let content_yml = if self.use_keyring {
+        Keyring::new(self.profile.as_deref()).set(CLIENT_SECRET, &config_yml.client_secret)?;
+        serde_yaml::to_string(&ClientConfig {
+          client_secret: String::new(),
+          ..config_yml.clone()
+        })?
+      } else {
+        serde_yaml::to_string(&config_yml)?
+      };
 
       let mut new_config = fs::File::create(&paths.config_file_path)?;
       write!(new_config, "{}", content_yml)?;
@@ -198,3 +349,20 @@ impl ClientConfig {
     }
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn parse_profile_test() {
+    assert_eq!(parse_profile("work").unwrap(), Some("work".to_string()));
+    assert_eq!(
+      parse_profile("family_2").unwrap(),
+      Some("family_2".to_string())
+    );
+    assert_eq!(parse_profile("default").unwrap(), None);
+    assert!(parse_profile("").is_err());
+    assert!(parse_profile("../work").is_err());
+  }
+}
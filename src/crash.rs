@@ -0,0 +1,115 @@
+// Gives the terminal back however the TUI ends, and saves a report of panics
+// since the message on the alternate screen would be gone with it
+use crate::logging;
+use backtrace::Backtrace;
+use crossterm::{
+  cursor::Show,
+  event::DisableMouseCapture,
+  execute,
+  terminal::{disable_raw_mode, is_raw_mode_enabled, LeaveAlternateScreen},
+};
+use std::env;
+use std::fs;
+use std::io;
+use std::panic::PanicHookInfo;
+use std::path::PathBuf;
+use std::process;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub fn restore_terminal() -> io::Result<()> {
+  disable_raw_mode()?;
+  execute!(
+    io::stdout(),
+    LeaveAlternateScreen,
+    DisableMouseCapture,
+    Show
+  )
+}
+
+// Restores the terminal when the UI returns, also early with an error
+pub struct TerminalGuard;
+
+impl Drop for TerminalGuard {
+  fn drop(&mut self) {
+    let _ = restore_terminal();
+  }
+}
+
+fn message(info: &PanicHookInfo<'_>) -> String {
+  let msg = match info.payload().downcast_ref::<&'static str>() {
+    Some(s) => *s,
+    None => match info.payload().downcast_ref::<String>() {
+      Some(s) => &s[..],
+      None => "Box<Any>",
+    },
+  };
+  match info.location() {
+    Some(location) => format!("'{}', {}", msg, location),
+    None => format!("'{}'", msg),
+  }
+}
+
+fn report(message: &str, log: &[String], backtrace: &str) -> String {
+  let log = if log.is_empty() {
+    "(nothing)".to_string()
+  } else {
+    log.join("\n")
+  };
+  format!(
+    "spt {} ({} {}) panicked at {}\n\nLast log lines:\n{}\n\nBacktrace:\n{}\n",
+    env!("CARGO_PKG_VERSION"),
+    env::consts::OS,
+    env::consts::ARCH,
+    message,
+    log,
+    backtrace
+  )
+}
+
+fn save(report: &str) -> io::Result<PathBuf> {
+  let time = SystemTime::now()
+    .duration_since(UNIX_EPOCH)
+    .map(|d| d.as_secs())
+    .unwrap_or(0);
+  let path = env::temp_dir().join(format!("spt-crash-{}.txt", time));
+  fs::write(&path, report)?;
+  Ok(path)
+}
+
+// A panic on any thread ends spt, the UI can't go on without the terminal
+pub fn panic_hook(info: &PanicHookInfo<'_>) {
+  if is_raw_mode_enabled().unwrap_or(false) {
+    let _ = restore_terminal();
+  }
+  let message = message(info);
+  let backtrace = format!("{:?}", Backtrace::new());
+  let report = report(&message, &logging::recent_lines(), &backtrace);
+  eprintln!("spt panicked at {}", message);
+  if cfg!(debug_assertions) {
+    eprintln!("{}", backtrace);
+  }
+  match save(&report) {
+    Ok(path) => eprintln!(
+      "A crash report was saved to {}, please attach it to an issue",
+      path.display()
+    ),
+    Err(e) => eprintln!("Couldn't save a crash report: {}\n\n{}", e, report),
+  }
+  process::exit(1);
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn report_test() {
+    let lines = vec!["1.000 INFO  spt::network: request request=GetUser".to_string()];
+    let report = report("'oops', src/app.rs:1:1", &lines, "0: main");
+    assert!(report.starts_with(&format!("spt {} (", env!("CARGO_PKG_VERSION"))));
+    assert!(report.contains("panicked at 'oops', src/app.rs:1:1"));
+    assert!(report.contains("Last log lines:\n1.000 INFO"));
+    assert!(report.ends_with("Backtrace:\n0: main\n"));
+    assert!(super::report("", &[], "").contains("(nothing)"));
+  }
+}
@@ -0,0 +1,187 @@
+use serde_json::{json, Value};
+use std::io::{self, Read, Write};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::thread;
+use std::time::Duration;
+
+const OP_HANDSHAKE: u32 = 0;
+const OP_FRAME: u32 = 1;
+
+// An activity whose start moved by less than this is considered unchanged,
+// the progress estimate drifts a little between polls
+const START_TOLERANCE_MS: u64 = 2_000;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Activity {
+  pub details: String,
+  pub state: String,
+  // Unix timestamps in milliseconds, used by Discord to show the elapsed time
+  pub start_ms: u64,
+  pub end_ms: u64,
+}
+
+impl Activity {
+  pub fn same_as(&self, other: &Activity) -> bool {
+    self.details == other.details
+      && self.state == other.state
+      && self.start_ms.abs_diff(other.start_ms) < START_TOLERANCE_MS
+  }
+}
+
+trait Stream: Read + Write + Send {}
+impl<T: Read + Write + Send> Stream for T {}
+
+// Frames are an opcode and a length, both little endian u32, then the json
+fn encode(opcode: u32, payload: &Value) -> Vec<u8> {
+  let body = payload.to_string();
+  let mut frame = Vec::with_capacity(8 + body.len());
+  frame.extend_from_slice(&opcode.to_le_bytes());
+  frame.extend_from_slice(&(body.len() as u32).to_le_bytes());
+  frame.extend_from_slice(body.as_bytes());
+  frame
+}
+
+fn activity_payload(activity: Option<&Activity>, nonce: u64) -> Value {
+  let activity = activity.map(|a| {
+    json!({
+      "details": a.details,
+      "state": a.state,
+      "timestamps": { "start": a.start_ms, "end": a.end_ms },
+    })
+  });
+  json!({
+    "cmd": "SET_ACTIVITY",
+    "args": { "pid": std::process::id(), "activity": activity },
+    "nonce": nonce.to_string(),
+  })
+}
+
+// Discord answers every frame, the replies are read and dropped so the socket
+// buffer doesn't fill up
+fn read_frame(stream: &mut dyn Stream) -> io::Result<()> {
+  let mut header = [0u8; 8];
+  stream.read_exact(&mut header)?;
+  let len = u32::from_le_bytes([header[4], header[5], header[6], header[7]]);
+  io::copy(&mut stream.take(u64::from(len)), &mut io::sink())?;
+  Ok(())
+}
+
+#[cfg(unix)]
+fn connect_socket(i: u8) -> io::Result<Box<dyn Stream>> {
+  use std::os::unix::net::UnixStream;
+
+  let dirs = ["XDG_RUNTIME_DIR", "TMPDIR", "TMP", "TEMP"]
+    .iter()
+    .filter_map(|var| std::env::var(var).ok())
+    .chain(std::iter::once("/tmp".to_string()));
+  let mut last_err = io::Error::new(io::ErrorKind::NotFound, "Discord is not running");
+  for dir in dirs {
+    match UnixStream::connect(format!("{}/discord-ipc-{}", dir, i)) {
+      Ok(stream) => {
+        stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+        return Ok(Box::new(stream));
+      }
+      Err(e) => last_err = e,
+    }
+  }
+  Err(last_err)
+}
+
+#[cfg(windows)]
+fn connect_socket(i: u8) -> io::Result<Box<dyn Stream>> {
+  let pipe = std::fs::OpenOptions::new()
+    .read(true)
+    .write(true)
+    .open(format!(r"\\.\pipe\discord-ipc-{}", i))?;
+  Ok(Box::new(pipe))
+}
+
+fn connect(client_id: &str) -> io::Result<Box<dyn Stream>> {
+  // Discord uses the first free slot out of ten
+  let mut result = Err(io::Error::new(
+    io::ErrorKind::NotFound,
+    "Discord is not running",
+  ));
+  for i in 0..10 {
+    result = connect_socket(i);
+    if result.is_ok() {
+      break;
+    }
+  }
+  let mut stream = result?;
+  stream.write_all(&encode(
+    OP_HANDSHAKE,
+    &json!({ "v": 1, "client_id": client_id }),
+  ))?;
+  read_frame(stream.as_mut())?;
+  Ok(stream)
+}
+
+fn run(client_id: String, rx: Receiver<Option<Activity>>) {
+  let mut stream: Option<Box<dyn Stream>> = None;
+  let mut nonce = 0;
+  // Only the latest activity matters, so after an error the next update
+  // simply reconnects
+  while let Ok(activity) = rx.recv() {
+    if stream.is_none() {
+      stream = connect(&client_id).ok();
+    }
+    if let Some(s) = stream.as_mut() {
+      nonce += 1;
+      let sent = s
+        .write_all(&encode(
+          OP_FRAME,
+          &activity_payload(activity.as_ref(), nonce),
+        ))
+        .and_then(|_| read_frame(s.as_mut()));
+      if sent.is_err() {
+        stream = None;
+      }
+    }
+  }
+}
+
+// Starts the thread talking to the local Discord client, `None` clears the
+// activity. Updates are silently dropped while Discord isn't running
+pub fn spawn(client_id: String) -> Sender<Option<Activity>> {
+  let (tx, rx) = channel();
+  thread::spawn(move || run(client_id, rx));
+  tx
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn encode_test() {
+    let frame = encode(OP_HANDSHAKE, &json!({ "v": 1 }));
+    assert_eq!(&frame[0..4], &[0, 0, 0, 0]);
+    assert_eq!(&frame[4..8], &[7, 0, 0, 0]);
+    assert_eq!(&frame[8..], b"{\"v\":1}");
+  }
+
+  #[test]
+  fn activity_payload_test() {
+    let activity = Activity {
+      details: "Song".to_string(),
+      state: "Artist — Album".to_string(),
+      start_ms: 1000,
+      end_ms: 5000,
+    };
+    let payload = activity_payload(Some(&activity), 3);
+    assert_eq!(payload["cmd"], "SET_ACTIVITY");
+    assert_eq!(payload["nonce"], "3");
+    assert_eq!(payload["args"]["activity"]["details"], "Song");
+    assert_eq!(payload["args"]["activity"]["timestamps"]["end"], 5000);
+
+    let cleared = activity_payload(None, 4);
+    assert!(cleared["args"]["activity"].is_null());
+
+    let mut moved = activity.clone();
+    moved.start_ms += 1500;
+    assert!(activity.same_as(&moved));
+    moved.start_ms += 1500;
+    assert!(!activity.same_as(&moved));
+  }
+}
@@ -0,0 +1,297 @@
+// Finds Spotify Connect devices on the local network over mDNS, they answer
+// even while spotify doesn't list them for the account yet
+use anyhow::{anyhow, Result};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::net::{Ipv4Addr, SocketAddr};
+use std::time::Duration;
+use tokio::net::UdpSocket;
+use tokio::process::Command;
+use tokio::time::{timeout_at, Instant};
+
+const MDNS_ADDR: (Ipv4Addr, u16) = (Ipv4Addr::new(224, 0, 0, 251), 5353);
+const SERVICE: &str = "_spotify-connect._tcp.local";
+// How long answers are collected for
+const LISTEN_FOR: Duration = Duration::from_millis(1500);
+const INFO_TIMEOUT_SECONDS: &str = "3";
+
+const TYPE_A: u16 = 1;
+const TYPE_PTR: u16 = 12;
+const TYPE_TXT: u16 = 16;
+const TYPE_SRV: u16 = 33;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConnectDevice {
+  pub name: String,
+  pub address: SocketAddr,
+  // Where the device serves the Spotify Connect (zeroconf) API
+  pub path: String,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Record {
+  Ptr(String),
+  Srv(String, u16),
+  Txt(Vec<String>),
+  A(Ipv4Addr),
+}
+
+// A question for the devices offering the service. It's sent from a random
+// port, so they answer to that port directly instead of to the whole network
+fn query() -> Vec<u8> {
+  let mut packet = vec![0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0];
+  for label in SERVICE.split('.') {
+    packet.push(label.len() as u8);
+    packet.extend(label.as_bytes());
+  }
+  packet.push(0);
+  packet.extend(TYPE_PTR.to_be_bytes());
+  // Class IN
+  packet.extend(1u16.to_be_bytes());
+  packet
+}
+
+fn read_u16(packet: &[u8], pos: usize) -> Option<u16> {
+  packet
+    .get(pos..pos + 2)
+    .map(|b| u16::from_be_bytes([b[0], b[1]]))
+}
+
+// A name, which can end in a pointer to a name earlier in the packet. Returns
+// it with the position after it
+fn read_name(packet: &[u8], mut pos: usize) -> Option<(String, usize)> {
+  let mut labels = Vec::new();
+  let mut end = None;
+  // Pointers could loop
+  let mut jumps = 0;
+  loop {
+    let len = *packet.get(pos)? as usize;
+    if len == 0 {
+      pos += 1;
+      break;
+    }
+    if len & 0xC0 == 0xC0 {
+      jumps += 1;
+      if jumps > 16 {
+        return None;
+      }
+      end.get_or_insert(pos + 2);
+      pos = ((len & 0x3F) << 8) | *packet.get(pos + 1)? as usize;
+      continue;
+    }
+    let label = packet.get(pos + 1..pos + 1 + len)?;
+    labels.push(String::from_utf8_lossy(label).into_owned());
+    pos += 1 + len;
+  }
+  Some((labels.join("."), end.unwrap_or(pos)))
+}
+
+// The records of an answer (in any section), as many as could be read
+fn parse(packet: &[u8]) -> Vec<(String, Record)> {
+  let mut records = Vec::new();
+  parse_into(packet, &mut records);
+  records
+}
+
+fn parse_into(packet: &[u8], records: &mut Vec<(String, Record)>) -> Option<()> {
+  let questions = read_u16(packet, 4)?;
+  // Summed as u32 since any host on the network can send crafted counts
+  let answers = [6, 8, 10]
+    .iter()
+    .map(|&pos| read_u16(packet, pos).map(u32::from))
+    .sum::<Option<u32>>()?;
+  let mut pos = 12;
+  for _ in 0..questions {
+    pos = read_name(packet, pos)?.1 + 4;
+  }
+  for _ in 0..answers {
+    let (name, after_name) = read_name(packet, pos)?;
+    let kind = read_u16(packet, after_name)?;
+    let len = read_u16(packet, after_name + 8)? as usize;
+    let start = after_name + 10;
+    let data = packet.get(start..start + len)?;
+    let record = match kind {
+      TYPE_PTR => Some(Record::Ptr(read_name(packet, start)?.0)),
+      TYPE_SRV => Some(Record::Srv(
+        read_name(packet, start + 6)?.0,
+        read_u16(packet, start + 4)?,
+      )),
+      TYPE_TXT => {
+        let mut entries = Vec::new();
+        let mut i = 0;
+        while let Some(&entry_len) = data.get(i) {
+          let entry = data.get(i + 1..i + 1 + entry_len as usize)?;
+          entries.push(String::from_utf8_lossy(entry).into_owned());
+          i += 1 + entry_len as usize;
+        }
+        Some(Record::Txt(entries))
+      }
+      TYPE_A if len == 4 => Some(Record::A(Ipv4Addr::new(data[0], data[1], data[2], data[3]))),
+      _ => None,
+    };
+    if let Some(record) = record {
+      records.push((name, record));
+    }
+    pos = start + len;
+  }
+  Some(())
+}
+
+// Puts the records of all answers together. A device without an address
+// record is reached at the address it answered from
+fn devices(answers: &[(Ipv4Addr, Vec<(String, Record)>)]) -> Vec<ConnectDevice> {
+  let mut addresses = HashMap::new();
+  let mut services = HashMap::new();
+  let mut paths = HashMap::new();
+  let mut instances = Vec::new();
+  for (sender, records) in answers {
+    for (name, record) in records {
+      match record {
+        Record::Ptr(instance) if name == SERVICE && !instances.contains(instance) => {
+          instances.push(instance.clone())
+        }
+        Record::Srv(target, port) => {
+          services.insert(name.clone(), (target.clone(), *port, *sender));
+        }
+        Record::Txt(entries) => {
+          if let Some(path) = entries.iter().find_map(|e| e.strip_prefix("CPath=")) {
+            paths.insert(name.clone(), path.to_string());
+          }
+        }
+        Record::A(ip) => {
+          addresses.insert(name.clone(), *ip);
+        }
+        _ => {}
+      }
+    }
+  }
+  instances
+    .into_iter()
+    .filter_map(|instance| {
+      let (target, port, sender) = services.get(&instance)?;
+      let ip = addresses.get(target).unwrap_or(sender);
+      Some(ConnectDevice {
+        name: instance
+          .strip_suffix(&format!(".{}", SERVICE))
+          .unwrap_or(&instance)
+          .to_string(),
+        address: SocketAddr::from((*ip, *port)),
+        path: paths
+          .get(&instance)
+          .cloned()
+          .unwrap_or_else(|| "/".to_string()),
+      })
+    })
+    .collect()
+}
+
+// Asks the network and collects the answers for a moment
+pub async fn discover() -> Result<Vec<ConnectDevice>> {
+  let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)).await?;
+  socket.send_to(&query(), MDNS_ADDR).await?;
+  let deadline = Instant::now() + LISTEN_FOR;
+  let mut answers = Vec::new();
+  let mut buffer = [0u8; 9000];
+  while let Ok(received) = timeout_at(deadline, socket.recv_from(&mut buffer)).await {
+    if let (len, SocketAddr::V4(sender)) = received? {
+      answers.push((*sender.ip(), parse(&buffer[..len])));
+    }
+  }
+  Ok(devices(&answers))
+}
+
+// Asking a device for its info wakes up some that sleep, the name it returns
+// is the one spotify lists it under
+pub async fn get_info(device: &ConnectDevice) -> Result<String> {
+  let url = format!("http://{}{}?action=getInfo", device.address, device.path);
+  let output = Command::new("curl")
+    .args(["--silent", "--max-time", INFO_TIMEOUT_SECONDS])
+    .arg(url)
+    .output()
+    .await
+    .map_err(|e| anyhow!("waking up devices needs curl: {}", e))?;
+  if !output.status.success() {
+    return Err(anyhow!(
+      "{} didn't answer (curl {})",
+      device.name,
+      output.status
+    ));
+  }
+  let info: Value = serde_json::from_slice(&output.stdout)?;
+  Ok(
+    info["remoteName"]
+      .as_str()
+      .unwrap_or(&device.name)
+      .to_string(),
+  )
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn name(packet: &mut Vec<u8>, name: &str) {
+    for label in name.split('.') {
+      packet.push(label.len() as u8);
+      packet.extend(label.as_bytes());
+    }
+    packet.push(0);
+  }
+
+  fn record(packet: &mut Vec<u8>, owner: &[u8], kind: u16, data: &[u8]) {
+    packet.extend(owner);
+    packet.extend(kind.to_be_bytes());
+    packet.extend([0, 1, 0, 0, 0, 120]);
+    packet.extend((data.len() as u16).to_be_bytes());
+    packet.extend(data);
+  }
+
+  #[test]
+  fn parse_test() {
+    let mut packet = vec![0, 0, 0x84, 0, 0, 0, 0, 4, 0, 0, 0, 0];
+    let mut service = Vec::new();
+    name(&mut service, SERVICE);
+    // The other names point at the service name, right after the header
+    let mut instance = vec![11];
+    instance.extend(b"Living Room");
+    instance.extend([0xC0, 12]);
+    record(&mut packet, &service, TYPE_PTR, &instance);
+
+    let mut srv = vec![0, 0, 0, 0, 0x1F, 0x90];
+    name(&mut srv, "speaker.local");
+    record(&mut packet, &instance, TYPE_SRV, &srv);
+    let mut txt = vec![7];
+    txt.extend(b"VERSION");
+    txt.push(13);
+    txt.extend(b"CPath=/zc/0.1");
+    record(&mut packet, &instance, TYPE_TXT, &txt);
+    let mut host = Vec::new();
+    name(&mut host, "speaker.local");
+    record(&mut packet, &host, TYPE_A, &[192, 168, 1, 20]);
+
+    let records = parse(&packet);
+    assert_eq!(records.len(), 4);
+    assert_eq!(
+      records[0],
+      (
+        SERVICE.to_string(),
+        Record::Ptr(format!("Living Room.{}", SERVICE))
+      )
+    );
+    let found = devices(&[(Ipv4Addr::new(192, 168, 1, 1), records)]);
+    assert_eq!(
+      found,
+      vec![ConnectDevice {
+        name: "Living Room".to_string(),
+        address: SocketAddr::from(([192, 168, 1, 20], 8080)),
+        path: "/zc/0.1".to_string(),
+      }]
+    );
+    // Cut off answers keep what could be read
+    assert_eq!(parse(&packet[..packet.len() - 2]).len(), 3);
+    assert!(parse(&query()).is_empty());
+    // Counts far beyond what the packet holds
+    let malformed = [0, 0, 0x84, 0, 0, 0, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF];
+    assert!(parse(&malformed).is_empty());
+  }
+}
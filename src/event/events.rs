@@ -1,5 +1,5 @@
 use crate::event::Key;
-use crossterm::event;
+use crossterm::event::{self, MouseEvent};
 use std::{sync::mpsc, thread, time::Duration};
 
 #[derive(Debug, Clone, Copy)]
@@ -24,6 +24,10 @@ impl Default for EventConfig {
 pub enum Event<I> {
   /// An input event occurred.
   Input(I),
+  /// A mouse event occurred, only sent with mouse capture enabled.
+  Mouse(MouseEvent),
+  /// The terminal was resized.
+  Resize,
   /// An tick event occurred.
   Tick,
 }
@@ -54,10 +58,15 @@ impl Events {
       loop {
         // poll for tick rate duration, if no event, sent tick event.
         if event::poll(config.tick_rate).unwrap() {
-          if let event::Event::Key(key) = event::read().unwrap() {
-            let key = Key::from(key);
+          match event::read().unwrap() {
+            event::Event::Key(key) => {
+              let key = Key::from(key);
 
-            event_tx.send(Event::Input(key)).unwrap();
+              event_tx.send(Event::Input(key)).unwrap();
+            }
+            event::Event::Mouse(mouse) => event_tx.send(Event::Mouse(mouse)).unwrap(),
+            event::Event::Resize(_, _) => event_tx.send(Event::Resize).unwrap(),
+            _ => {}
           }
         }
 
@@ -65,6 +65,8 @@ pub enum Key {
   Ctrl(char),
   Alt(char),
   Unknown,
+  /// Sent when the multi-key binding with this index in `KeyBindings::sequences` is completed
+  Sequence(usize),
 }
 
 impl Key {
@@ -0,0 +1,901 @@
+// The `%` format language of `spt --format` and the playbar of the TUI
+use crate::user_config::UserConfig;
+use rspotify::{
+  model::{
+    album::FullAlbum, album::SimplifiedAlbum, artist::FullArtist, artist::SimplifiedArtist,
+    audio::AudioFeatures, context::CurrentlyPlaybackContext, playlist::SimplifiedPlaylist,
+    show::FullEpisode, show::SimplifiedShow, track::FullTrack, PlayingItem,
+  },
+  senum::{DisallowKey, RepeatState},
+};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+// Types to create a Format enum from
+// Boxing was proposed by cargo clippy
+// to reduce the size of this enum
+pub enum FormatType {
+  Album(Box<SimplifiedAlbum>),
+  Artist(Box<FullArtist>),
+  Playlist(Box<SimplifiedPlaylist>),
+  Track(Box<FullTrack>),
+  Episode(Box<FullEpisode>),
+  Show(Box<SimplifiedShow>),
+}
+
+// Types that can be formatted
+#[derive(Clone)]
+pub enum Format {
+  Album(String),
+  Artist(String),
+  Playlist(String),
+  Track(String),
+  Show(String),
+  Uri(String),
+  Device(String),
+  Volume(u32),
+  // Current position, duration
+  Position((u32, u32)),
+  // Progress of the current track in percent
+  Percentage(u32),
+  // Time left in the playlist or album (needs all of its tracks)
+  ContextRemaining(Option<u32>),
+  // This is a bit long, should it be splitted up?
+  Flags((RepeatState, bool, bool)),
+  Playing(bool),
+  // These need an extra call to the audio-features endpoint
+  Key(String),
+  Tempo(String),
+  // `None` if the features couldn't be fetched
+  Danceability(Option<f32>),
+  Energy(Option<f32>),
+  Valence(Option<f32>),
+  Loudness(Option<f32>),
+  Mode(Option<f32>),
+  TimeSignature(Option<i32>),
+  // These need the full album object
+  Label(String),
+  Copyright(String),
+  Disc(Option<i32>),
+  // Markets the item is available in, `None` if the endpoint left them out
+  Markets(Option<Vec<String>>),
+  // In milliseconds
+  Length(u32),
+  Elapsed(u32),
+  // Empty if the release date is unknown
+  Year(String),
+  TrackNumber(u32),
+  Popularity(u32),
+  Isrc(String),
+  // Why the current item can't be controlled, empty if nothing is restricted
+  Source(String),
+}
+
+// All known placeholders, unknown ones are printed as they are
+pub const PLACEHOLDERS: [&str; 32] = [
+  "%a", "%b", "%t", "%p", "%h", "%u", "%d", "%v", "%r", "%pct", "%dur_ctx", "%f", "%s", "%k",
+  "%bpm", "%dance", "%energy", "%valence", "%loud", "%mode", "%ts", "%l", "%cr", "%disc", "%avail",
+  "%src", "%len", "%e", "%y", "%n", "%i", "%c",
+];
+
+const ALBUM_PLACEHOLDERS: [&str; 3] = ["%l", "%cr", "%disc"];
+
+const AUDIO_FEATURE_PLACEHOLDERS: [&str; 8] = [
+  "%k", "%bpm", "%dance", "%energy", "%valence", "%loud", "%mode", "%ts",
+];
+
+const PITCH_CLASSES: [&str; 12] = [
+  "C", "C#", "D", "D#", "E", "F", "F#", "G", "G#", "A", "A#", "B",
+];
+
+// Converts spotify's pitch class notation to a key, e.g. `C#m`
+pub fn pitch_class_to_key(key: i32, mode: f32) -> String {
+  match PITCH_CLASSES.get(key as usize) {
+    // A key of -1 means that no key was detected
+    Some(k) if key >= 0 => {
+      // Mode is 1 for major and 0 for minor
+      if mode < 1.0 {
+        format!("{}m", k)
+      } else {
+        k.to_string()
+      }
+    }
+    _ => String::new(),
+  }
+}
+
+fn copyright_text(copyrights: &[HashMap<String, String>], kind: &str) -> Option<String> {
+  copyrights
+    .iter()
+    .find(|c| c.get("type").map(|t| t.as_str()) == Some(kind))
+    .or_else(|| copyrights.first())
+    .and_then(|c| c.get("text").cloned())
+}
+
+// rspotify doesn't expose the label of an album, so it's taken from the
+// phonographic copyright instead, e.g. `℗ 2020 Some Label` -> `Some Label`
+pub fn label_from_copyrights(copyrights: &[HashMap<String, String>]) -> String {
+  let text = copyright_text(copyrights, "P").unwrap_or_default();
+  let label = text
+    .trim_start_matches(|c: char| "℗©(PC) ".contains(c))
+    .trim_start_matches(|c: char| c.is_ascii_digit())
+    .trim();
+  label.to_string()
+}
+
+// The disc of a track, `None` if the album only has one. Without the album, only
+// tracks on later discs can be detected
+pub fn disc_number(disc: i32, album_discs: &[i32]) -> Option<i32> {
+  if disc > 1 || album_discs.iter().any(|d| *d > 1) {
+    Some(disc)
+  } else {
+    None
+  }
+}
+
+// Rounded to the nearest integer and clamped to 0-100
+pub fn progress_percent(progress_ms: u32, duration_ms: u32) -> u32 {
+  if duration_ms == 0 {
+    return 0;
+  }
+  let percent = (progress_ms as f64 / duration_ms as f64 * 100.0).round();
+  percent.clamp(0.0, 100.0) as u32
+}
+
+// Pausing and resuming are always disallowed in one of the two states,
+// so only the actions that say something about the item are listed
+const RESTRICTED_ACTIONS: [DisallowKey; 8] = [
+  DisallowKey::SkippingNext,
+  DisallowKey::SkippingPrev,
+  DisallowKey::Seeking,
+  DisallowKey::TogglingShuffle,
+  DisallowKey::TogglingRepeatContext,
+  DisallowKey::TogglingRepeatTrack,
+  DisallowKey::TransferringPlayback,
+  DisallowKey::InterruptingPlayback,
+];
+
+// The notes of %src, e.g. `local-file,no-seeking`
+pub fn playback_source(
+  is_local: bool,
+  is_playable: bool,
+  device_restricted: bool,
+  disallows: &HashMap<DisallowKey, bool>,
+) -> String {
+  let mut notes = Vec::new();
+  if is_local {
+    notes.push("local-file".to_string());
+  }
+  if !is_playable {
+    notes.push("unplayable".to_string());
+  }
+  if device_restricted {
+    notes.push("restricted".to_string());
+  }
+  for key in RESTRICTED_ACTIONS.iter() {
+    if disallows.get(key) == Some(&true) {
+      notes.push(format!("no-{}", key.as_str().replace('_', "-")));
+    }
+  }
+  notes.join(",")
+}
+
+// Release dates are `1981`, `1981-12` or `1981-12-15`, depending on their precision
+pub fn release_year(date: &str) -> String {
+  date.get(..4).unwrap_or_default().to_string()
+}
+
+pub fn join_artists(a: Vec<SimplifiedArtist>) -> String {
+  a.iter()
+    .map(|l| l.name.clone())
+    .collect::<Vec<String>>()
+    .join(", ")
+}
+
+impl Format {
+  // Extract important information from types
+  pub fn from_type(t: FormatType) -> Vec<Self> {
+    match t {
+      FormatType::Album(a) => {
+        let joined_artists = join_artists(a.artists.clone());
+        let mut vec = vec![
+          Self::Album(a.name),
+          Self::Artist(joined_artists),
+          Self::markets(a.available_markets),
+          Self::Year(release_year(&a.release_date.unwrap_or_default())),
+        ];
+        if let Some(uri) = a.uri {
+          vec.push(Self::Uri(uri));
+        }
+        vec
+      }
+      FormatType::Artist(a) => vec![
+        Self::Artist(a.name),
+        Self::Uri(a.uri),
+        Self::Popularity(a.popularity),
+      ],
+      FormatType::Playlist(p) => vec![Self::Playlist(p.name), Self::Uri(p.uri)],
+      FormatType::Track(t) => {
+        let joined_artists = join_artists(t.artists.clone());
+        vec![
+          Self::Year(release_year(
+            t.album.release_date.as_deref().unwrap_or_default(),
+          )),
+          Self::Album(t.album.name),
+          Self::Artist(joined_artists),
+          Self::Track(t.name),
+          Self::Uri(t.uri),
+          Self::markets(t.available_markets),
+          Self::Length(t.duration_ms),
+          Self::TrackNumber(t.track_number),
+          Self::Popularity(t.popularity),
+          Self::Isrc(t.external_ids.get("isrc").cloned().unwrap_or_default()),
+        ]
+      }
+      FormatType::Show(r) => vec![
+        Self::Artist(r.publisher),
+        Self::Show(r.name),
+        Self::Uri(r.uri),
+      ],
+      FormatType::Episode(e) => vec![
+        Self::Show(e.show.name),
+        Self::Artist(e.show.publisher),
+        Self::Track(e.name),
+        Self::Uri(e.uri),
+        Self::Length(e.duration_ms),
+        Self::Year(release_year(&e.release_date)),
+      ],
+    }
+  }
+
+  // What's known about the playback without further requests, so everything
+  // but the audio features, the album details and %dur_ctx
+  pub fn from_playback(
+    context: &CurrentlyPlaybackContext,
+    progress_ms: Option<u32>,
+    liked: bool,
+  ) -> Vec<Self> {
+    let (mut values, duration_ms) = match &context.item {
+      Some(PlayingItem::Track(track)) => {
+        let mut values = Self::from_type(FormatType::Track(Box::new(track.clone())));
+        values.push(Self::Source(playback_source(
+          track.is_local,
+          track.is_playable.unwrap_or(true),
+          context.device.is_restricted,
+          &context.actions.disallows,
+        )));
+        values.push(Self::Flags((
+          context.repeat_state,
+          context.shuffle_state,
+          liked,
+        )));
+        (values, track.duration_ms)
+      }
+      Some(PlayingItem::Episode(episode)) => {
+        let mut values = Self::from_type(FormatType::Episode(Box::new(episode.clone())));
+        values.push(Self::Source(playback_source(
+          false,
+          episode.is_playable,
+          context.device.is_restricted,
+          &context.actions.disallows,
+        )));
+        values.push(Self::Flags((
+          context.repeat_state,
+          context.shuffle_state,
+          false,
+        )));
+        (values, episode.duration_ms)
+      }
+      None => (Vec::new(), 0),
+    };
+    if let Some(ms) = progress_ms {
+      values.push(Self::Position((ms, duration_ms)));
+      values.push(Self::Elapsed(ms));
+    }
+    values.push(Self::Percentage(progress_percent(
+      progress_ms.unwrap_or(0),
+      duration_ms,
+    )));
+    values.push(Self::Device(context.device.name.clone()));
+    values.push(Self::Volume(context.device.volume_percent));
+    values.push(Self::Playing(context.is_playing));
+    values
+  }
+
+  // Some endpoints (e.g. with a market given) leave the markets out
+  fn markets(markets: Vec<String>) -> Self {
+    if markets.is_empty() {
+      Self::Markets(None)
+    } else {
+      Self::Markets(Some(markets))
+    }
+  }
+
+  pub fn needs_audio_features(format: &str) -> bool {
+    AUDIO_FEATURE_PLACEHOLDERS
+      .iter()
+      .any(|p| format.contains(p))
+  }
+
+  // Expands to empty strings if the audio features couldn't be fetched
+  pub fn from_audio_features(features: Option<&AudioFeatures>) -> Vec<Self> {
+    match features {
+      Some(f) => vec![
+        Self::Key(pitch_class_to_key(f.key, f.mode)),
+        Self::Tempo(format!("{:.0}", f.tempo)),
+        Self::Danceability(Some(f.danceability)),
+        Self::Energy(Some(f.energy)),
+        Self::Valence(Some(f.valence)),
+        Self::Loudness(Some(f.loudness)),
+        Self::Mode(Some(f.mode)),
+        Self::TimeSignature(Some(f.time_signature)),
+      ],
+      None => vec![
+        Self::Key(String::new()),
+        Self::Tempo(String::new()),
+        Self::Danceability(None),
+        Self::Energy(None),
+        Self::Valence(None),
+        Self::Loudness(None),
+        Self::Mode(None),
+        Self::TimeSignature(None),
+      ],
+    }
+  }
+
+  pub fn needs_album(format: &str) -> bool {
+    ALBUM_PLACEHOLDERS.iter().any(|p| format.contains(p))
+  }
+
+  // Expands to empty strings if the album couldn't be fetched, %disc
+  // is only set for tracks of albums with multiple discs
+  pub fn from_album(album: Option<&FullAlbum>, track: Option<&FullTrack>) -> Vec<Self> {
+    let disc = track.and_then(|t| {
+      let discs = album
+        .map(|a| {
+          a.tracks
+            .items
+            .iter()
+            .map(|t| t.disc_number)
+            .collect::<Vec<i32>>()
+        })
+        .unwrap_or_default();
+      disc_number(t.disc_number, &discs)
+    });
+    match album {
+      Some(a) => vec![
+        Self::Label(label_from_copyrights(&a.copyrights)),
+        Self::Copyright(copyright_text(&a.copyrights, "C").unwrap_or_default()),
+        Self::Disc(disc),
+      ],
+      None => vec![
+        Self::Label(String::new()),
+        Self::Copyright(String::new()),
+        Self::Disc(disc),
+      ],
+    }
+  }
+
+  // Is there a better way?
+  pub fn inner(&self, conf: UserConfig) -> String {
+    match self {
+      Self::Album(s) => s.clone(),
+      Self::Artist(s) => s.clone(),
+      Self::Playlist(s) => s.clone(),
+      Self::Track(s) => s.clone(),
+      Self::Show(s) => s.clone(),
+      Self::Uri(s) => s.clone(),
+      Self::Device(s) => s.clone(),
+      Self::Key(s) => s.clone(),
+      Self::Tempo(s) => s.clone(),
+      Self::Label(s) => s.clone(),
+      Self::Copyright(s) => s.clone(),
+      Self::Source(s) => s.clone(),
+      Self::Year(s) => s.clone(),
+      Self::Isrc(s) => s.clone(),
+      Self::TrackNumber(n) | Self::Popularity(n) => n.to_string(),
+      Self::Length(ms) | Self::Elapsed(ms) => crate::ui::util::millis_to_minutes(*ms as u128),
+      Self::Disc(d) => d.map(|d| d.to_string()).unwrap_or_default(),
+      Self::Markets(m) => m.as_ref().map(|m| m.len().to_string()).unwrap_or_default(),
+      Self::Danceability(f) | Self::Energy(f) | Self::Valence(f) => {
+        f.map(|f| format!("{:.2}", f)).unwrap_or_default()
+      }
+      Self::Loudness(f) => f.map(|f| format!("{:.1}", f)).unwrap_or_default(),
+      Self::Mode(m) => m.map(mode_name).unwrap_or_default().to_string(),
+      Self::TimeSignature(t) => t.map(|t| t.to_string()).unwrap_or_default(),
+      // Because this match statements
+      // needs to return a &String, I have to do it this way
+      Self::Volume(s) => s.to_string(),
+      Self::Percentage(p) => p.to_string(),
+      Self::ContextRemaining(ms) => ms
+        .map(|ms| crate::ui::util::millis_to_minutes(ms as u128))
+        .unwrap_or_default(),
+      Self::Position((curr, duration)) => {
+        crate::ui::util::display_track_progress(*curr as u128, *duration)
+      }
+      Self::Flags((r, s, l)) => {
+        let like = if *l {
+          conf.behavior.liked_icon
+        } else {
+          String::new()
+        };
+        let shuffle = if *s {
+          conf.behavior.shuffle_icon
+        } else {
+          String::new()
+        };
+        let repeat = match r {
+          RepeatState::Off => String::new(),
+          RepeatState::Track => conf.behavior.repeat_track_icon,
+          RepeatState::Context => conf.behavior.repeat_context_icon,
+        };
+
+        // Add them together (only those that aren't empty)
+        [shuffle, repeat, like]
+          .iter()
+          .filter(|a| !a.is_empty())
+          // Convert &String to String to join them
+          .map(|s| s.to_string())
+          .collect::<Vec<String>>()
+          .join(" ")
+      }
+      Self::Playing(s) => {
+        if *s {
+          conf.behavior.playing_icon
+        } else {
+          conf.behavior.paused_icon
+        }
+      }
+    }
+  }
+
+  // Whether a `%(...)` section of this value is rendered. Empty values aren't
+  // set and neither is a paused playback
+  pub fn is_set(&self, conf: &UserConfig) -> bool {
+    match self {
+      Self::Playing(playing) => *playing,
+      _ => !self.inner(conf.clone()).is_empty(),
+    }
+  }
+
+  // The fields of `--json`, with numbers and booleans instead of the rendered text
+  pub fn json_fields(&self) -> Vec<(&'static str, Value)> {
+    match self {
+      Self::Album(s) => vec![("album", json!(s))],
+      Self::Artist(s) => vec![("artist", json!(s))],
+      Self::Playlist(s) => vec![("playlist", json!(s))],
+      Self::Track(s) => vec![("track", json!(s))],
+      Self::Show(s) => vec![("show", json!(s))],
+      Self::Uri(s) => vec![("uri", json!(s))],
+      Self::Device(s) => vec![("device", json!(s))],
+      Self::Key(s) => vec![("key", json!(s))],
+      Self::Tempo(s) => vec![("tempo", json!(s.parse::<f64>().ok()))],
+      Self::Label(s) => vec![("label", json!(s))],
+      Self::Copyright(s) => vec![("copyright", json!(s))],
+      Self::Source(s) => vec![("source", json!(s))],
+      Self::Year(s) => vec![("year", json!(s.parse::<u32>().ok()))],
+      Self::Isrc(s) => vec![("isrc", json!(s))],
+      Self::TrackNumber(n) => vec![("track_number", json!(n))],
+      Self::Popularity(n) => vec![("popularity", json!(n))],
+      Self::Length(ms) => vec![("duration_ms", json!(ms))],
+      Self::Elapsed(ms) => vec![("progress_ms", json!(ms))],
+      Self::Disc(d) => vec![("disc", json!(d))],
+      Self::Markets(m) => vec![("markets", json!(m))],
+      Self::Danceability(f) => vec![("danceability", json!(f))],
+      Self::Energy(f) => vec![("energy", json!(f))],
+      Self::Valence(f) => vec![("valence", json!(f))],
+      Self::Loudness(f) => vec![("loudness", json!(f))],
+      Self::Mode(m) => vec![("mode", json!(m.map(mode_name)))],
+      Self::TimeSignature(t) => vec![("time_signature", json!(t))],
+      Self::Volume(v) => vec![("volume", json!(v))],
+      Self::Percentage(p) => vec![("percent", json!(p))],
+      Self::ContextRemaining(ms) => vec![("context_remaining_ms", json!(ms))],
+      Self::Position((curr, duration)) => {
+        vec![
+          ("progress_ms", json!(curr)),
+          ("duration_ms", json!(duration)),
+        ]
+      }
+      Self::Flags((r, s, l)) => {
+        let repeat = match r {
+          RepeatState::Off => "off",
+          RepeatState::Track => "track",
+          RepeatState::Context => "context",
+        };
+        vec![
+          ("shuffle", json!(s)),
+          ("repeat", json!(repeat)),
+          ("liked", json!(l)),
+        ]
+      }
+      Self::Playing(p) => vec![("playing", json!(p))],
+    }
+  }
+
+  pub fn get_placeholder(&self) -> &str {
+    match self {
+      Self::Album(_) => "%b",
+      Self::Artist(_) => "%a",
+      Self::Playlist(_) => "%p",
+      Self::Track(_) => "%t",
+      Self::Show(_) => "%h",
+      Self::Uri(_) => "%u",
+      Self::Device(_) => "%d",
+      Self::Volume(_) => "%v",
+      Self::Position(_) => "%r",
+      Self::Percentage(_) => "%pct",
+      Self::ContextRemaining(_) => "%dur_ctx",
+      Self::Flags(_) => "%f",
+      Self::Playing(_) => "%s",
+      Self::Key(_) => "%k",
+      Self::Tempo(_) => "%bpm",
+      Self::Danceability(_) => "%dance",
+      Self::Energy(_) => "%energy",
+      Self::Valence(_) => "%valence",
+      Self::Loudness(_) => "%loud",
+      Self::Mode(_) => "%mode",
+      Self::TimeSignature(_) => "%ts",
+      Self::Label(_) => "%l",
+      Self::Copyright(_) => "%cr",
+      Self::Disc(_) => "%disc",
+      Self::Markets(_) => "%avail",
+      Self::Source(_) => "%src",
+      Self::Length(_) => "%len",
+      Self::Elapsed(_) => "%e",
+      Self::Year(_) => "%y",
+      Self::TrackNumber(_) => "%n",
+      Self::Popularity(_) => "%i",
+      Self::Isrc(_) => "%c",
+    }
+  }
+}
+
+// Escapes quotes, backslashes and control characters
+pub fn json_escape(s: &str) -> String {
+  let quoted = serde_json::to_string(s).unwrap_or_default();
+  // Remove the surrounding quotes
+  quoted
+    .strip_prefix('"')
+    .and_then(|q| q.strip_suffix('"'))
+    .unwrap_or_default()
+    .to_string()
+}
+
+// Returns the index of the `close` that ends the block starting at `s`
+fn find_closing(s: &str, open: char, close: char) -> Option<usize> {
+  let mut depth = 0;
+  for (i, c) in s.char_indices() {
+    match c {
+      c if c == open => depth += 1,
+      c if c == close && depth == 0 => return Some(i),
+      c if c == close => depth -= 1,
+      _ => {}
+    }
+  }
+  None
+}
+
+// Splits the inside of `%(f? [%f])` into the placeholder and the section
+fn split_condition(section: &str) -> Option<(&'static str, &str)> {
+  let (name, body) = section.split_once('?')?;
+  let p = match_placeholder(&format!("%{}", name))?;
+  // The whole name has to be the placeholder, `%(fx?...)` isn't `%f`
+  if p.len() == name.len() + 1 {
+    Some((p, body))
+  } else {
+    None
+  }
+}
+
+// Returns the longest placeholder `s` starts with
+fn match_placeholder(s: &str) -> Option<&'static str> {
+  PLACEHOLDERS
+    .iter()
+    .filter(|p| s.starts_with(*p))
+    .max_by_key(|p| p.len())
+    .copied()
+}
+
+// Replaces every placeholder in `format` with its value. The longest matching
+// placeholder wins, so `%dance` isn't read as the device (`%d`) followed by 'ance'.
+// Known placeholders without a value are replaced with 'None'.
+// Everything inside of `%j{...}` gets JSON-escaped after rendering.
+// `%(f? [%f])` only renders ` [%f]` if %f is set, see `Format::is_set`.
+pub fn render(format: &str, values: &[Format], conf: &UserConfig) -> String {
+  let mut output = String::new();
+  let mut rest = format;
+  while let Some(i) = rest.find('%') {
+    output.push_str(&rest[..i]);
+    rest = &rest[i..];
+
+    if let Some(section) = rest.strip_prefix("%(") {
+      if let Some(end) = find_closing(section, '(', ')') {
+        if let Some((p, body)) = split_condition(&section[..end]) {
+          if values
+            .iter()
+            .any(|v| v.get_placeholder() == p && v.is_set(conf))
+          {
+            output.push_str(&render(body, values, conf));
+          }
+          rest = &section[end + 1..];
+          continue;
+        }
+      }
+    }
+
+    if let Some(block) = rest.strip_prefix("%j{") {
+      if let Some(end) = find_closing(block, '{', '}') {
+        output.push_str(&json_escape(&render(&block[..end], values, conf)));
+        rest = &block[end + 1..];
+        continue;
+      }
+    }
+
+    match match_token(rest) {
+      Some(token) => {
+        let (width, p) = split_padding(token);
+        let market = p.strip_prefix("%avail?");
+        let key = if market.is_some() { "%avail" } else { &p };
+        let value = match values.iter().find(|v| v.get_placeholder() == key) {
+          Some(Format::Markets(markets)) if market.is_some() => {
+            let market = market.unwrap_or_default().to_uppercase();
+            match markets {
+              Some(m) if m.contains(&market) => "yes".to_string(),
+              Some(_) => "no".to_string(),
+              None => String::new(),
+            }
+          }
+          Some(v) => v.inner(conf.clone()),
+          None => "None".to_string(),
+        };
+        output.push_str(&pad(value, width));
+        rest = &rest[token.len()..];
+      }
+      None => {
+        output.push('%');
+        rest = &rest[1..];
+      }
+    }
+  }
+  output.push_str(rest);
+  output
+}
+
+fn mode_name(mode: f32) -> &'static str {
+  if mode < 1.0 {
+    "minor"
+  } else {
+    "major"
+  }
+}
+
+// Like `match_placeholder`, but also matches specifiers with an argument (`%avail?CC`)
+// and padded ones (`%{03}n`)
+pub fn match_token(s: &str) -> Option<&str> {
+  if let Some(market) = s.strip_prefix("%avail?") {
+    let code = market
+      .get(..2)
+      .filter(|m| m.chars().all(|c| c.is_ascii_alphabetic()));
+    if code.is_some() {
+      return Some(&s[.."%avail?CC".len()]);
+    }
+  }
+  if let Some(padded) = s.strip_prefix("%{") {
+    let end = padded.find('}')?;
+    let width = &padded[..end];
+    if !width.is_empty() && width.chars().all(|c| c.is_ascii_digit()) {
+      let p = match_placeholder(&format!("%{}", &padded[end + 1..]))?;
+      // `%{`, the width, `}` and the placeholder without its `%`
+      return Some(&s[..2 + end + p.len()]);
+    }
+  }
+  match_placeholder(s)
+}
+
+// Splits `%{03}n` into the width and `%n`, other tokens have no width
+pub fn split_padding(token: &str) -> (Option<&str>, String) {
+  match token
+    .strip_prefix("%{")
+    .and_then(|padded| padded.split_once('}'))
+  {
+    Some((width, p)) => (Some(width), format!("%{}", p)),
+    None => (None, token.to_string()),
+  }
+}
+
+// A width starting with 0 pads with zeros, otherwise with spaces
+fn pad(value: String, width: Option<&str>) -> String {
+  let zeros = width.is_some_and(|w| w.starts_with('0'));
+  match width.and_then(|w| w.parse::<usize>().ok()) {
+    Some(width) if zeros => format!("{:0>width$}", value, width = width),
+    Some(width) => format!("{:>width$}", value, width = width),
+    None => value,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn render_test() {
+    let conf = UserConfig::new();
+    let values = vec![
+      Format::Track("Song".to_string()),
+      Format::Device("Kitchen".to_string()),
+      Format::Volume(50),
+      Format::Danceability(Some(0.5)),
+    ];
+    assert_eq!(render("%t on %d", &values, &conf), "Song on Kitchen");
+    assert_eq!(render("%v% %dance", &values, &conf), "50% 0.50");
+    // Known but missing placeholders
+    assert_eq!(render("%b", &values, &conf), "None");
+    // Unknown placeholders are kept
+    assert_eq!(render("100%z", &values, &conf), "100%z");
+    let values = Format::from_audio_features(None);
+    assert_eq!(
+      render(
+        "[%k|%bpm|%dance|%energy|%valence|%loud|%mode|%ts]",
+        &values,
+        &conf
+      ),
+      "[|||||||]"
+    );
+  }
+
+  #[test]
+  fn json_escape_test() {
+    let conf = UserConfig::new();
+    let values = vec![
+      Format::Track("Say \"Hello\"\nGoodbye".to_string()),
+      Format::Artist("AC\\DC".to_string()),
+    ];
+    assert_eq!(
+      render("{\"track\": \"%j{%t}\"}", &values, &conf),
+      "{\"track\": \"Say \\\"Hello\\\"\\nGoodbye\"}"
+    );
+    assert_eq!(
+      render("%j{%a - %t}", &values, &conf),
+      "AC\\\\DC - Say \\\"Hello\\\"\\nGoodbye"
+    );
+    assert_eq!(json_escape("tab\there"), "tab\\there");
+    // Unclosed blocks are kept as they are
+    assert_eq!(render("%j{%a", &values, &conf), "%j{AC\\DC");
+  }
+
+  #[test]
+  fn render_condition_test() {
+    let conf = UserConfig::new();
+    let values = vec![
+      Format::Track("Song".to_string()),
+      Format::Playlist(String::new()),
+      Format::Flags((RepeatState::Off, false, false)),
+      Format::Playing(false),
+      Format::Disc(Some(2)),
+    ];
+    assert_eq!(render("%t%(p? from %p)%(f? [%f])", &values, &conf), "Song");
+    assert_eq!(render("%(s?playing: )%t", &values, &conf), "Song");
+    // Sections can be nested and contain parentheses
+    assert_eq!(
+      render("%(t?%t (%(disc?disc %disc)))", &values, &conf),
+      "Song (disc 2)"
+    );
+    // Missing values aren't set either
+    assert_eq!(render("%t%(b? on %b)", &values, &conf), "Song");
+    // Unknown conditions and unclosed sections are kept
+    assert_eq!(render("%(x?y)", &values, &conf), "%(x?y)");
+    assert_eq!(render("%(t?%t", &values, &conf), "%(t?Song");
+  }
+
+  #[test]
+  fn label_from_copyrights_test() {
+    let copyright = |kind: &str, text: &str| {
+      let mut c = HashMap::new();
+      c.insert("type".to_string(), kind.to_string());
+      c.insert("text".to_string(), text.to_string());
+      c
+    };
+    let copyrights = vec![
+      copyright("C", "© 2020 Some Label Ltd."),
+      copyright("P", "℗ 2020 Some Label"),
+    ];
+    assert_eq!(label_from_copyrights(&copyrights), "Some Label");
+    assert_eq!(
+      copyright_text(&copyrights, "C").unwrap(),
+      "© 2020 Some Label Ltd."
+    );
+    assert_eq!(
+      label_from_copyrights(&[copyright("P", "(P) 1999 Label")]),
+      "Label"
+    );
+    assert_eq!(label_from_copyrights(&[]), "");
+  }
+
+  #[test]
+  fn render_disc_and_context_test() {
+    let conf = UserConfig::new();
+    let values = vec![Format::Disc(Some(2)), Format::ContextRemaining(Some(90000))];
+    assert_eq!(
+      render("disc %disc, %dur_ctx left", &values, &conf),
+      "disc 2, 1:30 left"
+    );
+    let values = vec![Format::Disc(None), Format::ContextRemaining(None)];
+    assert_eq!(render("[%disc][%dur_ctx]", &values, &conf), "[][]");
+  }
+
+  #[test]
+  fn disc_number_test() {
+    assert_eq!(disc_number(1, &[1, 1, 1]), None);
+    assert_eq!(disc_number(1, &[1, 1, 2]), Some(1));
+    assert_eq!(disc_number(2, &[]), Some(2));
+    assert_eq!(disc_number(1, &[]), None);
+  }
+
+  #[test]
+  fn playback_source_test() {
+    let mut disallows = HashMap::new();
+    assert_eq!(playback_source(false, true, false, &disallows), "");
+
+    // Pausing says nothing about the item and is left out
+    disallows.insert(DisallowKey::Pausing, true);
+    disallows.insert(DisallowKey::SkippingNext, false);
+    assert_eq!(playback_source(false, true, false, &disallows), "");
+
+    disallows.insert(DisallowKey::Seeking, true);
+    disallows.insert(DisallowKey::SkippingPrev, true);
+    assert_eq!(
+      playback_source(true, false, true, &disallows),
+      "local-file,unplayable,restricted,no-skipping-prev,no-seeking"
+    );
+  }
+
+  #[test]
+  fn progress_percent_test() {
+    assert_eq!(progress_percent(0, 200000), 0);
+    assert_eq!(progress_percent(100000, 200000), 50);
+    // Rounds to the nearest integer
+    assert_eq!(progress_percent(1990, 2000), 100);
+    assert_eq!(progress_percent(1010, 3000), 34);
+    // Clamped when the progress is ahead of the duration
+    assert_eq!(progress_percent(3000, 2000), 100);
+    assert_eq!(progress_percent(1000, 0), 0);
+  }
+
+  #[test]
+  fn render_track_details_test() {
+    let conf = UserConfig::new();
+    let values = vec![
+      Format::Length(215_000),
+      Format::Elapsed(65_000),
+      Format::Year(release_year("1981-12-15")),
+      Format::TrackNumber(7),
+      Format::Popularity(64),
+      Format::Isrc("USUM71703861".to_string()),
+      Format::Energy(Some(0.5)),
+    ];
+    assert_eq!(
+      render("%e/%len %y #%n %i %c", &values, &conf),
+      "1:05/3:35 1981 #7 64 USUM71703861"
+    );
+    assert_eq!(render("%energy", &values, &conf), "0.50");
+    assert_eq!(
+      render("%{03}n|%{4}i|%{2}y", &values, &conf),
+      "007|  64|1981"
+    );
+    // Widths have to be numbers
+    assert_eq!(render("%{x}n", &values, &conf), "%{x}n");
+    assert_eq!(match_token("%{03}n - %t"), Some("%{03}n"));
+    assert_eq!(match_token("%avail?de"), Some("%avail?de"));
+    assert_eq!(match_token("%avail?éu"), Some("%avail"));
+    assert_eq!(split_padding("%{03}n"), (Some("03"), "%n".to_string()));
+    assert_eq!(release_year(""), "");
+  }
+
+  #[test]
+  fn pitch_class_to_key_test() {
+    assert_eq!(pitch_class_to_key(0, 1.0), "C");
+    assert_eq!(pitch_class_to_key(1, 0.0), "C#m");
+    assert_eq!(pitch_class_to_key(11, 1.0), "B");
+    assert_eq!(pitch_class_to_key(-1, 1.0), "");
+  }
+}
@@ -53,7 +53,9 @@ pub fn handler(key: Key, app: &mut App) {
     }
     k if k == app.user_config.keys.next_page => app.get_current_user_saved_albums_next(),
     k if k == app.user_config.keys.previous_page => app.get_current_user_saved_albums_previous(),
-    Key::Char('D') => app.current_user_saved_album_delete(ActiveBlock::AlbumList),
+    _ if key == app.user_config.keys.unfollow => {
+      app.current_user_saved_album_delete(ActiveBlock::AlbumList)
+    }
     _ => {}
   };
 }
@@ -4,6 +4,7 @@ use crate::{
   event::Key,
   network::IoEvent,
 };
+use rspotify::model::track::SimplifiedTrack;
 
 pub fn handler(key: Key, app: &mut App) {
   match key {
@@ -51,8 +52,8 @@ pub fn handler(key: Key, app: &mut App) {
     k if common_key_events::high_event(k) => handle_high_event(app),
     k if common_key_events::middle_event(k) => handle_middle_event(app),
     k if common_key_events::low_event(k) => handle_low_event(app),
-    Key::Char('s') => handle_save_event(app),
-    Key::Char('w') => handle_save_album_event(app),
+    _ if key == app.user_config.keys.save_item => handle_save_event(app),
+    _ if key == app.user_config.keys.follow => handle_save_album_event(app),
     Key::Enter => match app.album_table_context {
       AlbumTableContext::Full => {
         if let Some(selected_album) = app.selected_album_full.clone() {
@@ -74,9 +75,19 @@ pub fn handler(key: Key, app: &mut App) {
       }
     },
     //recommended playlist based on selected track
-    Key::Char('r') => {
+    _ if key == app.user_config.keys.play_recommendations => {
       handle_recommended_tracks(app);
     }
+    _ if key == app.user_config.keys.add_to_playlist => {
+      if let Some(uri) = selected_track(app).map(|t| t.uri.clone()) {
+        app.open_playlist_picker(vec![uri]);
+      }
+    }
+    _ if key == app.user_config.keys.audio_features => {
+      if let Some((uri, name)) = selected_track(app).map(|t| (t.uri.clone(), t.name.clone())) {
+        app.show_track_features(&uri, name);
+      }
+    }
     _ if key == app.user_config.keys.add_item_to_queue => match app.album_table_context {
       AlbumTableContext::Full => {
         if let Some(selected_album) = app.selected_album_full.clone() {
@@ -159,6 +170,23 @@ fn handle_low_event(app: &mut App) {
   }
 }
 
+// The hovered track of the album that's open
+fn selected_track(app: &App) -> Option<&SimplifiedTrack> {
+  match app.album_table_context {
+    AlbumTableContext::Full => app.selected_album_full.as_ref().and_then(|selected| {
+      selected
+        .album
+        .tracks
+        .items
+        .get(app.saved_album_tracks_index)
+    }),
+    AlbumTableContext::Simplified => app
+      .selected_album_simplified
+      .as_ref()
+      .and_then(|selected| selected.tracks.items.get(selected.selected_index)),
+  }
+}
+
 fn handle_recommended_tracks(app: &mut App) {
   match app.album_table_context {
     AlbumTableContext::Full => {
@@ -32,7 +32,7 @@ fn handle_down_press_on_selected_block(app: &mut App) {
   }
 }
 
-fn handle_down_press_on_hovered_block(app: &mut App) {
+fn select_next_tab(app: &mut App) {
   if let Some(artist) = &mut app.artist {
     match artist.artist_hovered_block {
       ArtistBlock::TopTracks => {
@@ -78,7 +78,7 @@ fn handle_up_press_on_selected_block(app: &mut App) {
   }
 }
 
-fn handle_up_press_on_hovered_block(app: &mut App) {
+fn select_previous_tab(app: &mut App) {
   if let Some(artist) = &mut app.artist {
     match artist.artist_hovered_block {
       ArtistBlock::TopTracks => {
@@ -206,7 +206,9 @@ fn handle_enter_event_on_selected_block(app: &mut App) {
           .get(artist.selected_album_index)
           .cloned()
         {
-          app.track_table.context = Some(TrackTableContext::AlbumSearch);
+          app
+            .track_table
+            .set_context(Some(TrackTableContext::AlbumSearch));
           app.dispatch(IoEvent::GetAlbumTracks(Box::new(selected_album)));
         }
       }
@@ -238,36 +240,46 @@ pub fn handler(key: Key, app: &mut App) {
       Key::Esc => {
         artist.artist_selected_block = ArtistBlock::Empty;
       }
+      // Only one tab is shown, so moving down enters its list
       k if common_key_events::down_event(k) => {
         if artist.artist_selected_block != ArtistBlock::Empty {
           handle_down_press_on_selected_block(app);
         } else {
-          handle_down_press_on_hovered_block(app);
+          handle_enter_event_on_hovered_block(app);
         }
       }
       k if common_key_events::up_event(k) => {
         if artist.artist_selected_block != ArtistBlock::Empty {
           handle_up_press_on_selected_block(app);
         } else {
-          handle_up_press_on_hovered_block(app);
+          handle_enter_event_on_hovered_block(app);
         }
       }
+      // Switching tabs stays in the lists if one was selected
       k if common_key_events::left_event(k) => {
+        let in_list = artist.artist_selected_block != ArtistBlock::Empty;
         artist.artist_selected_block = ArtistBlock::Empty;
-        match artist.artist_hovered_block {
-          ArtistBlock::TopTracks => common_key_events::handle_left_event(app),
-          ArtistBlock::Albums => {
-            artist.artist_hovered_block = ArtistBlock::TopTracks;
-          }
-          ArtistBlock::RelatedArtists => {
-            artist.artist_hovered_block = ArtistBlock::Albums;
+        if artist.artist_hovered_block == ArtistBlock::TopTracks {
+          common_key_events::handle_left_event(app);
+        } else {
+          select_previous_tab(app);
+          if in_list {
+            handle_enter_event_on_hovered_block(app);
           }
-          ArtistBlock::Empty => {}
         }
       }
       k if common_key_events::right_event(k) => {
+        let in_list = artist.artist_selected_block != ArtistBlock::Empty;
         artist.artist_selected_block = ArtistBlock::Empty;
-        handle_down_press_on_hovered_block(app);
+        select_next_tab(app);
+        if in_list {
+          handle_enter_event_on_hovered_block(app);
+        }
+      }
+      _ if key == app.user_config.keys.cycle_range
+        && artist.artist_hovered_block == ArtistBlock::Albums =>
+      {
+        app.cycle_artist_album_group()
       }
       k if common_key_events::high_event(k) => {
         if artist.artist_selected_block != ArtistBlock::Empty {
@@ -291,21 +303,28 @@ pub fn handler(key: Key, app: &mut App) {
           handle_enter_event_on_hovered_block(app);
         }
       }
-      Key::Char('r') => {
+      _ if key == app.user_config.keys.play_recommendations => {
         if artist.artist_selected_block != ArtistBlock::Empty {
           handle_recommend_event_on_selected_block(app);
         }
       }
-      Key::Char('w') => match artist.artist_selected_block {
+      // Outside the albums and related artists it's the artist of the view
+      _ if key == app.user_config.keys.follow => match artist.artist_selected_block {
         ArtistBlock::Albums => app.current_user_saved_album_add(ActiveBlock::ArtistBlock),
-        ArtistBlock::RelatedArtists => app.user_follow_artists(ActiveBlock::ArtistBlock),
-        _ => (),
+        _ => app.user_follow_artists(ActiveBlock::ArtistBlock),
       },
-      Key::Char('D') => match artist.artist_selected_block {
+      _ if key == app.user_config.keys.unfollow => match artist.artist_selected_block {
         ArtistBlock::Albums => app.current_user_saved_album_delete(ActiveBlock::ArtistBlock),
-        ArtistBlock::RelatedArtists => app.user_unfollow_artists(ActiveBlock::ArtistBlock),
-        _ => (),
+        _ => app.user_unfollow_artists(ActiveBlock::ArtistBlock),
       },
+      _ if key == app.user_config.keys.add_to_playlist => {
+        if let ArtistBlock::TopTracks = artist.artist_selected_block {
+          if let Some(track) = artist.top_tracks.get(artist.selected_top_track_index) {
+            let uri = track.uri.clone();
+            app.open_playlist_picker(vec![uri]);
+          };
+        }
+      }
       _ if key == app.user_config.keys.add_item_to_queue => {
         if let ArtistBlock::TopTracks = artist.artist_selected_block {
           if let Some(track) = artist.top_tracks.get(artist.selected_top_track_index) {
@@ -33,7 +33,7 @@ pub fn handler(key: Key, app: &mut App) {
                     .get(artist_albums.selected_index)
                     .cloned()
                 {
-                    app.track_table.context = Some(TrackTableContext::AlbumSearch);
+                    app.track_table.set_context(Some(TrackTableContext::AlbumSearch));
                     app.get_album_tracks(selected_album);
                 }
             };
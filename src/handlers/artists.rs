@@ -48,8 +48,8 @@ pub fn handler(key: Key, app: &mut App) {
         app.push_navigation_stack(RouteId::Artist, ActiveBlock::ArtistBlock);
       }
     }
-    Key::Char('D') => app.user_unfollow_artists(ActiveBlock::AlbumList),
-    Key::Char('e') => {
+    _ if key == app.user_config.keys.unfollow => app.user_unfollow_artists(ActiveBlock::AlbumList),
+    _ if key == app.user_config.keys.play_artist => {
       let artists = app.artists.to_owned();
       let artist = artists.get(app.artists_list_index);
       if let Some(artist) = artist {
@@ -60,7 +60,7 @@ pub fn handler(key: Key, app: &mut App) {
         ));
       }
     }
-    Key::Char('r') => {
+    _ if key == app.user_config.keys.play_recommendations => {
       let artists = app.artists.to_owned();
       let artist = artists.get(app.artists_list_index);
       if let Some(artist) = artist {
@@ -0,0 +1,93 @@
+use super::{handle_app, library};
+use crate::app::App;
+use crate::command_palette::{Action, Command};
+use crate::event::Key;
+use crate::network::IoEvent;
+use rspotify::model::{context::CurrentlyPlaybackContext, PlayingItem};
+
+pub enum QueryEvent {
+  Close,
+  Submit,
+  Edited,
+}
+
+// Typing edits the query, so only keys that can't be part of it navigate.
+// Shared with the library finder
+pub fn handle_query_key(
+  key: Key,
+  query: &mut String,
+  selected: &mut usize,
+  count: usize,
+) -> QueryEvent {
+  match key {
+    Key::Esc => return QueryEvent::Close,
+    Key::Enter => return QueryEvent::Submit,
+    Key::Down | Key::Ctrl('n') => {
+      *selected = std::cmp::min(*selected + 1, count.saturating_sub(1));
+    }
+    Key::Up | Key::Ctrl('p') => {
+      *selected = selected.saturating_sub(1);
+    }
+    Key::Backspace => {
+      query.pop();
+      *selected = 0;
+    }
+    Key::Ctrl('u') => {
+      query.clear();
+      *selected = 0;
+    }
+    Key::Char(c) => {
+      query.push(c);
+      *selected = 0;
+    }
+    _ => {}
+  }
+  QueryEvent::Edited
+}
+
+pub fn handler(key: Key, app: &mut App) {
+  let palette = match &mut app.command_palette {
+    Some(palette) => palette,
+    None => return,
+  };
+  let matches = palette.matches();
+  match handle_query_key(
+    key,
+    &mut palette.query,
+    &mut palette.selected,
+    matches.len(),
+  ) {
+    QueryEvent::Close => app.command_palette = None,
+    QueryEvent::Submit => {
+      let command = matches.get(palette.selected).copied();
+      app.command_palette = None;
+      if let Some(command) = command {
+        run(command, app);
+      }
+    }
+    QueryEvent::Edited => {}
+  }
+}
+
+fn run(command: &Command, app: &mut App) {
+  match command.action {
+    Action::Key(key) => handle_app(key(&app.user_config.keys), app),
+    Action::Library(index) => {
+      app.library.selected_index = index;
+      library::handler(Key::Enter, app);
+    }
+    Action::SaveTrack => app.toggle_save_playing_item(),
+    Action::Repeat(state) => app.dispatch(IoEvent::SetRepeat(state)),
+    Action::SaveAlbum => {
+      if let Some(CurrentlyPlaybackContext {
+        item: Some(PlayingItem::Track(track)),
+        ..
+      }) = &app.current_playback_context
+      {
+        if let Some(album_id) = track.album.id.clone() {
+          app.dispatch(IoEvent::CurrentUserSavedAlbumAdd(album_id));
+        }
+      }
+    }
+  }
+}
@@ -117,6 +117,9 @@ pub fn handle_right_event(app: &mut App) {
           Some(ActiveBlock::RecentlyPlayed),
         );
       }
+      RouteId::TopItems => {
+        app.set_current_route_state(Some(ActiveBlock::TopItems), Some(ActiveBlock::TopItems));
+      }
       RouteId::Search => {
         app.set_current_route_state(
           Some(ActiveBlock::SearchResultBlock),
@@ -133,6 +136,7 @@ pub fn handle_right_event(app: &mut App) {
       RouteId::SelectedDevice => {}
       RouteId::Error => {}
       RouteId::Analysis => {}
+      RouteId::Lyrics => {}
       RouteId::BasicView => {}
       RouteId::Dialog => {}
     },
@@ -141,6 +145,8 @@ pub fn handle_right_event(app: &mut App) {
 }
 
 pub fn handle_left_event(app: &mut App) {
+  // The library can't be selected while it's hidden
+  app.sidebar_collapsed = false;
   // TODO: This should send you back to either library or playlist based on last selection
   app.set_current_route_state(Some(ActiveBlock::Empty), Some(ActiveBlock::Library));
 }
@@ -25,6 +25,7 @@ pub fn handler(key: Key, app: &mut App) {
       | ActiveBlock::MadeForYou
       | ActiveBlock::MyPlaylists
       | ActiveBlock::RecentlyPlayed
+      | ActiveBlock::TopItems
       | ActiveBlock::TrackTable => {
         app.set_current_route_state(None, Some(ActiveBlock::PlayBar));
       }
@@ -49,6 +50,7 @@ pub fn handler(key: Key, app: &mut App) {
       | ActiveBlock::Home
       | ActiveBlock::MadeForYou
       | ActiveBlock::RecentlyPlayed
+      | ActiveBlock::TopItems
       | ActiveBlock::TrackTable => {
         app.set_current_route_state(None, Some(ActiveBlock::Library));
       }
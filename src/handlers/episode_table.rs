@@ -5,6 +5,7 @@ use super::{
 use crate::app::ActiveBlock;
 use crate::event::Key;
 use crate::network::IoEvent;
+use rspotify::model::show::ResumePoint;
 
 pub fn handler(key: Key, app: &mut App) {
   match key {
@@ -48,9 +49,9 @@ pub fn handler(key: Key, app: &mut App) {
     k if k == app.user_config.keys.next_page => handle_next_event(app),
     // Scroll up
     k if k == app.user_config.keys.previous_page => handle_prev_event(app),
-    Key::Char('S') => toggle_sort_by_date(app),
-    Key::Char('s') => handle_follow_event(app),
-    Key::Char('D') => handle_unfollow_event(app),
+    _ if key == app.user_config.keys.sort_episodes => toggle_sort_by_date(app),
+    _ if key == app.user_config.keys.save_item => handle_follow_event(app),
+    _ if key == app.user_config.keys.unfollow => handle_unfollow_event(app),
     Key::Ctrl('e') => jump_to_end(app),
     Key::Ctrl('a') => jump_to_start(app),
     _ => {}
@@ -64,6 +65,8 @@ fn jump_to_end(app: &mut App) {
   }
 }
 
+// Episodes that were started before continue where they were left, finished
+// ones start over
 fn on_enter(app: &mut App) {
   if let Some(episodes) = app.library.show_episodes.get_results(None) {
     let episode_uris = episodes
@@ -71,11 +74,22 @@ fn on_enter(app: &mut App) {
       .iter()
       .map(|episode| episode.uri.to_owned())
       .collect::<Vec<String>>();
-    app.dispatch(IoEvent::StartPlayback(
-      None,
-      Some(episode_uris),
-      Some(app.episode_list_index),
-    ));
+    let resume_position_ms = match episodes
+      .items
+      .get(app.episode_list_index)
+      .and_then(|episode| episode.resume_point.as_ref())
+    {
+      Some(ResumePoint {
+        fully_played: false,
+        resume_position_ms,
+      }) if *resume_position_ms > 0 => Some(*resume_position_ms),
+      _ => None,
+    };
+    let offset = Some(app.episode_list_index);
+    app.dispatch(match resume_position_ms {
+      Some(position_ms) => IoEvent::StartPlaybackAt(None, Some(episode_uris), offset, position_ms),
+      None => IoEvent::StartPlayback(None, Some(episode_uris), offset),
+    });
   }
 }
 
@@ -1,6 +1,7 @@
 extern crate unicode_width;
 
 use super::super::app::{ActiveBlock, App, RouteId};
+use crate::cli::link_to_uri;
 use crate::event::Key;
 use crate::network::IoEvent;
 use std::convert::TryInto;
@@ -103,6 +104,9 @@ fn process_input(app: &mut App, input: String) {
   // On searching for a track, clear the playlist selection
   app.selected_playlist_index = Some(0);
 
+  // Shared links can have a locale and other parts spotify: uris don't have
+  let input = link_to_uri(&input).unwrap_or(input);
+
   if attempt_process_uri(app, &input, "https://open.spotify.com/", "/")
     || attempt_process_uri(app, &input, "spotify:", ":")
   {
@@ -495,6 +499,15 @@ mod tests {
       assert_eq!(actual_id, expected_id);
     }
 
+    #[test]
+    fn shared_link_with_locale() {
+      let link = "https://open.spotify.com/intl-de/album/5gzLOflH95LkKYE6XSXE9k?si=abc";
+      let uri = link_to_uri(link).unwrap();
+      let (actual_id, matched) = spotify_resource_id(URI_BASE, &uri, ":", "album");
+      assert!(matched);
+      assert_eq!(actual_id, "5gzLOflH95LkKYE6XSXE9k");
+    }
+
     #[test]
     fn mismatched_resource_types_do_not_match() {
       let playlist_url =
@@ -65,6 +65,11 @@ pub fn handler(key: Key, app: &mut App) {
         app.dispatch(IoEvent::GetCurrentUserSavedShows(None));
         app.push_navigation_stack(RouteId::Podcasts, ActiveBlock::Podcasts);
       }
+      // Top Items,
+      6 => {
+        app.get_top_items();
+        app.push_navigation_stack(RouteId::TopItems, ActiveBlock::TopItems);
+      }
       // This is required because Rust can't tell if this pattern in exhaustive
       _ => {}
     },
@@ -0,0 +1,64 @@
+use super::command_palette::{handle_query_key, QueryEvent};
+use super::playlist;
+use crate::app::{ActiveBlock, AlbumTableContext, App, RouteId, SelectedFullAlbum};
+use crate::event::Key;
+use crate::library_finder::Target;
+use crate::network::IoEvent;
+
+pub fn handler(key: Key, app: &mut App) {
+  let count = match &app.library_finder {
+    Some(finder) => finder.matches(app).len(),
+    None => return,
+  };
+  let finder = match &mut app.library_finder {
+    Some(finder) => finder,
+    None => return,
+  };
+  match handle_query_key(key, &mut finder.query, &mut finder.selected, count) {
+    QueryEvent::Close => app.library_finder = None,
+    QueryEvent::Submit => {
+      let selected = finder.selected;
+      let target = app
+        .library_finder
+        .as_ref()
+        .and_then(|finder| finder.matches(app).get(selected).map(|e| e.target));
+      app.library_finder = None;
+      if let Some(target) = target {
+        open(target, app);
+      }
+    }
+    QueryEvent::Edited => {}
+  }
+}
+
+// Opens the entry like selecting it in its own view would
+fn open(target: Target, app: &mut App) {
+  match target {
+    Target::Playlist(i) => {
+      app.selected_playlist_index = Some(i);
+      playlist::handler(Key::Enter, app);
+    }
+    Target::Album(page, i) => {
+      let album = app.library.saved_albums.pages[page].items[i].album.clone();
+      app.selected_album_full = Some(SelectedFullAlbum {
+        album,
+        selected_index: 0,
+      });
+      app.album_table_context = AlbumTableContext::Full;
+      app.push_navigation_stack(RouteId::AlbumTracks, ActiveBlock::AlbumTracks);
+    }
+    Target::Artist(page, i) => {
+      let artist = &app.library.saved_artists.pages[page].items[i];
+      let (id, name) = (artist.id.clone(), artist.name.clone());
+      app.get_artist(id, name);
+      app.push_navigation_stack(RouteId::Artist, ActiveBlock::ArtistBlock);
+    }
+    Target::LikedSong(page, i) => {
+      let uri = app.library.saved_tracks.pages[page].items[i]
+        .track
+        .uri
+        .clone();
+      app.dispatch(IoEvent::StartPlayback(None, Some(vec![uri]), None));
+    }
+  }
+}
@@ -0,0 +1,15 @@
+use super::common_key_events;
+use crate::{app::App, event::Key};
+
+// Synced lyrics follow the playback, plain ones are scrolled by hand
+pub fn handler(key: Key, app: &mut App) {
+  match key {
+    k if common_key_events::down_event(k) => {
+      app.lyrics_scroll = app.lyrics_scroll.saturating_add(1);
+    }
+    k if common_key_events::up_event(k) => {
+      app.lyrics_scroll = app.lyrics_scroll.saturating_sub(1);
+    }
+    _ => {}
+  }
+}
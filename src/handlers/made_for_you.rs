@@ -45,7 +45,9 @@ pub fn handler(key: Key, app: &mut App) {
         &app.library.made_for_you_playlists.get_results(Some(0)),
         &app.made_for_you_index,
       ) {
-        app.track_table.context = Some(TrackTableContext::MadeForYou);
+        app
+          .track_table
+          .set_context(Some(TrackTableContext::MadeForYou));
         app.playlist_offset = 0;
         if let Some(selected_playlist) = playlists.items.get(selected_playlist_index.to_owned()) {
           app.made_for_you_offset = 0;
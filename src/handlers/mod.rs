@@ -4,6 +4,7 @@ mod analysis;
 mod artist;
 mod artists;
 mod basic_view;
+mod command_palette;
 mod common_key_events;
 mod dialog;
 mod empty;
@@ -13,23 +14,48 @@ mod help_menu;
 mod home;
 mod input;
 mod library;
+mod library_finder;
+mod lyrics;
 mod made_for_you;
+mod mouse;
 mod playbar;
 mod playlist;
+mod playlist_picker;
 mod podcasts;
+mod profile_menu;
 mod recently_played;
 mod search_results;
 mod select_device;
+mod sort_menu;
+mod top_items;
+mod track_filter;
 mod track_table;
 
 use super::app::{ActiveBlock, App, ArtistBlock, RouteId, SearchResultBlock};
 use crate::event::Key;
 use crate::network::IoEvent;
 use rspotify::model::{context::CurrentlyPlaybackContext, PlayingItem};
+use tracing::debug;
 
+pub use command_palette::handler as command_palette_handler;
 pub use input::handler as input_handler;
+pub use library_finder::handler as library_finder_handler;
+pub use mouse::handler as mouse_handler;
+pub use playlist_picker::handler as playlist_picker_handler;
+pub use profile_menu::handler as profile_menu_handler;
+pub use sort_menu::handler as sort_menu_handler;
+pub use track_filter::handler as track_filter_handler;
 
 pub fn handle_app(key: Key, app: &mut App) {
+  debug!(key = ?key, block = ?app.get_current_route().active_block, "key");
+  // `g 5` seeks to 50%, any other key is handled as usual
+  if std::mem::take(&mut app.seeking_to_percent) {
+    if let Key::Char(digit @ '0'..='9') = key {
+      app.seek_to_ratio(f64::from(digit as u8 - b'0') / 10.0);
+      return;
+    }
+  }
+
   // First handle any global event and then move to block event
   match key {
     Key::Esc => {
@@ -46,6 +72,7 @@ pub fn handle_app(key: Key, app: &mut App) {
     }
     _ if key == app.user_config.keys.manage_devices => {
       app.dispatch(IoEvent::GetDevices);
+      app.dispatch(IoEvent::DiscoverDevices);
     }
     _ if key == app.user_config.keys.decrease_volume => {
       app.decrease_volume();
@@ -63,6 +90,22 @@ pub fn handle_app(key: Key, app: &mut App) {
     _ if key == app.user_config.keys.seek_forwards => {
       app.seek_forwards();
     }
+    _ if key == app.user_config.keys.seek_to_percent => {
+      app.seeking_to_percent = true;
+    }
+    _ if key == app.user_config.keys.switch_profile => {
+      app.open_profile_menu();
+    }
+    _ if key == app.user_config.keys.toggle_sidebar => {
+      // The selection moves out of the sidebar before it's hidden
+      if !app.sidebar_collapsed {
+        common_key_events::handle_right_event(app);
+      }
+      app.sidebar_collapsed = !app.sidebar_collapsed;
+    }
+    _ if key == app.user_config.keys.undo => {
+      app.undo();
+    }
     _ if key == app.user_config.keys.next_track => {
       app.dispatch(IoEvent::NextTrack);
     }
@@ -88,12 +131,27 @@ pub fn handle_app(key: Key, app: &mut App) {
     _ if key == app.user_config.keys.copy_album_url => {
       app.copy_album_url();
     }
+    _ if key == app.user_config.keys.toggle_save_album => {
+      app.toggle_save_album();
+    }
     _ if key == app.user_config.keys.audio_analysis => {
       app.get_audio_analysis();
     }
     _ if key == app.user_config.keys.basic_view => {
       app.push_navigation_stack(RouteId::BasicView, ActiveBlock::BasicView);
     }
+    _ if key == app.user_config.keys.command_palette => {
+      app.command_palette = Some(Default::default());
+    }
+    _ if key == app.user_config.keys.library_finder => {
+      app.open_library_finder();
+    }
+    _ if key == app.user_config.keys.lyrics => {
+      app.show_lyrics();
+    }
+    _ if key == app.user_config.keys.toggle_discord_presence => {
+      app.toggle_discord_presence();
+    }
     _ => handle_block_events(key, app),
   }
 }
@@ -105,6 +163,9 @@ fn handle_block_events(key: Key, app: &mut App) {
     ActiveBlock::Analysis => {
       analysis::handler(key, app);
     }
+    ActiveBlock::Lyrics => {
+      lyrics::handler(key, app);
+    }
     ActiveBlock::ArtistBlock => {
       artist::handler(key, app);
     }
@@ -150,6 +211,9 @@ fn handle_block_events(key: Key, app: &mut App) {
     ActiveBlock::RecentlyPlayed => {
       recently_played::handler(key, app);
     }
+    ActiveBlock::TopItems => {
+      top_items::handler(key, app);
+    }
     ActiveBlock::Artists => {
       artists::handler(key, app);
     }
@@ -188,7 +252,7 @@ fn handle_escape(app: &mut App) {
       app.pop_navigation_stack();
     }
     // These are global views that have no active/inactive distinction so do nothing
-    ActiveBlock::SelectDevice | ActiveBlock::Analysis => {}
+    ActiveBlock::SelectDevice | ActiveBlock::Analysis | ActiveBlock::Lyrics => {}
     _ => {
       app.set_current_route_state(Some(ActiveBlock::Empty), None);
     }
@@ -0,0 +1,157 @@
+use super::handle_app;
+use crate::app::{ActiveBlock, App, ClickArea, ClickTarget, RouteId};
+use crate::event::Key;
+use crossterm::event::{MouseButton, MouseEvent, MouseEventKind};
+
+pub fn handler(mouse: MouseEvent, app: &mut App) {
+  // Overlays and typing take every key, so they'd miss what the mouse did
+  let current_route = app.get_current_route();
+  if app.command_palette.is_some()
+    || app.library_finder.is_some()
+    || app.playlist_picker.is_some()
+    || app.sort_menu.is_some()
+    || app.profile_menu.is_some()
+    || app.track_features.is_some()
+    || app.track_table.filtering
+    || current_route.active_block == ActiveBlock::Input
+    || current_route.id == RouteId::Dialog
+  {
+    return;
+  }
+  let area = match area_at(&app.click_areas.borrow(), mouse.column, mouse.row) {
+    Some(area) => area,
+    None => return,
+  };
+  match mouse.kind {
+    MouseEventKind::ScrollDown => {
+      focus(app, area.block);
+      handle_app(Key::Down, app);
+    }
+    MouseEventKind::ScrollUp => {
+      focus(app, area.block);
+      handle_app(Key::Up, app);
+    }
+    MouseEventKind::Down(MouseButton::Left) => match area.target {
+      ClickTarget::Progress if area.rect.width > 0 => {
+        let ratio = f64::from(mouse.column - area.rect.x) / f64::from(area.rect.width);
+        app.seek_to_ratio(ratio);
+      }
+      ClickTarget::Rows {
+        first_row,
+        offset,
+        count,
+      } if mouse.row >= first_row => {
+        focus(app, area.block);
+        let index = offset + usize::from(mouse.row - first_row);
+        if index >= count {
+          return;
+        }
+        // Clicking the selected row again opens or plays it
+        if selected_row(app, area.block) == Some(index) {
+          handle_app(Key::Enter, app);
+        } else {
+          select_row(app, area.block, index);
+        }
+      }
+      _ => focus(app, area.block),
+    },
+    _ => {}
+  }
+}
+
+// The last area drawn is the one on top
+fn area_at(areas: &[ClickArea], column: u16, row: u16) -> Option<ClickArea> {
+  areas
+    .iter()
+    .rev()
+    .find(|area| {
+      let rect = area.rect;
+      (rect.x..rect.x + rect.width).contains(&column)
+        && (rect.y..rect.y + rect.height).contains(&row)
+    })
+    .copied()
+}
+
+fn focus(app: &mut App, block: ActiveBlock) {
+  app.set_current_route_state(Some(block), Some(block));
+}
+
+fn selected_row(app: &App, block: ActiveBlock) -> Option<usize> {
+  match block {
+    ActiveBlock::Library => Some(app.library.selected_index),
+    ActiveBlock::MyPlaylists => app.selected_playlist_index,
+    ActiveBlock::TrackTable => Some(app.track_table.selected_index),
+    ActiveBlock::AlbumList => Some(app.album_list_index),
+    ActiveBlock::Artists => Some(app.artists_list_index),
+    ActiveBlock::Podcasts => Some(app.shows_list_index),
+    ActiveBlock::EpisodeTable => Some(app.episode_list_index),
+    ActiveBlock::MadeForYou => Some(app.made_for_you_index),
+    ActiveBlock::RecentlyPlayed => Some(app.recently_played.index),
+    ActiveBlock::TopItems => Some(app.top_items.index),
+    _ => None,
+  }
+}
+
+fn select_row(app: &mut App, block: ActiveBlock, index: usize) {
+  match block {
+    ActiveBlock::Library => app.library.selected_index = index,
+    ActiveBlock::MyPlaylists => app.selected_playlist_index = Some(index),
+    ActiveBlock::TrackTable => app.track_table.selected_index = index,
+    ActiveBlock::AlbumList => app.album_list_index = index,
+    ActiveBlock::Artists => app.artists_list_index = index,
+    ActiveBlock::Podcasts => app.shows_list_index = index,
+    ActiveBlock::EpisodeTable => app.episode_list_index = index,
+    ActiveBlock::MadeForYou => app.made_for_you_index = index,
+    ActiveBlock::RecentlyPlayed => app.recently_played.index = index,
+    ActiveBlock::TopItems => app.top_items.index = index,
+    _ => {}
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crossterm::event::KeyModifiers;
+  use tui::layout::Rect;
+
+  fn click(column: u16, row: u16) -> MouseEvent {
+    MouseEvent {
+      kind: MouseEventKind::Down(MouseButton::Left),
+      column,
+      row,
+      modifiers: KeyModifiers::NONE,
+    }
+  }
+
+  #[test]
+  fn click_rows() {
+    let mut app = App::default();
+    app.click_areas.borrow_mut().extend([
+      ClickArea {
+        rect: Rect::new(0, 0, 20, 10),
+        block: ActiveBlock::Library,
+        target: ClickTarget::Rows {
+          first_row: 1,
+          offset: 2,
+          count: 5,
+        },
+      },
+      ClickArea {
+        rect: Rect::new(20, 0, 40, 10),
+        block: ActiveBlock::Home,
+        target: ClickTarget::Pane,
+      },
+    ]);
+
+    // The third row shows the fifth item
+    handler(click(5, 3), &mut app);
+    assert_eq!(app.library.selected_index, 4);
+    assert_eq!(app.get_current_route().active_block, ActiveBlock::Library);
+    // Below the last item
+    handler(click(5, 4), &mut app);
+    assert_eq!(app.library.selected_index, 4);
+
+    handler(click(30, 3), &mut app);
+    assert_eq!(app.get_current_route().active_block, ActiveBlock::Home);
+  }
+}
@@ -11,7 +11,7 @@ pub fn handler(key: Key, app: &mut App) {
     k if common_key_events::up_event(k) => {
       app.set_current_route_state(Some(ActiveBlock::Empty), Some(ActiveBlock::MyPlaylists));
     }
-    Key::Char('s') => {
+    _ if key == app.user_config.keys.save_item => {
       if let Some(CurrentlyPlaybackContext {
         item: Some(item), ..
       }) = app.current_playback_context.to_owned()
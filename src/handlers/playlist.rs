@@ -5,6 +5,7 @@ use super::{
 use crate::app::{ActiveBlock, RouteId};
 use crate::event::Key;
 use crate::network::IoEvent;
+use crate::track_sort::SortMenu;
 
 pub fn handler(key: Key, app: &mut App) {
   match key {
@@ -63,7 +64,9 @@ pub fn handler(key: Key, app: &mut App) {
         (&app.playlists, &app.selected_playlist_index)
       {
         app.active_playlist_index = Some(selected_playlist_index.to_owned());
-        app.track_table.context = Some(TrackTableContext::MyPlaylists);
+        app
+          .track_table
+          .set_context(Some(TrackTableContext::MyPlaylists));
         app.playlist_offset = 0;
         if let Some(selected_playlist) = playlists.items.get(selected_playlist_index.to_owned()) {
           let playlist_id = selected_playlist.id.to_owned();
@@ -71,7 +74,13 @@ pub fn handler(key: Key, app: &mut App) {
         }
       };
     }
-    Key::Char('D') => {
+    _ if key == app.user_config.keys.sort_tracks => {
+      app.sort_menu = Some(SortMenu {
+        playlists: true,
+        ..Default::default()
+      })
+    }
+    _ if key == app.user_config.keys.unfollow => {
       if let (Some(playlists), Some(selected_index)) = (&app.playlists, app.selected_playlist_index)
       {
         let selected_playlist = &playlists.items[selected_index].name;
@@ -0,0 +1,36 @@
+use super::command_palette::{handle_query_key, QueryEvent};
+use crate::app::App;
+use crate::event::Key;
+use crate::network::IoEvent;
+use crate::playlist_picker::PickerEntry;
+
+pub fn handler(key: Key, app: &mut App) {
+  let count = match &app.playlist_picker {
+    Some(picker) => picker.matches(app).len(),
+    None => return,
+  };
+  let picker = match &mut app.playlist_picker {
+    Some(picker) => picker,
+    None => return,
+  };
+  match handle_query_key(key, &mut picker.query, &mut picker.selected, count) {
+    QueryEvent::Close => app.playlist_picker = None,
+    QueryEvent::Submit => {
+      if let Some(picker) = app.playlist_picker.take() {
+        match picker.matches(app).into_iter().nth(picker.selected) {
+          Some(PickerEntry::Playlist { id, .. }) => {
+            app.recent_playlist_ids.retain(|recent| *recent != id);
+            app.recent_playlist_ids.insert(0, id.clone());
+            app.dispatch(IoEvent::AddTracksToPlaylist(id, picker.uris));
+          }
+          Some(PickerEntry::Create(name)) => {
+            app.dispatch(IoEvent::CreatePlaylistWithTracks(name, picker.uris));
+          }
+          None => return,
+        }
+        app.track_table.clear_marks();
+      }
+    }
+    QueryEvent::Edited => {}
+  }
+}
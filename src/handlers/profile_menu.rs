@@ -0,0 +1,25 @@
+use super::command_palette::{handle_query_key, QueryEvent};
+use crate::app::App;
+use crate::event::Key;
+use crate::network::IoEvent;
+
+pub fn handler(key: Key, app: &mut App) {
+  let menu = match &mut app.profile_menu {
+    Some(menu) => menu,
+    None => return,
+  };
+  let count = menu.matches().len();
+  match handle_query_key(key, &mut menu.query, &mut menu.selected, count) {
+    QueryEvent::Close => app.profile_menu = None,
+    QueryEvent::Submit => {
+      if let Some(menu) = app.profile_menu.take() {
+        if let Some(profile) = menu.matches().into_iter().nth(menu.selected) {
+          if profile != app.profile {
+            app.dispatch(IoEvent::SwitchProfile(profile));
+          }
+        }
+      }
+    }
+    QueryEvent::Edited => {}
+  }
+}
@@ -40,7 +40,7 @@ pub fn handler(key: Key, app: &mut App) {
         app.recently_played.index = next_index;
       }
     }
-    Key::Char('s') => {
+    _ if key == app.user_config.keys.save_item => {
       if let Some(recently_played_result) = &app.recently_played.result.clone() {
         if let Some(selected_track) = recently_played_result.items.get(app.recently_played.index) {
           if let Some(track_id) = &selected_track.track.id {
@@ -64,7 +64,7 @@ pub fn handler(key: Key, app: &mut App) {
         ));
       };
     }
-    Key::Char('r') => {
+    _ if key == app.user_config.keys.play_recommendations => {
       if let Some(recently_played_result) = &app.recently_played.result.clone() {
         let selected_track_history_item =
           recently_played_result.items.get(app.recently_played.index);
@@ -78,6 +78,28 @@ pub fn handler(key: Key, app: &mut App) {
         }
       }
     }
+    _ if key == app.user_config.keys.add_to_playlist => {
+      if let Some(history) = app
+        .recently_played
+        .result
+        .as_ref()
+        .and_then(|result| result.items.get(app.recently_played.index))
+      {
+        let uri = history.track.uri.clone();
+        app.open_playlist_picker(vec![uri]);
+      }
+    }
+    _ if key == app.user_config.keys.audio_features => {
+      if let Some(history) = app
+        .recently_played
+        .result
+        .as_ref()
+        .and_then(|result| result.items.get(app.recently_played.index))
+      {
+        let (uri, name) = (history.track.uri.clone(), history.track.name.clone());
+        app.show_track_features(&uri, name);
+      }
+    }
     _ if key == app.user_config.keys.add_item_to_queue => {
       if let Some(recently_played_result) = &app.recently_played.result.clone() {
         if let Some(history) = recently_played_result.items.get(app.recently_played.index) {
@@ -291,7 +291,9 @@ fn handle_enter_event_on_selected_block(app: &mut App) {
         &app.search_results.albums,
       ) {
         if let Some(album) = albums_result.items.get(index.to_owned()).cloned() {
-          app.track_table.context = Some(TrackTableContext::AlbumSearch);
+          app
+            .track_table
+            .set_context(Some(TrackTableContext::AlbumSearch));
           app.dispatch(IoEvent::GetAlbumTracks(Box::new(album)));
         };
       }
@@ -325,7 +327,9 @@ fn handle_enter_event_on_selected_block(app: &mut App) {
       ) {
         if let Some(playlist) = playlists_result.items.get(index) {
           // Go to playlist tracks table
-          app.track_table.context = Some(TrackTableContext::PlaylistSearch);
+          app
+            .track_table
+            .set_context(Some(TrackTableContext::PlaylistSearch));
           let playlist_id = playlist.id.to_owned();
           app.dispatch(IoEvent::GetPlaylistTracks(playlist_id, app.playlist_offset));
         };
@@ -500,7 +504,7 @@ pub fn handler(key: Key, app: &mut App) {
       }
       _ => handle_enter_event_on_selected_block(app),
     },
-    Key::Char('w') => match app.search_results.selected_block {
+    _ if key == app.user_config.keys.follow => match app.search_results.selected_block {
       SearchResultBlock::AlbumSearch => {
         app.current_user_saved_album_add(ActiveBlock::SearchResultBlock)
       }
@@ -512,7 +516,7 @@ pub fn handler(key: Key, app: &mut App) {
       SearchResultBlock::ShowSearch => app.user_follow_show(ActiveBlock::SearchResultBlock),
       SearchResultBlock::Empty => {}
     },
-    Key::Char('D') => match app.search_results.selected_block {
+    _ if key == app.user_config.keys.unfollow => match app.search_results.selected_block {
       SearchResultBlock::AlbumSearch => {
         app.current_user_saved_album_delete(ActiveBlock::SearchResultBlock)
       }
@@ -536,8 +540,20 @@ pub fn handler(key: Key, app: &mut App) {
       SearchResultBlock::ShowSearch => app.user_unfollow_show(ActiveBlock::SearchResultBlock),
       SearchResultBlock::Empty => {}
     },
-    Key::Char('r') => handle_recommended_tracks(app),
+    _ if key == app.user_config.keys.play_recommendations => handle_recommended_tracks(app),
     _ if key == app.user_config.keys.add_item_to_queue => handle_add_item_to_queue(app),
+    _ if key == app.user_config.keys.add_to_playlist => {
+      if let (SearchResultBlock::SongSearch, Some(index), Some(tracks)) = (
+        &app.search_results.selected_block,
+        app.search_results.selected_tracks_index,
+        &app.search_results.tracks,
+      ) {
+        if let Some(track) = tracks.items.get(index) {
+          let uri = track.uri.clone();
+          app.open_playlist_picker(vec![uri]);
+        }
+      }
+    }
     // Add `s` to "see more" on each option
     _ => {}
   }
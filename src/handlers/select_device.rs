@@ -11,66 +11,47 @@ pub fn handler(key: Key, app: &mut App) {
       app.set_current_route_state(Some(ActiveBlock::Library), None);
     }
     k if common_key_events::down_event(k) => {
-      match &app.devices {
-        Some(p) => {
-          if let Some(selected_device_index) = app.selected_device_index {
-            let next_index =
-              common_key_events::on_down_press_handler(&p.devices, Some(selected_device_index));
-            app.selected_device_index = Some(next_index);
-          }
-        }
-        None => {}
-      };
+      let devices = app.device_names();
+      if let Some(selected_device_index) = app.selected_device_index {
+        let next_index =
+          common_key_events::on_down_press_handler(&devices, Some(selected_device_index));
+        app.selected_device_index = Some(next_index);
+      }
     }
     k if common_key_events::up_event(k) => {
-      match &app.devices {
-        Some(p) => {
-          if let Some(selected_device_index) = app.selected_device_index {
-            let next_index =
-              common_key_events::on_up_press_handler(&p.devices, Some(selected_device_index));
-            app.selected_device_index = Some(next_index);
-          }
-        }
-        None => {}
-      };
+      let devices = app.device_names();
+      if let Some(selected_device_index) = app.selected_device_index {
+        let next_index =
+          common_key_events::on_up_press_handler(&devices, Some(selected_device_index));
+        app.selected_device_index = Some(next_index);
+      }
     }
-    k if common_key_events::high_event(k) => {
-      match &app.devices {
-        Some(_p) => {
-          if let Some(_selected_device_index) = app.selected_device_index {
-            let next_index = common_key_events::on_high_press_handler();
-            app.selected_device_index = Some(next_index);
-          }
-        }
-        None => {}
-      };
+    k if common_key_events::high_event(k) && app.selected_device_index.is_some() => {
+      let next_index = common_key_events::on_high_press_handler();
+      app.selected_device_index = Some(next_index);
     }
     k if common_key_events::middle_event(k) => {
-      match &app.devices {
-        Some(p) => {
-          if let Some(_selected_device_index) = app.selected_device_index {
-            let next_index = common_key_events::on_middle_press_handler(&p.devices);
-            app.selected_device_index = Some(next_index);
-          }
-        }
-        None => {}
-      };
+      let devices = app.device_names();
+      if app.selected_device_index.is_some() && !devices.is_empty() {
+        let next_index = common_key_events::on_middle_press_handler(&devices);
+        app.selected_device_index = Some(next_index);
+      }
     }
     k if common_key_events::low_event(k) => {
-      match &app.devices {
-        Some(p) => {
-          if let Some(_selected_device_index) = app.selected_device_index {
-            let next_index = common_key_events::on_low_press_handler(&p.devices);
-            app.selected_device_index = Some(next_index);
-          }
-        }
-        None => {}
-      };
+      let devices = app.device_names();
+      if app.selected_device_index.is_some() && !devices.is_empty() {
+        let next_index = common_key_events::on_low_press_handler(&devices);
+        app.selected_device_index = Some(next_index);
+      }
     }
     Key::Enter => {
-      if let (Some(devices), Some(index)) = (app.devices.clone(), app.selected_device_index) {
-        if let Some(device) = &devices.devices.get(index) {
+      if let Some(index) = app.selected_device_index {
+        let listed = app.devices.as_ref().map_or(&[][..], |p| &p.devices[..]);
+        if let Some(device) = listed.get(index) {
           app.dispatch(IoEvent::TransferPlaybackToDevice(device.id.clone()));
+        } else if let Some(device) = app.unlisted_devices().get(index - listed.len()) {
+          let device = (*device).clone();
+          app.dispatch(IoEvent::WakeDevice(device));
         }
       };
     }
@@ -0,0 +1,26 @@
+use super::command_palette::{handle_query_key, QueryEvent};
+use crate::app::App;
+use crate::event::Key;
+
+pub fn handler(key: Key, app: &mut App) {
+  let menu = match &mut app.sort_menu {
+    Some(menu) => menu,
+    None => return,
+  };
+  let count = menu.matches().len();
+  match handle_query_key(key, &mut menu.query, &mut menu.selected, count) {
+    QueryEvent::Close => app.sort_menu = None,
+    QueryEvent::Submit => {
+      if let Some(menu) = app.sort_menu.take() {
+        if let Some((_, sort)) = menu.matches().into_iter().nth(menu.selected) {
+          if menu.playlists {
+            app.set_playlist_sort(sort);
+          } else {
+            app.track_table.set_sort(sort);
+          }
+        }
+      }
+    }
+    QueryEvent::Edited => {}
+  }
+}
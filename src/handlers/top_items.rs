@@ -0,0 +1,128 @@
+use super::common_key_events;
+use crate::{
+  app::{ActiveBlock, App, RouteId, TopItemsKind},
+  event::Key,
+  network::IoEvent,
+};
+
+pub fn handler(key: Key, app: &mut App) {
+  match key {
+    k if common_key_events::left_event(k) => common_key_events::handle_left_event(app),
+    k if common_key_events::down_event(k) => {
+      let items = vec![(); app.top_items.len()];
+      app.top_items.index =
+        common_key_events::on_down_press_handler(&items, Some(app.top_items.index));
+    }
+    k if common_key_events::up_event(k) => {
+      let items = vec![(); app.top_items.len()];
+      app.top_items.index =
+        common_key_events::on_up_press_handler(&items, Some(app.top_items.index));
+    }
+    k if common_key_events::high_event(k) => {
+      app.top_items.index = common_key_events::on_high_press_handler();
+    }
+    k if common_key_events::middle_event(k) => {
+      let items = vec![(); app.top_items.len()];
+      app.top_items.index = common_key_events::on_middle_press_handler(&items);
+    }
+    k if common_key_events::low_event(k) => {
+      let items = vec![(); app.top_items.len()];
+      app.top_items.index = common_key_events::on_low_press_handler(&items);
+    }
+    _ if key == app.user_config.keys.switch_tab => app.toggle_top_items_kind(),
+    _ if key == app.user_config.keys.cycle_range => app.cycle_top_items_time_range(),
+    Key::Enter => match app.top_items.kind {
+      TopItemsKind::Tracks => {
+        let track_uris = app
+          .top_items
+          .tracks
+          .iter()
+          .map(|track| track.uri.to_owned())
+          .collect::<Vec<String>>();
+        if !track_uris.is_empty() {
+          app.dispatch(IoEvent::StartPlayback(
+            None,
+            Some(track_uris),
+            Some(app.top_items.index),
+          ));
+        }
+      }
+      TopItemsKind::Artists => {
+        if let Some(artist) = app.top_items.artists.get(app.top_items.index).cloned() {
+          app.get_artist(artist.id, artist.name);
+          app.push_navigation_stack(RouteId::Artist, ActiveBlock::ArtistBlock);
+        }
+      }
+    },
+    // Plays the artist, like in the artists list
+    _ if key == app.user_config.keys.play_artist => {
+      if let (TopItemsKind::Artists, Some(artist)) = (
+        app.top_items.kind,
+        app.top_items.artists.get(app.top_items.index),
+      ) {
+        let uri = artist.uri.to_owned();
+        app.dispatch(IoEvent::StartPlayback(Some(uri), None, None));
+      }
+    }
+    _ if key == app.user_config.keys.follow => app.user_follow_artists(ActiveBlock::TopItems),
+    _ if key == app.user_config.keys.unfollow => app.user_unfollow_artists(ActiveBlock::TopItems),
+    _ if key == app.user_config.keys.save_item => {
+      if let (TopItemsKind::Tracks, Some(track)) = (
+        app.top_items.kind,
+        app.top_items.tracks.get(app.top_items.index),
+      ) {
+        if let Some(id) = track.id.to_owned() {
+          app.dispatch(IoEvent::ToggleSaveTrack(id));
+        }
+      }
+    }
+    _ if key == app.user_config.keys.add_to_playlist => {
+      if let (TopItemsKind::Tracks, Some(track)) = (
+        app.top_items.kind,
+        app.top_items.tracks.get(app.top_items.index),
+      ) {
+        let uri = track.uri.to_owned();
+        app.open_playlist_picker(vec![uri]);
+      }
+    }
+    _ if key == app.user_config.keys.audio_features => {
+      if let (TopItemsKind::Tracks, Some(track)) = (
+        app.top_items.kind,
+        app.top_items.tracks.get(app.top_items.index),
+      ) {
+        let (uri, name) = (track.uri.to_owned(), track.name.to_owned());
+        app.show_track_features(&uri, name);
+      }
+    }
+    _ if key == app.user_config.keys.add_item_to_queue => {
+      if let (TopItemsKind::Tracks, Some(track)) = (
+        app.top_items.kind,
+        app.top_items.tracks.get(app.top_items.index),
+      ) {
+        let uri = track.uri.to_owned();
+        app.dispatch(IoEvent::AddItemToQueue(uri));
+      }
+    }
+    _ => {}
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn on_tab_and_time_range() {
+    let mut app = App::default();
+    app.top_items.index = 3;
+
+    handler(Key::Tab, &mut app);
+    assert_eq!(app.top_items.kind, TopItemsKind::Artists);
+    assert_eq!(app.top_items.index, 0);
+
+    handler(Key::Char('t'), &mut app);
+    assert_eq!(app.top_items.time_range_label(), "all time");
+    handler(Key::Char('t'), &mut app);
+    assert_eq!(app.top_items.time_range_label(), "last 4 weeks");
+  }
+}
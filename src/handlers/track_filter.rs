@@ -0,0 +1,81 @@
+use super::command_palette::{handle_query_key, QueryEvent};
+use crate::app::App;
+use crate::event::Key;
+
+// Up and down still move through the filtered tracks while typing
+pub fn handler(key: Key, app: &mut App) {
+  let mut filter = app.track_table.filter.clone();
+  let mut selected = app.track_table.selected_index;
+  let count = app.track_table.tracks.len();
+  match handle_query_key(key, &mut filter, &mut selected, count) {
+    QueryEvent::Close => {
+      app.track_table.filtering = false;
+      app.track_table.set_filter(String::new());
+    }
+    // Keeps the filter, so enter plays the hovered track next
+    QueryEvent::Submit => app.track_table.filtering = false,
+    QueryEvent::Edited if filter != app.track_table.filter => app.track_table.set_filter(filter),
+    QueryEvent::Edited => app.track_table.selected_index = selected,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::app::TrackTableContext;
+  use crate::test_util;
+  use rspotify::model::track::FullTrack;
+  use serde_json::json;
+  use std::collections::HashMap;
+
+  fn track(name: &str, artist: &str) -> FullTrack {
+    test_util::track(name, json!({ "artists": [test_util::artist(artist)] }))
+  }
+
+  #[test]
+  fn filter_tracks() {
+    let mut app = App::default();
+    app
+      .track_table
+      .set_context(Some(TrackTableContext::SavedTracks));
+    app.track_table.set_tracks(
+      vec![
+        track("Yellow", "Coldplay"),
+        track("Clocks", "Coldplay"),
+        track("Hello", "Adele"),
+      ],
+      HashMap::new(),
+    );
+    app.track_table.filtering = true;
+    let names = |app: &App| {
+      app
+        .track_table
+        .tracks
+        .iter()
+        .map(|t| t.name.clone())
+        .collect::<Vec<String>>()
+    };
+
+    for c in "ELLO".chars() {
+      handler(Key::Char(c), &mut app);
+    }
+    assert_eq!(names(&app), vec!["Yellow", "Hello"]);
+    handler(Key::Down, &mut app);
+    assert_eq!(app.track_table.selected_index, 1);
+
+    // Artists match too
+    handler(Key::Ctrl('u'), &mut app);
+    for c in "cold".chars() {
+      handler(Key::Char(c), &mut app);
+    }
+    assert_eq!(names(&app), vec!["Yellow", "Clocks"]);
+
+    handler(Key::Enter, &mut app);
+    assert!(!app.track_table.filtering);
+    assert_eq!(names(&app), vec!["Yellow", "Clocks"]);
+
+    app.track_table.filtering = true;
+    handler(Key::Esc, &mut app);
+    assert_eq!(names(&app).len(), 3);
+  }
+}
@@ -4,6 +4,7 @@ use super::{
 };
 use crate::event::Key;
 use crate::network::IoEvent;
+use crate::track_sort::SortMenu;
 use rand::{thread_rng, Rng};
 use serde_json::from_value;
 
@@ -51,8 +52,10 @@ pub fn handler(key: Key, app: &mut App) {
                 playlists.items.get(selected_playlist_index.to_owned())
               {
                 if let Some(playlist_tracks) = &app.playlist_tracks {
-                  if app.playlist_offset + app.large_search_limit < playlist_tracks.total {
-                    app.playlist_offset += app.large_search_limit;
+                  // After all the tracks that were loaded while scrolling
+                  let next_offset = app.playlist_offset + playlist_tracks.items.len() as u32;
+                  if next_offset < playlist_tracks.total {
+                    app.playlist_offset = next_offset;
                     let playlist_id = selected_playlist.id.to_owned();
                     app.dispatch(IoEvent::GetPlaylistTracks(playlist_id, app.playlist_offset));
                   }
@@ -77,8 +80,10 @@ pub fn handler(key: Key, app: &mut App) {
               .get(selected_playlist_index.to_owned())
             {
               if let Some(playlist_tracks) = &app.made_for_you_tracks {
-                if app.made_for_you_offset + app.large_search_limit < playlist_tracks.total {
-                  app.made_for_you_offset += app.large_search_limit;
+                // After all the tracks that were loaded while scrolling
+                let next_offset = app.made_for_you_offset + playlist_tracks.items.len() as u32;
+                if next_offset < playlist_tracks.total {
+                  app.made_for_you_offset = next_offset;
                   let playlist_id = selected_playlist.id.to_owned();
                   app.dispatch(IoEvent::GetMadeForYouPlaylistTracks(
                     playlist_id,
@@ -141,19 +146,109 @@ pub fn handler(key: Key, app: &mut App) {
         None => {}
       };
     }
-    Key::Char('s') => handle_save_track_event(app),
-    Key::Char('S') => play_random_song(app),
+    _ if key == app.user_config.keys.save_item && !app.track_table.selection().is_empty() => {
+      save_marked_tracks(app)
+    }
+    _ if key == app.user_config.keys.save_item => handle_save_track_event(app),
+    _ if key == app.user_config.keys.play_random_song => play_random_song(app),
+    _ if key == app.user_config.keys.follow => save_recommendations(app),
+    _ if key == app.user_config.keys.mark_track => {
+      app.track_table.toggle_mark();
+      app.track_table.selected_index = common_key_events::on_down_press_handler(
+        &app.track_table.tracks,
+        Some(app.track_table.selected_index),
+      );
+    }
+    _ if key == app.user_config.keys.visual_select => app.track_table.toggle_visual(),
+    _ if key == app.user_config.keys.clear_marks => app.track_table.clear_marks(),
+    _ if key == app.user_config.keys.add_to_playlist => add_to_playlist(app),
+    _ if key == app.user_config.keys.audio_features => {
+      if let Some(track) = app.track_table.tracks.get(app.track_table.selected_index) {
+        let (uri, name) = (track.uri.clone(), track.name.clone());
+        app.show_track_features(&uri, name);
+      }
+    }
+    _ if key == app.user_config.keys.sort_tracks && app.track_table.context.is_some() => {
+      app.sort_menu = Some(SortMenu::default())
+    }
+    _ if key == app.user_config.keys.filter => app.track_table.filtering = true,
+    _ if key == app.user_config.keys.move_track_down
+      && app.track_table.context == Some(TrackTableContext::MyPlaylists) =>
+    {
+      app.move_playlist_track(true)
+    }
+    _ if key == app.user_config.keys.move_track_up
+      && app.track_table.context == Some(TrackTableContext::MyPlaylists) =>
+    {
+      app.move_playlist_track(false)
+    }
     k if k == app.user_config.keys.jump_to_end => jump_to_end(app),
     k if k == app.user_config.keys.jump_to_start => jump_to_start(app),
     //recommended song radio
-    Key::Char('r') => {
+    _ if key == app.user_config.keys.play_recommendations => {
       handle_recommended_tracks(app);
     }
-    _ if key == app.user_config.keys.add_item_to_queue => on_queue(app),
+    _ if key == app.user_config.keys.add_item_to_queue => {
+      if app.track_table.selection().is_empty() {
+        on_queue(app);
+      } else {
+        queue_marked_tracks(app);
+      }
+    }
     _ => {}
   }
 }
 
+// Queued in the order of the table
+fn queue_marked_tracks(app: &mut App) {
+  for uri in app.track_table.selection() {
+    app.dispatch(IoEvent::AddItemToQueue(uri));
+  }
+  app.track_table.clear_marks();
+}
+
+// Likes every marked track, unlike `s` on a single track this never unlikes
+fn save_marked_tracks(app: &mut App) {
+  let ids = app
+    .track_table
+    .selection()
+    .iter()
+    .filter_map(|uri| uri.strip_prefix("spotify:track:"))
+    .map(|id| id.to_string())
+    .collect::<Vec<String>>();
+  if !ids.is_empty() {
+    app.dispatch(IoEvent::CurrentUserSavedTracksAdd(ids));
+  }
+  app.track_table.clear_marks();
+}
+
+// The marked tracks, or the hovered one if none are marked
+fn add_to_playlist(app: &mut App) {
+  let mut uris = app.track_table.selection();
+  if uris.is_empty() {
+    if let Some(track) = app.track_table.tracks.get(app.track_table.selected_index) {
+      uris.push(track.uri.clone());
+    }
+  }
+  app.open_playlist_picker(uris);
+}
+
+// Keeps the current radio as a playlist named after its seed
+fn save_recommendations(app: &mut App) {
+  if app.track_table.context != Some(TrackTableContext::RecommendedTracks)
+    || app.recommended_tracks.is_empty()
+  {
+    return;
+  }
+  let name = format!("{} Radio", app.recommendations_seed);
+  let uris = app
+    .recommended_tracks
+    .iter()
+    .map(|t| t.uri.clone())
+    .collect();
+  app.dispatch(IoEvent::CreatePlaylistWithTracks(name, uris));
+}
+
 fn play_random_song(app: &mut App) {
   if let Some(context) = &app.track_table.context {
     match context {
@@ -309,10 +404,26 @@ fn jump_to_end(app: &mut App) {
 }
 
 fn on_enter(app: &mut App) {
+  // The playlist or album would play in its own order or with the tracks that
+  // were filtered out, so play the table
+  if app.track_table.sort().is_some() || app.track_table.is_filtered() {
+    let uris = app
+      .track_table
+      .tracks
+      .iter()
+      .map(|t| t.uri.clone())
+      .collect::<Vec<String>>();
+    if !uris.is_empty() {
+      let offset = app.track_table.selected_index;
+      app.dispatch(IoEvent::StartPlayback(None, Some(uris), Some(offset)));
+    }
+    return;
+  }
   let TrackTable {
     context,
     selected_index,
     tracks,
+    ..
   } = &app.track_table;
   match &context {
     Some(context) => match context {
@@ -419,6 +530,7 @@ fn on_queue(app: &mut App) {
     context,
     selected_index,
     tracks,
+    ..
   } = &app.track_table;
   match &context {
     Some(context) => match context {
@@ -488,3 +600,197 @@ fn jump_to_start(app: &mut App) {
     None => {}
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::test_util;
+  use crate::track_sort::{SortField, TrackSort};
+  use rspotify::model::track::FullTrack;
+  use serde_json::json;
+
+  fn track(id: &str) -> FullTrack {
+    test_util::track(id, json!({}))
+  }
+
+  #[test]
+  fn mark_tracks() {
+    let mut app = App::default();
+    app.track_table.tracks = vec![track("a"), track("b"), track("c"), track("d")];
+
+    handler(Key::Char('x'), &mut app);
+    assert_eq!(app.track_table.selected_index, 1);
+    handler(Key::Char('x'), &mut app);
+    assert_eq!(
+      app.track_table.selection(),
+      vec!["spotify:track:a", "spotify:track:b"]
+    );
+
+    // The visual selection goes from the anchor to the selected track
+    handler(Key::Char('V'), &mut app);
+    handler(Key::Down, &mut app);
+    assert!(app.track_table.is_marked(3));
+    assert_eq!(app.track_table.selection().len(), 4);
+    handler(Key::Up, &mut app);
+    assert!(!app.track_table.is_marked(3));
+    handler(Key::Char('V'), &mut app);
+    assert_eq!(app.track_table.visual_anchor, None);
+    assert_eq!(app.track_table.selection().len(), 3);
+
+    // Unmarking the hovered track
+    app.track_table.selected_index = 0;
+    handler(Key::Char('x'), &mut app);
+    assert_eq!(
+      app.track_table.selection(),
+      vec!["spotify:track:b", "spotify:track:c"]
+    );
+
+    handler(Key::Char('X'), &mut app);
+    assert!(app.track_table.selection().is_empty());
+  }
+
+  #[test]
+  fn move_playlist_track() {
+    let mut app = App::default();
+    app.user = serde_json::from_value(serde_json::json!({
+      "external_urls": {},
+      "href": "",
+      "id": "me",
+      "type": "user",
+      "uri": "spotify:user:me",
+    }))
+    .unwrap();
+    app.playlists = serde_json::from_value(serde_json::json!({
+      "href": "",
+      "items": [{
+        "collaborative": false,
+        "external_urls": {},
+        "href": "",
+        "id": "playlist",
+        "images": [],
+        "name": "Mine",
+        "owner": {
+          "external_urls": {},
+          "href": "",
+          "id": "me",
+          "type": "user",
+          "uri": "spotify:user:me",
+        },
+        "snapshot_id": "1",
+        "tracks": {},
+        "type": "playlist",
+        "uri": "spotify:playlist:playlist",
+      }],
+      "limit": 20,
+      "next": null,
+      "offset": 0,
+      "previous": null,
+      "total": 1,
+    }))
+    .unwrap();
+    app.selected_playlist_index = Some(0);
+    let item = |id: Option<&str>| {
+      serde_json::json!({
+        "added_at": "2020-01-01T00:00:00Z",
+        "is_local": false,
+        "track": id.map(|id| serde_json::to_value(track(id)).unwrap()),
+      })
+    };
+    // The second item was removed from spotify, so it isn't in the table
+    app.playlist_tracks = serde_json::from_value(serde_json::json!({
+      "href": "",
+      "items": [item(Some("a")), item(None), item(Some("c"))],
+      "limit": 20,
+      "next": null,
+      "offset": 0,
+      "previous": null,
+      "total": 3,
+    }))
+    .unwrap();
+    app.track_table.tracks = vec![track("a"), track("c")];
+    app.track_table.context = Some(TrackTableContext::MyPlaylists);
+    app.track_table.selected_index = 1;
+
+    handler(Key::Char('K'), &mut app);
+    let names = |app: &App| {
+      app
+        .track_table
+        .tracks
+        .iter()
+        .map(|t| t.name.clone())
+        .collect::<Vec<String>>()
+    };
+    assert_eq!(names(&app), vec!["a", "c"]);
+    assert_eq!(app.track_table.selected_index, 1);
+    handler(Key::Char('K'), &mut app);
+    assert_eq!(names(&app), vec!["c", "a"]);
+    assert_eq!(app.track_table.selected_index, 0);
+    // Already at the top
+    handler(Key::Char('K'), &mut app);
+    assert_eq!(names(&app), vec!["c", "a"]);
+  }
+  #[test]
+  fn sort_tracks() {
+    let mut app = App::default();
+    app
+      .track_table
+      .set_context(Some(TrackTableContext::SavedTracks));
+    let added_at = [("spotify:track:b", 1), ("spotify:track:a", 2)]
+      .iter()
+      .map(|(uri, at)| (uri.to_string(), *at))
+      .collect();
+    app
+      .track_table
+      .set_tracks(vec![track("b"), track("a"), track("c")], added_at);
+    app.track_table.selected_index = 1;
+    let names = |app: &App| {
+      app
+        .track_table
+        .tracks
+        .iter()
+        .map(|t| t.name.clone())
+        .collect::<Vec<String>>()
+    };
+
+    app.track_table.set_sort(Some(TrackSort {
+      field: SortField::Title,
+      descending: false,
+    }));
+    assert_eq!(names(&app), vec!["a", "b", "c"]);
+    // Still on the same track
+    assert_eq!(app.track_table.selected_index, 0);
+
+    // Tracks without a date come first
+    app.track_table.set_sort(Some(TrackSort {
+      field: SortField::DateAdded,
+      descending: false,
+    }));
+    assert_eq!(names(&app), vec!["c", "b", "a"]);
+
+    // Each context keeps its own sort
+    app
+      .track_table
+      .set_context(Some(TrackTableContext::AlbumSearch));
+    assert_eq!(names(&app), vec!["b", "a", "c"]);
+    app
+      .track_table
+      .set_context(Some(TrackTableContext::SavedTracks));
+    app.track_table.set_sort(None);
+    assert_eq!(names(&app), vec!["b", "a", "c"]);
+
+    // An appended page sorts in under the selected track
+    app.track_table.set_sort(Some(TrackSort {
+      field: SortField::Title,
+      descending: true,
+    }));
+    app.track_table.selected_index = 2;
+    app
+      .track_table
+      .append_tracks(vec![track("0"), track("d")], Default::default());
+    assert_eq!(names(&app), vec!["d", "c", "b", "a", "0"]);
+    assert_eq!(
+      app.track_table.tracks[app.track_table.selected_index].name,
+      "a"
+    );
+  }
+}
@@ -0,0 +1,177 @@
+// Shell commands from the `hooks` section of the config, run on playback changes
+use crate::ui::util::create_artist_string;
+use crate::user_config::Hooks;
+use rspotify::model::{context::CurrentlyPlaybackContext, PlayingItem};
+use std::process::Command;
+use std::thread;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum HookEvent {
+  TrackChange,
+  Play,
+  Pause,
+  VolumeChange,
+}
+
+impl HookEvent {
+  fn name(self) -> &'static str {
+    match self {
+      HookEvent::TrackChange => "track_change",
+      HookEvent::Play => "play",
+      HookEvent::Pause => "pause",
+      HookEvent::VolumeChange => "volume_change",
+    }
+  }
+
+  fn command(self, hooks: &Hooks) -> Option<&String> {
+    let command = match self {
+      HookEvent::TrackChange => hooks.on_track_change.as_ref(),
+      HookEvent::Play => hooks.on_play.as_ref(),
+      HookEvent::Pause => hooks.on_pause.as_ref(),
+      HookEvent::VolumeChange => hooks.on_volume_change.as_ref(),
+    };
+    command.filter(|c| !c.trim().is_empty())
+  }
+}
+
+// What the hooks compare between two polls of the playback
+#[derive(Clone, Debug, PartialEq)]
+pub struct PlaybackState {
+  pub uri: Option<String>,
+  pub is_playing: bool,
+  pub volume: u32,
+}
+
+impl PlaybackState {
+  pub fn from_context(context: &CurrentlyPlaybackContext) -> Self {
+    let uri = match &context.item {
+      Some(PlayingItem::Track(track)) => Some(track.uri.clone()),
+      Some(PlayingItem::Episode(episode)) => Some(episode.uri.clone()),
+      None => None,
+    };
+    PlaybackState {
+      uri,
+      is_playing: context.is_playing,
+      volume: context.device.volume_percent,
+    }
+  }
+
+  // Starting or stopping the playback together with a new track only runs
+  // `on_track_change`, a paused track that changes only `on_pause` once
+  pub fn events(&self, next: &PlaybackState) -> Vec<HookEvent> {
+    let mut events = Vec::new();
+    if next.uri.is_some() && self.uri != next.uri {
+      events.push(HookEvent::TrackChange);
+    } else if !self.is_playing && next.is_playing {
+      events.push(HookEvent::Play);
+    } else if self.is_playing && !next.is_playing {
+      events.push(HookEvent::Pause);
+    }
+    if self.volume != next.volume {
+      events.push(HookEvent::VolumeChange);
+    }
+    events
+  }
+}
+
+// The playback as environment variables of the hook, empty if it's unknown
+pub fn environment(
+  event: HookEvent,
+  context: &CurrentlyPlaybackContext,
+) -> Vec<(&'static str, String)> {
+  let (title, artist, album, uri, duration_ms) = match &context.item {
+    Some(PlayingItem::Track(track)) => (
+      track.name.clone(),
+      create_artist_string(&track.artists),
+      track.album.name.clone(),
+      track.uri.clone(),
+      track.duration_ms,
+    ),
+    Some(PlayingItem::Episode(episode)) => (
+      episode.name.clone(),
+      episode.show.publisher.clone(),
+      episode.show.name.clone(),
+      episode.uri.clone(),
+      episode.duration_ms,
+    ),
+    None => Default::default(),
+  };
+  vec![
+    ("SPT_EVENT", event.name().to_string()),
+    ("SPT_TITLE", title),
+    ("SPT_ARTIST", artist),
+    ("SPT_ALBUM", album),
+    ("SPT_URI", uri),
+    ("SPT_DURATION_MS", duration_ms.to_string()),
+    (
+      "SPT_PROGRESS_MS",
+      context.progress_ms.unwrap_or(0).to_string(),
+    ),
+    ("SPT_IS_PLAYING", context.is_playing.to_string()),
+    ("SPT_DEVICE", context.device.name.clone()),
+    ("SPT_VOLUME", context.device.volume_percent.to_string()),
+  ]
+}
+
+fn shell(command: &str) -> Command {
+  if cfg!(windows) {
+    let mut cmd = Command::new("cmd");
+    cmd.args(["/C", command]);
+    cmd
+  } else {
+    let mut cmd = Command::new("sh");
+    cmd.args(["-c", command]);
+    cmd
+  }
+}
+
+// Runs the hook of `event` if one is configured. Like notifications, failures
+// are ignored and the UI doesn't wait for it
+pub fn run(hooks: &Hooks, event: HookEvent, context: &CurrentlyPlaybackContext) {
+  if let Some(command) = event.command(hooks) {
+    let mut cmd = shell(command);
+    cmd.envs(environment(event, context));
+    thread::spawn(move || {
+      let _ = cmd.output();
+    });
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn events_test() {
+    let state = |uri: &str, is_playing: bool, volume: u32| PlaybackState {
+      uri: Some(uri.to_string()),
+      is_playing,
+      volume,
+    };
+    let playing = state("spotify:track:1", true, 50);
+    assert!(playing.events(&playing).is_empty());
+    assert_eq!(
+      playing.events(&state("spotify:track:2", true, 50)),
+      vec![HookEvent::TrackChange]
+    );
+    assert_eq!(
+      playing.events(&state("spotify:track:1", false, 50)),
+      vec![HookEvent::Pause]
+    );
+    assert_eq!(
+      state("spotify:track:1", false, 50).events(&playing),
+      vec![HookEvent::Play]
+    );
+    assert_eq!(
+      playing.events(&state("spotify:track:2", false, 70)),
+      vec![HookEvent::TrackChange, HookEvent::VolumeChange]
+    );
+    // Nothing playing anymore isn't a new track
+    let stopped = PlaybackState {
+      uri: None,
+      is_playing: false,
+      volume: 50,
+    };
+    assert_eq!(playing.events(&stopped), vec![HookEvent::Pause]);
+  }
+}
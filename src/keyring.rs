@@ -0,0 +1,216 @@
+// Keeps the client secret and the spotify login in the keyring of the OS
+// instead of files in the config directory (`use_keyring`). There's no keyring
+// library that works with our runtime, so this uses `secret-tool` (libsecret)
+// on Linux and `security` on macOS
+use crate::config::{covers_scopes, profile_name};
+use anyhow::{anyhow, Result};
+use rspotify::oauth2::{SpotifyOAuth, TokenInfo};
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, ExitStatus, Stdio};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const SERVICE: &str = "spotify-tui";
+pub const CLIENT_SECRET: &str = "client_secret";
+const TOKEN: &str = "token";
+// A token this close to expiring is refreshed right away
+const EXPIRY_MARGIN_SECONDS: i64 = 60;
+
+// The secrets of one profile
+pub struct Keyring {
+  profile: String,
+}
+
+impl Keyring {
+  pub fn new(profile: Option<&str>) -> Self {
+    Keyring {
+      profile: profile_name(profile).to_string(),
+    }
+  }
+
+  fn account(&self, key: &str) -> String {
+    format!("{}/{}", self.profile, key)
+  }
+
+  pub fn get(&self, key: &str) -> Result<Option<String>> {
+    let account = self.account(key);
+    let mut cmd = if cfg!(target_os = "macos") {
+      let mut cmd = Command::new("security");
+      cmd.args(["find-generic-password", "-w", "-s", SERVICE, "-a", &account]);
+      cmd
+    } else if cfg!(unix) {
+      let mut cmd = Command::new("secret-tool");
+      cmd.args(["lookup", "service", SERVICE, "account", &account]);
+      cmd
+    } else {
+      return Err(unsupported());
+    };
+    let output = cmd.stderr(Stdio::null()).output().map_err(missing_tool)?;
+    // Both fail when there's no such secret
+    let secret = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    Ok(Some(secret).filter(|s| output.status.success() && !s.is_empty()))
+  }
+
+  pub fn set(&self, key: &str, secret: &str) -> Result<()> {
+    let account = self.account(key);
+    let label = format!("{} ({})", SERVICE, account);
+    // On stdin, arguments with the secret would show up in `ps`
+    let status = if cfg!(target_os = "macos") {
+      let mut cmd = Command::new("security");
+      cmd.arg("-i");
+      run_with_input(cmd, &security_add_command(&account, &label, secret))?
+    } else if cfg!(unix) {
+      let mut cmd = Command::new("secret-tool");
+      cmd.args([
+        "store", "--label", &label, "service", SERVICE, "account", &account,
+      ]);
+      run_with_input(cmd, secret)?
+    } else {
+      return Err(unsupported());
+    };
+    if status.success() {
+      Ok(())
+    } else {
+      Err(anyhow!(
+        "couldn't save {} in the keyring ({})",
+        account,
+        status
+      ))
+    }
+  }
+
+  pub fn load_token(&self) -> Result<Option<TokenInfo>> {
+    match self.get(TOKEN)? {
+      Some(token) => Ok(Some(serde_json::from_str(&token)?)),
+      None => Ok(None),
+    }
+  }
+
+  pub fn save_token(&self, token_info: &TokenInfo) -> Result<()> {
+    self.set(TOKEN, &serde_json::to_string(token_info)?)
+  }
+
+  // spotify's library saves new logins to the token file, they're moved into
+  // the keyring right after, like ones saved before `use_keyring` was enabled
+  pub fn take_token_file(&self, path: &Path) -> Result<()> {
+    if path.exists() {
+      let token_info: TokenInfo = serde_json::from_str(&fs::read_to_string(path)?)?;
+      self.save_token(&token_info)?;
+      fs::remove_file(path)?;
+    }
+    Ok(())
+  }
+}
+
+fn run_with_input(mut cmd: Command, input: &str) -> Result<ExitStatus> {
+  let mut child = cmd
+    .stdin(Stdio::piped())
+    .stdout(Stdio::null())
+    .stderr(Stdio::null())
+    .spawn()
+    .map_err(missing_tool)?;
+  if let Some(mut stdin) = child.stdin.take() {
+    stdin.write_all(input.as_bytes())?;
+  }
+  Ok(child.wait()?)
+}
+
+// A line for `security -i`, the secret goes in hex so it needs no quoting
+fn security_add_command(account: &str, label: &str, secret: &str) -> String {
+  let quote = |arg: &str| format!("\"{}\"", arg.replace('\\', "\\\\").replace('"', "\\\""));
+  let hex = secret
+    .bytes()
+    .map(|b| format!("{:02x}", b))
+    .collect::<String>();
+  format!(
+    "add-generic-password -U -s {} -a {} -l {} -X {}\n",
+    quote(SERVICE),
+    quote(account),
+    quote(label),
+    hex
+  )
+}
+
+fn unsupported() -> anyhow::Error {
+  anyhow!("`use_keyring` only works on Linux (secret-tool) and macOS so far")
+}
+
+fn missing_tool(e: std::io::Error) -> anyhow::Error {
+  anyhow!(
+    "`use_keyring` needs secret-tool on Linux (usually in libsecret-tools): {}",
+    e
+  )
+}
+
+fn is_expired(token_info: &TokenInfo, now: i64) -> bool {
+  match token_info.expires_at {
+    Some(expires_at) => expires_at < now + EXPIRY_MARGIN_SECONDS,
+    None => true,
+  }
+}
+
+// Like `SpotifyOAuth::get_cached_token`, with the token in the keyring. `None`
+// if there's none or it can't be used anymore, so the user logs in again
+pub async fn cached_token(
+  keyring: &Keyring,
+  oauth: &SpotifyOAuth,
+  token_path: &Path,
+) -> Result<Option<TokenInfo>> {
+  keyring.take_token_file(token_path)?;
+  let token_info = match keyring.load_token()? {
+    Some(token_info) if covers_scopes(&token_info.scope) => token_info,
+    _ => return Ok(None),
+  };
+  let now = SystemTime::now()
+    .duration_since(UNIX_EPOCH)
+    .map(|d| d.as_secs() as i64)
+    .unwrap_or(0);
+  if !is_expired(&token_info, now) {
+    return Ok(Some(token_info));
+  }
+  let refresh_token = match &token_info.refresh_token {
+    Some(refresh_token) => refresh_token,
+    None => return Ok(None),
+  };
+  match oauth
+    .refresh_access_token_without_cache(refresh_token)
+    .await
+  {
+    Some(token_info) => {
+      keyring.save_token(&token_info)?;
+      Ok(Some(token_info))
+    }
+    None => Ok(None),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn is_expired_test() {
+    let token = |expires_at| TokenInfo {
+      expires_at,
+      ..TokenInfo::default()
+    };
+    assert!(!is_expired(&token(Some(1_000)), 500));
+    assert!(is_expired(&token(Some(1_000)), 990));
+    assert!(is_expired(&token(None), 0));
+    assert_eq!(Keyring::new(Some("work")).account(TOKEN), "work/token");
+    assert_eq!(
+      Keyring::new(None).account(CLIENT_SECRET),
+      "default/client_secret"
+    );
+  }
+
+  #[test]
+  fn security_add_command_test() {
+    assert_eq!(
+      security_add_command("work/token", "spotify-tui (work/token)", "{\"a\": 1}"),
+      "add-generic-password -U -s \"spotify-tui\" -a \"work/token\" -l \"spotify-tui (work/token)\" -X 7b2261223a20317d\n"
+    );
+    assert!(security_add_command("a\"b", "", "").contains("-a \"a\\\"b\""));
+  }
+}
@@ -0,0 +1,103 @@
+use crate::app::App;
+use crate::command_palette::fuzzy_score;
+use crate::ui::util::create_artist_string;
+use std::collections::HashSet;
+
+// Where an entry lives in the app's cache, as (page, index) for paged results
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Target {
+  Playlist(usize),
+  Album(usize, usize),
+  Artist(usize, usize),
+  LikedSong(usize, usize),
+}
+
+pub struct Entry {
+  pub name: String,
+  pub target: Target,
+}
+
+impl Entry {
+  pub fn kind(&self) -> &'static str {
+    match self.target {
+      Target::Playlist(_) => "playlist",
+      Target::Album(..) => "album",
+      Target::Artist(..) => "artist",
+      Target::LikedSong(..) => "liked song",
+    }
+  }
+}
+
+// Everything that has been loaded so far, pages fetched more than once only
+// count once
+pub fn entries(app: &App) -> Vec<Entry> {
+  let mut seen = HashSet::new();
+  let mut entries = Vec::new();
+  if let Some(playlists) = &app.playlists {
+    for (i, playlist) in playlists.items.iter().enumerate() {
+      if seen.insert(playlist.uri.as_str()) {
+        entries.push(Entry {
+          name: playlist.name.clone(),
+          target: Target::Playlist(i),
+        });
+      }
+    }
+  }
+  for (page, albums) in app.library.saved_albums.pages.iter().enumerate() {
+    for (i, saved) in albums.items.iter().enumerate() {
+      if seen.insert(saved.album.uri.as_str()) {
+        entries.push(Entry {
+          name: format!(
+            "{} - {}",
+            saved.album.name,
+            create_artist_string(&saved.album.artists)
+          ),
+          target: Target::Album(page, i),
+        });
+      }
+    }
+  }
+  for (page, artists) in app.library.saved_artists.pages.iter().enumerate() {
+    for (i, artist) in artists.items.iter().enumerate() {
+      if seen.insert(artist.uri.as_str()) {
+        entries.push(Entry {
+          name: artist.name.clone(),
+          target: Target::Artist(page, i),
+        });
+      }
+    }
+  }
+  for (page, tracks) in app.library.saved_tracks.pages.iter().enumerate() {
+    for (i, saved) in tracks.items.iter().enumerate() {
+      if seen.insert(saved.track.uri.as_str()) {
+        entries.push(Entry {
+          name: format!(
+            "{} - {}",
+            saved.track.name,
+            create_artist_string(&saved.track.artists)
+          ),
+          target: Target::LikedSong(page, i),
+        });
+      }
+    }
+  }
+  entries
+}
+
+#[derive(Default)]
+pub struct LibraryFinder {
+  pub query: String,
+  pub selected: usize,
+}
+
+impl LibraryFinder {
+  // Built on every call, so results that finish loading show up right away
+  pub fn matches(&self, app: &App) -> Vec<Entry> {
+    let mut scored = entries(app)
+      .into_iter()
+      .filter_map(|e| fuzzy_score(&self.query, &e.name).map(|score| (score, e)))
+      .collect::<Vec<_>>();
+    scored.sort_by_key(|(score, _)| -score);
+    scored.into_iter().map(|(_, e)| e).collect()
+  }
+}
@@ -0,0 +1,206 @@
+// Submits the played tracks to ListenBrainz (or a self-hosted instance),
+// like the lyrics it uses curl since there's no http client for our runtime
+use crate::ui::util::create_artist_string;
+use rspotify::model::{context::CurrentlyPlaybackContext, PlayingItem};
+use serde_json::{json, Value};
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub const DEFAULT_URL: &str = "https://api.listenbrainz.org";
+
+// ListenBrainz counts a track as listened to after half of it or 4 minutes
+const MIN_LISTEN_MS: u32 = 4 * 60 * 1000;
+
+const TIMEOUT_SECONDS: &str = "10";
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ListenType {
+  PlayingNow,
+  Single,
+}
+
+impl ListenType {
+  fn name(self) -> &'static str {
+    match self {
+      ListenType::PlayingNow => "playing_now",
+      ListenType::Single => "single",
+    }
+  }
+}
+
+// The track that's playing and how far it got, episodes aren't submitted
+#[derive(Clone, Debug, PartialEq)]
+pub struct Listen {
+  pub uri: String,
+  pub artist: String,
+  // Last.fm only takes one artist
+  pub main_artist: String,
+  pub title: String,
+  pub album: String,
+  pub duration_ms: u32,
+  // When the track started, in seconds since the epoch
+  pub listened_at: u64,
+  pub progress_ms: u32,
+}
+
+impl Listen {
+  pub fn from_context(context: &CurrentlyPlaybackContext) -> Option<Self> {
+    let track = match &context.item {
+      Some(PlayingItem::Track(track)) => track,
+      _ => return None,
+    };
+    let progress_ms = context.progress_ms.unwrap_or(0);
+    let now = SystemTime::now()
+      .duration_since(UNIX_EPOCH)
+      .map_or(0, |d| d.as_secs());
+    Some(Listen {
+      uri: track.uri.clone(),
+      artist: create_artist_string(&track.artists),
+      main_artist: track
+        .artists
+        .first()
+        .map(|a| a.name.clone())
+        .unwrap_or_default(),
+      title: track.name.clone(),
+      album: track.album.name.clone(),
+      duration_ms: track.duration_ms,
+      listened_at: now.saturating_sub(u64::from(progress_ms / 1000)),
+      progress_ms,
+    })
+  }
+
+  // Seeking forward counts as listening, as it does for spotify
+  pub fn update(&mut self, context: &CurrentlyPlaybackContext) {
+    let uri = match &context.item {
+      Some(PlayingItem::Track(track)) => &track.uri,
+      _ => return,
+    };
+    if *uri == self.uri {
+      self.progress_ms = self.progress_ms.max(context.progress_ms.unwrap_or(0));
+    }
+  }
+
+  pub fn is_complete(&self) -> bool {
+    self.duration_ms > 0 && self.progress_ms >= MIN_LISTEN_MS.min(self.duration_ms / 2)
+  }
+
+  fn payload(&self, listen_type: ListenType) -> Value {
+    let id = self.uri.rsplit(':').next().unwrap_or_default();
+    let mut listen = json!({
+      "track_metadata": {
+        "artist_name": self.artist,
+        "track_name": self.title,
+        "release_name": self.album,
+        "additional_info": {
+          "duration_ms": self.duration_ms,
+          "spotify_id": format!("https://open.spotify.com/track/{}", id),
+          "submission_client": "spotify-tui",
+          "submission_client_version": env!("CARGO_PKG_VERSION"),
+        },
+      },
+    });
+    // Only finished listens have a time, `playing_now` must not
+    if listen_type == ListenType::Single {
+      listen["listened_at"] = json!(self.listened_at);
+    }
+    json!({
+      "listen_type": listen_type.name(),
+      "payload": [listen],
+    })
+  }
+}
+
+// The token is read from stdin, as an argument it would show up in `ps`
+fn command(url: &str, body: &str) -> Command {
+  let mut cmd = Command::new("curl");
+  cmd
+    .args(["--silent", "--max-time", TIMEOUT_SECONDS])
+    .args(["--header", "@-"])
+    .args(["--header", "Content-Type: application/json"])
+    .arg("--data")
+    .arg(body)
+    .arg(format!("{}/1/submit-listens", url.trim_end_matches('/')));
+  cmd
+}
+
+// Like notifications, failures are ignored and the UI doesn't wait for it
+pub fn submit(url: &str, token: &str, listen_type: ListenType, listen: &Listen) {
+  let mut cmd = command(url, &listen.payload(listen_type).to_string());
+  let header = format!("Authorization: Token {}\n", token);
+  thread::spawn(move || {
+    let child = cmd
+      .stdin(Stdio::piped())
+      .stdout(Stdio::null())
+      .stderr(Stdio::null())
+      .spawn();
+    if let Ok(mut child) = child {
+      if let Some(mut stdin) = child.stdin.take() {
+        let _ = stdin.write_all(header.as_bytes());
+      }
+      let _ = child.wait();
+    }
+  });
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn listen(duration_ms: u32, progress_ms: u32) -> Listen {
+    Listen {
+      uri: "spotify:track:4uLU6hMCjMI75M1A2tKUQC".to_string(),
+      artist: "Artist".to_string(),
+      main_artist: "Artist".to_string(),
+      title: "Title".to_string(),
+      album: "Album".to_string(),
+      duration_ms,
+      listened_at: 1_700_000_000,
+      progress_ms,
+    }
+  }
+
+  #[test]
+  fn is_complete_test() {
+    assert!(!listen(180_000, 89_999).is_complete());
+    assert!(listen(180_000, 90_000).is_complete());
+    // Long tracks only need 4 minutes
+    assert!(listen(1_200_000, 240_000).is_complete());
+    assert!(!listen(0, 0).is_complete());
+  }
+
+  #[test]
+  fn payload_test() {
+    let single = listen(180_000, 90_000).payload(ListenType::Single);
+    assert_eq!(single["listen_type"], "single");
+    assert_eq!(single["payload"][0]["listened_at"], 1_700_000_000);
+    let metadata = &single["payload"][0]["track_metadata"];
+    assert_eq!(metadata["artist_name"], "Artist");
+    assert_eq!(metadata["track_name"], "Title");
+    assert_eq!(metadata["release_name"], "Album");
+    assert_eq!(
+      metadata["additional_info"]["spotify_id"],
+      "https://open.spotify.com/track/4uLU6hMCjMI75M1A2tKUQC"
+    );
+
+    let playing_now = listen(180_000, 0).payload(ListenType::PlayingNow);
+    assert_eq!(playing_now["listen_type"], "playing_now");
+    assert!(playing_now["payload"][0].get("listened_at").is_none());
+  }
+
+  #[test]
+  fn command_test() {
+    let cmd = command("https://listenbrainz.example.org/", "{}");
+    let args = cmd
+      .get_args()
+      .map(|a| a.to_string_lossy().into_owned())
+      .collect::<Vec<String>>();
+    assert!(args.windows(2).any(|a| a == ["--header", "@-"]));
+    assert!(!args.iter().any(|a| a.contains("Authorization")));
+    assert_eq!(
+      args.last().unwrap(),
+      "https://listenbrainz.example.org/1/submit-listens"
+    );
+  }
+}
@@ -0,0 +1,153 @@
+// Writes the `tracing` events of spt to `--log-file` (or stderr for the CLI),
+// so there's something to attach to a bug report. `-v` logs every request
+// and change of the UI, `-vv` what they contained too. The last ones are
+// always kept for crash reports
+use anyhow::{anyhow, Result};
+use std::collections::VecDeque;
+use std::fmt::{self, Write as _};
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Level, Metadata, Subscriber};
+
+// Events of the libraries (e.g. hyper) would drown ours
+const TARGET: &str = env!("CARGO_CRATE_NAME");
+const RECENT_LINES: usize = 100;
+
+static RECENT: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+
+// What was logged last, with the detail of `-v` or more. Empty if the log is
+// in use right now, e.g. by the thread that crashed
+pub fn recent_lines() -> Vec<String> {
+  match RECENT.try_lock() {
+    Ok(recent) => recent.iter().cloned().collect(),
+    Err(_) => Vec::new(),
+  }
+}
+
+pub fn level(verbosity: u64) -> Level {
+  match verbosity {
+    0 => Level::WARN,
+    1 => Level::INFO,
+    2 => Level::DEBUG,
+    _ => Level::TRACE,
+  }
+}
+
+struct LogSubscriber {
+  level: Level,
+  out: Option<Mutex<Box<dyn Write + Send>>>,
+  next_span: AtomicU64,
+}
+
+// The message first, then the other fields as `name=value`
+#[derive(Default)]
+struct Line(String);
+
+impl Visit for Line {
+  fn record_str(&mut self, field: &Field, value: &str) {
+    if field.name() == "message" {
+      self.0.insert_str(0, value);
+    } else {
+      let _ = write!(self.0, " {}={}", field.name(), value);
+    }
+  }
+
+  fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+    if field.name() == "message" {
+      self.0.insert_str(0, &format!("{:?}", value));
+    } else {
+      let _ = write!(self.0, " {}={:?}", field.name(), value);
+    }
+  }
+}
+
+impl Subscriber for LogSubscriber {
+  fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+    *metadata.level() <= self.level.max(Level::INFO) && metadata.target().starts_with(TARGET)
+  }
+
+  // Spans aren't used, they only need an id
+  fn new_span(&self, _: &Attributes<'_>) -> Id {
+    Id::from_u64(self.next_span.fetch_add(1, Ordering::Relaxed))
+  }
+
+  fn record(&self, _: &Id, _: &Record<'_>) {}
+
+  fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+  fn event(&self, event: &Event<'_>) {
+    let mut line = Line::default();
+    event.record(&mut line);
+    let time = SystemTime::now()
+      .duration_since(UNIX_EPOCH)
+      .unwrap_or_default();
+    let metadata = event.metadata();
+    let line = format!(
+      "{}.{:03} {:5} {}: {}",
+      time.as_secs(),
+      time.subsec_millis(),
+      metadata.level(),
+      metadata.target(),
+      line.0
+    );
+    if let (Some(out), true) = (&self.out, metadata.level() <= &self.level) {
+      if let Ok(mut out) = out.lock() {
+        let _ = writeln!(out, "{}", line);
+        let _ = out.flush();
+      }
+    }
+    if let Ok(mut recent) = RECENT.lock() {
+      if recent.len() == RECENT_LINES {
+        recent.pop_front();
+      }
+      recent.push_back(line);
+    }
+  }
+
+  fn enter(&self, _: &Id) {}
+
+  fn exit(&self, _: &Id) {}
+}
+
+// Logs to `file` if there's one, appending to it. The TUI only logs with a
+// file, anything on stderr would end up on top of it
+pub fn init(file: Option<&Path>, verbosity: u64, tui: bool) -> Result<()> {
+  let out: Option<Box<dyn Write + Send>> = match file {
+    Some(path) => Some(Box::new(
+      OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| anyhow!("couldn't open the log file {}: {}", path.display(), e))?,
+    )),
+    None if verbosity == 0 => None,
+    None if tui => return Err(anyhow!("`-v` needs `--log-file` for the TUI")),
+    None => Some(Box::new(io::stderr())),
+  };
+  let subscriber = LogSubscriber {
+    level: level(verbosity),
+    out: out.map(Mutex::new),
+    next_span: AtomicU64::new(1),
+  };
+  tracing::subscriber::set_global_default(subscriber)?;
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn level_test() {
+    assert_eq!(level(0), Level::WARN);
+    assert_eq!(level(2), Level::DEBUG);
+    assert_eq!(level(5), Level::TRACE);
+    assert!(Level::DEBUG > level(1));
+  }
+}
@@ -0,0 +1,275 @@
+use anyhow::{anyhow, Result};
+use rspotify::model::track::FullTrack;
+use serde_json::Value;
+use tokio::process::Command;
+
+// Gives up on a provider that doesn't answer, the view just shows the error
+const TIMEOUT_SECONDS: &str = "10";
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Lyrics {
+  // Each line with the time it starts at in milliseconds
+  Synced(Vec<(u32, String)>),
+  Plain(Vec<String>),
+}
+
+impl Lyrics {
+  // The synced line being sung at `progress_ms`
+  pub fn current_line(&self, progress_ms: u32) -> Option<usize> {
+    match self {
+      Lyrics::Synced(lines) => lines.iter().rposition(|(ms, _)| *ms <= progress_ms),
+      Lyrics::Plain(_) => None,
+    }
+  }
+
+  pub fn lines(&self) -> Vec<&str> {
+    match self {
+      Lyrics::Synced(lines) => lines.iter().map(|(_, l)| l.as_str()).collect(),
+      Lyrics::Plain(lines) => lines.iter().map(|l| l.as_str()).collect(),
+    }
+  }
+
+  // One `[mm:ss.xx]` timestamp per line, plain lyrics have none
+  pub fn to_lrc(&self) -> Option<String> {
+    match self {
+      Lyrics::Synced(lines) => Some(
+        lines
+          .iter()
+          .map(|(ms, line)| {
+            format!(
+              "[{:02}:{:02}.{:02}]{}",
+              ms / 60_000,
+              ms / 1000 % 60,
+              ms % 1000 / 10,
+              line
+            )
+          })
+          .collect::<Vec<String>>()
+          .join("\n"),
+      ),
+      Lyrics::Plain(_) => None,
+    }
+  }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum LyricsStatus {
+  Loading,
+  Found(Lyrics),
+  Missing,
+  Failed(String),
+}
+
+// What the provider needs to find the lyrics of a track
+#[derive(Clone, Debug, PartialEq)]
+pub struct LyricsQuery {
+  pub uri: String,
+  pub artist: String,
+  pub title: String,
+  pub album: String,
+  pub duration_ms: u32,
+}
+
+impl LyricsQuery {
+  // Providers match on the main artist
+  pub fn from_track(track: &FullTrack) -> Self {
+    LyricsQuery {
+      uri: track.uri.clone(),
+      artist: track
+        .artists
+        .first()
+        .map(|a| a.name.clone())
+        .unwrap_or_default(),
+      title: track.name.clone(),
+      album: track.album.name.clone(),
+      duration_ms: track.duration_ms,
+    }
+  }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LyricsProvider {
+  // https://lrclib.net, has synced lyrics for many tracks
+  Lrclib,
+  // https://lyrics.ovh, only plain lyrics
+  Ovh,
+}
+
+impl LyricsProvider {
+  pub fn from_name(name: &str) -> Result<Self> {
+    match name {
+      "lrclib" => Ok(Self::Lrclib),
+      "ovh" => Ok(Self::Ovh),
+      _ => Err(anyhow!(
+        "unknown lyrics provider '{}', use 'lrclib' or 'ovh'",
+        name
+      )),
+    }
+  }
+
+  fn url(self, query: &LyricsQuery) -> String {
+    match self {
+      Self::Lrclib => format!(
+        "https://lrclib.net/api/get?artist_name={}&track_name={}&album_name={}&duration={}",
+        encode(&query.artist),
+        encode(&query.title),
+        encode(&query.album),
+        query.duration_ms / 1000
+      ),
+      Self::Ovh => format!(
+        "https://api.lyrics.ovh/v1/{}/{}",
+        encode(&query.artist),
+        encode(&query.title)
+      ),
+    }
+  }
+
+  // Answers without lyrics (including "not found" errors) are `None`
+  fn parse(self, body: &str) -> Option<Lyrics> {
+    let json: Value = serde_json::from_str(body).ok()?;
+    let text = |key: &str| {
+      json[key]
+        .as_str()
+        .filter(|s| !s.trim().is_empty())
+        .map(|s| s.to_string())
+    };
+    match self {
+      Self::Lrclib => match text("syncedLyrics") {
+        Some(synced) => Some(Lyrics::Synced(parse_lrc(&synced))),
+        None => text("plainLyrics").map(|plain| parse_plain(&plain)),
+      },
+      Self::Ovh => text("lyrics").map(|plain| parse_plain(&plain)),
+    }
+  }
+
+  // There's no http client that works with our runtime, so this uses curl
+  pub async fn fetch(self, query: &LyricsQuery) -> Result<LyricsStatus> {
+    let output = Command::new("curl")
+      .args(["--silent", "--location", "--max-time", TIMEOUT_SECONDS])
+      .arg(self.url(query))
+      .output()
+      .await
+      .map_err(|e| anyhow!("fetching lyrics needs curl: {}", e))?;
+    if !output.status.success() {
+      return Err(anyhow!(
+        "couldn't reach the lyrics provider (curl {})",
+        output.status
+      ));
+    }
+    let body = String::from_utf8_lossy(&output.stdout);
+    Ok(match self.parse(&body) {
+      Some(lyrics) => LyricsStatus::Found(lyrics),
+      None => LyricsStatus::Missing,
+    })
+  }
+}
+
+// Percent-encodes everything except unreserved characters
+pub fn encode(s: &str) -> String {
+  s.bytes()
+    .map(|b| match b {
+      b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+        (b as char).to_string()
+      }
+      _ => format!("%{:02X}", b),
+    })
+    .collect()
+}
+
+fn parse_plain(s: &str) -> Lyrics {
+  Lyrics::Plain(s.lines().map(|l| l.trim_end().to_string()).collect())
+}
+
+// Parses `[mm:ss.xx]` lines, a line can have several timestamps and lines
+// without one (like `[ar: ...]` tags) are skipped
+pub fn parse_lrc(s: &str) -> Vec<(u32, String)> {
+  let mut lines = Vec::new();
+  for line in s.lines() {
+    let mut rest = line.trim();
+    let mut times = Vec::new();
+    while let Some(end) = rest.strip_prefix('[').and_then(|r| r.find(']')) {
+      match parse_timestamp(&rest[1..=end]) {
+        Some(ms) => times.push(ms),
+        None => break,
+      }
+      rest = &rest[end + 2..];
+    }
+    for ms in times {
+      lines.push((ms, rest.trim().to_string()));
+    }
+  }
+  lines.sort_by_key(|(ms, _)| *ms);
+  lines
+}
+
+fn parse_timestamp(s: &str) -> Option<u32> {
+  let (minutes, seconds) = s.split_once(':')?;
+  let minutes = minutes.parse::<u32>().ok()?;
+  let seconds = seconds.parse::<f64>().ok()?;
+  Some(minutes * 60_000 + (seconds * 1000.0).round() as u32)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn parse_lrc_test() {
+    let lrc = "[ar: Someone]\n[00:01.50]First\n[00:10.00][01:00.25]Chorus\n[00:05.00]\n";
+    assert_eq!(
+      parse_lrc(lrc),
+      vec![
+        (1500, "First".to_string()),
+        (5000, "".to_string()),
+        (10000, "Chorus".to_string()),
+        (60250, "Chorus".to_string()),
+      ]
+    );
+
+    let lyrics = Lyrics::Synced(parse_lrc(lrc));
+    assert_eq!(lyrics.current_line(0), None);
+    assert_eq!(lyrics.current_line(1500), Some(0));
+    assert_eq!(lyrics.current_line(30000), Some(2));
+    assert_eq!(
+      lyrics.to_lrc().unwrap(),
+      "[00:01.50]First\n[00:05.00]\n[00:10.00]Chorus\n[01:00.25]Chorus"
+    );
+    assert_eq!(Lyrics::Plain(vec!["Hi".to_string()]).to_lrc(), None);
+  }
+
+  #[test]
+  fn provider_test() {
+    let query = LyricsQuery {
+      uri: "spotify:track:1".to_string(),
+      artist: "AC/DC".to_string(),
+      title: "T.N.T.".to_string(),
+      album: "High Voltage".to_string(),
+      duration_ms: 214_000,
+    };
+    assert_eq!(
+      LyricsProvider::Lrclib.url(&query),
+      "https://lrclib.net/api/get?artist_name=AC%2FDC&track_name=T.N.T.&album_name=High%20Voltage&duration=214"
+    );
+    assert_eq!(
+      LyricsProvider::Ovh.url(&query),
+      "https://api.lyrics.ovh/v1/AC%2FDC/T.N.T."
+    );
+
+    assert_eq!(
+      LyricsProvider::Lrclib.parse(r#"{"syncedLyrics":"[00:01.00]Hi","plainLyrics":"Hi"}"#),
+      Some(Lyrics::Synced(vec![(1000, "Hi".to_string())]))
+    );
+    assert_eq!(
+      LyricsProvider::Lrclib.parse(r#"{"syncedLyrics":null,"plainLyrics":"Hi\nthere"}"#),
+      Some(Lyrics::Plain(vec!["Hi".to_string(), "there".to_string()]))
+    );
+    assert_eq!(
+      LyricsProvider::Lrclib.parse(r#"{"code":404,"name":"TrackNotFound"}"#),
+      None
+    );
+    assert_eq!(
+      LyricsProvider::Ovh.parse(r#"{"error":"No lyrics found"}"#),
+      None
+    );
+  }
+}
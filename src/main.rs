@@ -1,130 +1,131 @@
 mod app;
 mod banner;
+mod cache;
 mod cli;
+mod command_palette;
 mod config;
+mod crash;
+mod discord;
+mod discovery;
 mod event;
+mod format;
 mod handlers;
+mod hooks;
+mod keyring;
+mod library_finder;
+mod listenbrainz;
+mod logging;
+mod lyrics;
 mod network;
+mod notify;
+mod playlist_picker;
+mod profile_menu;
+mod proxy;
 mod redirect_uri;
+mod scrobbler;
+mod spotifyd;
+#[cfg(test)]
+mod test_util;
+mod track_sort;
 mod ui;
+mod undo;
 mod user_config;
 
 use crate::app::RouteId;
 use crate::event::Key;
 use anyhow::{anyhow, Result};
 use app::{ActiveBlock, App};
-use backtrace::Backtrace;
 use banner::BANNER;
+use cache::ResponseCache;
 use clap::{App as ClapApp, Arg, Shell};
 use config::ClientConfig;
 use crossterm::{
   cursor::MoveTo,
-  event::{DisableMouseCapture, EnableMouseCapture},
+  event::EnableMouseCapture,
   execute,
-  style::Print,
-  terminal::{
-    disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen, SetTitle,
-  },
+  terminal::{enable_raw_mode, EnterAlternateScreen, SetTitle},
   ExecutableCommand,
 };
+use keyring::Keyring;
 use network::{get_spotify, IoEvent, Network};
 use redirect_uri::redirect_uri_web_server;
 use rspotify::{
   oauth2::{SpotifyOAuth, TokenInfo},
-  util::{process_token, request_token},
+  util::{generate_random_string, process_token, request_token},
 };
 use std::{
   cmp::{max, min},
+  fs,
   io::{self, stdout},
-  panic::{self, PanicInfo},
-  path::PathBuf,
+  panic,
+  path::{Path, PathBuf},
   sync::Arc,
-  time::SystemTime,
+  time::{Instant, SystemTime},
 };
 use tokio::sync::Mutex;
+use tracing::error;
 use tui::{
   backend::{Backend, CrosstermBackend},
   Terminal,
 };
 use user_config::{UserConfig, UserConfigPaths};
 
-const SCOPES: [&str; 14] = [
-  "playlist-read-collaborative",
-  "playlist-read-private",
-  "playlist-modify-private",
-  "playlist-modify-public",
-  "user-follow-read",
-  "user-follow-modify",
-  "user-library-modify",
-  "user-library-read",
-  "user-modify-playback-state",
-  "user-read-currently-playing",
-  "user-read-playback-state",
-  "user-read-playback-position",
-  "user-read-private",
-  "user-read-recently-played",
-];
+const PROFILE_ENV_VAR: &str = "SPOTIFY_TUI_PROFILE";
 
 /// get token automatically with local webserver
-pub async fn get_token_auto(spotify_oauth: &mut SpotifyOAuth, port: u16) -> Option<TokenInfo> {
-  match spotify_oauth.get_cached_token().await {
+pub async fn get_token_auto(
+  spotify_oauth: &mut SpotifyOAuth,
+  port: u16,
+  no_browser: bool,
+  keyring: Option<&Keyring>,
+) -> Result<Option<TokenInfo>> {
+  let token_path = spotify_oauth.cache_path.clone();
+  let cached = match keyring {
+    Some(keyring) => keyring::cached_token(keyring, spotify_oauth, &token_path).await?,
+    None => spotify_oauth.get_cached_token().await,
+  };
+  let token_info = match cached {
     Some(token_info) => Some(token_info),
+    None if no_browser => {
+      // The login can happen on any machine, only the url of the redirect is needed
+      let auth_url = spotify_oauth.get_authorize_url(Some(&generate_random_string(16)), None);
+      println!(
+        "Open this URL in a browser on any machine and log in:\n\n{}\n",
+        auth_url
+      );
+      println!(
+        "The browser is then redirected to localhost, which fails to load on another machine."
+      );
+      read_redirect_url(spotify_oauth).await
+    }
     None => match redirect_uri_web_server(spotify_oauth, port) {
       Ok(mut url) => process_token(spotify_oauth, &mut url).await,
       Err(()) => {
         println!("Starting webserver failed. Continuing with manual authentication");
         request_token(spotify_oauth);
-        println!("Enter the URL you were redirected to: ");
-        let mut input = String::new();
-        match io::stdin().read_line(&mut input) {
-          Ok(_) => process_token(spotify_oauth, &mut input).await,
-          Err(_) => None,
-        }
+        read_redirect_url(spotify_oauth).await
       }
     },
+  };
+  // A new login was saved to the token file
+  if let Some(keyring) = keyring {
+    keyring.take_token_file(&token_path)?;
   }
+  Ok(token_info)
 }
 
-fn close_application() -> Result<()> {
-  disable_raw_mode()?;
-  let mut stdout = io::stdout();
-  execute!(stdout, LeaveAlternateScreen, DisableMouseCapture)?;
-  Ok(())
-}
-
-fn panic_hook(info: &PanicInfo<'_>) {
-  if cfg!(debug_assertions) {
-    let location = info.location().unwrap();
-
-    let msg = match info.payload().downcast_ref::<&'static str>() {
-      Some(s) => *s,
-      None => match info.payload().downcast_ref::<String>() {
-        Some(s) => &s[..],
-        None => "Box<Any>",
-      },
-    };
-
-    let stacktrace: String = format!("{:?}", Backtrace::new()).replace('\n', "\n\r");
-
-    disable_raw_mode().unwrap();
-    execute!(
-      io::stdout(),
-      LeaveAlternateScreen,
-      Print(format!(
-        "thread '<unnamed>' panicked at '{}', {}\n\r{}",
-        msg, location, stacktrace
-      )),
-      DisableMouseCapture
-    )
-    .unwrap();
+async fn read_redirect_url(spotify_oauth: &mut SpotifyOAuth) -> Option<TokenInfo> {
+  println!("Enter the URL you were redirected to: ");
+  let mut input = String::new();
+  match io::stdin().read_line(&mut input) {
+    Ok(_) => process_token(spotify_oauth, &mut input).await,
+    Err(_) => None,
   }
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
-  panic::set_hook(Box::new(|info| {
-    panic_hook(info);
-  }));
+  panic::set_hook(Box::new(crash::panic_hook));
 
   let mut clap_app = ClapApp::new(env!("CARGO_PKG_NAME"))
     .version(env!("CARGO_PKG_VERSION"))
@@ -154,6 +155,41 @@ of the app. Beware that this comes at a CPU cost!",
         .help("Specify configuration file path.")
         .takes_value(true),
     )
+    .arg(
+      Arg::with_name("no-browser")
+        .long("no-browser")
+        .help("Logs in without opening a browser, e.g. over SSH")
+        .long_help(
+          "Prints the login URL instead of opening a browser and waiting for the redirect \
+on localhost. Open it on any machine, log in and paste the URL the browser was redirected to \
+(the page itself won't load). Only needed if there's no cached login yet.",
+        ),
+    )
+    .arg(
+      Arg::with_name("offline")
+        .long("offline")
+        .help("Browses the library cached by earlier runs without reaching spotify")
+        .long_help(
+          "Starts the TUI with the playlists, liked songs, albums and artists cached by earlier \
+runs (see `library_cache_ttl_seconds`), however old they are. Nothing is sent to spotify, so \
+playback can't be controlled. Without this flag the TUI switches to the cache on its own when \
+spotify can't be reached, and back once it answers again.",
+        ),
+    )
+    .arg(
+      Arg::with_name("profile")
+        .long("profile")
+        .global(true)
+        .takes_value(true)
+        .value_name("NAME")
+        .help("Uses the spotify account of profile NAME")
+        .long_help(
+          "Every profile has its own login, client id and secret, last used device and cache, \
+in ~/.config/spotify-tui/profiles/NAME (the `default` profile is the one in \
+~/.config/spotify-tui). The first run with a new profile sets it up and logs in. Defaults to \
+$SPOTIFY_TUI_PROFILE. The TUI can switch between profiles that were set up with `U`.",
+        ),
+    )
     .arg(
       Arg::with_name("completions")
         .long("completions")
@@ -162,11 +198,72 @@ of the app. Beware that this comes at a CPU cost!",
         .possible_values(&["bash", "zsh", "fish", "power-shell", "elvish"])
         .value_name("SHELL"),
     )
+    .arg(
+      Arg::with_name("dry-run")
+        .long("dry-run")
+        .global(true)
+        .help("Prints what the command would change instead of doing it")
+        .long_help(
+          "Prints the actions a command would take instead of sending them to spotify. \
+This affects everything that changes the playback or your library: `playback` (toggle, \
+next, previous, seek, volume, shuffle, repeat, like, dislike, transfer), `play` (including \
+`--queue`), `queue` and `playlist`, and the selected device isn't saved. Read-only commands like \
+`list`, `search` and `analyze` are run as usual.",
+        ),
+    )
+    .arg(
+      Arg::with_name("json")
+        .long("json")
+        .global(true)
+        .help("Prints the output of `list`, `search`, `playback` and `play` as JSON")
+        .long_help(
+          "Prints one JSON object per item (one per line for `list` and `search`) with every \
+field spt knows about it: uri, track, album, artist, device, volume, progress_ms, duration_ms, \
+playing, shuffle, repeat, liked and so on, depending on the item. Numbers and booleans aren't \
+formatted and `--format` is ignored. Overrides `--output`.",
+        ),
+    )
+    .arg(
+      Arg::with_name("verbose")
+        .long("verbose")
+        .global(true)
+        .help("Reports retries and other details on stderr"),
+    )
+    .arg(
+      Arg::with_name("log-file")
+        .long("log-file")
+        .takes_value(true)
+        .value_name("FILE")
+        .global(true)
+        .help("Appends a log of the requests and errors to FILE")
+        .long_help(
+          "Appends a log to FILE: every request and how it went, errors, and with `-v` the \
+changes of the UI and the playback. Useful to attach to a bug report.",
+        ),
+    )
+    .arg(
+      Arg::with_name("log-level")
+        .short("v")
+        .multiple(true)
+        .help("Logs more: -v every request and UI change, -vv what they contained")
+        .long_help(
+          "Logs more, to `--log-file` or else stderr (the TUI needs `--log-file`): -v every \
+request and change of the UI and the playback, -vv the requests with what they contained and \
+every key. Goes before the subcommand, `spt pb -v` sets the volume.",
+        ),
+    )
     // Control spotify from the command line
     .subcommand(cli::playback_subcommand())
     .subcommand(cli::play_subcommand())
     .subcommand(cli::list_subcommand())
-    .subcommand(cli::search_subcommand());
+    .subcommand(cli::search_subcommand())
+    .subcommand(cli::analyze_subcommand())
+    .subcommand(cli::queue_subcommand())
+    .subcommand(cli::playlist_subcommand())
+    .subcommand(cli::lyrics_subcommand())
+    .subcommand(cli::top_subcommand())
+    .subcommand(cli::recommend_subcommand())
+    .subcommand(cli::daemon_subcommand());
 
   let matches = clap_app.clone().get_matches();
 
@@ -184,6 +281,12 @@ of the app. Beware that this comes at a CPU cost!",
     return Ok(());
   }
 
+  logging::init(
+    matches.value_of("log-file").map(Path::new),
+    matches.occurrences_of("log-level"),
+    matches.subcommand_name().is_none(),
+  )?;
+
   let mut user_config = UserConfig::new();
   if let Some(config_file_path) = matches.value_of("config") {
     let config_file_path = PathBuf::from(config_file_path);
@@ -203,22 +306,51 @@ of the app. Beware that this comes at a CPU cost!",
     }
   }
 
-  let mut client_config = ClientConfig::new();
+  proxy::configure(user_config.behavior.proxy.as_deref())?;
+
+  let profile = match matches
+    .value_of("profile")
+    .map(str::to_string)
+    .or_else(|| std::env::var(PROFILE_ENV_VAR).ok())
+  {
+    Some(name) => config::parse_profile(&name)?,
+    None => None,
+  };
+  let mut client_config = ClientConfig::for_profile(profile.clone());
+  client_config.use_keyring = user_config.behavior.use_keyring;
   client_config.load_config()?;
 
   let config_paths = client_config.get_or_build_paths()?;
 
   // Start authorization with spotify
-  let mut oauth = SpotifyOAuth::default()
-    .client_id(&client_config.client_id)
-    .client_secret(&client_config.client_secret)
-    .redirect_uri(&client_config.get_redirect_uri())
-    .cache_path(config_paths.token_cache_path)
-    .scope(&SCOPES.join(" "))
-    .build();
+  let mut oauth = client_config.oauth(&config_paths);
+
+  let offline = matches.is_present("offline");
+  if offline && matches.subcommand_name().is_some() {
+    return Err(anyhow!("`--offline` only works for the TUI"));
+  }
 
   let config_port = client_config.get_port();
-  match get_token_auto(&mut oauth, config_port).await {
+  let keyring = Some(Keyring::new(profile.as_deref())).filter(|_| client_config.use_keyring);
+  let token_info = if offline {
+    // Logging in or refreshing the token needs spotify, the token is never used
+    let saved = match &keyring {
+      Some(keyring) => keyring.load_token().ok().flatten(),
+      None => fs::read_to_string(&config_paths.token_cache_path)
+        .ok()
+        .and_then(|token| serde_json::from_str(&token).ok()),
+    };
+    Some(saved.unwrap_or_else(TokenInfo::default))
+  } else {
+    get_token_auto(
+      &mut oauth,
+      config_port,
+      matches.is_present("no-browser"),
+      keyring.as_ref(),
+    )
+    .await?
+  };
+  match token_info {
     Some(token_info) => {
       let (sync_io_tx, sync_io_rx) = std::sync::mpsc::channel::<IoEvent>();
 
@@ -230,21 +362,63 @@ of the app. Beware that this comes at a CPU cost!",
         user_config.clone(),
         token_expiry,
       )));
-
+      app.lock().await.profile = profile;
+
+      if let Some(m) = matches.subcommand_matches("daemon") {
+        let socket = m
+          .value_of("socket")
+          .map(PathBuf::from)
+          .unwrap_or(config_paths.daemon_socket_path);
+        let daemon = cli::Daemon {
+          clap_app,
+          oauth,
+          spotify,
+          token_expiry,
+          client_config,
+          user_config,
+          app: &app,
+        };
+        daemon.run(&socket).await?;
       // Work with the cli (not really async)
-      if let Some(cmd) = matches.subcommand_name() {
+      } else if let Some(cmd) = matches.subcommand_name() {
         // Save, because we checked if the subcommand is present at runtime
         let m = matches.subcommand_matches(cmd).unwrap();
         let network = Network::new(oauth, spotify, client_config, &app);
-        println!(
-          "{}",
-          cli::handle_matches(m, cmd.to_string(), network, user_config).await?
-        );
+        let output = cli::handle_matches(m, cmd.to_string(), network, user_config).await;
+        if let Err(e) = &output {
+          error!(command = cmd, error = %e, "command failed");
+        }
+        println!("{}", output?);
       // Launch the UI (async)
       } else {
         let cloned_app = Arc::clone(&app);
+        let cache = ResponseCache::new(
+          config_paths.cache_dir.clone(),
+          user_config.behavior.library_cache_ttl_seconds,
+          user_config.behavior.catalog_cache_ttl_seconds,
+        );
+        app.lock().await.offline = offline;
+        // Stopped again when the TUI exits and `_spotifyd` is dropped
+        let _spotifyd = match &user_config.spotifyd.path {
+          Some(path) if !offline => {
+            match spotifyd::Supervisor::start(&user_config.spotifyd, path) {
+              Ok(supervisor) => {
+                app.lock().await.device_claim = Some(spotifyd::DeviceClaim::new());
+                Some(supervisor)
+              }
+              Err(e) => {
+                let error = anyhow!("Couldn't start spotifyd ({}): {}", path, e);
+                app.lock().await.handle_error(error);
+                None
+              }
+            }
+          }
+          _ => None,
+        };
         std::thread::spawn(move || {
           let mut network = Network::new(oauth, spotify, client_config, &app);
+          network.cache = Some(cache);
+          network.forced_offline = offline;
           start_tokio(sync_io_rx, &mut network);
         });
         // The UI must run in the "main" thread
@@ -257,6 +431,26 @@ of the app. Beware that this comes at a CPU cost!",
   Ok(())
 }
 
+// Returns false if the app should exit
+fn handle_keys(keys: Vec<Key>, app: &mut App) -> bool {
+  for key in keys {
+    if key == app.user_config.keys.back {
+      // Go back through navigation stack when not in search input mode and exit the app if there are no more places to back to
+      let pop_result = match app.pop_navigation_stack() {
+        Some(ref x) if x.id == RouteId::Search => app.pop_navigation_stack(),
+        Some(x) => Some(x),
+        None => None,
+      };
+      if pop_result.is_none() {
+        return false;
+      }
+    } else {
+      handlers::handle_app(key, app);
+    }
+  }
+  true
+}
+
 #[tokio::main]
 async fn start_tokio<'a>(io_rx: std::sync::mpsc::Receiver<IoEvent>, network: &mut Network) {
   while let Ok(io_event) = io_rx.recv() {
@@ -264,10 +458,14 @@ async fn start_tokio<'a>(io_rx: std::sync::mpsc::Receiver<IoEvent>, network: &mu
   }
 }
 
-async fn start_ui(user_config: UserConfig, app: &Arc<Mutex<App>>) -> Result<()> {
+async fn start_ui(user_config: UserConfig, shared_app: &Arc<Mutex<App>>) -> Result<()> {
   // Terminal initialization
   let mut stdout = stdout();
-  execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
+  execute!(stdout, EnterAlternateScreen)?;
+  let _restore = crash::TerminalGuard;
+  if user_config.behavior.enable_mouse {
+    execute!(stdout, EnableMouseCapture)?;
+  }
   enable_raw_mode()?;
 
   let mut backend = CrosstermBackend::new(stdout);
@@ -284,9 +482,11 @@ async fn start_ui(user_config: UserConfig, app: &Arc<Mutex<App>>) -> Result<()>
   // play music on, if not send them to the device selection view
 
   let mut is_first_render = true;
+  let mut redraw = true;
+  let mut last_draw = Instant::now();
 
   loop {
-    let mut app = app.lock().await;
+    let mut app = shared_app.lock().await;
     // Get the size of the screen on each loop to account for resize event
     if let Ok(size) = terminal.backend().size() {
       // Reset the help menu is the terminal was resized
@@ -296,6 +496,7 @@ async fn start_ui(user_config: UserConfig, app: &Arc<Mutex<App>>) -> Result<()>
         app.help_menu_page = 0;
 
         app.size = size;
+        redraw = true;
 
         // Based on the size of the terminal, adjust the search limit.
         let potential_limit = max((app.size.height as i32) - 13, 0) as u32;
@@ -318,52 +519,73 @@ async fn start_ui(user_config: UserConfig, app: &Arc<Mutex<App>>) -> Result<()>
       }
     };
 
-    let current_route = app.get_current_route();
-    terminal.draw(|mut f| match current_route.active_block {
-      ActiveBlock::HelpMenu => {
-        ui::draw_help_menu(&mut f, &app);
-      }
-      ActiveBlock::Error => {
-        ui::draw_error_screen(&mut f, &app);
-      }
-      ActiveBlock::SelectDevice => {
-        ui::draw_device_list(&mut f, &app);
-      }
-      ActiveBlock::Analysis => {
-        ui::audio_analysis::draw(&mut f, &app);
-      }
-      ActiveBlock::BasicView => {
-        ui::draw_basic_view(&mut f, &app);
-      }
-      _ => {
-        ui::draw_main_layout(&mut f, &app);
+    // Only changes are drawn, idle ticks don't cost a frame
+    if redraw || std::mem::take(&mut app.needs_redraw) {
+      let current_route = app.get_current_route();
+      app.click_areas.borrow_mut().clear();
+      terminal.draw(|mut f| {
+        match current_route.active_block {
+          ActiveBlock::HelpMenu => {
+            ui::draw_help_menu(&mut f, &app);
+          }
+          ActiveBlock::Error => {
+            ui::draw_error_screen(&mut f, &app);
+          }
+          ActiveBlock::SelectDevice => {
+            ui::draw_device_list(&mut f, &app);
+          }
+          ActiveBlock::Analysis => {
+            ui::audio_analysis::draw(&mut f, &app);
+          }
+          ActiveBlock::Lyrics => {
+            ui::lyrics::draw(f, &app);
+          }
+          ActiveBlock::BasicView => {
+            ui::draw_basic_view(&mut f, &app);
+          }
+          _ => {
+            ui::draw_main_layout(&mut f, &app);
+          }
+        }
+        // The palette and the finder can be opened on top of every view
+        ui::command_palette::draw(f, &app);
+        ui::track_features::draw(f, &app);
+        ui::draw_toast(f, &app);
+      })?;
+
+      if current_route.active_block == ActiveBlock::Input {
+        terminal.show_cursor()?;
+      } else {
+        terminal.hide_cursor()?;
       }
-    })?;
 
-    if current_route.active_block == ActiveBlock::Input {
-      terminal.show_cursor()?;
-    } else {
-      terminal.hide_cursor()?;
+      let cursor_offset = if app.size.height > ui::util::SMALL_TERMINAL_HEIGHT {
+        2
+      } else {
+        1
+      };
+
+      // Put the cursor back inside the input box
+      terminal.backend_mut().execute(MoveTo(
+        cursor_offset + app.input_cursor_position,
+        cursor_offset,
+      ))?;
+      last_draw = Instant::now();
     }
 
-    let cursor_offset = if app.size.height > ui::util::SMALL_TERMINAL_HEIGHT {
-      2
-    } else {
-      1
-    };
-
-    // Put the cursor back inside the input box
-    terminal.backend_mut().execute(MoveTo(
-      cursor_offset + app.input_cursor_position,
-      cursor_offset,
-    ))?;
-
     // Handle authentication refresh
     if SystemTime::now() > app.spotify_token_expiry {
       app.dispatch(IoEvent::RefreshAuthentication);
     }
 
-    match events.next()? {
+    // Waiting for the next event can take a whole tick, so don't hold the app
+    // in the meantime, otherwise every update from the network thread stalls
+    drop(app);
+    let event = events.next()?;
+    let mut app = shared_app.lock().await;
+
+    redraw = !matches!(event, event::Event::Tick);
+    match event {
       event::Event::Input(key) => {
         if key == Key::Ctrl('c') {
           break;
@@ -373,27 +595,39 @@ async fn start_ui(user_config: UserConfig, app: &Arc<Mutex<App>>) -> Result<()>
 
         // To avoid swallowing the global key presses `q` and `-` make a special
         // case for the input handler
-        if current_active_block == ActiveBlock::Input {
+        if app.command_palette.is_some() {
+          handlers::command_palette_handler(key, &mut app);
+        } else if app.library_finder.is_some() {
+          handlers::library_finder_handler(key, &mut app);
+        } else if app.playlist_picker.is_some() {
+          handlers::playlist_picker_handler(key, &mut app);
+        } else if app.sort_menu.is_some() {
+          handlers::sort_menu_handler(key, &mut app);
+        } else if app.profile_menu.is_some() {
+          handlers::profile_menu_handler(key, &mut app);
+        } else if app.track_features.is_some() {
+          // Any key closes the popup
+          app.track_features = None;
+        } else if app.track_table.filtering {
+          handlers::track_filter_handler(key, &mut app);
+        } else if current_active_block == ActiveBlock::Input {
           handlers::input_handler(key, &mut app);
-        } else if key == app.user_config.keys.back {
-          if app.get_current_route().active_block != ActiveBlock::Input {
-            // Go back through navigation stack when not in search input mode and exit the app if there are no more places to back to
-
-            let pop_result = match app.pop_navigation_stack() {
-              Some(ref x) if x.id == RouteId::Search => app.pop_navigation_stack(),
-              Some(x) => Some(x),
-              None => None,
-            };
-            if pop_result.is_none() {
-              break; // Exit application
-            }
-          }
         } else {
-          handlers::handle_app(key, &mut app);
+          let keys = app.resolve_key(key);
+          if !handle_keys(keys, &mut app) {
+            break; // Exit application
+          }
         }
       }
+      event::Event::Mouse(mouse) => handlers::mouse_handler(mouse, &mut app),
+      event::Event::Resize => {}
       event::Event::Tick => {
         app.update_on_tick();
+        let keys = app.take_expired_keys();
+        redraw = !keys.is_empty() || app.progress_redraw_due(last_draw.elapsed());
+        if !handle_keys(keys, &mut app) {
+          break;
+        }
       }
     }
 
@@ -409,8 +643,5 @@ async fn start_ui(user_config: UserConfig, app: &Arc<Mutex<App>>) -> Result<()>
     }
   }
 
-  terminal.show_cursor()?;
-  close_application()?;
-
   Ok(())
 }
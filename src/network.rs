@@ -3,12 +3,19 @@ use crate::app::{
   ScrollableResultPages, SelectedAlbum, SelectedFullAlbum, SelectedFullShow, SelectedShow,
   TrackTableContext,
 };
-use crate::config::ClientConfig;
+use crate::cache::{CacheKind, ResponseCache};
+use crate::config::{profile_name, ClientConfig};
+use crate::discovery::{self, ConnectDevice};
+use crate::keyring::{self, Keyring};
+use crate::lyrics::{LyricsProvider, LyricsQuery, LyricsStatus};
+use crate::spotifyd;
+use crate::undo::UndoAction;
 use anyhow::anyhow;
+use futures::stream::{self, StreamExt, TryStreamExt};
 use rspotify::{
   client::Spotify,
   model::{
-    album::SimplifiedAlbum,
+    album::{FullAlbum, SimplifiedAlbum},
     artist::FullArtist,
     offset::for_position,
     page::Page,
@@ -20,18 +27,297 @@ use rspotify::{
     PlayingItem,
   },
   oauth2::{SpotifyClientCredentials, SpotifyOAuth, TokenInfo},
-  senum::{AdditionalType, Country, RepeatState, SearchType},
+  senum::{AdditionalType, AlbumType, Country, RepeatState, SearchType, TimeRange},
   util::get_token,
 };
+use serde::{de::DeserializeOwned, Serialize};
 use serde_json::{map::Map, Value};
 use std::{
+  collections::HashMap,
+  fmt,
+  future::Future,
   sync::Arc,
   time::{Duration, Instant, SystemTime},
 };
 use tokio::sync::Mutex;
 use tokio::try_join;
+use tracing::{debug, info, warn};
+
+impl IoEvent {
+  // What the cache can answer, everything else waits for spotify
+  fn works_offline(&self) -> bool {
+    matches!(
+      self,
+      IoEvent::GetPlaylists
+        | IoEvent::GetPlaylistTracks(..)
+        | IoEvent::AppendPlaylistTracks(..)
+        | IoEvent::AppendSavedTracks(_)
+        | IoEvent::GetCurrentSavedTracks(_)
+        | IoEvent::GetCurrentUserSavedAlbums(_)
+        | IoEvent::GetFollowedArtists(_)
+        | IoEvent::GetAlbum(_)
+        | IoEvent::GetAlbumTracks(_)
+        | IoEvent::GetArtist(..)
+        | IoEvent::GetArtistAlbums(..)
+        | IoEvent::GetLyrics(..)
+        | IoEvent::GetAudioFeatures(_)
+        | IoEvent::GetTopTracks(_)
+        | IoEvent::GetTopArtists(_)
+        | IoEvent::SetTracksToTable(_)
+        | IoEvent::SetArtistsToTable(_)
+        | IoEvent::UpdateSearchLimits(..)
+    )
+  }
+}
+
+const RECENTLY_PLAYED_LIMIT: u32 = 50;
+// The most top items one request returns
+const TOP_ITEMS_LIMIT: u32 = 50;
+
+// How often spotify is asked for a woken up device and how long it waits between
+const WAKE_ATTEMPTS: u32 = 4;
+const WAKE_INTERVAL: Duration = Duration::from_secs(1);
+
+// How often an event (or a request) is repeated while spotify keeps rate
+// limiting it
+const RATE_LIMIT_RETRIES: u32 = 5;
+
+// Only the variant, the whole event (e.g. all the tracks to play) is logged
+// with `-vv`
+fn event_name(io_event: &IoEvent) -> &'static str {
+  match io_event {
+    IoEvent::GetCurrentPlayback => "GetCurrentPlayback",
+    IoEvent::RefreshAuthentication => "RefreshAuthentication",
+    IoEvent::GetPlaylists => "GetPlaylists",
+    IoEvent::GetDevices => "GetDevices",
+    IoEvent::GetSearchResults(..) => "GetSearchResults",
+    IoEvent::SetTracksToTable(..) => "SetTracksToTable",
+    IoEvent::GetMadeForYouPlaylistTracks(..) => "GetMadeForYouPlaylistTracks",
+    IoEvent::GetPlaylistTracks(..) => "GetPlaylistTracks",
+    IoEvent::AppendPlaylistTracks(..) => "AppendPlaylistTracks",
+    IoEvent::GetCurrentSavedTracks(..) => "GetCurrentSavedTracks",
+    IoEvent::AppendSavedTracks(..) => "AppendSavedTracks",
+    IoEvent::StartPlayback(..) => "StartPlayback",
+    IoEvent::StartPlaybackAt(..) => "StartPlaybackAt",
+    IoEvent::UpdateSearchLimits(..) => "UpdateSearchLimits",
+    IoEvent::Seek(..) => "Seek",
+    IoEvent::NextTrack => "NextTrack",
+    IoEvent::PreviousTrack => "PreviousTrack",
+    IoEvent::Shuffle(..) => "Shuffle",
+    IoEvent::Repeat(..) => "Repeat",
+    IoEvent::SetRepeat(..) => "SetRepeat",
+    IoEvent::PausePlayback => "PausePlayback",
+    IoEvent::ChangeVolume(..) => "ChangeVolume",
+    IoEvent::GetArtist(..) => "GetArtist",
+    IoEvent::GetArtistAlbums(..) => "GetArtistAlbums",
+    IoEvent::GetAlbumTracks(..) => "GetAlbumTracks",
+    IoEvent::GetRecommendationsForSeed(..) => "GetRecommendationsForSeed",
+    IoEvent::GetCurrentUserSavedAlbums(..) => "GetCurrentUserSavedAlbums",
+    IoEvent::CurrentUserSavedAlbumsContains(..) => "CurrentUserSavedAlbumsContains",
+    IoEvent::CurrentUserSavedAlbumDelete(..) => "CurrentUserSavedAlbumDelete",
+    IoEvent::CurrentUserSavedAlbumAdd(..) => "CurrentUserSavedAlbumAdd",
+    IoEvent::UserUnfollowArtists(..) => "UserUnfollowArtists",
+    IoEvent::UserFollowArtists(..) => "UserFollowArtists",
+    IoEvent::UserFollowPlaylist(..) => "UserFollowPlaylist",
+    IoEvent::UserUnfollowPlaylist(..) => "UserUnfollowPlaylist",
+    IoEvent::CreatePlaylistWithTracks(..) => "CreatePlaylistWithTracks",
+    IoEvent::AddTracksToPlaylist(..) => "AddTracksToPlaylist",
+    IoEvent::ReorderPlaylistTracks(..) => "ReorderPlaylistTracks",
+    IoEvent::CurrentUserSavedTracksAdd(..) => "CurrentUserSavedTracksAdd",
+    IoEvent::MadeForYouSearchAndAdd(..) => "MadeForYouSearchAndAdd",
+    IoEvent::GetAudioAnalysis(..) => "GetAudioAnalysis",
+    IoEvent::GetAudioFeatures(..) => "GetAudioFeatures",
+    IoEvent::GetLyrics(..) => "GetLyrics",
+    IoEvent::GetUser => "GetUser",
+    IoEvent::ToggleSaveTrack(..) => "ToggleSaveTrack",
+    IoEvent::ToggleSaveAlbum(..) => "ToggleSaveAlbum",
+    IoEvent::GetRecommendationsForTrackId(..) => "GetRecommendationsForTrackId",
+    IoEvent::GetRecentlyPlayed => "GetRecentlyPlayed",
+    IoEvent::GetTopTracks(..) => "GetTopTracks",
+    IoEvent::GetTopArtists(..) => "GetTopArtists",
+    IoEvent::GetFollowedArtists(..) => "GetFollowedArtists",
+    IoEvent::SetArtistsToTable(..) => "SetArtistsToTable",
+    IoEvent::UserArtistFollowCheck(..) => "UserArtistFollowCheck",
+    IoEvent::GetAlbum(..) => "GetAlbum",
+    IoEvent::TransferPlaybackToDevice(..) => "TransferPlaybackToDevice",
+    IoEvent::ClaimSpotifydDevice => "ClaimSpotifydDevice",
+    IoEvent::DiscoverDevices => "DiscoverDevices",
+    IoEvent::SwitchProfile(..) => "SwitchProfile",
+    IoEvent::WakeDevice(..) => "WakeDevice",
+    IoEvent::GetAlbumForTrack(..) => "GetAlbumForTrack",
+    IoEvent::CurrentUserSavedTracksContains(..) => "CurrentUserSavedTracksContains",
+    IoEvent::GetCurrentUserSavedShows(..) => "GetCurrentUserSavedShows",
+    IoEvent::CurrentUserSavedShowsContains(..) => "CurrentUserSavedShowsContains",
+    IoEvent::CurrentUserSavedShowDelete(..) => "CurrentUserSavedShowDelete",
+    IoEvent::CurrentUserSavedShowAdd(..) => "CurrentUserSavedShowAdd",
+    IoEvent::GetShowEpisodes(..) => "GetShowEpisodes",
+    IoEvent::GetShow(..) => "GetShow",
+    IoEvent::GetCurrentShowEpisodes(..) => "GetCurrentShowEpisodes",
+    IoEvent::AddItemToQueue(..) => "AddItemToQueue",
+  }
+}
+
+// The seconds spotify asks to wait in a rate limit error, `Some(None)` if it
+// didn't say
+fn rate_limit_delay(error: &str) -> Option<Option<u64>> {
+  if !error.contains("Exceeded API request limit") {
+    return None;
+  }
+  Some(
+    error
+      .split("please wait ")
+      .nth(1)
+      .and_then(|rest| rest.split(' ').next())
+      .and_then(|seconds| seconds.parse().ok()),
+  )
+}
+
+// The delay spotify asks for or a growing one, plus some jitter so queued
+// requests don't all come back at once
+fn rate_limit_backoff(delay: Option<u64>, retries: u32) -> Duration {
+  let seconds = delay.unwrap_or_else(|| 2u64.pow(retries));
+  Duration::from_secs(seconds) + Duration::from_millis(rand::random::<u64>() % 1000)
+}
+
+async fn wait_rate_limited(app: &Mutex<App>, delay: Duration) {
+  let toast = format!("Rate limited, retrying in {}s", delay.as_secs_f32().ceil());
+  set_toast(app, Some(toast)).await;
+  tokio::time::sleep(delay).await;
+  set_toast(app, None).await;
+}
+
+async fn set_toast(app: &Mutex<App>, toast: Option<String>) {
+  let mut app = app.lock().await;
+  app.toast = toast;
+  app.toast_expires = None;
+  app.needs_redraw = true;
+}
+
+// Repeats only `request` while spotify rate limits it, for events that send
+// several requests which can't all be sent again (like adding tracks)
+async fn retry_rate_limited<T, F, Fut, W, WFut>(mut request: F, mut wait: W) -> anyhow::Result<T>
+where
+  F: FnMut() -> Fut,
+  Fut: Future<Output = anyhow::Result<T>>,
+  W: FnMut(Duration) -> WFut,
+  WFut: Future<Output = ()>,
+{
+  let mut retries = 0;
+  loop {
+    match request().await {
+      Err(e) if retries < RATE_LIMIT_RETRIES => match rate_limit_delay(&e.to_string()) {
+        Some(delay) => {
+          warn!(retries, error = %e, "rate limited");
+          wait(rate_limit_backoff(delay, retries)).await;
+          retries += 1;
+        }
+        None => return Err(e),
+      },
+      result => return result,
+    }
+  }
+}
+
+// Adds the tracks one chunk after the other, a rate limited chunk is sent
+// again without the ones before it
+async fn add_in_chunks<F, Fut, W, WFut>(
+  track_uris: &[String],
+  mut add: F,
+  mut wait: W,
+) -> anyhow::Result<()>
+where
+  F: FnMut(Vec<String>) -> Fut,
+  Fut: Future<Output = anyhow::Result<()>>,
+  W: FnMut(Duration) -> WFut,
+  WFut: Future<Output = ()>,
+{
+  for chunk in track_uris.chunks(PLAYLIST_TRACKS_CHANGE_LIMIT) {
+    retry_rate_limited(|| add(chunk.to_vec()), &mut wait).await?;
+  }
+  Ok(())
+}
+
+// The most items one request returns
+const MAX_PAGE_LIMIT: u32 = 50;
+// The most ids (or uris) each endpoint accepts in one request
+pub const SAVED_TRACKS_CONTAINS_LIMIT: usize = 50;
+pub const SAVED_TRACKS_CHANGE_LIMIT: usize = 50;
+pub const PLAYLIST_TRACKS_CHANGE_LIMIT: usize = 100;
+pub const TRACKS_LIMIT: usize = 50;
+pub const AUDIO_FEATURES_LIMIT: usize = 100;
+pub const ALBUMS_LIMIT: usize = 20;
+// The search doesn't page past this many results
+const MAX_SEARCH_RESULTS: u32 = 1000;
+
+// Only the cli asks for more than one request returns, those limits are
+// fetched page by page and joined into one page
+async fn paged<T, E, F, Fut>(limit: u32, offset: u32, request: F) -> Result<Page<T>, E>
+where
+  F: Fn(u32, u32) -> Fut,
+  Fut: Future<Output = Result<Page<T>, E>>,
+{
+  let mut page = request(limit.min(MAX_PAGE_LIMIT), offset).await?;
+  while (page.items.len() as u32) < limit && page.next.is_some() {
+    let fetched = page.items.len() as u32;
+    let next = request((limit - fetched).min(MAX_PAGE_LIMIT), offset + fetched).await?;
+    if next.items.is_empty() {
+      break;
+    }
+    page.next = next.next;
+    page.items.extend(next.items);
+  }
+  Ok(page)
+}
+
+// Complete lists are fetched this many pages at a time
+const MAX_PAGES_IN_FLIGHT: usize = 8;
+
+// Every item of a list (e.g. to export a playlist), the first page tells how
+// many there are and the rest are requested together, in order
+pub async fn all_items<T, E, F, Fut>(page_size: u32, request: F) -> Result<Vec<T>, E>
+where
+  F: Fn(u32, u32) -> Fut,
+  Fut: Future<Output = Result<Page<T>, E>>,
+{
+  let first = request(page_size, 0).await?;
+  let mut items = first.items;
+  if first.next.is_none() || items.is_empty() {
+    return Ok(items);
+  }
+  // Steps as far as spotify went, it may hand out less than asked for
+  let offsets = (items.len() as u32..first.total).step_by(items.len());
+  let pages: Vec<Page<T>> = stream::iter(offsets)
+    .map(|offset| request(page_size, offset))
+    .buffered(MAX_PAGES_IN_FLIGHT)
+    .try_collect()
+    .await?;
+  for page in pages {
+    items.extend(page.items);
+  }
+  Ok(items)
+}
+
+fn album_track_ids(album: &FullAlbum) -> Vec<String> {
+  album
+    .tracks
+    .items
+    .iter()
+    .filter_map(|track| track.id.to_owned())
+    .collect()
+}
 
-#[derive(Debug)]
+// Some artists appear on thousands of compilations
+const MAX_ARTIST_ALBUMS: usize = 1000;
+
+// The name, albums, top tracks and related artists of an artist page
+type CachedArtist = (
+  String,
+  Page<SimplifiedAlbum>,
+  Vec<FullTrack>,
+  Vec<FullArtist>,
+);
+
+#[derive(Debug, Clone)]
 pub enum IoEvent {
   GetCurrentPlayback,
   RefreshAuthentication,
@@ -41,17 +327,28 @@ pub enum IoEvent {
   SetTracksToTable(Vec<FullTrack>),
   GetMadeForYouPlaylistTracks(String, u32),
   GetPlaylistTracks(String, u32),
+  // The page at the offset, added to the loaded tracks of the playlist
+  AppendPlaylistTracks(String, u32),
   GetCurrentSavedTracks(Option<u32>),
+  // The page at the offset, added to the loaded liked songs
+  AppendSavedTracks(u32),
   StartPlayback(Option<String>, Option<Vec<String>>, Option<usize>),
+  // Like `StartPlayback`, from the given position in milliseconds
+  StartPlaybackAt(Option<String>, Option<Vec<String>>, Option<usize>, u32),
   UpdateSearchLimits(u32, u32),
   Seek(u32),
   NextTrack,
   PreviousTrack,
   Shuffle(bool),
+  // Cycles from the given state to the next one
   Repeat(RepeatState),
+  // Sets the given state
+  SetRepeat(RepeatState),
   PausePlayback,
   ChangeVolume(u8),
   GetArtist(String, String, Option<Country>),
+  // The artist and the album group to show, `None` for every group
+  GetArtistAlbums(String, Option<AlbumType>, Option<Country>),
   GetAlbumTracks(Box<SimplifiedAlbum>),
   GetRecommendationsForSeed(
     Option<Vec<String>>,
@@ -67,17 +364,35 @@ pub enum IoEvent {
   UserFollowArtists(Vec<String>),
   UserFollowPlaylist(String, String, Option<bool>),
   UserUnfollowPlaylist(String, String),
+  // A new private playlist with the name and the track uris
+  CreatePlaylistWithTracks(String, Vec<String>),
+  AddTracksToPlaylist(String, Vec<String>),
+  // The playlist, the position of the moved track and where it's inserted
+  ReorderPlaylistTracks(String, u32, u32),
+  CurrentUserSavedTracksAdd(Vec<String>),
   MadeForYouSearchAndAdd(String, Option<Country>),
   GetAudioAnalysis(String),
+  GetAudioFeatures(String),
+  GetLyrics(LyricsProvider, LyricsQuery),
   GetUser,
   ToggleSaveTrack(String),
+  ToggleSaveAlbum(String),
   GetRecommendationsForTrackId(String, Option<Country>),
   GetRecentlyPlayed,
+  GetTopTracks(TimeRange),
+  GetTopArtists(TimeRange),
   GetFollowedArtists(Option<String>),
   SetArtistsToTable(Vec<FullArtist>),
   UserArtistFollowCheck(Vec<String>),
   GetAlbum(String),
   TransferPlaybackToDevice(String),
+  // Selects the spotifyd the TUI started once it's in the device list
+  ClaimSpotifydDevice,
+  DiscoverDevices,
+  // Logs in with the cached token of another profile, `None` is the default one
+  SwitchProfile(Option<String>),
+  // Wakes up a device found on the network and plays on it once spotify lists it
+  WakeDevice(ConnectDevice),
   GetAlbumForTrack(String),
   CurrentUserSavedTracksContains(Vec<String>),
   GetCurrentUserSavedShows(Option<u32>),
@@ -117,10 +432,19 @@ pub fn get_spotify(token_info: TokenInfo) -> (Spotify, SystemTime) {
 pub struct Network<'a> {
   oauth: SpotifyOAuth,
   pub spotify: Spotify,
-  large_search_limit: u32,
+  pub large_search_limit: u32,
   small_search_limit: u32,
   pub client_config: ClientConfig,
   pub app: &'a Arc<Mutex<App>>,
+  // Only the tui keeps responses around, the cli always asks the API
+  pub cache: Option<ResponseCache>,
+  // Set by `--offline`, otherwise polling the playback brings the app back
+  // online once spotify answers again
+  pub forced_offline: bool,
+  // The last rate limit error of the running event with the delay it asked for
+  rate_limited: Option<(Option<u64>, String)>,
+  // The snapshot after the last reorder of each playlist
+  playlist_snapshots: HashMap<String, String>,
 }
 
 impl<'a> Network<'a> {
@@ -137,11 +461,101 @@ impl<'a> Network<'a> {
       small_search_limit: 4,
       client_config,
       app,
+      cache: None,
+      forced_offline: false,
+      rate_limited: None,
+      playlist_snapshots: HashMap::new(),
     }
   }
 
-  #[allow(clippy::cognitive_complexity)]
+  // Serves `key` from the cache while it's fresh, or at all while offline. When
+  // the request fails but an older response is around, the app goes offline
+  async fn cached<T, E>(
+    &self,
+    kind: CacheKind,
+    key: &str,
+    request: impl Future<Output = Result<T, E>>,
+  ) -> anyhow::Result<T>
+  where
+    T: Serialize + DeserializeOwned,
+    E: fmt::Display + fmt::Debug + Send + Sync + 'static,
+  {
+    let cache = match &self.cache {
+      Some(cache) => cache,
+      None => return request.await.map_err(|e| anyhow!(e)),
+    };
+    if self.app.lock().await.offline {
+      return cache
+        .get_any(key)
+        .ok_or_else(|| anyhow!("this wasn't loaded before going offline"));
+    }
+    if let Some(value) = cache.get(kind, key) {
+      return Ok(value);
+    }
+    match request.await {
+      Ok(value) => {
+        cache.put(kind, key, &value);
+        Ok(value)
+      }
+      Err(e) => match cache.get_any(key) {
+        Some(value) => {
+          self.app.lock().await.offline = true;
+          Ok(value)
+        }
+        None => Err(anyhow!(e)),
+      },
+    }
+  }
+
+  fn cache_invalidate(&self, prefix: &str) {
+    if let Some(cache) = &self.cache {
+      cache.invalidate(prefix);
+    }
+  }
+
+  // Rate limited events are repeated after the delay spotify asks for
   pub async fn handle_network_event(&mut self, io_event: IoEvent) {
+    let request = event_name(&io_event);
+    info!(request = %request, "request");
+    debug!(event = ?io_event);
+    let started = Instant::now();
+    let mut retries = 0;
+    loop {
+      self.handle_event(io_event.clone()).await;
+      let (delay, error) = match self.rate_limited.take() {
+        Some(rate_limited) => rate_limited,
+        None => {
+          let elapsed_ms = started.elapsed().as_millis() as u64;
+          info!(request = %request, elapsed_ms, "request done");
+          self.app.lock().await.needs_redraw = true;
+          return;
+        }
+      };
+      warn!(request = %request, retries, error = %error, "rate limited");
+      if retries == RATE_LIMIT_RETRIES {
+        let mut app = self.app.lock().await;
+        app.handle_error(anyhow!(error));
+        app.needs_redraw = true;
+        return;
+      }
+      let delay = rate_limit_backoff(delay, retries);
+      retries += 1;
+      wait_rate_limited(self.app, delay).await;
+    }
+  }
+
+  #[allow(clippy::cognitive_complexity)]
+  async fn handle_event(&mut self, io_event: IoEvent) {
+    {
+      let mut app = self.app.lock().await;
+      let probe = !self.forced_offline && matches!(io_event, IoEvent::GetCurrentPlayback);
+      if app.offline && !io_event.works_offline() && !probe {
+        app.is_loading = false;
+        app.is_fetching_current_playback = false;
+        return;
+      }
+    }
+
     match io_event {
       IoEvent::RefreshAuthentication => {
         self.refresh_authentication().await;
@@ -172,11 +586,22 @@ impl<'a> Network<'a> {
       IoEvent::GetPlaylistTracks(playlist_id, playlist_offset) => {
         self.get_playlist_tracks(playlist_id, playlist_offset).await;
       }
+      IoEvent::AppendPlaylistTracks(playlist_id, offset) => {
+        self.append_playlist_tracks(playlist_id, offset).await;
+      }
       IoEvent::GetCurrentSavedTracks(offset) => {
         self.get_current_user_saved_tracks(offset).await;
       }
+      IoEvent::AppendSavedTracks(offset) => {
+        self.append_saved_tracks(offset).await;
+      }
       IoEvent::StartPlayback(context_uri, uris, offset) => {
-        self.start_playback(context_uri, uris, offset).await;
+        self.start_playback(context_uri, uris, offset, None).await;
+      }
+      IoEvent::StartPlaybackAt(context_uri, uris, offset, position_ms) => {
+        self
+          .start_playback(context_uri, uris, offset, Some(position_ms))
+          .await;
       }
       IoEvent::UpdateSearchLimits(large_search_limit, small_search_limit) => {
         self.large_search_limit = large_search_limit;
@@ -194,6 +619,9 @@ impl<'a> Network<'a> {
       IoEvent::Repeat(repeat_state) => {
         self.repeat(repeat_state).await;
       }
+      IoEvent::SetRepeat(repeat_state) => {
+        self.set_repeat(repeat_state).await;
+      }
       IoEvent::PausePlayback => {
         self.pause_playback().await;
       }
@@ -203,6 +631,9 @@ impl<'a> Network<'a> {
       IoEvent::GetArtist(artist_id, input_artist_name, country) => {
         self.get_artist(artist_id, input_artist_name, country).await;
       }
+      IoEvent::GetArtistAlbums(artist_id, group, country) => {
+        self.get_artist_albums(artist_id, group, country).await;
+      }
       IoEvent::GetAlbumTracks(album) => {
         self.get_album_tracks(album).await;
       }
@@ -237,15 +668,38 @@ impl<'a> Network<'a> {
       IoEvent::UserUnfollowPlaylist(user_id, playlist_id) => {
         self.user_unfollow_playlist(user_id, playlist_id).await;
       }
+      IoEvent::ReorderPlaylistTracks(playlist_id, range_start, insert_before) => {
+        self
+          .reorder_playlist_tracks(playlist_id, range_start, insert_before)
+          .await;
+      }
+      IoEvent::AddTracksToPlaylist(playlist_id, track_uris) => {
+        self.add_tracks_to_playlist(playlist_id, track_uris).await;
+      }
+      IoEvent::CurrentUserSavedTracksAdd(track_ids) => {
+        self.current_user_saved_tracks_add(track_ids).await;
+      }
+      IoEvent::CreatePlaylistWithTracks(name, track_uris) => {
+        self.create_playlist_with_tracks(name, track_uris).await;
+      }
       IoEvent::MadeForYouSearchAndAdd(search_term, country) => {
         self.made_for_you_search_and_add(search_term, country).await;
       }
       IoEvent::GetAudioAnalysis(uri) => {
         self.get_audio_analysis(uri).await;
       }
+      IoEvent::GetAudioFeatures(track_id) => {
+        self.get_audio_features(track_id).await;
+      }
+      IoEvent::GetLyrics(provider, query) => {
+        self.get_lyrics(provider, query);
+      }
       IoEvent::ToggleSaveTrack(track_id) => {
         self.toggle_save_track(track_id).await;
       }
+      IoEvent::ToggleSaveAlbum(album_id) => {
+        self.toggle_save_album(album_id).await;
+      }
       IoEvent::GetRecommendationsForTrackId(track_id, country) => {
         self
           .get_recommendations_for_track_id(track_id, country)
@@ -254,6 +708,12 @@ impl<'a> Network<'a> {
       IoEvent::GetRecentlyPlayed => {
         self.get_recently_played().await;
       }
+      IoEvent::GetTopTracks(time_range) => {
+        self.get_top_tracks(time_range).await;
+      }
+      IoEvent::GetTopArtists(time_range) => {
+        self.get_top_artists(time_range).await;
+      }
       IoEvent::GetFollowedArtists(after) => {
         self.get_followed_artists(after).await;
       }
@@ -269,6 +729,18 @@ impl<'a> Network<'a> {
       IoEvent::TransferPlaybackToDevice(device_id) => {
         self.transfert_playback_to_device(device_id).await;
       }
+      IoEvent::ClaimSpotifydDevice => {
+        self.claim_spotifyd_device().await;
+      }
+      IoEvent::DiscoverDevices => {
+        self.discover_devices();
+      }
+      IoEvent::SwitchProfile(profile) => {
+        self.switch_profile(profile).await;
+      }
+      IoEvent::WakeDevice(device) => {
+        self.wake_device(device).await;
+      }
       IoEvent::GetAlbumForTrack(track_id) => {
         self.get_album_for_track(track_id).await;
       }
@@ -309,6 +781,12 @@ impl<'a> Network<'a> {
   }
 
   async fn handle_error(&mut self, e: anyhow::Error) {
+    let error = e.to_string();
+    debug!(error = %error, "request failed");
+    if let Some(delay) = rate_limit_delay(&error) {
+      self.rate_limited = Some((delay, error));
+      return;
+    }
     let mut app = self.app.lock().await;
     app.handle_error(e);
   }
@@ -349,6 +827,7 @@ impl<'a> Network<'a> {
     match context {
       Ok(Some(c)) => {
         let mut app = self.app.lock().await;
+        app.offline = false;
         app.current_playback_context = Some(c.clone());
         app.instant_since_last_current_playback_poll = Instant::now();
 
@@ -356,6 +835,8 @@ impl<'a> Network<'a> {
           match item {
             PlayingItem::Track(track) => {
               if let Some(track_id) = track.id {
+                // It could have been liked on another device in the meantime
+                app.liked_song_ids_checked.remove(&track_id);
                 app.dispatch(IoEvent::CurrentUserSavedTracksContains(vec![track_id]));
               };
             }
@@ -365,10 +846,14 @@ impl<'a> Network<'a> {
       }
       Ok(None) => {
         let mut app = self.app.lock().await;
+        app.offline = false;
         app.instant_since_last_current_playback_poll = Instant::now();
       }
       Err(e) => {
-        self.handle_error(anyhow!(e)).await;
+        // Failing while offline only means spotify still can't be reached
+        if !self.app.lock().await.offline {
+          self.handle_error(anyhow!(e)).await;
+        }
       }
     }
 
@@ -378,65 +863,146 @@ impl<'a> Network<'a> {
   }
 
   async fn current_user_saved_tracks_contains(&mut self, ids: Vec<String>) {
-    match self.spotify.current_user_saved_tracks_contains(&ids).await {
-      Ok(is_saved_vec) => {
-        let mut app = self.app.lock().await;
-        for (i, id) in ids.iter().enumerate() {
-          if let Some(is_liked) = is_saved_vec.get(i) {
-            if *is_liked {
+    let mut ids = {
+      let app = self.app.lock().await;
+      ids
+        .into_iter()
+        .filter(|id| !app.liked_song_ids_checked.contains(id))
+        .collect::<Vec<String>>()
+    };
+    ids.sort();
+    ids.dedup();
+    for chunk in ids.chunks(SAVED_TRACKS_CONTAINS_LIMIT) {
+      match self.spotify.current_user_saved_tracks_contains(chunk).await {
+        Ok(is_saved_vec) => {
+          let mut app = self.app.lock().await;
+          for (id, is_liked) in chunk.iter().zip(is_saved_vec) {
+            if is_liked {
               app.liked_song_ids_set.insert(id.to_string());
             } else {
-              // The song is not liked, so check if it should be removed
-              if app.liked_song_ids_set.contains(id) {
-                app.liked_song_ids_set.remove(id);
-              }
+              app.liked_song_ids_set.remove(id);
             }
-          };
+            app.liked_song_ids_checked.insert(id.to_string());
+          }
+        }
+        Err(e) => {
+          self.handle_error(anyhow!(e)).await;
+          break;
         }
-      }
-      Err(e) => {
-        self.handle_error(anyhow!(e)).await;
       }
     }
   }
 
   async fn get_playlist_tracks(&mut self, playlist_id: String, playlist_offset: u32) {
-    if let Ok(playlist_tracks) = self
-      .spotify
-      .user_playlist_tracks(
-        "spotify",
-        &playlist_id,
-        None,
-        Some(self.large_search_limit),
-        Some(playlist_offset),
-        None,
-      )
-      .await
-    {
-      self.set_playlist_tracks_to_table(&playlist_tracks).await;
+    // Freshly loaded tracks are the current version of the playlist
+    self.playlist_snapshots.remove(&playlist_id);
+    let key = format!("playlist_tracks-{}-{}", playlist_id, playlist_offset);
+    let playlist_tracks = self.spotify.user_playlist_tracks(
+      "spotify",
+      &playlist_id,
+      None,
+      Some(self.large_search_limit),
+      Some(playlist_offset),
+      None,
+    );
+    match self.cached(CacheKind::Library, &key, playlist_tracks).await {
+      Ok(playlist_tracks) => {
+        self.set_playlist_tracks_to_table(&playlist_tracks).await;
 
-      let mut app = self.app.lock().await;
-      app.playlist_tracks = Some(playlist_tracks);
-      app.push_navigation_stack(RouteId::TrackTable, ActiveBlock::TrackTable);
+        let mut app = self.app.lock().await;
+        app.playlist_tracks = Some(playlist_tracks);
+        app.playlist_tracks_id = Some(playlist_id);
+        app.push_navigation_stack(RouteId::TrackTable, ActiveBlock::TrackTable);
+      }
+      Err(e) => {
+        self.handle_error(e).await;
+      }
+    };
+  }
+
+  async fn append_playlist_tracks(&mut self, playlist_id: String, offset: u32) {
+    let key = format!("playlist_tracks-{}-{}", playlist_id, offset);
+    let playlist_tracks = self.spotify.user_playlist_tracks(
+      "spotify",
+      &playlist_id,
+      None,
+      Some(self.large_search_limit),
+      Some(offset),
+      None,
+    );
+    let page = match self.cached(CacheKind::Library, &key, playlist_tracks).await {
+      Ok(page) => page,
+      Err(e) => {
+        self.handle_error(e).await;
+        return;
+      }
+    };
+    let mut app = self.app.lock().await;
+    // Another playlist or page was opened in the meantime otherwise
+    if app.track_table.prefetch != Some(offset) {
+      return;
+    }
+    let made_for_you = app.track_table.context == Some(TrackTableContext::MadeForYou);
+    if !made_for_you && app.playlist_tracks_id.as_ref() != Some(&playlist_id) {
+      return;
+    }
+    let (loaded_offset, loaded) = if made_for_you {
+      (app.made_for_you_offset, &mut app.made_for_you_tracks)
+    } else {
+      (app.playlist_offset, &mut app.playlist_tracks)
     };
+    let loaded = match loaded {
+      Some(loaded) if loaded_offset + loaded.items.len() as u32 == offset => loaded,
+      _ => return,
+    };
+    loaded.items.extend(page.items.iter().cloned());
+    loaded.total = page.total;
+    let added_at = page
+      .items
+      .iter()
+      .filter_map(|item| Some((item.track.as_ref()?.uri.clone(), item.added_at.timestamp())))
+      .collect();
+    let tracks: Vec<FullTrack> = page
+      .items
+      .into_iter()
+      .filter_map(|item| item.track)
+      .collect();
+    app.track_table.append_tracks(tracks.clone(), added_at);
+    app.dispatch(IoEvent::CurrentUserSavedTracksContains(
+      tracks.into_iter().filter_map(|track| track.id).collect(),
+    ));
   }
 
   async fn set_playlist_tracks_to_table(&mut self, playlist_track_page: &Page<PlaylistTrack>) {
+    let added_at = playlist_track_page
+      .items
+      .iter()
+      .filter_map(|item| Some((item.track.as_ref()?.uri.clone(), item.added_at.timestamp())))
+      .collect();
     self
-      .set_tracks_to_table(
+      .set_dated_tracks_to_table(
         playlist_track_page
           .items
           .clone()
           .into_iter()
           .filter_map(|item| item.track)
           .collect::<Vec<FullTrack>>(),
+        added_at,
       )
       .await;
   }
 
   async fn set_tracks_to_table(&mut self, tracks: Vec<FullTrack>) {
+    self.set_dated_tracks_to_table(tracks, HashMap::new()).await;
+  }
+
+  async fn set_dated_tracks_to_table(
+    &mut self,
+    tracks: Vec<FullTrack>,
+    added_at: HashMap<String, i64>,
+  ) {
     let mut app = self.app.lock().await;
-    app.track_table.tracks = tracks.clone();
+    app.track_table.set_tracks(tracks.clone(), added_at);
 
     // Send this event round (don't block here)
     app.dispatch(IoEvent::CurrentUserSavedTracksContains(
@@ -482,10 +1048,13 @@ impl<'a> Network<'a> {
   }
 
   async fn get_current_user_saved_shows(&mut self, offset: Option<u32>) {
-    match self
-      .spotify
-      .get_saved_show(self.large_search_limit, offset)
-      .await
+    let spotify = &self.spotify;
+    match paged(
+      self.large_search_limit,
+      offset.unwrap_or(0),
+      |limit, offset| spotify.get_saved_show(limit, offset),
+    )
+    .await
     {
       Ok(saved_shows) => {
         // not to show a blank page
@@ -579,50 +1148,27 @@ impl<'a> Network<'a> {
   }
 
   async fn get_search_results(&mut self, search_term: String, country: Option<Country>) {
-    let search_track = self.spotify.search(
-      &search_term,
-      SearchType::Track,
-      self.small_search_limit,
-      0,
-      country,
-      None,
-    );
-
-    let search_artist = self.spotify.search(
-      &search_term,
-      SearchType::Artist,
-      self.small_search_limit,
-      0,
-      country,
-      None,
-    );
-
-    let search_album = self.spotify.search(
-      &search_term,
-      SearchType::Album,
-      self.small_search_limit,
-      0,
-      country,
-      None,
-    );
-
-    let search_playlist = self.spotify.search(
-      &search_term,
-      SearchType::Playlist,
-      self.small_search_limit,
-      0,
-      country,
-      None,
-    );
-
-    let search_show = self.spotify.search(
-      &search_term,
-      SearchType::Show,
-      self.small_search_limit,
-      0,
-      country,
-      None,
-    );
+    let search_track = self.search_paged(&search_term, SearchType::Track, country, |r| match r {
+      SearchResult::Tracks(page) => Some(page),
+      _ => None,
+    });
+    let search_artist = self.search_paged(&search_term, SearchType::Artist, country, |r| match r {
+      SearchResult::Artists(page) => Some(page),
+      _ => None,
+    });
+    let search_album = self.search_paged(&search_term, SearchType::Album, country, |r| match r {
+      SearchResult::Albums(page) => Some(page),
+      _ => None,
+    });
+    let search_playlist =
+      self.search_paged(&search_term, SearchType::Playlist, country, |r| match r {
+        SearchResult::Playlists(page) => Some(page),
+        _ => None,
+      });
+    let search_show = self.search_paged(&search_term, SearchType::Show, country, |r| match r {
+      SearchResult::Shows(page) => Some(page),
+      _ => None,
+    });
 
     // Run the futures concurrently
     match try_join!(
@@ -632,13 +1178,7 @@ impl<'a> Network<'a> {
       search_playlist,
       search_show
     ) {
-      Ok((
-        SearchResult::Tracks(track_results),
-        SearchResult::Artists(artist_results),
-        SearchResult::Albums(album_results),
-        SearchResult::Playlists(playlist_results),
-        SearchResult::Shows(show_results),
-      )) => {
+      Ok((track_results, artist_results, album_results, playlist_results, show_results)) => {
         let mut app = self.app.lock().await;
 
         let artist_ids = album_results
@@ -668,6 +1208,14 @@ impl<'a> Network<'a> {
         // check if these shows are saved
         app.dispatch(IoEvent::CurrentUserSavedShowsContains(show_ids));
 
+        app.dispatch(IoEvent::CurrentUserSavedTracksContains(
+          track_results
+            .items
+            .iter()
+            .filter_map(|track| track.id.to_owned())
+            .collect(),
+        ));
+
         app.search_results.tracks = Some(track_results);
         app.search_results.artists = Some(artist_results);
         app.search_results.albums = Some(album_results);
@@ -675,35 +1223,72 @@ impl<'a> Network<'a> {
         app.search_results.shows = Some(show_results);
       }
       Err(e) => {
-        self.handle_error(anyhow!(e)).await;
+        self.handle_error(e).await;
       }
-      _ => {}
     };
   }
 
+  // The search wraps its page in a `SearchResult` of the searched type
+  async fn search_paged<T>(
+    &self,
+    term: &str,
+    search_type: SearchType,
+    country: Option<Country>,
+    unwrap: fn(SearchResult) -> Option<Page<T>>,
+  ) -> anyhow::Result<Page<T>> {
+    let spotify = &self.spotify;
+    let limit = self.small_search_limit.min(MAX_SEARCH_RESULTS);
+    paged(limit, 0, |limit, offset| async move {
+      let result = spotify
+        .search(term, search_type, limit, offset, country, None)
+        .await
+        .map_err(|e| anyhow!(e))?;
+      unwrap(result).ok_or_else(|| anyhow!("spotify returned other results than searched for"))
+    })
+    .await
+  }
+
   async fn get_current_user_saved_tracks(&mut self, offset: Option<u32>) {
-    match self
-      .spotify
-      .current_user_saved_tracks(self.large_search_limit, offset)
-      .await
-    {
+    let key = format!("saved_tracks-{}", offset.unwrap_or(0));
+    let saved_tracks = self
+      .cached(
+        CacheKind::Library,
+        &key,
+        paged(
+          self.large_search_limit,
+          offset.unwrap_or(0),
+          |limit, offset| self.spotify.current_user_saved_tracks(limit, offset),
+        ),
+      )
+      .await;
+    match saved_tracks {
       Ok(saved_tracks) => {
         let mut app = self.app.lock().await;
-        app.track_table.tracks = saved_tracks
-          .items
-          .clone()
-          .into_iter()
-          .map(|item| item.track)
-          .collect::<Vec<FullTrack>>();
+        app.track_table.set_tracks(
+          saved_tracks
+            .items
+            .clone()
+            .into_iter()
+            .map(|item| item.track)
+            .collect::<Vec<FullTrack>>(),
+          saved_tracks
+            .items
+            .iter()
+            .map(|item| (item.track.uri.clone(), item.added_at.timestamp()))
+            .collect(),
+        );
 
         saved_tracks.items.iter().for_each(|item| {
           if let Some(track_id) = &item.track.id {
             app.liked_song_ids_set.insert(track_id.to_string());
+            app.liked_song_ids_checked.insert(track_id.to_string());
           }
         });
 
         app.library.saved_tracks.add_pages(saved_tracks);
-        app.track_table.context = Some(TrackTableContext::SavedTracks);
+        app
+          .track_table
+          .set_context(Some(TrackTableContext::SavedTracks));
       }
       Err(e) => {
         self.handle_error(anyhow!(e)).await;
@@ -711,11 +1296,54 @@ impl<'a> Network<'a> {
     }
   }
 
+  async fn append_saved_tracks(&mut self, offset: u32) {
+    let key = format!("saved_tracks-{}", offset);
+    let saved_tracks = paged(self.large_search_limit, offset, |limit, offset| {
+      self.spotify.current_user_saved_tracks(limit, offset)
+    });
+    let page = match self.cached(CacheKind::Library, &key, saved_tracks).await {
+      Ok(page) => page,
+      Err(e) => {
+        self.handle_error(e).await;
+        return;
+      }
+    };
+    let mut app = self.app.lock().await;
+    // Another page was opened in the meantime otherwise
+    if app.track_table.prefetch != Some(offset)
+      || app.track_table.context != Some(TrackTableContext::SavedTracks)
+    {
+      return;
+    }
+    let loaded = match app.library.saved_tracks.get_mut_results(None) {
+      Some(loaded) if loaded.offset + loaded.items.len() as u32 == offset => loaded,
+      _ => return,
+    };
+    loaded.items.extend(page.items.iter().cloned());
+    loaded.total = page.total;
+    for item in &page.items {
+      if let Some(track_id) = &item.track.id {
+        app.liked_song_ids_set.insert(track_id.to_string());
+        app.liked_song_ids_checked.insert(track_id.to_string());
+      }
+    }
+    let added_at = page
+      .items
+      .iter()
+      .map(|item| (item.track.uri.clone(), item.added_at.timestamp()))
+      .collect();
+    app.track_table.append_tracks(
+      page.items.into_iter().map(|item| item.track).collect(),
+      added_at,
+    );
+  }
+
   async fn start_playback(
     &mut self,
     context_uri: Option<String>,
     uris: Option<Vec<String>>,
     offset: Option<usize>,
+    position_ms: Option<u32>,
   ) {
     let (uris, context_uri) = if context_uri.is_some() {
       (None, context_uri)
@@ -736,7 +1364,7 @@ impl<'a> Network<'a> {
             context_uri.clone(),
             uris.clone(),
             offset.clone(),
-            None,
+            position_ms,
           )
           .await
         {
@@ -750,7 +1378,7 @@ impl<'a> Network<'a> {
     match result {
       Ok(()) => {
         let mut app = self.app.lock().await;
-        app.song_progress_ms = 0;
+        app.song_progress_ms = position_ms.unwrap_or(0).into();
         app.dispatch(IoEvent::GetCurrentPlayback);
       }
       Err(e) => {
@@ -835,15 +1463,19 @@ impl<'a> Network<'a> {
       RepeatState::Context => RepeatState::Track,
       RepeatState::Track => RepeatState::Off,
     };
+    self.set_repeat(next_repeat_state).await;
+  }
+
+  async fn set_repeat(&mut self, repeat_state: RepeatState) {
     match self
       .spotify
-      .repeat(next_repeat_state, self.client_config.device_id.clone())
+      .repeat(repeat_state, self.client_config.device_id.clone())
       .await
     {
       Ok(()) => {
         let mut app = self.app.lock().await;
         if let Some(current_playback_context) = &mut app.current_playback_context {
-          current_playback_context.repeat_state = next_repeat_state;
+          current_playback_context.repeat_state = repeat_state;
         };
       }
       Err(e) => {
@@ -891,59 +1523,167 @@ impl<'a> Network<'a> {
     input_artist_name: String,
     country: Option<Country>,
   ) {
-    let albums = self.spotify.artist_albums(
-      &artist_id,
-      None,
-      country,
-      Some(self.large_search_limit),
-      Some(0),
+    let key = format!(
+      "artist-{}-{}",
+      artist_id,
+      country.as_ref().map(|c| c.as_str()).unwrap_or("")
     );
-    let artist_name = if input_artist_name.is_empty() {
+    let artist = self.cached(CacheKind::Catalog, &key, async {
+      let albums = self.artist_discography(&artist_id, None, country);
+      let artist_name = if input_artist_name.is_empty() {
+        self
+          .spotify
+          .artist(&artist_id)
+          .await
+          .map(|full_artist| full_artist.name)
+          .unwrap_or_default()
+      } else {
+        input_artist_name
+      };
+      let top_tracks = async {
+        self
+          .spotify
+          .artist_top_tracks(&artist_id, country)
+          .await
+          .map_err(|e| anyhow!(e))
+      };
+      let related_artist = async {
+        self
+          .spotify
+          .artist_related_artists(&artist_id)
+          .await
+          .map_err(|e| anyhow!(e))
+      };
+
+      try_join!(albums, top_tracks, related_artist).map(|(albums, top_tracks, related_artist)| {
+        (
+          artist_name,
+          albums,
+          top_tracks.tracks,
+          related_artist.artists,
+        )
+      })
+    });
+    let (artist_name, albums, top_tracks, related_artists): CachedArtist = match artist.await {
+      Ok(artist) => artist,
+      Err(_) => return,
+    };
+
+    let mut app = self.app.lock().await;
+
+    let mut artist_ids = vec![artist_id.clone()];
+    artist_ids.extend(related_artists.iter().map(|a| a.id.clone()));
+    app.dispatch(IoEvent::UserArtistFollowCheck(artist_ids));
+    app.dispatch(IoEvent::CurrentUserSavedAlbumsContains(
+      albums
+        .items
+        .iter()
+        .filter_map(|item| item.id.to_owned())
+        .collect(),
+    ));
+    app.dispatch(IoEvent::CurrentUserSavedTracksContains(
+      top_tracks
+        .iter()
+        .filter_map(|track| track.id.to_owned())
+        .collect(),
+    ));
+
+    app.artist = Some(Artist {
+      artist_id,
+      artist_name,
+      albums,
+      album_group: None,
+      related_artists,
+      top_tracks,
+      selected_album_index: 0,
+      selected_related_artist_index: 0,
+      selected_top_track_index: 0,
+      artist_hovered_block: ArtistBlock::TopTracks,
+      artist_selected_block: ArtistBlock::Empty,
+    });
+  }
+
+  // Every page, the first one alone leaves out most of a long discography
+  async fn artist_discography(
+    &self,
+    artist_id: &str,
+    group: Option<AlbumType>,
+    country: Option<Country>,
+  ) -> anyhow::Result<Page<SimplifiedAlbum>> {
+    let page = |offset: usize| async move {
       self
         .spotify
-        .artist(&artist_id)
+        .artist_albums(artist_id, group, country, Some(50), Some(offset as u32))
         .await
-        .map(|full_artist| full_artist.name)
-        .unwrap_or_default()
-    } else {
-      input_artist_name
+        .map_err(|e| anyhow!(e))
     };
-    let top_tracks = self.spotify.artist_top_tracks(&artist_id, country);
-    let related_artist = self.spotify.artist_related_artists(&artist_id);
-
-    if let Ok((albums, top_tracks, related_artist)) = try_join!(albums, top_tracks, related_artist)
-    {
-      let mut app = self.app.lock().await;
-
-      app.dispatch(IoEvent::CurrentUserSavedAlbumsContains(
-        albums
-          .items
-          .iter()
-          .filter_map(|item| item.id.to_owned())
-          .collect(),
-      ));
+    let mut albums = page(0).await?;
+    while albums.next.is_some() && albums.items.len() < MAX_ARTIST_ALBUMS {
+      let next = page(albums.items.len()).await?;
+      if next.items.is_empty() {
+        break;
+      }
+      albums.next = next.next;
+      albums.items.extend(next.items);
+    }
+    Ok(albums)
+  }
 
-      app.artist = Some(Artist {
-        artist_name,
-        albums,
-        related_artists: related_artist.artists,
-        top_tracks: top_tracks.tracks,
-        selected_album_index: 0,
-        selected_related_artist_index: 0,
-        selected_top_track_index: 0,
-        artist_hovered_block: ArtistBlock::TopTracks,
-        artist_selected_block: ArtistBlock::Empty,
-      });
+  async fn get_artist_albums(
+    &mut self,
+    artist_id: String,
+    group: Option<AlbumType>,
+    country: Option<Country>,
+  ) {
+    let key = format!(
+      "artist_albums-{}-{}-{}",
+      artist_id,
+      group.as_ref().map(|g| g.as_str()).unwrap_or(""),
+      country.as_ref().map(|c| c.as_str()).unwrap_or("")
+    );
+    let albums = self
+      .cached(
+        CacheKind::Catalog,
+        &key,
+        self.artist_discography(&artist_id, group, country),
+      )
+      .await;
+    match albums {
+      Ok(albums) => {
+        let mut app = self.app.lock().await;
+        app.dispatch(IoEvent::CurrentUserSavedAlbumsContains(
+          albums
+            .items
+            .iter()
+            .filter_map(|item| item.id.to_owned())
+            .collect(),
+        ));
+        if let Some(artist) = &mut app.artist {
+          // Another artist could have been opened in the meantime
+          if artist.artist_id == artist_id {
+            artist.albums = albums;
+            artist.album_group = group;
+            artist.selected_album_index = 0;
+          }
+        }
+      }
+      Err(e) => self.handle_error(e).await,
     }
   }
 
   async fn get_album_tracks(&mut self, album: Box<SimplifiedAlbum>) {
     if let Some(album_id) = &album.id {
-      match self
-        .spotify
-        .album_track(&album_id.clone(), self.large_search_limit, 0)
-        .await
-      {
+      let key = format!("album_tracks-{}", album_id);
+      let tracks = self
+        .cached(
+          CacheKind::Catalog,
+          &key,
+          self
+            .spotify
+            .album_track(&album_id.clone(), self.large_search_limit, 0),
+        )
+        .await;
+      match tracks {
         Ok(tracks) => {
           let track_ids = tracks
             .items
@@ -952,6 +1692,9 @@ impl<'a> Network<'a> {
             .collect::<Vec<String>>();
 
           let mut app = self.app.lock().await;
+          app.dispatch(IoEvent::CurrentUserSavedAlbumsContains(vec![
+            album_id.clone()
+          ]));
           app.selected_album_simplified = Some(SelectedAlbum {
             album: *album,
             tracks,
@@ -1006,7 +1749,9 @@ impl<'a> Network<'a> {
 
           let mut app = self.app.lock().await;
           app.recommended_tracks = recommended_tracks;
-          app.track_table.context = Some(TrackTableContext::RecommendedTracks);
+          app
+            .track_table
+            .set_context(Some(TrackTableContext::RecommendedTracks));
 
           if app.get_current_route().id != RouteId::Recommendations {
             app.push_navigation_stack(RouteId::Recommendations, ActiveBlock::TrackTable);
@@ -1065,8 +1810,13 @@ impl<'a> Network<'a> {
             .await
           {
             Ok(()) => {
+              self.cache_invalidate("saved_tracks");
               let mut app = self.app.lock().await;
               app.liked_song_ids_set.remove(&track_id);
+              app.liked_song_ids_checked.insert(track_id.clone());
+              app
+                .undo_stack
+                .push(UndoAction::UnlikeTracks(vec![track_id]));
             }
             Err(e) => {
               self.handle_error(anyhow!(e)).await;
@@ -1079,9 +1829,11 @@ impl<'a> Network<'a> {
             .await
           {
             Ok(()) => {
+              self.cache_invalidate("saved_tracks");
               // TODO: This should ideally use the same logic as `self.current_user_saved_tracks_contains`
               let mut app = self.app.lock().await;
-              app.liked_song_ids_set.insert(track_id);
+              app.liked_song_ids_set.insert(track_id.clone());
+              app.liked_song_ids_checked.insert(track_id);
             }
             Err(e) => {
               self.handle_error(anyhow!(e)).await;
@@ -1096,11 +1848,17 @@ impl<'a> Network<'a> {
   }
 
   async fn get_followed_artists(&mut self, after: Option<String>) {
-    match self
-      .spotify
-      .current_user_followed_artists(self.large_search_limit, after)
-      .await
-    {
+    let key = format!("followed_artists-{}", after.as_deref().unwrap_or(""));
+    let saved_artists = self
+      .cached(
+        CacheKind::Library,
+        &key,
+        self
+          .spotify
+          .current_user_followed_artists(self.large_search_limit.min(MAX_PAGE_LIMIT), after),
+      )
+      .await;
+    match saved_artists {
       Ok(saved_artists) => {
         let mut app = self.app.lock().await;
         app.artists = saved_artists.artists.items.to_owned();
@@ -1126,11 +1884,19 @@ impl<'a> Network<'a> {
   }
 
   async fn get_current_user_saved_albums(&mut self, offset: Option<u32>) {
-    match self
-      .spotify
-      .current_user_saved_albums(self.large_search_limit, offset)
-      .await
-    {
+    let key = format!("saved_albums-{}", offset.unwrap_or(0));
+    let saved_albums = self
+      .cached(
+        CacheKind::Library,
+        &key,
+        paged(
+          self.large_search_limit,
+          offset.unwrap_or(0),
+          |limit, offset| self.spotify.current_user_saved_albums(limit, offset),
+        ),
+      )
+      .await;
+    match saved_albums {
       Ok(saved_albums) => {
         // not to show a blank page
         if !saved_albums.items.is_empty() {
@@ -1161,6 +1927,20 @@ impl<'a> Network<'a> {
     }
   }
 
+  async fn toggle_save_album(&mut self, album_id: String) {
+    match self
+      .spotify
+      .current_user_saved_albums_contains(std::slice::from_ref(&album_id))
+      .await
+    {
+      Ok(saved) if saved.first() == Some(&true) => {
+        self.current_user_saved_album_delete(album_id).await
+      }
+      Ok(_) => self.current_user_saved_album_add(album_id).await,
+      Err(e) => self.handle_error(anyhow!(e)).await,
+    }
+  }
+
   pub async fn current_user_saved_album_delete(&mut self, album_id: String) {
     match self
       .spotify
@@ -1168,9 +1948,11 @@ impl<'a> Network<'a> {
       .await
     {
       Ok(_) => {
+        self.cache_invalidate("saved_albums");
         self.get_current_user_saved_albums(None).await;
         let mut app = self.app.lock().await;
         app.saved_album_ids_set.remove(&album_id.to_owned());
+        app.undo_stack.push(UndoAction::UnsaveAlbum(album_id));
       }
       Err(e) => {
         self.handle_error(anyhow!(e)).await;
@@ -1185,6 +1967,7 @@ impl<'a> Network<'a> {
       .await
     {
       Ok(_) => {
+        self.cache_invalidate("saved_albums");
         let mut app = self.app.lock().await;
         app.saved_album_ids_set.insert(album_id.to_owned());
       }
@@ -1202,6 +1985,7 @@ impl<'a> Network<'a> {
         self.get_current_user_saved_shows(None).await;
         let mut app = self.app.lock().await;
         app.saved_show_ids_set.remove(&show_id.to_owned());
+        app.undo_stack.push(UndoAction::UnsaveShow(show_id));
       }
       Err(e) => {
         self.handle_error(anyhow!(e)).await;
@@ -1225,11 +2009,13 @@ impl<'a> Network<'a> {
   async fn user_unfollow_artists(&mut self, artist_ids: Vec<String>) {
     match self.spotify.user_unfollow_artists(&artist_ids).await {
       Ok(_) => {
+        self.cache_invalidate("followed_artists");
         self.get_followed_artists(None).await;
         let mut app = self.app.lock().await;
         artist_ids.iter().for_each(|id| {
           app.followed_artist_ids_set.remove(&id.to_owned());
         });
+        app.undo_stack.push(UndoAction::UnfollowArtists(artist_ids));
       }
       Err(e) => {
         self.handle_error(anyhow!(e)).await;
@@ -1240,6 +2026,7 @@ impl<'a> Network<'a> {
   async fn user_follow_artists(&mut self, artist_ids: Vec<String>) {
     match self.spotify.user_follow_artists(&artist_ids).await {
       Ok(_) => {
+        self.cache_invalidate("followed_artists");
         self.get_followed_artists(None).await;
         let mut app = self.app.lock().await;
         artist_ids.iter().for_each(|id| {
@@ -1264,6 +2051,7 @@ impl<'a> Network<'a> {
       .await
     {
       Ok(_) => {
+        self.cache_invalidate("playlists");
         self.get_current_user_playlists().await;
       }
       Err(e) => {
@@ -1273,12 +2061,26 @@ impl<'a> Network<'a> {
   }
 
   async fn user_unfollow_playlist(&mut self, user_id: String, playlist_id: String) {
+    // It's gone from the list afterwards
+    let (owner_id, name) = self
+      .app
+      .lock()
+      .await
+      .find_playlist(&playlist_id)
+      .unwrap_or_else(|| (user_id.clone(), playlist_id.clone()));
     match self
       .spotify
       .user_playlist_unfollow(&user_id, &playlist_id)
       .await
     {
       Ok(_) => {
+        self
+          .app
+          .lock()
+          .await
+          .undo_stack
+          .push(UndoAction::UnfollowPlaylist(owner_id, playlist_id, name));
+        self.cache_invalidate("playlists");
         self.get_current_user_playlists().await;
       }
       Err(e) => {
@@ -1287,6 +2089,138 @@ impl<'a> Network<'a> {
     }
   }
 
+  async fn reorder_playlist_tracks(
+    &mut self,
+    playlist_id: String,
+    range_start: u32,
+    insert_before: u32,
+  ) {
+    let user_id = match &self.app.lock().await.user {
+      Some(user) => user.id.clone(),
+      None => return,
+    };
+    // Positions of a move made right after another one are meant for the
+    // playlist after the first move
+    let snapshot_id = self.playlist_snapshots.get(&playlist_id).cloned();
+    match self
+      .spotify
+      .user_playlist_recorder_tracks(
+        &user_id,
+        &playlist_id,
+        range_start as i32,
+        1,
+        insert_before as i32,
+        snapshot_id,
+      )
+      .await
+    {
+      Ok(result) => {
+        self
+          .playlist_snapshots
+          .insert(playlist_id.clone(), result.snapshot_id);
+        self.cache_invalidate(&format!("playlist_tracks-{}", playlist_id));
+      }
+      Err(e) => {
+        // The table was already changed, so it's loaded again
+        self.cache_invalidate(&format!("playlist_tracks-{}", playlist_id));
+        let offset = self.app.lock().await.playlist_offset;
+        self.get_playlist_tracks(playlist_id, offset).await;
+        self.handle_error(anyhow!(e)).await;
+      }
+    }
+  }
+
+  async fn add_tracks_to_playlist(&mut self, playlist_id: String, track_uris: Vec<String>) {
+    let user_id = match &self.app.lock().await.user {
+      Some(user) => user.id.clone(),
+      None => return,
+    };
+    let (spotify, app) = (&self.spotify, self.app);
+    let (user, playlist) = (&user_id, &playlist_id);
+    let added = add_in_chunks(
+      &track_uris,
+      move |chunk| async move {
+        spotify
+          .user_playlist_add_tracks(user, playlist, &chunk, None)
+          .await
+          .map(|_| ())
+          .map_err(|e| anyhow!(e))
+      },
+      move |delay| wait_rate_limited(app, delay),
+    )
+    .await;
+    // Already retried, repeating the whole event would add the tracks twice
+    if let Err(e) = added {
+      self.app.lock().await.handle_error(e);
+    }
+    self.cache_invalidate(&format!("playlist_tracks-{}", playlist_id));
+    self.cache_invalidate("playlists");
+    self.get_current_user_playlists().await;
+  }
+
+  async fn current_user_saved_tracks_add(&mut self, track_ids: Vec<String>) {
+    for chunk in track_ids.chunks(SAVED_TRACKS_CHANGE_LIMIT) {
+      match self.spotify.current_user_saved_tracks_add(chunk).await {
+        Ok(()) => {
+          let mut app = self.app.lock().await;
+          app.liked_song_ids_set.extend(chunk.iter().cloned());
+          app.liked_song_ids_checked.extend(chunk.iter().cloned());
+        }
+        Err(e) => {
+          self.handle_error(anyhow!(e)).await;
+          break;
+        }
+      }
+    }
+    self.cache_invalidate("saved_tracks");
+  }
+
+  async fn create_playlist_with_tracks(&mut self, name: String, track_uris: Vec<String>) {
+    let user_id = match &self.app.lock().await.user {
+      Some(user) => user.id.clone(),
+      None => return,
+    };
+    // Each request is retried on its own, repeating the whole event would
+    // create the playlist twice
+    let (spotify, app) = (&self.spotify, self.app);
+    let (user, name) = (&user_id, &name);
+    let created = retry_rate_limited(
+      move || async move {
+        spotify
+          .user_playlist_create(user, name, false, None)
+          .await
+          .map_err(|e| anyhow!(e))
+      },
+      move |delay| wait_rate_limited(app, delay),
+    )
+    .await;
+    let playlist = match created {
+      Ok(playlist) => playlist,
+      Err(e) => {
+        self.app.lock().await.handle_error(e);
+        return;
+      }
+    };
+    let playlist_id = &playlist.id;
+    let added = add_in_chunks(
+      &track_uris,
+      move |chunk| async move {
+        spotify
+          .user_playlist_add_tracks(user, playlist_id, &chunk, None)
+          .await
+          .map(|_| ())
+          .map_err(|e| anyhow!(e))
+      },
+      move |delay| wait_rate_limited(app, delay),
+    )
+    .await;
+    if let Err(e) = added {
+      self.app.lock().await.handle_error(e);
+    }
+    self.cache_invalidate("playlists");
+    self.get_current_user_playlists().await;
+  }
+
   async fn made_for_you_search_and_add(&mut self, search_string: String, country: Option<Country>) {
     const SPOTIFY_ID: &str = "spotify";
 
@@ -1346,16 +2280,59 @@ impl<'a> Network<'a> {
     }
   }
 
+  // A track's features never change
+  async fn get_audio_features(&mut self, track_id: String) {
+    let key = format!("audio_features-{}", track_id);
+    match self
+      .cached(
+        CacheKind::Catalog,
+        &key,
+        self.spotify.audio_features(&track_id),
+      )
+      .await
+    {
+      Ok(features) => {
+        let mut app = self.app.lock().await;
+        if let Some(popup) = &mut app.track_features {
+          popup.features = Some(features);
+        }
+      }
+      Err(e) => {
+        self.app.lock().await.track_features = None;
+        self.handle_error(e).await;
+      }
+    }
+  }
+
+  // Providers can be slow, so the other events don't wait for them
+  fn get_lyrics(&mut self, provider: LyricsProvider, query: LyricsQuery) {
+    let app = Arc::clone(self.app);
+    tokio::spawn(async move {
+      let status = provider
+        .fetch(&query)
+        .await
+        .unwrap_or_else(|e| LyricsStatus::Failed(e.to_string()));
+      let mut app = app.lock().await;
+      app.lyrics.insert(query.uri, status);
+      app.needs_redraw = true;
+    });
+  }
+
   async fn get_current_user_playlists(&mut self) {
     let playlists = self
-      .spotify
-      .current_user_playlists(self.large_search_limit, None)
+      .cached(
+        CacheKind::Library,
+        "playlists",
+        paged(self.large_search_limit, 0, |limit, offset| {
+          self.spotify.current_user_playlists(limit, offset)
+        }),
+      )
       .await;
 
     match playlists {
       Ok(p) => {
         let mut app = self.app.lock().await;
-        app.playlists = Some(p);
+        app.set_playlists(p);
         // Select the first playlist
         app.selected_playlist_index = Some(0);
       }
@@ -1365,10 +2342,12 @@ impl<'a> Network<'a> {
     };
   }
 
+  // Spotify only keeps the last 50 plays, and 50 is also the most one request
+  // returns, so there's nothing to page through
   async fn get_recently_played(&mut self) {
     match self
       .spotify
-      .current_user_recently_played(self.large_search_limit)
+      .current_user_recently_played(RECENTLY_PLAYED_LIMIT)
       .await
     {
       Ok(result) => {
@@ -1390,8 +2369,58 @@ impl<'a> Network<'a> {
     }
   }
 
+  async fn get_top_tracks(&mut self, time_range: TimeRange) {
+    let key = format!("top_tracks-{}", time_range.as_str());
+    let top_tracks = self
+      .spotify
+      .current_user_top_tracks(TOP_ITEMS_LIMIT, 0, time_range);
+    match self.cached(CacheKind::Library, &key, top_tracks).await {
+      Ok(top_tracks) => {
+        let mut app = self.app.lock().await;
+        // The time range could have changed again in the meantime
+        if app.top_items.time_range == time_range {
+          app.dispatch(IoEvent::CurrentUserSavedTracksContains(
+            top_tracks
+              .items
+              .iter()
+              .filter_map(|track| track.id.clone())
+              .collect(),
+          ));
+          app.top_items.tracks = top_tracks.items;
+        }
+      }
+      Err(e) => {
+        self.handle_error(e).await;
+      }
+    }
+  }
+
+  async fn get_top_artists(&mut self, time_range: TimeRange) {
+    let key = format!("top_artists-{}", time_range.as_str());
+    let top_artists = self
+      .spotify
+      .current_user_top_artists(TOP_ITEMS_LIMIT, 0, time_range);
+    match self.cached(CacheKind::Library, &key, top_artists).await {
+      Ok(top_artists) => {
+        let mut app = self.app.lock().await;
+        if app.top_items.time_range == time_range {
+          let artist_ids = top_artists.items.iter().map(|a| a.id.clone()).collect();
+          app.dispatch(IoEvent::UserArtistFollowCheck(artist_ids));
+          app.top_items.artists = top_artists.items;
+        }
+      }
+      Err(e) => {
+        self.handle_error(e).await;
+      }
+    }
+  }
+
   async fn get_album(&mut self, album_id: String) {
-    match self.spotify.album(&album_id).await {
+    let key = format!("album-{}", album_id);
+    let album = self
+      .cached(CacheKind::Catalog, &key, self.spotify.album(&album_id))
+      .await;
+    match album {
       Ok(album) => {
         let selected_album = SelectedFullAlbum {
           album,
@@ -1400,6 +2429,10 @@ impl<'a> Network<'a> {
 
         let mut app = self.app.lock().await;
 
+        app.dispatch(IoEvent::CurrentUserSavedAlbumsContains(vec![album_id]));
+        app.dispatch(IoEvent::CurrentUserSavedTracksContains(album_track_ids(
+          &selected_album.album,
+        )));
         app.selected_album_full = Some(selected_album);
         app.album_table_context = AlbumTableContext::Full;
         app.push_navigation_stack(RouteId::AlbumTracks, ActiveBlock::AlbumTracks);
@@ -1431,6 +2464,9 @@ impl<'a> Network<'a> {
 
           let mut app = self.app.lock().await;
 
+          app.dispatch(IoEvent::CurrentUserSavedTracksContains(album_track_ids(
+            &selected_album.album,
+          )));
           app.selected_album_full = Some(selected_album.clone());
           app.saved_album_tracks_index = selected_album.selected_index;
           app.album_table_context = AlbumTableContext::Full;
@@ -1465,8 +2501,155 @@ impl<'a> Network<'a> {
     };
   }
 
+  // Only profiles that are logged in already, logging in needs the browser
+  async fn switch_profile(&mut self, profile: Option<String>) {
+    if self.forced_offline {
+      let e = anyhow!("switching profiles needs spotify, it doesn't work with `--offline`");
+      self.handle_error(e).await;
+      return;
+    }
+    let name = profile_name(profile.as_deref()).to_string();
+    let mut client_config = ClientConfig::for_profile(profile.clone());
+    client_config.use_keyring = self.app.lock().await.user_config.behavior.use_keyring;
+    let paths = match client_config.get_or_build_paths() {
+      Ok(paths) => paths,
+      Err(e) => {
+        self.handle_error(e).await;
+        return;
+      }
+    };
+    if !paths.config_file_path.exists() {
+      let e = anyhow!(
+        "profile '{}' isn't set up, run `spt --profile {}` once",
+        name,
+        name
+      );
+      self.handle_error(e).await;
+      return;
+    }
+    if let Err(e) = client_config.load_config() {
+      self.handle_error(e).await;
+      return;
+    }
+    let mut oauth = client_config.oauth(&paths);
+    let cached = if client_config.use_keyring {
+      let keyring = Keyring::new(profile.as_deref());
+      keyring::cached_token(&keyring, &oauth, &paths.token_cache_path).await
+    } else {
+      Ok(oauth.get_cached_token().await)
+    };
+    let token_info = match cached {
+      Ok(Some(token_info)) => token_info,
+      Err(e) => {
+        self.handle_error(e).await;
+        return;
+      }
+      Ok(None) => {
+        let e = anyhow!(
+          "profile '{}' isn't logged in, run `spt --profile {}` once",
+          name,
+          name
+        );
+        self.handle_error(e).await;
+        return;
+      }
+    };
+    let (spotify, token_expiry) = get_spotify(token_info);
+    self.oauth = oauth;
+    self.spotify = spotify;
+    self.client_config = client_config;
+    let mut app = self.app.lock().await;
+    if self.cache.is_some() {
+      let behavior = &app.user_config.behavior;
+      self.cache = Some(ResponseCache::new(
+        paths.cache_dir,
+        behavior.library_cache_ttl_seconds,
+        behavior.catalog_cache_ttl_seconds,
+      ));
+    }
+    app.switch_profile(profile, token_expiry);
+  }
+
+  // Doesn't hold up other requests while it listens for answers. Networks
+  // without multicast just find nothing
+  fn discover_devices(&mut self) {
+    let app = Arc::clone(self.app);
+    tokio::spawn(async move {
+      let devices = discovery::discover().await.unwrap_or_default();
+      let mut app = app.lock().await;
+      if app.selected_device_index.is_none() && !devices.is_empty() {
+        app.selected_device_index = Some(0);
+      }
+      app.discovered_devices = devices;
+      app.needs_redraw = true;
+    });
+  }
+
+  async fn wake_device(&mut self, device: ConnectDevice) {
+    let name = match discovery::get_info(&device).await {
+      Ok(name) => name,
+      Err(e) => {
+        self.handle_error(e).await;
+        return;
+      }
+    };
+    for attempt in 0..WAKE_ATTEMPTS {
+      if attempt > 0 {
+        tokio::time::sleep(WAKE_INTERVAL).await;
+      }
+      if let Ok(result) = self.spotify.device().await {
+        if let Some(listed) = result.devices.into_iter().find(|d| d.name == name) {
+          self.transfert_playback_to_device(listed.id).await;
+          return;
+        }
+      }
+    }
+    self
+      .handle_error(anyhow!(
+        "{} is on the network, but spotify doesn't list it for your account. Devices only show up once they're signed in, e.g. after playing on them once from the official app",
+        name
+      ))
+      .await;
+  }
+
+  // Checked again on the next try when it isn't there yet. Playback that
+  // already runs on another device stays there, spotifyd is only selected
+  async fn claim_spotifyd_device(&mut self) {
+    let devices = match self.spotify.device().await {
+      Ok(result) => result.devices,
+      Err(_) => return,
+    };
+    let config = self.app.lock().await.user_config.spotifyd.clone();
+    let device = match devices
+      .iter()
+      .find(|d| spotifyd::is_device(&config, &d.name))
+    {
+      Some(device) => device,
+      None => return,
+    };
+    self.app.lock().await.device_claim = None;
+    if !devices.iter().any(|d| d.is_active) {
+      if let Err(e) = self.spotify.transfer_playback(&device.id, false).await {
+        self.handle_error(anyhow!(e)).await;
+        return;
+      }
+    }
+    if let Err(e) = self.client_config.set_device_id(device.id.clone()) {
+      self.handle_error(e).await;
+    }
+  }
+
   async fn refresh_authentication(&mut self) {
-    if let Some(new_token_info) = get_token(&mut self.oauth).await {
+    let new_token_info = if self.client_config.use_keyring {
+      let keyring = Keyring::new(self.client_config.profile.as_deref());
+      let token_path = self.oauth.cache_path.clone();
+      keyring::cached_token(&keyring, &self.oauth, &token_path)
+        .await
+        .unwrap_or_default()
+    } else {
+      get_token(&mut self.oauth).await
+    };
+    if let Some(new_token_info) = new_token_info {
       let (new_spotify, new_token_expiry) = get_spotify(new_token_info);
       self.spotify = new_spotify;
       let mut app = self.app.lock().await;
@@ -1490,3 +2673,124 @@ impl<'a> Network<'a> {
     }
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn rate_limit_delay_test() {
+    assert_eq!(
+      rate_limit_delay("Exceeded API request limit - please wait 7 seconds"),
+      Some(Some(7))
+    );
+    assert_eq!(rate_limit_delay("Exceeded API request limit"), Some(None));
+    assert_eq!(
+      rate_limit_delay("Spotify API error code 404: Not found"),
+      None
+    );
+  }
+
+  #[tokio::test]
+  async fn add_in_chunks_test() {
+    let uris = (0..150)
+      .map(|i| format!("spotify:track:{}", i))
+      .collect::<Vec<String>>();
+    let sent = std::sync::Mutex::new(Vec::new());
+    let waits = std::sync::Mutex::new(0);
+    let added = add_in_chunks(
+      &uris,
+      |chunk| {
+        let mut sent = sent.lock().unwrap();
+        // The second chunk is rate limited once
+        let result = if chunk.len() == 50 && !sent.contains(&50) {
+          Err(anyhow!(
+            "Exceeded API request limit - please wait 0 seconds"
+          ))
+        } else {
+          Ok(())
+        };
+        sent.push(chunk.len());
+        async move { result }
+      },
+      |_| {
+        *waits.lock().unwrap() += 1;
+        async {}
+      },
+    )
+    .await;
+    assert!(added.is_ok());
+    assert_eq!(*sent.lock().unwrap(), vec![100, 50, 50]);
+    assert_eq!(*waits.lock().unwrap(), 1);
+
+    // Other errors stop without retrying
+    let sent = std::sync::Mutex::new(0);
+    let added = add_in_chunks(
+      &uris,
+      |_| {
+        *sent.lock().unwrap() += 1;
+        async { Err(anyhow!("Spotify API error code 403: Forbidden")) }
+      },
+      |_| async {},
+    )
+    .await;
+    assert!(added.is_err());
+    assert_eq!(*sent.lock().unwrap(), 1);
+  }
+
+  #[test]
+  fn event_name_test() {
+    assert_eq!(event_name(&IoEvent::GetUser), "GetUser");
+    assert_eq!(event_name(&IoEvent::SwitchProfile(None)), "SwitchProfile");
+    assert_eq!(
+      event_name(&IoEvent::StartPlayback(
+        None,
+        Some(vec!["a".to_string()]),
+        None
+      )),
+      "StartPlayback"
+    );
+  }
+
+  #[tokio::test]
+  async fn paged_test() {
+    // 120 numbers, the API hands out 50 at most
+    let requests = std::sync::atomic::AtomicU32::new(0);
+    let request = |limit: u32, offset: u32| {
+      requests.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+      let end = (offset + limit.min(50)).min(120);
+      async move {
+        Ok::<_, anyhow::Error>(Page {
+          href: String::new(),
+          items: (offset..end).collect::<Vec<u32>>(),
+          limit,
+          next: if end < 120 { Some(String::new()) } else { None },
+          offset,
+          previous: None,
+          total: 120,
+        })
+      }
+    };
+
+    let page = paged(20, 10, request).await.unwrap();
+    assert_eq!(page.items, (10..30).collect::<Vec<u32>>());
+    assert_eq!(requests.swap(0, std::sync::atomic::Ordering::SeqCst), 1);
+
+    let page = paged(75, 0, request).await.unwrap();
+    assert_eq!(page.items, (0..75).collect::<Vec<u32>>());
+    assert_eq!(requests.swap(0, std::sync::atomic::Ordering::SeqCst), 2);
+
+    let page = paged(u32::MAX, 0, request).await.unwrap();
+    assert_eq!(page.items.len(), 120);
+    assert_eq!(page.next, None);
+    assert_eq!(requests.swap(0, std::sync::atomic::Ordering::SeqCst), 3);
+
+    let items = all_items(50, request).await.unwrap();
+    assert_eq!(items, (0..120).collect::<Vec<u32>>());
+    assert_eq!(requests.swap(0, std::sync::atomic::Ordering::SeqCst), 3);
+
+    let items = all_items(100, request).await.unwrap();
+    assert_eq!(items, (0..120).collect::<Vec<u32>>());
+    assert_eq!(requests.swap(0, std::sync::atomic::Ordering::SeqCst), 3);
+  }
+}
@@ -0,0 +1,51 @@
+use std::process::Command;
+use std::thread;
+
+// Escapes a string for a double-quoted AppleScript literal
+fn applescript_string(s: &str) -> String {
+  format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn command(title: &str, body: &str) -> Option<Command> {
+  if cfg!(target_os = "macos") {
+    let mut cmd = Command::new("osascript");
+    cmd.arg("-e").arg(format!(
+      "display notification {} with title {}",
+      applescript_string(body),
+      applescript_string(title)
+    ));
+    Some(cmd)
+  } else if cfg!(unix) {
+    let mut cmd = Command::new("notify-send");
+    // A track named like an option (e.g. `-t 0`) isn't taken for one
+    cmd.args(["--app-name", "spotify-tui", "--", title, body]);
+    Some(cmd)
+  } else {
+    None
+  }
+}
+
+// Shows a desktop notification with `notify-send` (or `osascript` on macOS),
+// failures are ignored since it's only informational
+pub fn send(title: &str, body: &str) {
+  if let Some(mut cmd) = command(title, body) {
+    // Waiting in another thread avoids both blocking the UI and leaving zombies
+    thread::spawn(move || {
+      let _ = cmd.output();
+    });
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn applescript_string_test() {
+    assert_eq!(applescript_string("Song"), "\"Song\"");
+    assert_eq!(
+      applescript_string("Say \"Hi\" \\o/"),
+      "\"Say \\\"Hi\\\" \\\\o/\""
+    );
+  }
+}
@@ -0,0 +1,136 @@
+use crate::app::App;
+use crate::command_palette::fuzzy_score;
+
+// Picks the playlist tracks are added to, or creates a new one
+pub struct PlaylistPicker {
+  pub query: String,
+  pub selected: usize,
+  pub uris: Vec<String>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum PickerEntry {
+  Playlist {
+    name: String,
+    id: String,
+    total: u64,
+  },
+  // Creates a playlist named after the query
+  Create(String),
+}
+
+impl PlaylistPicker {
+  pub fn new(uris: Vec<String>) -> Self {
+    PlaylistPicker {
+      query: String::new(),
+      selected: 0,
+      uris,
+    }
+  }
+
+  // Only playlists the user can add tracks to. The best matches come first,
+  // then the ones added to most recently
+  pub fn matches(&self, app: &App) -> Vec<PickerEntry> {
+    let user_id = app.user.as_ref().map(|u| u.id.as_str());
+    let recency = |id: &str| {
+      app
+        .recent_playlist_ids
+        .iter()
+        .position(|recent| recent == id)
+        .unwrap_or(usize::MAX)
+    };
+    let mut scored = match &app.playlists {
+      Some(playlists) => playlists
+        .items
+        .iter()
+        .filter(|p| p.collaborative || Some(p.owner.id.as_str()) == user_id)
+        .filter_map(|p| fuzzy_score(&self.query, &p.name).map(|score| (score, p)))
+        .collect::<Vec<_>>(),
+      None => Vec::new(),
+    };
+    scored.sort_by_key(|(score, p)| (-score, recency(&p.id)));
+    let mut entries = scored
+      .into_iter()
+      .map(|(_, p)| PickerEntry::Playlist {
+        name: p.name.clone(),
+        id: p.id.clone(),
+        total: p.tracks.get("total").and_then(|t| t.as_u64()).unwrap_or(0),
+      })
+      .collect::<Vec<PickerEntry>>();
+    let query = self.query.trim();
+    let exists = entries
+      .iter()
+      .any(|e| matches!(e, PickerEntry::Playlist { name, .. } if name == query));
+    if !query.is_empty() && !exists {
+      entries.push(PickerEntry::Create(query.to_string()));
+    }
+    entries
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn matches_test() {
+    let mut app = App::default();
+    let owner = serde_json::json!({
+      "external_urls": {},
+      "href": "",
+      "id": "me",
+      "type": "user",
+      "uri": "spotify:user:me",
+    });
+    app.user = serde_json::from_value(owner.clone()).unwrap();
+    let playlist = |id: &str, name: &str, owner_id: &str| {
+      let mut owner = owner.clone();
+      owner["id"] = owner_id.into();
+      serde_json::json!({
+        "collaborative": false,
+        "external_urls": {},
+        "href": "",
+        "id": id,
+        "images": [],
+        "name": name,
+        "owner": owner,
+        "snapshot_id": "",
+        "tracks": {"total": 3},
+        "type": "playlist",
+        "uri": format!("spotify:playlist:{}", id),
+      })
+    };
+    app.playlists = serde_json::from_value(serde_json::json!({
+      "href": "",
+      "items": [
+        playlist("1", "Running", "me"),
+        playlist("2", "Chill", "me"),
+        playlist("3", "Someone else's", "other"),
+      ],
+      "limit": 20,
+      "next": null,
+      "offset": 0,
+      "previous": null,
+      "total": 3,
+    }))
+    .unwrap();
+    app.recent_playlist_ids = vec!["2".to_string()];
+    let names = |picker: &PlaylistPicker| {
+      picker
+        .matches(&app)
+        .into_iter()
+        .map(|e| match e {
+          PickerEntry::Playlist { name, .. } => name,
+          PickerEntry::Create(name) => format!("new {}", name),
+        })
+        .collect::<Vec<String>>()
+    };
+
+    let mut picker = PlaylistPicker::new(vec!["spotify:track:1".to_string()]);
+    assert_eq!(names(&picker), vec!["Chill", "Running"]);
+    picker.query = "run".to_string();
+    assert_eq!(names(&picker), vec!["Running", "new run"]);
+    picker.query = "Running".to_string();
+    assert_eq!(names(&picker), vec!["Running"]);
+  }
+}
@@ -0,0 +1,48 @@
+use crate::command_palette::fuzzy_score;
+use crate::config::profile_name;
+
+// Picks the profile the TUI switches to, `None` is the default profile
+pub struct ProfileMenu {
+  pub query: String,
+  pub selected: usize,
+  profiles: Vec<Option<String>>,
+}
+
+impl ProfileMenu {
+  pub fn new(profiles: Vec<Option<String>>) -> Self {
+    ProfileMenu {
+      query: String::new(),
+      selected: 0,
+      profiles,
+    }
+  }
+
+  pub fn matches(&self) -> Vec<Option<String>> {
+    let mut scored = self
+      .profiles
+      .iter()
+      .filter_map(|p| fuzzy_score(&self.query, profile_name(p.as_deref())).map(|s| (s, p)))
+      .collect::<Vec<_>>();
+    scored.sort_by_key(|(score, _)| -score);
+    scored.into_iter().map(|(_, p)| p.clone()).collect()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn matches_test() {
+    let mut menu = ProfileMenu::new(vec![
+      None,
+      Some("family".to_string()),
+      Some("work".to_string()),
+    ]);
+    assert_eq!(menu.matches().len(), 3);
+    menu.query = "wk".to_string();
+    assert_eq!(menu.matches(), vec![Some("work".to_string())]);
+    menu.query = "def".to_string();
+    assert_eq!(menu.matches(), vec![None]);
+  }
+}
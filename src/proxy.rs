@@ -0,0 +1,89 @@
+// Sends the requests to spotify (the API and the login) through a proxy. The
+// HTTP client of rspotify is created on the first request and reads the proxy
+// from `HTTP_PROXY`/`HTTPS_PROXY` then, so the chosen one is put there before
+use anyhow::{anyhow, Result};
+use std::env;
+
+const SCHEMES: [&str; 4] = ["http://", "https://", "socks5://", "socks5h://"];
+// Already read by the HTTP client (and curl)
+const REQUEST_VARS: [&str; 4] = ["HTTPS_PROXY", "https_proxy", "HTTP_PROXY", "http_proxy"];
+const ALL_VARS: [&str; 2] = ["ALL_PROXY", "all_proxy"];
+
+fn check(proxy: &str) -> Result<()> {
+  if SCHEMES.iter().any(|s| proxy.starts_with(s)) {
+    Ok(())
+  } else {
+    Err(anyhow!(
+      "proxy '{}' needs to start with one of {}",
+      proxy,
+      SCHEMES.join(", ")
+    ))
+  }
+}
+
+// The proxy that has to be set, `None` if there's none or the environment
+// has it already. `proxy` from the config comes first, `ALL_PROXY` last
+fn choose(config: Option<&str>, var: impl Fn(&str) -> Option<String>) -> Result<Option<String>> {
+  let set = |name: &str| var(name).filter(|v| !v.trim().is_empty());
+  if let Some(proxy) = config.map(str::trim).filter(|p| !p.is_empty()) {
+    check(proxy)?;
+    return Ok(Some(proxy.to_string()));
+  }
+  if REQUEST_VARS.iter().any(|name| set(name).is_some()) {
+    return Ok(None);
+  }
+  match ALL_VARS.iter().find_map(|name| set(name)) {
+    Some(proxy) => {
+      check(&proxy)?;
+      Ok(Some(proxy))
+    }
+    None => Ok(None),
+  }
+}
+
+// Has to run before the first request
+pub fn configure(config: Option<&str>) -> Result<()> {
+  if let Some(proxy) = choose(config, |name| env::var(name).ok())? {
+    // curl only reads the lowercase `http_proxy`, devices on the local
+    // network (see discovery) are still asked directly
+    env::set_var("HTTPS_PROXY", &proxy);
+    env::set_var("HTTP_PROXY", &proxy);
+  }
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn choose_test() {
+    let vars = |vars: &'static [(&'static str, &'static str)]| {
+      move |name: &str| {
+        vars
+          .iter()
+          .find(|(n, _)| *n == name)
+          .map(|(_, v)| v.to_string())
+      }
+    };
+    assert_eq!(choose(None, vars(&[])).unwrap(), None);
+    assert_eq!(
+      choose(
+        Some("socks5://localhost:1080"),
+        vars(&[("HTTPS_PROXY", "http://corp:3128")])
+      )
+      .unwrap(),
+      Some("socks5://localhost:1080".to_string())
+    );
+    assert_eq!(
+      choose(None, vars(&[("HTTPS_PROXY", "http://corp:3128")])).unwrap(),
+      None
+    );
+    assert_eq!(
+      choose(None, vars(&[("all_proxy", "socks5h://localhost:1080")])).unwrap(),
+      Some("socks5h://localhost:1080".to_string())
+    );
+    assert_eq!(choose(Some(""), vars(&[])).unwrap(), None);
+    assert!(choose(Some("corp:3128"), vars(&[])).is_err());
+  }
+}
@@ -0,0 +1,244 @@
+// Scrobbles the played tracks to Last.fm, with the same track-change detection
+// and the same rule for a finished listen as ListenBrainz. Like the lyrics it
+// uses curl, the parameters go on stdin so the password and the session key
+// don't show up in `ps`
+use crate::listenbrainz::Listen;
+use crate::lyrics::encode;
+use crate::user_config::BehaviorConfig;
+use anyhow::{anyhow, Result};
+use serde_json::Value;
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use tracing::warn;
+
+const API_URL: &str = "https://ws.audioscrobbler.com/2.0/";
+
+// Last.fm doesn't take tracks shorter than this
+const MIN_DURATION_MS: u32 = 30 * 1000;
+
+const TIMEOUT_SECONDS: &str = "10";
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ScrobbleType {
+  NowPlaying,
+  Scrobble,
+}
+
+impl ScrobbleType {
+  fn method(self) -> &'static str {
+    match self {
+      ScrobbleType::NowPlaying => "track.updateNowPlaying",
+      ScrobbleType::Scrobble => "track.scrobble",
+    }
+  }
+}
+
+#[derive(Clone)]
+pub struct Scrobbler {
+  api_key: String,
+  api_secret: String,
+  username: String,
+  password: String,
+  // Logged in on the first submission, and again after a failed login
+  session_key: Arc<Mutex<Option<String>>>,
+}
+
+impl Scrobbler {
+  // `None` unless `scrobble` is on, the config checks the credentials are set
+  pub fn from_config(behavior: &BehaviorConfig) -> Option<Self> {
+    if !behavior.scrobble {
+      return None;
+    }
+    Some(Scrobbler {
+      api_key: behavior.lastfm_api_key.clone()?,
+      api_secret: behavior.lastfm_api_secret.clone()?,
+      username: behavior.lastfm_username.clone()?,
+      password: behavior.lastfm_password.clone()?,
+      session_key: Arc::new(Mutex::new(None)),
+    })
+  }
+
+  // Like notifications, failures don't show up in the UI and it doesn't wait
+  pub fn submit(&self, scrobble_type: ScrobbleType, listen: &Listen) {
+    if listen.duration_ms < MIN_DURATION_MS {
+      return;
+    }
+    let scrobbler = self.clone();
+    let listen = listen.clone();
+    thread::spawn(move || {
+      if let Err(e) = scrobbler.send(scrobble_type, &listen) {
+        warn!(error = %e, "scrobbling failed");
+      }
+    });
+  }
+
+  fn send(&self, scrobble_type: ScrobbleType, listen: &Listen) -> Result<()> {
+    let session_key = self.session_key()?;
+    let duration = (listen.duration_ms / 1000).to_string();
+    let timestamp = listen.listened_at.to_string();
+    let mut params = vec![
+      ("method", scrobble_type.method()),
+      ("artist", listen.main_artist.as_str()),
+      ("track", listen.title.as_str()),
+      ("album", listen.album.as_str()),
+      ("duration", duration.as_str()),
+      ("api_key", self.api_key.as_str()),
+      ("sk", session_key.as_str()),
+    ];
+    if scrobble_type == ScrobbleType::Scrobble {
+      params.push(("timestamp", timestamp.as_str()));
+    }
+    self.post(&params).map(|_| ())
+  }
+
+  fn session_key(&self) -> Result<String> {
+    let mut session_key = self
+      .session_key
+      .lock()
+      .map_err(|_| anyhow!("the last.fm session is poisoned"))?;
+    if let Some(key) = session_key.as_ref() {
+      return Ok(key.clone());
+    }
+    let response = self.post(&[
+      ("method", "auth.getMobileSession"),
+      ("username", self.username.as_str()),
+      ("password", self.password.as_str()),
+      ("api_key", self.api_key.as_str()),
+    ])?;
+    let key = response["session"]["key"]
+      .as_str()
+      .ok_or_else(|| anyhow!("last.fm login failed: {}", response["message"]))?
+      .to_string();
+    *session_key = Some(key.clone());
+    Ok(key)
+  }
+
+  // Signs and sends the parameters, returns the answer if it isn't an error
+  fn post(&self, params: &[(&str, &str)]) -> Result<Value> {
+    let body = request_body(params, &self.api_secret);
+    let mut child = Command::new("curl")
+      .args(["--silent", "--max-time", TIMEOUT_SECONDS])
+      .args(["--data", "@-", API_URL])
+      .stdin(Stdio::piped())
+      .stdout(Stdio::piped())
+      .stderr(Stdio::null())
+      .spawn()
+      .map_err(|e| anyhow!("scrobbling needs curl: {}", e))?;
+    if let Some(mut stdin) = child.stdin.take() {
+      stdin.write_all(body.as_bytes())?;
+    }
+    let output = child.wait_with_output()?;
+    let response: Value = serde_json::from_slice(&output.stdout)
+      .map_err(|_| anyhow!("couldn't reach last.fm (curl {})", output.status))?;
+    if response.get("error").is_some() {
+      return Err(anyhow!("last.fm answered: {}", response["message"]));
+    }
+    Ok(response)
+  }
+}
+
+// The form body with the `api_sig` every write request needs: the md5 of the
+// sorted parameters and their values, followed by the secret
+fn request_body(params: &[(&str, &str)], secret: &str) -> String {
+  let mut sorted = params.to_vec();
+  sorted.sort_by_key(|(name, _)| *name);
+  let mut signed = sorted
+    .iter()
+    .map(|(name, value)| format!("{}{}", name, value))
+    .collect::<String>();
+  signed.push_str(secret);
+  let mut body = params
+    .iter()
+    .map(|(name, value)| format!("{}={}", name, encode(value)))
+    .collect::<Vec<String>>();
+  body.push(format!("api_sig={}", md5_hex(signed.as_bytes())));
+  // Not part of the signature
+  body.push("format=json".to_string());
+  body.join("&")
+}
+
+// RFC 1321, only used for the signature so it isn't worth a dependency
+fn md5_hex(input: &[u8]) -> String {
+  const SHIFTS: [u32; 64] = [
+    7, 12, 17, 22, 7, 12, 17, 22, 7, 12, 17, 22, 7, 12, 17, 22, 5, 9, 14, 20, 5, 9, 14, 20, 5, 9,
+    14, 20, 5, 9, 14, 20, 4, 11, 16, 23, 4, 11, 16, 23, 4, 11, 16, 23, 4, 11, 16, 23, 6, 10, 15,
+    21, 6, 10, 15, 21, 6, 10, 15, 21, 6, 10, 15, 21,
+  ];
+  let constants = (0..64)
+    .map(|i| ((i as f64 + 1.0).sin().abs() * 4_294_967_296.0) as u32)
+    .collect::<Vec<u32>>();
+
+  let mut message = input.to_vec();
+  message.push(0x80);
+  while message.len() % 64 != 56 {
+    message.push(0);
+  }
+  message.extend(((input.len() as u64).wrapping_mul(8)).to_le_bytes());
+
+  let mut state: [u32; 4] = [0x6745_2301, 0xefcd_ab89, 0x98ba_dcfe, 0x1032_5476];
+  for block in message.chunks(64) {
+    let words = block
+      .chunks(4)
+      .map(|w| u32::from_le_bytes([w[0], w[1], w[2], w[3]]))
+      .collect::<Vec<u32>>();
+    let [mut a, mut b, mut c, mut d] = state;
+    for i in 0..64 {
+      let (f, g) = match i / 16 {
+        0 => ((b & c) | (!b & d), i),
+        1 => ((d & b) | (!d & c), (5 * i + 1) % 16),
+        2 => (b ^ c ^ d, (3 * i + 5) % 16),
+        _ => (c ^ (b | !d), (7 * i) % 16),
+      };
+      let rotated = a
+        .wrapping_add(f)
+        .wrapping_add(constants[i])
+        .wrapping_add(words[g])
+        .rotate_left(SHIFTS[i]);
+      a = d;
+      d = c;
+      c = b;
+      b = b.wrapping_add(rotated);
+    }
+    for (s, v) in state.iter_mut().zip([a, b, c, d]) {
+      *s = s.wrapping_add(v);
+    }
+  }
+  state
+    .iter()
+    .flat_map(|s| s.to_le_bytes())
+    .map(|b| format!("{:02x}", b))
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn md5_hex_test() {
+    assert_eq!(md5_hex(b""), "d41d8cd98f00b204e9800998ecf8427e");
+    assert_eq!(
+      md5_hex(b"The quick brown fox jumps over the lazy dog"),
+      "9e107d9d372bb6826bd81d3542a419d6"
+    );
+    // More than one block
+    assert_eq!(md5_hex(&[b'a'; 100]), "36a92cc94a9e0fa21f625f8bfb007adf");
+  }
+
+  #[test]
+  fn request_body_test() {
+    let body = request_body(
+      &[("method", "track.scrobble"), ("artist", "AC/DC")],
+      "secret",
+    );
+    assert_eq!(
+      body,
+      format!(
+        "method=track.scrobble&artist=AC%2FDC&api_sig={}&format=json",
+        md5_hex(b"artistAC/DCmethodtrack.scrobblesecret")
+      )
+    );
+  }
+}
@@ -0,0 +1,211 @@
+// Runs spotifyd next to the TUI (the `spotifyd` section of the config), so
+// there's a device to play on without the official client
+use crate::user_config::SpotifydConfig;
+use std::io;
+use std::process::{Child, Command, Stdio};
+use std::sync::{
+  atomic::{AtomicBool, Ordering},
+  Arc, Mutex,
+};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// How often the supervisor checks if spotifyd is still running
+const WAIT_INTERVAL: Duration = Duration::from_millis(500);
+const RESTART_DELAY: Duration = Duration::from_secs(2);
+// A crash after running this long doesn't count towards `MAX_RESTARTS`
+const STABLE_AFTER: Duration = Duration::from_secs(60);
+// Crashes in a row, e.g. because of a wrong argument, it's given up after
+const MAX_RESTARTS: u32 = 5;
+// How long the TUI looks for the new device and how often
+const CLAIM_TIMEOUT: Duration = Duration::from_secs(60);
+const CLAIM_INTERVAL: Duration = Duration::from_secs(2);
+// spotifyd calls itself `Spotifyd@<hostname>` without `--device-name`
+const DEFAULT_DEVICE_PREFIX: &str = "spotifyd";
+
+// spotifyd forks into the background by default, it can only be supervised in
+// the foreground
+fn arguments(config: &SpotifydConfig) -> Vec<String> {
+  let mut args = config.args.clone().unwrap_or_default();
+  if !args.iter().any(|a| a == "--no-daemon") {
+    args.push("--no-daemon".to_string());
+  }
+  args
+}
+
+// The device name from the config, or the one passed to spotifyd
+fn device_name(config: &SpotifydConfig) -> Option<String> {
+  if let Some(name) = &config.device_name {
+    return Some(name.clone());
+  }
+  let args = config.args.as_deref().unwrap_or_default();
+  args.iter().enumerate().find_map(|(i, arg)| {
+    if arg == "--device-name" || arg == "-n" {
+      args.get(i + 1).cloned()
+    } else {
+      arg.strip_prefix("--device-name=").map(str::to_string)
+    }
+  })
+}
+
+pub fn is_device(config: &SpotifydConfig, name: &str) -> bool {
+  match device_name(config) {
+    Some(device_name) => device_name == name,
+    None => name.to_lowercase().starts_with(DEFAULT_DEVICE_PREFIX),
+  }
+}
+
+fn spawn(program: &str, args: &[String]) -> io::Result<Child> {
+  // Its output would end up on top of the UI
+  Command::new(program)
+    .args(args)
+    .stdin(Stdio::null())
+    .stdout(Stdio::null())
+    .stderr(Stdio::null())
+    .spawn()
+}
+
+// Keeps spotifyd running until it's dropped, which stops it
+pub struct Supervisor {
+  child: Arc<Mutex<Child>>,
+  stopped: Arc<AtomicBool>,
+}
+
+impl Supervisor {
+  // Only failing to start it the first time is an error, later crashes just
+  // restart it (if `restart_on_crash` is on)
+  pub fn start(config: &SpotifydConfig, program: &str) -> io::Result<Self> {
+    let args = arguments(config);
+    let child = Arc::new(Mutex::new(spawn(program, &args)?));
+    let stopped = Arc::new(AtomicBool::new(false));
+    let restart = config.restart_on_crash.unwrap_or(true);
+    let program = program.to_string();
+
+    let (child_ref, stopped_ref) = (Arc::clone(&child), Arc::clone(&stopped));
+    thread::spawn(move || {
+      let mut crashes = 0;
+      loop {
+        let started = Instant::now();
+        let status = loop {
+          thread::sleep(WAIT_INTERVAL);
+          if stopped_ref.load(Ordering::SeqCst) {
+            return;
+          }
+          match child_ref.lock().map(|mut c| c.try_wait()) {
+            Ok(Ok(Some(status))) => break status,
+            Ok(Ok(None)) => continue,
+            _ => return,
+          }
+        };
+        if status.success() || !restart {
+          return;
+        }
+        crashes = if started.elapsed() < STABLE_AFTER {
+          crashes + 1
+        } else {
+          1
+        };
+        if crashes > MAX_RESTARTS {
+          return;
+        }
+        thread::sleep(RESTART_DELAY);
+        if stopped_ref.load(Ordering::SeqCst) {
+          return;
+        }
+        match (spawn(&program, &args), child_ref.lock()) {
+          (Ok(new_child), Ok(mut child)) => *child = new_child,
+          _ => return,
+        }
+      }
+    });
+
+    Ok(Supervisor { child, stopped })
+  }
+}
+
+impl Drop for Supervisor {
+  fn drop(&mut self) {
+    self.stopped.store(true, Ordering::SeqCst);
+    if let Ok(mut child) = self.child.lock() {
+      let _ = child.kill();
+      let _ = child.wait();
+    }
+  }
+}
+
+// Looking for the spotifyd device after it was started, it takes a moment to
+// show up in the device list
+pub struct DeviceClaim {
+  started: Instant,
+  checked: Option<Instant>,
+}
+
+impl DeviceClaim {
+  pub fn new() -> Self {
+    DeviceClaim {
+      started: Instant::now(),
+      checked: None,
+    }
+  }
+
+  // `Some(true)` if the devices should be checked now, `None` once it's no
+  // use waiting anymore
+  pub fn poll(&mut self, now: Instant) -> Option<bool> {
+    if now.duration_since(self.started) > CLAIM_TIMEOUT {
+      return None;
+    }
+    let due = match self.checked {
+      Some(checked) => now.duration_since(checked) >= CLAIM_INTERVAL,
+      None => true,
+    };
+    if due {
+      self.checked = Some(now);
+    }
+    Some(due)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn config(args: &[&str], device_name: Option<&str>) -> SpotifydConfig {
+    SpotifydConfig {
+      path: Some("spotifyd".to_string()),
+      args: Some(args.iter().map(|a| a.to_string()).collect()),
+      restart_on_crash: None,
+      device_name: device_name.map(str::to_string),
+    }
+  }
+
+  #[test]
+  fn device_test() {
+    let default = config(&[], None);
+    assert_eq!(arguments(&default), vec!["--no-daemon"]);
+    assert!(is_device(&default, "Spotifyd@laptop"));
+    assert!(!is_device(&default, "Phone"));
+
+    let named = config(&["--no-daemon", "--device-name", "spt"], None);
+    assert_eq!(
+      arguments(&named),
+      vec!["--no-daemon", "--device-name", "spt"]
+    );
+    assert!(is_device(&named, "spt"));
+    assert!(!is_device(&named, "Spotifyd@laptop"));
+    assert!(is_device(&config(&["--device-name=spt"], None), "spt"));
+    assert!(is_device(
+      &config(&["-n", "spt"], Some("Kitchen")),
+      "Kitchen"
+    ));
+  }
+
+  #[test]
+  fn device_claim_test() {
+    let mut claim = DeviceClaim::new();
+    let start = claim.started;
+    assert_eq!(claim.poll(start), Some(true));
+    assert_eq!(claim.poll(start + Duration::from_secs(1)), Some(false));
+    assert_eq!(claim.poll(start + CLAIM_INTERVAL), Some(true));
+    assert_eq!(claim.poll(start + CLAIM_TIMEOUT * 2), None);
+  }
+}
@@ -0,0 +1,50 @@
+// Fixtures for the tests of several modules
+use rspotify::model::track::FullTrack;
+use serde_json::{json, Value};
+
+// A track with the given id, name and uri. `fields` replace the other defaults,
+// e.g. `json!({ "artists": [artist("Adele")] })`
+pub fn track(id: &str, fields: Value) -> FullTrack {
+  let mut track = json!({
+    "album": {
+      "artists": [],
+      "available_markets": [],
+      "external_urls": {},
+      "images": [],
+      "name": "Album",
+      "type": "album",
+    },
+    "artists": [],
+    "available_markets": [],
+    "disc_number": 1,
+    "duration_ms": 1000,
+    "explicit": false,
+    "external_ids": {},
+    "external_urls": {},
+    "href": null,
+    "id": id,
+    "is_local": false,
+    "name": id,
+    "popularity": 0,
+    "preview_url": null,
+    "track_number": 1,
+    "type": "track",
+    "uri": format!("spotify:track:{}", id),
+  });
+  if let (Some(track), Value::Object(fields)) = (track.as_object_mut(), fields) {
+    track.extend(fields);
+  }
+  serde_json::from_value(track).unwrap()
+}
+
+// A simplified artist for the `artists` of `track`
+pub fn artist(name: &str) -> Value {
+  json!({
+    "external_urls": {},
+    "href": null,
+    "id": null,
+    "name": name,
+    "type": "artist",
+    "uri": null,
+  })
+}
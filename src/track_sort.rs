@@ -0,0 +1,156 @@
+use crate::command_palette::fuzzy_score;
+use rspotify::model::{playlist::SimplifiedPlaylist, track::FullTrack};
+use std::collections::HashMap;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SortField {
+  Title,
+  Artist,
+  Album,
+  Duration,
+  DateAdded,
+  Popularity,
+}
+
+impl SortField {
+  const ALL: [SortField; 6] = [
+    SortField::Title,
+    SortField::Artist,
+    SortField::Album,
+    SortField::Duration,
+    SortField::DateAdded,
+    SortField::Popularity,
+  ];
+
+  // What a playlist in the sidebar can be sorted by
+  const PLAYLIST: [SortField; 1] = [SortField::Title];
+
+  fn name(self) -> &'static str {
+    match self {
+      SortField::Title => "Title",
+      SortField::Artist => "Artist",
+      SortField::Album => "Album",
+      SortField::Duration => "Duration",
+      SortField::DateAdded => "Date added",
+      SortField::Popularity => "Popularity",
+    }
+  }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TrackSort {
+  pub field: SortField,
+  pub descending: bool,
+}
+
+impl TrackSort {
+  pub fn label(self) -> String {
+    format!(
+      "{} {}",
+      self.field.name(),
+      if self.descending {
+        "descending"
+      } else {
+        "ascending"
+      }
+    )
+  }
+}
+
+// Stable, so tracks that compare equal keep their order. Tracks without a date
+// (like in recommendations) keep their order when sorting by date added
+pub fn sort_tracks(tracks: &mut [FullTrack], sort: TrackSort, added_at: &HashMap<String, i64>) {
+  let text = |s: &str| s.to_lowercase();
+  tracks.sort_by(|a, b| {
+    let ordering = match sort.field {
+      SortField::Title => text(&a.name).cmp(&text(&b.name)),
+      SortField::Artist => {
+        let artist = |t: &FullTrack| t.artists.first().map(|a| text(&a.name));
+        artist(a).cmp(&artist(b))
+      }
+      SortField::Album => text(&a.album.name).cmp(&text(&b.album.name)),
+      SortField::Duration => a.duration_ms.cmp(&b.duration_ms),
+      SortField::DateAdded => added_at.get(&a.uri).cmp(&added_at.get(&b.uri)),
+      SortField::Popularity => a.popularity.cmp(&b.popularity),
+    };
+    if sort.descending {
+      ordering.reverse()
+    } else {
+      ordering
+    }
+  });
+}
+
+// By name, the other fields are about tracks
+pub fn sort_playlists(playlists: &mut [SimplifiedPlaylist], sort: TrackSort) {
+  if sort.field != SortField::Title {
+    return;
+  }
+  playlists.sort_by(|a, b| {
+    let ordering = a.name.to_lowercase().cmp(&b.name.to_lowercase());
+    if sort.descending {
+      ordering.reverse()
+    } else {
+      ordering
+    }
+  });
+}
+
+// Picks how the track table (or with `playlists` the sidebar playlists) is
+// sorted, `None` is the order spotify sent
+#[derive(Default)]
+pub struct SortMenu {
+  pub query: String,
+  pub selected: usize,
+  pub playlists: bool,
+}
+
+impl SortMenu {
+  pub fn matches(&self) -> Vec<(String, Option<TrackSort>)> {
+    let mut entries = vec![("Original order".to_string(), None)];
+    let fields: &[SortField] = if self.playlists {
+      &SortField::PLAYLIST
+    } else {
+      &SortField::ALL
+    };
+    for &field in fields {
+      for descending in [false, true] {
+        let sort = TrackSort { field, descending };
+        entries.push((sort.label(), Some(sort)));
+      }
+    }
+    let mut scored = entries
+      .into_iter()
+      .filter_map(|(label, sort)| fuzzy_score(&self.query, &label).map(|s| (s, (label, sort))))
+      .collect::<Vec<_>>();
+    scored.sort_by_key(|(score, _)| -score);
+    scored.into_iter().map(|(_, entry)| entry).collect()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn sort_menu_test() {
+    let mut menu = SortMenu::default();
+    assert_eq!(menu.matches().len(), 13);
+    assert_eq!(menu.matches()[0].1, None);
+
+    let playlists = SortMenu {
+      playlists: true,
+      ..Default::default()
+    };
+    assert_eq!(playlists.matches().len(), 3);
+
+    menu.query = "dur desc".to_string();
+    assert_eq!(
+      menu.matches()[0].1,
+      Some(TrackSort {
+        field: SortField::Duration,
+        descending: true,
+      })
+    );
+  }
+}
@@ -1,16 +1,208 @@
 use super::util;
 use crate::app::App;
+use rspotify::model::audio::{AudioAnalysis, AudioAnalysisMeasure, AudioAnalysisSegment};
 use tui::{
   backend::Backend,
-  layout::{Constraint, Direction, Layout},
-  style::Style,
+  layout::{Constraint, Direction, Layout, Rect},
+  style::{Modifier, Style},
   text::{Span, Spans},
-  widgets::{BarChart, Block, Borders, Paragraph},
+  widgets::{Block, Borders, Paragraph},
   Frame,
 };
 const PITCHES: [&str; 12] = [
   "C", "C#", "D", "D#", "E", "F", "F#", "G", "G#", "A", "A#", "B",
 ];
+// How much of the track the timeline shows, the cursor is in the middle
+const WINDOW_SECONDS: f32 = 12.0;
+// From empty to full
+const SHADES: [char; 5] = [' ', '░', '▒', '▓', '█'];
+// Room for the row names on the left
+const LABEL_WIDTH: u16 = 9;
+
+// Which part of the track each column of the timeline covers
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Window {
+  from: f32,
+  step: f32,
+  columns: usize,
+}
+
+impl Window {
+  fn new(progress_seconds: f32, columns: usize) -> Self {
+    Window {
+      from: progress_seconds - WINDOW_SECONDS / 2.0,
+      step: WINDOW_SECONDS / columns.max(1) as f32,
+      columns,
+    }
+  }
+
+  fn to(self) -> f32 {
+    self.from + WINDOW_SECONDS
+  }
+
+  fn cursor(self) -> usize {
+    self.columns / 2
+  }
+
+  fn column(self, seconds: f32) -> Option<usize> {
+    let column = ((seconds - self.from) / self.step).floor();
+    if column >= 0.0 && (column as usize) < self.columns {
+      Some(column as usize)
+    } else {
+      None
+    }
+  }
+
+  // The middle of a column
+  fn seconds(self, column: usize) -> f32 {
+    self.from + (column as f32 + 0.5) * self.step
+  }
+}
+
+// The items overlapping the window. They're sorted by their start, so this
+// is a binary search and a frame only walks what's on screen
+fn visible<T>(items: &[T], span: impl Fn(&T) -> (f32, f32), window: Window) -> &[T] {
+  let first = items.partition_point(|item| {
+    let (start, duration) = span(item);
+    start + duration <= window.from
+  });
+  let last = items.partition_point(|item| span(item).0 < window.to());
+  &items[first..last.max(first)]
+}
+
+fn measures(items: &[AudioAnalysisMeasure], window: Window) -> &[AudioAnalysisMeasure] {
+  visible(items, |m| (m.start, m.duration), window)
+}
+
+// A marker where each item starts and a line in between
+fn marker_row(starts: impl Iterator<Item = f32>, window: Window, marker: char) -> Vec<char> {
+  let mut row = vec!['─'; window.columns];
+  for start in starts {
+    if let Some(column) = window.column(start) {
+      row[column] = marker;
+    }
+  }
+  row
+}
+
+// The segment playing in each column, `None` before and after the track
+fn segment_columns(segments: &[AudioAnalysisSegment], window: Window) -> Vec<Option<usize>> {
+  let end = |segment: &AudioAnalysisSegment| segment.start + segment.duration;
+  let mut current = segments.partition_point(|segment| end(segment) <= window.from);
+  (0..window.columns)
+    .map(|column| {
+      let seconds = window.seconds(column);
+      // Columns move forward in time, so the segment only ever moves forward
+      while current < segments.len() && end(&segments[current]) <= seconds {
+        current += 1;
+      }
+      segments
+        .get(current)
+        .filter(|segment| segment.start <= seconds)
+        .map(|_| current)
+    })
+    .collect()
+}
+
+// A row per value, shaded by how high it is in the visible part of the track.
+// Pitches are already between 0 and 1, timbre is scaled per row
+fn heatmap_rows(
+  segments: &[AudioAnalysisSegment],
+  columns: &[Option<usize>],
+  values: impl Fn(&AudioAnalysisSegment) -> &[f32],
+  normalize: bool,
+) -> Vec<Vec<char>> {
+  (0..12)
+    .map(|row| {
+      let value = |index: usize| values(&segments[index]).get(row).copied().unwrap_or(0.0);
+      let (min, max) = if normalize {
+        columns
+          .iter()
+          .flatten()
+          .map(|&index| value(index))
+          .fold((f32::MAX, f32::MIN), |(min, max), v| {
+            (min.min(v), max.max(v))
+          })
+      } else {
+        (0.0, 1.0)
+      };
+      columns
+        .iter()
+        .map(|column| match column {
+          Some(index) => {
+            let ratio = if max > min {
+              (value(*index) - min) / (max - min)
+            } else {
+              0.0
+            };
+            let shade = (ratio.clamp(0.0, 1.0) * (SHADES.len() - 1) as f32).round();
+            SHADES[shade as usize]
+          }
+          None => ' ',
+        })
+        .collect()
+    })
+    .collect()
+}
+
+// The column under the cursor stands out in every row
+fn row_spans(label: &str, row: Vec<char>, cursor: usize, style: Style) -> Spans<'static> {
+  let text = |chars: &[char]| chars.iter().collect::<String>();
+  let (before, rest) = row.split_at(cursor.min(row.len()));
+  let (at, after) = rest.split_at(rest.len().min(1));
+  Spans::from(vec![
+    Span::styled(
+      format!("{:<width$}", label, width = LABEL_WIDTH as usize),
+      style,
+    ),
+    Span::styled(text(before), style),
+    Span::styled(text(at), style.add_modifier(Modifier::REVERSED)),
+    Span::styled(text(after), style),
+  ])
+}
+
+fn summary(analysis: &AudioAnalysis, progress_seconds: f32) -> Vec<Spans<'static>> {
+  // The one that's playing, not the next one
+  let section_index = analysis
+    .sections
+    .partition_point(|s| s.start <= progress_seconds);
+  let bar = analysis
+    .bars
+    .partition_point(|b| b.start <= progress_seconds);
+  let beat = analysis
+    .beats
+    .partition_point(|b| b.start <= progress_seconds);
+  let section = match analysis.sections.get(section_index.saturating_sub(1)) {
+    Some(section) => section,
+    None => return vec![Spans::from("No section information available")],
+  };
+  vec![
+    Spans::from(format!(
+      "Section {}/{} | Bar {}/{} | Beat {}/{}",
+      section_index.max(1),
+      analysis.sections.len(),
+      bar,
+      analysis.bars.len(),
+      beat,
+      analysis.beats.len()
+    )),
+    Spans::from(format!(
+      "Tempo: {:.0} (confidence {:.0}%)",
+      section.tempo,
+      section.tempo_confidence * 100.0
+    )),
+    Spans::from(format!(
+      "Key: {} (confidence {:.0}%)",
+      PITCHES.get(section.key as usize).unwrap_or(&PITCHES[0]),
+      section.key_confidence * 100.0
+    )),
+    Spans::from(format!(
+      "Time Signature: {}/4 (confidence {:.0}%)",
+      section.time_signature,
+      section.time_signature_confidence * 100.0
+    )),
+  ]
+}
 
 pub fn draw<B>(f: &mut Frame<B>, app: &App)
 where
@@ -20,117 +212,162 @@ where
 
   let chunks = Layout::default()
     .direction(Direction::Vertical)
-    .constraints([Constraint::Min(5), Constraint::Length(95)].as_ref())
+    .constraints(
+      [
+        Constraint::Length(6),
+        Constraint::Length(5),
+        Constraint::Length(14),
+        Constraint::Min(14),
+      ]
+      .as_ref(),
+    )
     .margin(margin)
     .split(f.size());
 
-  let analysis_block = Block::default()
-    .title(Span::styled(
-      "Analysis",
-      Style::default().fg(app.user_config.theme.inactive),
-    ))
-    .borders(Borders::ALL)
-    .border_style(Style::default().fg(app.user_config.theme.inactive));
-
   let white = Style::default().fg(app.user_config.theme.text);
   let gray = Style::default().fg(app.user_config.theme.inactive);
-  let width = (chunks[1].width) as f32 / (1 + PITCHES.len()) as f32;
+  let bar = Style::default().fg(app.user_config.theme.analysis_bar);
   let tick_rate = app.user_config.behavior.tick_rate_milliseconds;
-  let bar_chart_title = &format!("Pitches | Tick Rate {} {}FPS", tick_rate, 1000 / tick_rate);
-
-  let bar_chart_block = Block::default()
-    .borders(Borders::ALL)
-    .style(white)
-    .title(Span::styled(bar_chart_title, gray))
-    .border_style(gray);
-
-  let empty_analysis_block = || {
-    Paragraph::new("No analysis available")
-      .block(analysis_block.clone())
-      .style(Style::default().fg(app.user_config.theme.text))
+  let block = |title: String| {
+    Block::default()
+      .borders(Borders::ALL)
+      .style(white)
+      .title(Span::styled(title, gray))
+      .border_style(gray)
   };
-  let empty_pitches_block = || {
-    Paragraph::new("No pitch information available")
-      .block(bar_chart_block.clone())
-      .style(Style::default().fg(app.user_config.theme.text))
+  let titles = [
+    "Analysis".to_string(),
+    format!("Timeline | {:.0}s", WINDOW_SECONDS),
+    format!("Pitches | Tick Rate {} {}FPS", tick_rate, 1000 / tick_rate),
+    "Timbre".to_string(),
+  ];
+  let render = |f: &mut Frame<B>, index: usize, lines: Vec<Spans<'static>>| {
+    let area: Rect = chunks[index];
+    f.render_widget(
+      Paragraph::new(lines).block(block(titles[index].clone())),
+      area,
+    );
   };
 
-  if let Some(analysis) = &app.audio_analysis {
-    let progress_seconds = (app.song_progress_ms as f32) / 1000.0;
-
-    let beat = analysis
-      .beats
-      .iter()
-      .find(|beat| beat.start >= progress_seconds);
-
-    let beat_offset = beat
-      .map(|beat| beat.start - progress_seconds)
-      .unwrap_or(0.0);
-    let segment = analysis
-      .segments
-      .iter()
-      .find(|segment| segment.start >= progress_seconds);
-    let section = analysis
-      .sections
-      .iter()
-      .find(|section| section.start >= progress_seconds);
-
-    if let (Some(segment), Some(section)) = (segment, section) {
-      let texts = vec![
-        Spans::from(format!(
-          "Tempo: {} (confidence {:.0}%)",
-          section.tempo,
-          section.tempo_confidence * 100.0
-        )),
-        Spans::from(format!(
-          "Key: {} (confidence {:.0}%)",
-          PITCHES.get(section.key as usize).unwrap_or(&PITCHES[0]),
-          section.key_confidence * 100.0
-        )),
-        Spans::from(format!(
-          "Time Signature: {}/4 (confidence {:.0}%)",
-          section.time_signature,
-          section.time_signature_confidence * 100.0
-        )),
-      ];
-      let p = Paragraph::new(texts)
-        .block(analysis_block)
-        .style(Style::default().fg(app.user_config.theme.text));
-      f.render_widget(p, chunks[0]);
-
-      let data: Vec<(&str, u64)> = segment
-        .clone()
-        .pitches
-        .iter()
-        .enumerate()
-        .map(|(index, pitch)| {
-          let display_pitch = *PITCHES.get(index).unwrap_or(&PITCHES[0]);
-          let bar_value = ((pitch * 1000.0) as u64)
-            // Add a beat offset to make the bar animate between beats
-            .checked_add((beat_offset * 3000.0) as u64)
-            .unwrap_or(0);
-
-          (display_pitch, bar_value)
-        })
-        .collect();
-
-      let analysis_bar = BarChart::default()
-        .block(bar_chart_block)
-        .data(&data)
-        .bar_width(width as u16)
-        .bar_style(Style::default().fg(app.user_config.theme.analysis_bar))
-        .value_style(
-          Style::default()
-            .fg(app.user_config.theme.analysis_bar_text)
-            .bg(app.user_config.theme.analysis_bar),
-        );
-      f.render_widget(analysis_bar, chunks[1]);
-    } else {
-      f.render_widget(empty_analysis_block(), chunks[0]);
-      f.render_widget(empty_pitches_block(), chunks[1]);
-    };
-  } else {
-    f.render_widget(empty_analysis_block(), chunks[0]);
-    f.render_widget(empty_pitches_block(), chunks[1]);
+  let analysis = match &app.audio_analysis {
+    Some(analysis) => analysis,
+    None => {
+      render(f, 0, vec![Spans::from("No analysis available")]);
+      render(f, 1, vec![]);
+      render(f, 2, vec![Spans::from("No pitch information available")]);
+      render(f, 3, vec![]);
+      return;
+    }
+  };
+
+  let progress_seconds = (app.song_progress_ms as f32) / 1000.0;
+  let columns = chunks[1].width.saturating_sub(2 + LABEL_WIDTH) as usize;
+  let window = Window::new(progress_seconds, columns);
+  let cursor = window.cursor();
+
+  render(f, 0, summary(analysis, progress_seconds));
+
+  let sections = visible(&analysis.sections, |s| (s.start, s.duration), window);
+  let timeline = vec![
+    row_spans(
+      "Sections",
+      marker_row(sections.iter().map(|s| s.start), window, '┃'),
+      cursor,
+      white,
+    ),
+    row_spans(
+      "Bars",
+      marker_row(
+        measures(&analysis.bars, window).iter().map(|b| b.start),
+        window,
+        '|',
+      ),
+      cursor,
+      white,
+    ),
+    row_spans(
+      "Beats",
+      marker_row(
+        measures(&analysis.beats, window).iter().map(|b| b.start),
+        window,
+        '•',
+      ),
+      cursor,
+      bar,
+    ),
+  ];
+  render(f, 1, timeline);
+
+  let playing = segment_columns(&analysis.segments, window);
+  let pitches = heatmap_rows(&analysis.segments, &playing, |s| &s.pitches, false)
+    .into_iter()
+    .zip(PITCHES.iter())
+    .map(|(row, pitch)| row_spans(pitch, row, cursor, bar))
+    .collect();
+  render(f, 2, pitches);
+
+  let timbre = heatmap_rows(&analysis.segments, &playing, |s| &s.timbre, true)
+    .into_iter()
+    .enumerate()
+    .map(|(index, row)| row_spans(&format!("T{}", index + 1), row, cursor, bar))
+    .collect();
+  render(f, 3, timbre);
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn segment(start: f32, pitch: f32) -> AudioAnalysisSegment {
+    AudioAnalysisSegment {
+      start,
+      duration: 1.0,
+      confidence: 1.0,
+      loudness_start: 0.0,
+      loudness_max_time: 0.0,
+      loudness_max: 0.0,
+      loudness_end: None,
+      pitches: vec![pitch; 12],
+      timbre: vec![pitch * 10.0; 12],
+    }
+  }
+
+  #[test]
+  fn timeline_test() {
+    // 12 columns of a second each, from 4s to 16s
+    let window = Window::new(10.0, 12);
+    assert_eq!(window.column(3.9), None);
+    assert_eq!(window.column(4.0), Some(0));
+    assert_eq!(window.column(10.5), Some(6));
+    assert_eq!(window.cursor(), 6);
+
+    let beats = (0..30)
+      .map(|i| AudioAnalysisMeasure {
+        start: i as f32,
+        duration: 1.0,
+        confidence: 1.0,
+      })
+      .collect::<Vec<_>>();
+    let shown = measures(&beats, window);
+    assert_eq!(shown.first().map(|b| b.start), Some(4.0));
+    assert_eq!(shown.last().map(|b| b.start), Some(15.0));
+    let row = marker_row([5.0, 7.0].iter().copied(), window, '|');
+    assert_eq!(row.iter().collect::<String>(), "─|─|────────");
+  }
+
+  #[test]
+  fn heatmap_test() {
+    let segments = vec![segment(0.0, 0.0), segment(5.0, 1.0), segment(6.0, 0.5)];
+    let window = Window::new(8.0, 6);
+    // Columns are 2s wide from 2s, the track has nothing from 1s to 5s
+    let columns = segment_columns(&segments, window);
+    assert_eq!(columns, vec![None, Some(1), None, None, None, None]);
+
+    let window = Window::new(6.0, 12);
+    let columns = segment_columns(&segments, window);
+    let pitches = heatmap_rows(&segments, &columns, |s| &s.pitches, false);
+    assert_eq!(pitches[0].iter().collect::<String>(), "     █▒     ");
+    let timbre = heatmap_rows(&segments, &columns, |s| &s.timbre, true);
+    assert_eq!(timbre[0].iter().collect::<String>(), "     █▒     ");
   }
 }
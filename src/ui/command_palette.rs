@@ -0,0 +1,183 @@
+use crate::app::App;
+use crate::command_palette::Action;
+use crate::config::profile_name;
+use crate::playlist_picker::PickerEntry;
+use tui::{
+  backend::Backend,
+  layout::{Constraint, Direction, Layout, Rect},
+  style::{Modifier, Style},
+  text::{Span, Spans},
+  widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph},
+  Frame,
+};
+
+// How many rows fit in an overlay before it scrolls
+const MAX_VISIBLE_ROWS: u16 = 12;
+
+pub fn draw<B>(f: &mut Frame<B>, app: &App)
+where
+  B: Backend,
+{
+  if let Some(palette) = &app.command_palette {
+    let rows = palette
+      .matches()
+      .iter()
+      .map(|command| {
+        let key = match command.action {
+          Action::Key(key) => app.user_config.keys.describe(key(&app.user_config.keys)),
+          _ => String::new(),
+        };
+        (command.name.to_string(), key)
+      })
+      .collect();
+    draw_overlay(
+      f,
+      app,
+      "Command palette",
+      &palette.query,
+      rows,
+      palette.selected,
+    );
+  }
+  if let Some(finder) = &app.library_finder {
+    let rows = finder
+      .matches(app)
+      .iter()
+      .map(|entry| (entry.name.clone(), entry.kind().to_string()))
+      .collect();
+    draw_overlay(f, app, "Library", &finder.query, rows, finder.selected);
+  }
+  if let Some(picker) = &app.playlist_picker {
+    let rows = picker
+      .matches(app)
+      .into_iter()
+      .map(|entry| match entry {
+        PickerEntry::Playlist { name, total, .. } => (name, format!("{} tracks", total)),
+        PickerEntry::Create(name) => (format!("Create '{}'", name), "new playlist".to_string()),
+      })
+      .collect();
+    let title = match picker.uris.len() {
+      1 => "Add the track to".to_string(),
+      n => format!("Add {} tracks to", n),
+    };
+    draw_overlay(f, app, &title, &picker.query, rows, picker.selected);
+  }
+  if let Some(menu) = &app.sort_menu {
+    let current = if menu.playlists {
+      app.playlist_sort
+    } else {
+      app.track_table.sort()
+    };
+    let rows = menu
+      .matches()
+      .into_iter()
+      .map(|(label, sort)| {
+        let hint = if sort == current { "current" } else { "" };
+        (label, hint.to_string())
+      })
+      .collect();
+    draw_overlay(f, app, "Sort by", &menu.query, rows, menu.selected);
+  }
+  if let Some(menu) = &app.profile_menu {
+    let rows = menu
+      .matches()
+      .into_iter()
+      .map(|profile| {
+        let hint = if profile == app.profile {
+          "current"
+        } else {
+          ""
+        };
+        (
+          profile_name(profile.as_deref()).to_string(),
+          hint.to_string(),
+        )
+      })
+      .collect();
+    draw_overlay(
+      f,
+      app,
+      "Switch to profile",
+      &menu.query,
+      rows,
+      menu.selected,
+    );
+  }
+}
+
+// A query box above the matching rows, each with a hint on the right
+fn draw_overlay<B>(
+  f: &mut Frame<B>,
+  app: &App,
+  title: &str,
+  query: &str,
+  rows: Vec<(String, String)>,
+  selected: usize,
+) where
+  B: Backend,
+{
+  let theme = app.user_config.theme;
+
+  let bounds = f.size();
+  let width = std::cmp::min(bounds.width.saturating_sub(4), 60);
+  // The query box, the list borders and at least one row
+  let height = std::cmp::min(
+    bounds.height.saturating_sub(2),
+    3 + 2 + (rows.len() as u16).clamp(1, MAX_VISIBLE_ROWS),
+  );
+  let rect = Rect::new((bounds.width - width) / 2, bounds.height / 6, width, height);
+  f.render_widget(Clear, rect);
+
+  let chunks = Layout::default()
+    .direction(Direction::Vertical)
+    .constraints([Constraint::Length(3), Constraint::Min(1)].as_ref())
+    .split(rect);
+
+  let input = Paragraph::new(Span::styled(
+    format!("> {}", query),
+    Style::default().fg(theme.text),
+  ))
+  .block(
+    Block::default()
+      .borders(Borders::ALL)
+      .title(Span::styled(title, Style::default().fg(theme.active)))
+      .border_style(Style::default().fg(theme.active)),
+  );
+  f.render_widget(input, chunks[0]);
+
+  let row_width = chunks[1].width.saturating_sub(2) as usize;
+  let is_empty = rows.is_empty();
+  let items = rows
+    .into_iter()
+    .map(|(name, hint)| {
+      let padding = row_width.saturating_sub(name.chars().count() + hint.chars().count());
+      ListItem::new(Spans::from(vec![
+        Span::raw(name),
+        Span::raw(" ".repeat(padding)),
+        Span::styled(hint, Style::default().fg(theme.hint)),
+      ]))
+    })
+    .collect::<Vec<ListItem>>();
+  let list = if is_empty {
+    List::new(vec![ListItem::new("No matches")])
+  } else {
+    List::new(items)
+  }
+  .block(
+    Block::default()
+      .borders(Borders::ALL)
+      .border_style(Style::default().fg(theme.inactive)),
+  )
+  .style(Style::default().fg(theme.text))
+  .highlight_style(
+    Style::default()
+      .fg(theme.selected)
+      .add_modifier(Modifier::BOLD),
+  );
+
+  let mut state = ListState::default();
+  if !is_empty {
+    state.select(Some(selected));
+  }
+  f.render_stateful_widget(list, chunks[1], &mut state);
+}
@@ -4,87 +4,114 @@ pub fn get_help_docs(key_bindings: &KeyBindings) -> Vec<Vec<String>> {
   vec![
     vec![
       String::from("Scroll down to next result page"),
-      key_bindings.next_page.to_string(),
+      key_bindings.describe(key_bindings.next_page),
       String::from("Pagination"),
     ],
     vec![
       String::from("Scroll up to previous result page"),
-      key_bindings.previous_page.to_string(),
+      key_bindings.describe(key_bindings.previous_page),
       String::from("Pagination"),
     ],
     vec![
       String::from("Jump to start of playlist"),
-      key_bindings.jump_to_start.to_string(),
+      key_bindings.describe(key_bindings.jump_to_start),
       String::from("Pagination"),
     ],
     vec![
       String::from("Jump to end of playlist"),
-      key_bindings.jump_to_end.to_string(),
+      key_bindings.describe(key_bindings.jump_to_end),
       String::from("Pagination"),
     ],
     vec![
       String::from("Jump to currently playing album"),
-      key_bindings.jump_to_album.to_string(),
+      key_bindings.describe(key_bindings.jump_to_album),
       String::from("General"),
     ],
     vec![
       String::from("Jump to currently playing artist's album list"),
-      key_bindings.jump_to_artist_album.to_string(),
+      key_bindings.describe(key_bindings.jump_to_artist_album),
       String::from("General"),
     ],
     vec![
       String::from("Jump to current play context"),
-      key_bindings.jump_to_context.to_string(),
+      key_bindings.describe(key_bindings.jump_to_context),
       String::from("General"),
     ],
     vec![
       String::from("Increase volume by 10%"),
-      key_bindings.increase_volume.to_string(),
+      key_bindings.describe(key_bindings.increase_volume),
       String::from("General"),
     ],
     vec![
       String::from("Decrease volume by 10%"),
-      key_bindings.decrease_volume.to_string(),
+      key_bindings.describe(key_bindings.decrease_volume),
       String::from("General"),
     ],
     vec![
       String::from("Skip to next track"),
-      key_bindings.next_track.to_string(),
+      key_bindings.describe(key_bindings.next_track),
       String::from("General"),
     ],
     vec![
       String::from("Skip to previous track"),
-      key_bindings.previous_track.to_string(),
+      key_bindings.describe(key_bindings.previous_track),
       String::from("General"),
     ],
     vec![
       String::from("Seek backwards 5 seconds"),
-      key_bindings.seek_backwards.to_string(),
+      key_bindings.describe(key_bindings.seek_backwards),
       String::from("General"),
     ],
     vec![
       String::from("Seek forwards 5 seconds"),
-      key_bindings.seek_forwards.to_string(),
+      key_bindings.describe(key_bindings.seek_forwards),
       String::from("General"),
     ],
     vec![
       String::from("Toggle shuffle"),
-      key_bindings.shuffle.to_string(),
+      key_bindings.describe(key_bindings.shuffle),
       String::from("General"),
     ],
     vec![
       String::from("Copy url to currently playing song/episode"),
-      key_bindings.copy_song_url.to_string(),
+      key_bindings.describe(key_bindings.copy_song_url),
       String::from("General"),
     ],
     vec![
       String::from("Copy url to currently playing album/show"),
-      key_bindings.copy_album_url.to_string(),
+      key_bindings.describe(key_bindings.copy_album_url),
+      String::from("General"),
+    ],
+    vec![
+      String::from(
+        "Save or unsave the album of the hovered track or album, or of the playing track",
+      ),
+      key_bindings.describe(key_bindings.toggle_save_album),
+      String::from("General"),
+    ],
+    vec![
+      String::from("Seek to a tenth of the track, followed by a digit (e.g. 5 for 50%)"),
+      key_bindings.describe(key_bindings.seek_to_percent),
+      String::from("General"),
+    ],
+    vec![
+      String::from("Switch to another account profile (see `spt --profile`)"),
+      key_bindings.describe(key_bindings.switch_profile),
+      String::from("General"),
+    ],
+    vec![
+      String::from("Hide or show the library and playlists"),
+      key_bindings.describe(key_bindings.toggle_sidebar),
+      String::from("General"),
+    ],
+    vec![
+      String::from("Undo the last unlike, unfollow or playlist deletion"),
+      key_bindings.describe(key_bindings.undo),
       String::from("General"),
     ],
     vec![
       String::from("Cycle repeat mode"),
-      key_bindings.repeat.to_string(),
+      key_bindings.describe(key_bindings.repeat),
       String::from("General"),
     ],
     vec![
@@ -124,12 +151,12 @@ pub fn get_help_docs(key_bindings: &KeyBindings) -> Vec<Vec<String>> {
     ],
     vec![
       String::from("Enter input for search"),
-      key_bindings.search.to_string(),
+      key_bindings.describe(key_bindings.search),
       String::from("General"),
     ],
     vec![
       String::from("Pause/Resume playback"),
-      key_bindings.toggle_playback.to_string(),
+      key_bindings.describe(key_bindings.toggle_playback),
       String::from("General"),
     ],
     vec![
@@ -139,22 +166,42 @@ pub fn get_help_docs(key_bindings: &KeyBindings) -> Vec<Vec<String>> {
     ],
     vec![
       String::from("Go to audio analysis screen"),
-      key_bindings.audio_analysis.to_string(),
+      key_bindings.describe(key_bindings.audio_analysis),
       String::from("General"),
     ],
     vec![
       String::from("Go to playbar only screen (basic view)"),
-      key_bindings.basic_view.to_string(),
+      key_bindings.describe(key_bindings.basic_view),
+      String::from("General"),
+    ],
+    vec![
+      String::from("Find playlists, albums, artists and liked songs that were loaded"),
+      key_bindings.describe(key_bindings.library_finder),
+      String::from("General"),
+    ],
+    vec![
+      String::from("Open the command palette to search all actions"),
+      key_bindings.describe(key_bindings.command_palette),
+      String::from("General"),
+    ],
+    vec![
+      String::from("Show the lyrics of the current track"),
+      key_bindings.describe(key_bindings.lyrics),
+      String::from("General"),
+    ],
+    vec![
+      String::from("Toggle Discord Rich Presence"),
+      key_bindings.describe(key_bindings.toggle_discord_presence),
       String::from("General"),
     ],
     vec![
       String::from("Go back or exit when nowhere left to back to"),
-      key_bindings.back.to_string(),
+      key_bindings.describe(key_bindings.back),
       String::from("General"),
     ],
     vec![
       String::from("Select device to play music on"),
-      key_bindings.manage_devices.to_string(),
+      key_bindings.describe(key_bindings.manage_devices),
       String::from("General"),
     ],
     vec![
@@ -164,22 +211,113 @@ pub fn get_help_docs(key_bindings: &KeyBindings) -> Vec<Vec<String>> {
     ],
     vec![
       String::from("Save track in list or table"),
-      String::from("s"),
+      key_bindings.describe(key_bindings.save_item),
       String::from("Selected block"),
     ],
     vec![
       String::from("Start playback or enter album/artist/playlist"),
-      key_bindings.submit.to_string(),
+      key_bindings.describe(key_bindings.submit),
       String::from("Selected block"),
     ],
     vec![
       String::from("Play recommendations for song/artist"),
-      String::from("r"),
+      key_bindings.describe(key_bindings.play_recommendations),
       String::from("Selected block"),
     ],
+    vec![
+      String::from("Save the recommendations as a new playlist"),
+      key_bindings.describe(key_bindings.follow),
+      String::from("Recommendations"),
+    ],
+    vec![
+      String::from("Move track down/up in your playlist"),
+      format!(
+        "{} / {}",
+        key_bindings.describe(key_bindings.move_track_down),
+        key_bindings.describe(key_bindings.move_track_up)
+      ),
+      String::from("Selected Playlist"),
+    ],
+    vec![
+      String::from("Mark/unmark track"),
+      key_bindings.describe(key_bindings.mark_track),
+      String::from("Track table"),
+    ],
+    vec![
+      String::from("Start/end visual selection"),
+      key_bindings.describe(key_bindings.visual_select),
+      String::from("Track table"),
+    ],
+    vec![
+      String::from("Clear marked tracks"),
+      key_bindings.describe(key_bindings.clear_marks),
+      String::from("Track table"),
+    ],
+    vec![
+      String::from("Filter the tracks by title, artist or album (enter keeps it, esc clears it)"),
+      key_bindings.describe(key_bindings.filter),
+      String::from("Track table"),
+    ],
+    vec![
+      String::from("Sort the tracks by title, artist, album, duration, date added or popularity"),
+      key_bindings.describe(key_bindings.sort_tracks),
+      String::from("Track table"),
+    ],
+    vec![
+      String::from("Sort the playlists by name"),
+      key_bindings.describe(key_bindings.sort_tracks),
+      String::from("Playlists"),
+    ],
+    vec![
+      String::from("Show the tempo, key, energy, danceability, valence and loudness of a track"),
+      key_bindings.describe(key_bindings.audio_features),
+      String::from("Hovered over track"),
+    ],
+    vec![
+      String::from("Add the marked or hovered tracks to a playlist, or a new one"),
+      key_bindings.describe(key_bindings.add_to_playlist),
+      String::from("Hovered over track"),
+    ],
+    vec![
+      String::from("Queue or like all marked tracks"),
+      format!(
+        "{} / {}",
+        key_bindings.describe(key_bindings.add_item_to_queue),
+        key_bindings.describe(key_bindings.save_item)
+      ),
+      String::from("Track table"),
+    ],
+    vec![
+      String::from("Switch between the top tracks, albums and related artists"),
+      String::from("<Left> / <Right>"),
+      String::from("Artist"),
+    ],
+    vec![
+      String::from("Follow / unfollow the artist, or the hovered related artist"),
+      format!(
+        "{} / {}",
+        key_bindings.describe(key_bindings.follow),
+        key_bindings.describe(key_bindings.unfollow)
+      ),
+      String::from("Artist"),
+    ],
+    vec![
+      String::from("Follow / unfollow the hovered artist"),
+      format!(
+        "{} / {}",
+        key_bindings.describe(key_bindings.follow),
+        key_bindings.describe(key_bindings.unfollow)
+      ),
+      String::from("Top Items"),
+    ],
+    vec![
+      String::from("Cycle the albums shown (all, albums, singles, compilations, appears on)"),
+      key_bindings.describe(key_bindings.cycle_range),
+      String::from("Artist -> Albums"),
+    ],
     vec![
       String::from("Play all tracks for artist"),
-      String::from("e"),
+      key_bindings.describe(key_bindings.play_artist),
       String::from("Library -> Artists"),
     ],
     vec![
@@ -234,37 +372,47 @@ pub fn get_help_docs(key_bindings: &KeyBindings) -> Vec<Vec<String>> {
     ],
     vec![
       String::from("Delete saved album"),
-      String::from("D"),
+      key_bindings.describe(key_bindings.unfollow),
       String::from("Library -> Albums"),
     ],
     vec![
       String::from("Delete saved playlist"),
-      String::from("D"),
+      key_bindings.describe(key_bindings.unfollow),
       String::from("Playlist"),
     ],
     vec![
       String::from("Follow an artist/playlist"),
-      String::from("w"),
+      key_bindings.describe(key_bindings.follow),
       String::from("Search result"),
     ],
     vec![
       String::from("Save (like) album to library"),
-      String::from("w"),
+      key_bindings.describe(key_bindings.follow),
       String::from("Search result"),
     ],
     vec![
       String::from("Play random song in playlist"),
-      String::from("S"),
+      key_bindings.describe(key_bindings.play_random_song),
       String::from("Selected Playlist"),
     ],
     vec![
       String::from("Toggle sort order of podcast episodes"),
-      String::from("S"),
+      key_bindings.describe(key_bindings.sort_episodes),
       String::from("Selected Show"),
     ],
+    vec![
+      String::from("Switch between top tracks and top artists"),
+      key_bindings.describe(key_bindings.switch_tab),
+      String::from("Top Items"),
+    ],
+    vec![
+      String::from("Cycle the time range (4 weeks, 6 months, all time)"),
+      key_bindings.describe(key_bindings.cycle_range),
+      String::from("Top Items"),
+    ],
     vec![
       String::from("Add track to queue"),
-      key_bindings.add_item_to_queue.to_string(),
+      key_bindings.describe(key_bindings.add_item_to_queue),
       String::from("Hovered over track"),
     ],
   ]
@@ -0,0 +1,139 @@
+// Where the panes of the main layout go, sized by the `layout` section of the
+// config. The sidebar (library and playlists) can be collapsed to give the
+// content all of the width
+use crate::user_config::LayoutConfig;
+use tui::layout::{Constraint, Direction, Layout, Rect};
+
+const SIDEBAR_WIDTH_PERCENT: u16 = 20;
+const LIBRARY_HEIGHT_PERCENT: u16 = 30;
+const PLAYBAR_HEIGHT: u16 = 6;
+const SEARCH_HEIGHT: u16 = 3;
+
+impl LayoutConfig {
+  pub fn sidebar_width_percent(&self) -> u16 {
+    self
+      .sidebar_width_percent
+      .unwrap_or(SIDEBAR_WIDTH_PERCENT)
+      .clamp(10, 60)
+  }
+
+  pub fn library_height_percent(&self) -> u16 {
+    self
+      .library_height_percent
+      .unwrap_or(LIBRARY_HEIGHT_PERCENT)
+      .clamp(10, 90)
+  }
+
+  // The borders and a line of text at least
+  pub fn playbar_height(&self) -> u16 {
+    self.playbar_height.unwrap_or(PLAYBAR_HEIGHT).clamp(3, 12)
+  }
+}
+
+pub struct MainLayout {
+  // Only if the search isn't at the top of the sidebar
+  pub search: Option<Rect>,
+  // `None` while it's collapsed
+  pub sidebar: Option<Rect>,
+  pub content: Rect,
+  pub playbar: Rect,
+}
+
+pub fn main_layout(
+  area: Rect,
+  config: &LayoutConfig,
+  search_in_sidebar: bool,
+  sidebar_collapsed: bool,
+) -> MainLayout {
+  let search_height = if search_in_sidebar { 0 } else { SEARCH_HEIGHT };
+  let rows = Layout::default()
+    .direction(Direction::Vertical)
+    .constraints(
+      [
+        Constraint::Length(search_height),
+        Constraint::Min(1),
+        Constraint::Length(config.playbar_height()),
+      ]
+      .as_ref(),
+    )
+    .split(area);
+  let (sidebar, content) = if sidebar_collapsed {
+    (None, rows[1])
+  } else {
+    let width = config.sidebar_width_percent();
+    let columns = Layout::default()
+      .direction(Direction::Horizontal)
+      .constraints(
+        [
+          Constraint::Percentage(width),
+          Constraint::Percentage(100 - width),
+        ]
+        .as_ref(),
+      )
+      .split(rows[1]);
+    (Some(columns[0]), columns[1])
+  };
+  MainLayout {
+    search: Some(rows[0]).filter(|_| !search_in_sidebar),
+    sidebar,
+    content,
+    playbar: rows[2],
+  }
+}
+
+pub struct SidebarLayout {
+  pub search: Option<Rect>,
+  pub library: Rect,
+  pub playlists: Rect,
+}
+
+pub fn sidebar_layout(area: Rect, config: &LayoutConfig, with_search: bool) -> SidebarLayout {
+  let search_height = if with_search { SEARCH_HEIGHT } else { 0 };
+  let library = config.library_height_percent();
+  let rows = Layout::default()
+    .direction(Direction::Vertical)
+    .constraints(
+      [
+        Constraint::Length(search_height),
+        Constraint::Percentage(library),
+        Constraint::Percentage(100 - library),
+      ]
+      .as_ref(),
+    )
+    .split(area);
+  SidebarLayout {
+    search: Some(rows[0]).filter(|_| with_search),
+    library: rows[1],
+    playlists: rows[2],
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn main_layout_test() {
+    let area = Rect::new(0, 0, 200, 50);
+    let config = LayoutConfig::default();
+    let layout = main_layout(area, &config, true, false);
+    assert!(layout.search.is_none());
+    assert_eq!(layout.sidebar, Some(Rect::new(0, 0, 40, 44)));
+    assert_eq!(layout.content, Rect::new(40, 0, 160, 44));
+    assert_eq!(layout.playbar, Rect::new(0, 44, 200, 6));
+
+    let config = LayoutConfig {
+      sidebar_width_percent: Some(95),
+      playbar_height: Some(4),
+      ..LayoutConfig::default()
+    };
+    let layout = main_layout(area, &config, false, false);
+    assert_eq!(layout.search, Some(Rect::new(0, 0, 200, 3)));
+    assert_eq!(layout.sidebar.map(|s| s.width), Some(120));
+    assert_eq!(layout.playbar.height, 4);
+
+    let layout = main_layout(area, &config, false, true);
+    assert!(layout.sidebar.is_none());
+    assert_eq!(layout.content, Rect::new(0, 3, 200, 43));
+  }
+}
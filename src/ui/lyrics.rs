@@ -0,0 +1,91 @@
+use super::{draw_playbar, util};
+use crate::app::App;
+use crate::lyrics::LyricsStatus;
+use rspotify::model::{context::CurrentlyPlaybackContext, PlayingItem};
+use tui::{
+  backend::Backend,
+  layout::{Alignment, Constraint, Direction, Layout},
+  style::{Modifier, Style},
+  text::{Span, Spans},
+  widgets::{Block, Borders, Paragraph},
+  Frame,
+};
+
+pub fn draw<B>(f: &mut Frame<B>, app: &App)
+where
+  B: Backend,
+{
+  let margin = util::get_main_layout_margin(app);
+
+  let chunks = Layout::default()
+    .direction(Direction::Vertical)
+    .constraints(
+      [
+        Constraint::Min(3),
+        Constraint::Length(util::BASIC_VIEW_HEIGHT),
+      ]
+      .as_ref(),
+    )
+    .margin(margin)
+    .split(f.size());
+
+  let block = Block::default()
+    .title(Span::styled(
+      "Lyrics",
+      Style::default().fg(app.user_config.theme.inactive),
+    ))
+    .borders(Borders::ALL)
+    .border_style(Style::default().fg(app.user_config.theme.inactive));
+
+  let status = match &app.current_playback_context {
+    Some(CurrentlyPlaybackContext {
+      item: Some(PlayingItem::Track(track)),
+      ..
+    }) => app.lyrics.get(&track.uri),
+    _ => None,
+  };
+
+  let text_style = Style::default().fg(app.user_config.theme.text);
+  let message = |text: &str| Paragraph::new(text.to_string()).style(text_style);
+  let paragraph = match status {
+    Some(LyricsStatus::Found(lyrics)) => {
+      let current = lyrics.current_line(app.song_progress_ms as u32);
+      let highlight = if app.user_config.behavior.enable_text_emphasis {
+        Style::default()
+          .fg(app.user_config.theme.active)
+          .add_modifier(Modifier::BOLD)
+      } else {
+        Style::default().fg(app.user_config.theme.active)
+      };
+      let lines = lyrics
+        .lines()
+        .into_iter()
+        .enumerate()
+        .map(|(i, line)| {
+          let style = if Some(i) == current {
+            highlight
+          } else {
+            text_style
+          };
+          Spans::from(Span::styled(line.to_string(), style))
+        })
+        .collect::<Vec<Spans>>();
+      // Keeps the current line in the middle of the block
+      let scroll = match current {
+        Some(i) => (i as u16).saturating_sub(chunks[0].height.saturating_sub(2) / 2),
+        None => app.lyrics_scroll,
+      };
+      Paragraph::new(lines).scroll((scroll, 0))
+    }
+    Some(LyricsStatus::Loading) => message("Loading lyrics..."),
+    Some(LyricsStatus::Missing) => message("No lyrics found for this track"),
+    Some(LyricsStatus::Failed(e)) => message(&format!("Couldn't load the lyrics: {}", e)),
+    None => message("Lyrics are only available for tracks"),
+  };
+
+  f.render_widget(
+    paragraph.block(block).alignment(Alignment::Center),
+    chunks[0],
+  );
+  draw_playbar(f, app, chunks[1]);
+}
@@ -1,29 +1,38 @@
 pub mod audio_analysis;
+pub mod command_palette;
 pub mod help;
+pub mod layout;
+pub mod lyrics;
+pub mod track_features;
 pub mod util;
 use super::{
   app::{
-    ActiveBlock, AlbumTableContext, App, ArtistBlock, EpisodeTableContext, RecommendationsContext,
-    RouteId, SearchResultBlock, LIBRARY_OPTIONS,
+    ActiveBlock, AlbumTableContext, App, ArtistBlock, ClickArea, ClickTarget, EpisodeTableContext,
+    RecommendationsContext, RouteId, SearchResultBlock, TablePosition, TopItemsKind,
+    LIBRARY_OPTIONS,
   },
   banner::BANNER,
+  format::{render, Format},
 };
 use help::get_help_docs;
 use rspotify::model::show::ResumePoint;
-use rspotify::model::PlayingItem;
-use rspotify::senum::RepeatState;
+use rspotify::model::{page::Page, PlayingItem};
+use rspotify::senum::{AlbumType, RepeatState};
 use tui::{
   backend::Backend,
-  layout::{Alignment, Constraint, Direction, Layout, Rect},
+  layout::{Alignment, Constraint, Direction, Layout, Margin, Rect},
   style::{Modifier, Style},
   text::{Span, Spans, Text},
-  widgets::{Block, Borders, Clear, Gauge, List, ListItem, ListState, Paragraph, Row, Table, Wrap},
+  widgets::{
+    Block, Borders, Cell, Clear, Gauge, List, ListItem, ListState, Paragraph, Row, Table, Tabs,
+    Wrap,
+  },
   Frame,
 };
 use util::{
   create_artist_string, display_track_progress, get_artist_highlight_state, get_color,
   get_percentage_width, get_search_results_highlight_state, get_track_progress_percentage,
-  millis_to_minutes, BASIC_VIEW_HEIGHT, SMALL_TERMINAL_WIDTH,
+  highlight_matches, millis_to_minutes, scrollbar_thumb, BASIC_VIEW_HEIGHT, SMALL_TERMINAL_WIDTH,
 };
 
 pub enum TableId {
@@ -127,14 +136,11 @@ where
   // Check for the width and change the contraints accordingly
   let chunks = Layout::default()
     .direction(Direction::Horizontal)
-    .constraints(
-      if app.size.width >= SMALL_TERMINAL_WIDTH && !app.user_config.behavior.enforce_wide_search_bar
-      {
-        [Constraint::Percentage(65), Constraint::Percentage(35)].as_ref()
-      } else {
-        [Constraint::Percentage(90), Constraint::Percentage(10)].as_ref()
-      },
-    )
+    .constraints(if search_in_sidebar(app) {
+      [Constraint::Percentage(65), Constraint::Percentage(35)].as_ref()
+    } else {
+      [Constraint::Percentage(90), Constraint::Percentage(10)].as_ref()
+    })
     .split(layout_chunk);
 
   let current_route = app.get_current_route();
@@ -181,100 +187,93 @@ where
   B: Backend,
 {
   let margin = util::get_main_layout_margin(app);
-  // Responsive layout: new one kicks in at width 150 or higher
-  if app.size.width >= SMALL_TERMINAL_WIDTH && !app.user_config.behavior.enforce_wide_search_bar {
-    let parent_layout = Layout::default()
-      .direction(Direction::Vertical)
-      .constraints([Constraint::Min(1), Constraint::Length(6)].as_ref())
-      .margin(margin)
-      .split(f.size());
-
-    // Nested main block with potential routes
-    draw_routes(f, app, parent_layout[0]);
+  let area = f.size().inner(&Margin {
+    vertical: margin,
+    horizontal: margin,
+  });
+  let layout = layout::main_layout(
+    area,
+    &app.user_config.layout,
+    search_in_sidebar(app),
+    app.sidebar_collapsed,
+  );
 
-    // Currently playing
-    draw_playbar(f, app, parent_layout[1]);
-  } else {
-    let parent_layout = Layout::default()
-      .direction(Direction::Vertical)
-      .constraints(
-        [
-          Constraint::Length(3),
-          Constraint::Min(1),
-          Constraint::Length(6),
-        ]
-        .as_ref(),
-      )
-      .margin(margin)
-      .split(f.size());
+  // Search input and help
+  if let Some(search) = layout.search {
+    draw_input_and_help_box(f, app, search);
+  }
 
-    // Search input and help
-    draw_input_and_help_box(f, app, parent_layout[0]);
+  if let Some(sidebar) = layout.sidebar {
+    draw_user_block(f, app, sidebar);
+  }
 
-    // Nested main block with potential routes
-    draw_routes(f, app, parent_layout[1]);
+  // Nested main block with potential routes
+  draw_routes(f, app, layout.content);
 
-    // Currently playing
-    draw_playbar(f, app, parent_layout[2]);
-  }
+  // Currently playing
+  draw_playbar(f, app, layout.playbar);
 
   // Possibly draw confirm dialog
   draw_dialog(f, app);
 }
 
+// Responsive layout: the search moves into the sidebar at width 150 or higher
+fn search_in_sidebar(app: &App) -> bool {
+  app.size.width >= SMALL_TERMINAL_WIDTH
+    && !app.user_config.behavior.enforce_wide_search_bar
+    && !app.sidebar_collapsed
+}
+
 pub fn draw_routes<B>(f: &mut Frame<B>, app: &App, layout_chunk: Rect)
 where
   B: Backend,
 {
-  let chunks = Layout::default()
-    .direction(Direction::Horizontal)
-    .constraints([Constraint::Percentage(20), Constraint::Percentage(80)].as_ref())
-    .split(layout_chunk);
-
-  draw_user_block(f, app, chunks[0]);
-
   let current_route = app.get_current_route();
 
   match current_route.id {
     RouteId::Search => {
-      draw_search_results(f, app, chunks[1]);
+      draw_search_results(f, app, layout_chunk);
     }
     RouteId::TrackTable => {
-      draw_song_table(f, app, chunks[1]);
+      draw_song_table(f, app, layout_chunk);
     }
     RouteId::AlbumTracks => {
-      draw_album_table(f, app, chunks[1]);
+      draw_album_table(f, app, layout_chunk);
     }
     RouteId::RecentlyPlayed => {
-      draw_recently_played_table(f, app, chunks[1]);
+      draw_recently_played_table(f, app, layout_chunk);
+    }
+    RouteId::TopItems => {
+      draw_top_items_table(f, app, layout_chunk);
     }
     RouteId::Artist => {
-      draw_artist_albums(f, app, chunks[1]);
+      draw_artist_albums(f, app, layout_chunk);
     }
     RouteId::AlbumList => {
-      draw_album_list(f, app, chunks[1]);
+      draw_album_list(f, app, layout_chunk);
     }
     RouteId::PodcastEpisodes => {
-      draw_show_episodes(f, app, chunks[1]);
+      draw_show_episodes(f, app, layout_chunk);
     }
     RouteId::Home => {
-      draw_home(f, app, chunks[1]);
+      draw_home(f, app, layout_chunk);
     }
     RouteId::MadeForYou => {
-      draw_made_for_you(f, app, chunks[1]);
+      draw_made_for_you(f, app, layout_chunk);
     }
     RouteId::Artists => {
-      draw_artist_table(f, app, chunks[1]);
+      draw_artist_table(f, app, layout_chunk);
     }
     RouteId::Podcasts => {
-      draw_podcast_table(f, app, chunks[1]);
+      draw_podcast_table(f, app, layout_chunk);
     }
     RouteId::Recommendations => {
-      draw_recommendations_table(f, app, chunks[1]);
+      draw_recommendations_table(f, app, layout_chunk);
     }
     RouteId::Error => {} // This is handled as a "full screen" route in main.rs
     RouteId::SelectedDevice => {} // This is handled as a "full screen" route in main.rs
     RouteId::Analysis => {} // This is handled as a "full screen" route in main.rs
+    RouteId::Lyrics => {} // This is handled as a "full screen" route in main.rs
     RouteId::BasicView => {} // This is handled as a "full screen" route in main.rs
     RouteId::Dialog => {} // This is handled in the draw_dialog function in mod.rs
   };
@@ -298,6 +297,13 @@ where
     highlight_state,
     Some(app.library.selected_index),
   );
+  register_list_rows(
+    app,
+    ActiveBlock::Library,
+    layout_chunk,
+    app.library.selected_index,
+    LIBRARY_OPTIONS.len(),
+  );
 }
 
 pub fn draw_playlist_block<B>(f: &mut Frame<B>, app: &App, layout_chunk: Rect)
@@ -325,40 +331,31 @@ where
     highlight_state,
     app.selected_playlist_index,
   );
+  register_list_rows(
+    app,
+    ActiveBlock::MyPlaylists,
+    layout_chunk,
+    app.selected_playlist_index.unwrap_or(0),
+    playlist_items.len(),
+  );
 }
 
 pub fn draw_user_block<B>(f: &mut Frame<B>, app: &App, layout_chunk: Rect)
 where
   B: Backend,
 {
-  // Check for width to make a responsive layout
-  if app.size.width >= SMALL_TERMINAL_WIDTH && !app.user_config.behavior.enforce_wide_search_bar {
-    let chunks = Layout::default()
-      .direction(Direction::Vertical)
-      .constraints(
-        [
-          Constraint::Length(3),
-          Constraint::Percentage(30),
-          Constraint::Percentage(70),
-        ]
-        .as_ref(),
-      )
-      .split(layout_chunk);
-
-    // Search input and help
-    draw_input_and_help_box(f, app, chunks[0]);
-    draw_library_block(f, app, chunks[1]);
-    draw_playlist_block(f, app, chunks[2]);
-  } else {
-    let chunks = Layout::default()
-      .direction(Direction::Vertical)
-      .constraints([Constraint::Percentage(30), Constraint::Percentage(70)].as_ref())
-      .split(layout_chunk);
+  let layout = layout::sidebar_layout(
+    layout_chunk,
+    &app.user_config.layout,
+    search_in_sidebar(app),
+  );
 
-    // Search input and help
-    draw_library_block(f, app, chunks[0]);
-    draw_playlist_block(f, app, chunks[1]);
+  // Search input and help
+  if let Some(search) = layout.search {
+    draw_input_and_help_box(f, app, search);
   }
+  draw_library_block(f, app, layout.library);
+  draw_playlist_block(f, app, layout.playlists);
 }
 
 pub fn draw_search_results<B>(f: &mut Frame<B>, app: &App, layout_chunk: Rect)
@@ -420,7 +417,7 @@ where
       f,
       app,
       song_artist_block[0],
-      "Songs",
+      &results_title("Songs", &app.search_results.tracks),
       &songs,
       get_search_results_highlight_state(app, SearchResultBlock::SongSearch),
       app.search_results.selected_tracks_index,
@@ -446,7 +443,7 @@ where
       f,
       app,
       song_artist_block[1],
-      "Artists",
+      &results_title("Artists", &app.search_results.artists),
       &artists,
       get_search_results_highlight_state(app, SearchResultBlock::ArtistSearch),
       app.search_results.selected_artists_index,
@@ -486,7 +483,7 @@ where
       f,
       app,
       albums_playlist_block[0],
-      "Albums",
+      &results_title("Albums", &app.search_results.albums),
       &albums,
       get_search_results_highlight_state(app, SearchResultBlock::AlbumSearch),
       app.search_results.selected_album_index,
@@ -504,7 +501,7 @@ where
       f,
       app,
       albums_playlist_block[1],
-      "Playlists",
+      &results_title("Playlists", &app.search_results.playlists),
       &playlists,
       get_search_results_highlight_state(app, SearchResultBlock::PlaylistSearch),
       app.search_results.selected_playlists_index,
@@ -536,7 +533,7 @@ where
       f,
       app,
       podcasts_block[0],
-      "Podcasts",
+      &results_title("Podcasts", &app.search_results.shows),
       &podcasts,
       get_search_results_highlight_state(app, SearchResultBlock::ShowSearch),
       app.search_results.selected_shows_index,
@@ -577,6 +574,13 @@ where
     })
     .collect::<Vec<TableItem>>();
 
+  register_table_rows(
+    app,
+    ActiveBlock::Artists,
+    layout_chunk,
+    app.artists_list_index,
+    items.len(),
+  );
   draw_table(
     f,
     app,
@@ -628,6 +632,13 @@ where
       })
       .collect::<Vec<TableItem>>();
 
+    register_table_rows(
+      app,
+      ActiveBlock::Podcasts,
+      layout_chunk,
+      app.shows_list_index,
+      items.len(),
+    );
     draw_table(
       f,
       app,
@@ -681,6 +692,10 @@ where
     current_route.hovered_block == ActiveBlock::AlbumTracks,
   );
 
+  let saved = |album_id: Option<&String>| match album_id {
+    Some(id) if app.saved_album_ids_set.contains(id) => app.user_config.padded_liked_icon(),
+    _ => String::new(),
+  };
   let album_ui = match &app.album_table_context {
     AlbumTableContext::Simplified => {
       app
@@ -703,7 +718,8 @@ where
             })
             .collect::<Vec<TableItem>>(),
           title: format!(
-            "{} by {}",
+            "{}{} by {}",
+            saved(selected_album_simplified.album.id.as_ref()),
             selected_album_simplified.album.name,
             create_artist_string(&selected_album_simplified.album.artists)
           ),
@@ -729,7 +745,8 @@ where
           })
           .collect::<Vec<TableItem>>(),
         title: format!(
-          "{} by {}",
+          "{}{} by {}",
+          saved(Some(&selected_album.album.id)),
           selected_album.album.name,
           create_artist_string(&selected_album.album.artists)
         ),
@@ -740,6 +757,13 @@ where
   };
 
   if let Some(album_ui) = album_ui {
+    // These rows belong to either the simplified or the full album
+    register_click_area(
+      app,
+      ActiveBlock::AlbumTracks,
+      layout_chunk,
+      ClickTarget::Pane,
+    );
     draw_table(
       f,
       app,
@@ -793,21 +817,7 @@ where
     current_route.hovered_block == ActiveBlock::TrackTable,
   );
 
-  let items = app
-    .track_table
-    .tracks
-    .iter()
-    .map(|item| TableItem {
-      id: item.id.clone().unwrap_or_else(|| "".to_string()),
-      format: vec![
-        "".to_string(),
-        item.name.to_owned(),
-        create_artist_string(&item.artists),
-        item.album.name.to_owned(),
-        millis_to_minutes(u128::from(item.duration_ms)),
-      ],
-    })
-    .collect::<Vec<TableItem>>();
+  let items = track_table_items(app);
   // match RecommendedContext
   let recommendations_ui = match &app.recommendations_context {
     Some(RecommendationsContext::Song) => format!(
@@ -820,17 +830,83 @@ where
     ),
     None => "Recommendations".to_string(),
   };
-  draw_table(
+  register_table_rows(
+    app,
+    ActiveBlock::TrackTable,
+    layout_chunk,
+    app.track_table.selected_index,
+    items.len(),
+  );
+  draw_table_with_highlight(
     f,
     app,
     layout_chunk,
-    (&recommendations_ui[..], &header),
+    (&track_table_title(app, &recommendations_ui), &header),
     &items,
     app.track_table.selected_index,
     highlight_state,
+    &app.track_table.filter,
+    Some(app.track_table_position()),
   )
 }
 
+// Marked tracks get a `+` in front of their name
+fn track_table_items(app: &App) -> Vec<TableItem> {
+  app
+    .track_table
+    .tracks
+    .iter()
+    .enumerate()
+    .map(|(i, item)| TableItem {
+      id: item.id.clone().unwrap_or_else(|| "".to_string()),
+      format: vec![
+        "".to_string(),
+        if app.track_table.is_marked(i) {
+          format!("+ {}", item.name)
+        } else {
+          item.name.to_owned()
+        },
+        create_artist_string(&item.artists),
+        item.album.name.to_owned(),
+        millis_to_minutes(u128::from(item.duration_ms)),
+      ],
+    })
+    .collect()
+}
+
+// The sort, the filter and how many tracks are marked
+fn track_table_title(app: &App, title: &str) -> String {
+  let mut title = match app.track_table.sort() {
+    Some(sort) => format!("{} (by {})", title, sort.label().to_lowercase()),
+    None => title.to_string(),
+  };
+  if app.track_table.filtering {
+    title = format!("{} /{}_", title, app.track_table.filter);
+  } else if app.track_table.is_filtered() {
+    title = format!("{} /{}", title, app.track_table.filter);
+  }
+  let position = app.track_table_position();
+  if position.total > 0 {
+    title = format!("{} {}/{}", title, position.selected + 1, position.total);
+  }
+  if position.loaded < position.total {
+    title = format!(
+      "{} ({}-{} loaded)",
+      title,
+      position.offset + 1,
+      position.offset + position.loaded
+    );
+  }
+  match (
+    app.track_table.visual_anchor,
+    app.track_table.selection().len(),
+  ) {
+    (Some(_), n) => format!("{} -- VISUAL ({} marked)", title, n),
+    (None, 0) => title,
+    (None, n) => format!("{} ({} marked)", title, n),
+  }
+}
+
 pub fn draw_song_table<B>(f: &mut Frame<B>, app: &App, layout_chunk: Rect)
 where
   B: Backend,
@@ -872,30 +948,25 @@ where
     current_route.hovered_block == ActiveBlock::TrackTable,
   );
 
-  let items = app
-    .track_table
-    .tracks
-    .iter()
-    .map(|item| TableItem {
-      id: item.id.clone().unwrap_or_else(|| "".to_string()),
-      format: vec![
-        "".to_string(),
-        item.name.to_owned(),
-        create_artist_string(&item.artists),
-        item.album.name.to_owned(),
-        millis_to_minutes(u128::from(item.duration_ms)),
-      ],
-    })
-    .collect::<Vec<TableItem>>();
+  let items = track_table_items(app);
 
-  draw_table(
+  register_table_rows(
+    app,
+    ActiveBlock::TrackTable,
+    layout_chunk,
+    app.track_table.selected_index,
+    items.len(),
+  );
+  draw_table_with_highlight(
     f,
     app,
     layout_chunk,
-    ("Songs", &header),
+    (&track_table_title(app, "Songs"), &header),
     &items,
     app.track_table.selected_index,
     highlight_state,
+    &app.track_table.filter,
+    Some(app.track_table_position()),
   )
 }
 
@@ -939,6 +1010,24 @@ where
     .margin(1)
     .split(layout_chunk);
 
+  // The playback can't be controlled, so the bar only says why
+  if app.offline {
+    let offline_block = Block::default()
+      .borders(Borders::ALL)
+      .title(Span::styled(
+        "Offline",
+        Style::default().fg(app.user_config.theme.inactive),
+      ))
+      .border_style(Style::default().fg(app.user_config.theme.inactive));
+    let banner = Paragraph::new(Span::styled(
+      "Showing your library from the last sync, playback controls are unavailable",
+      Style::default().fg(app.user_config.theme.inactive),
+    ))
+    .block(offline_block);
+    f.render_widget(banner, layout_chunk);
+    return;
+  }
+
   // If no track is playing, render paragraph showing which device is selected, if no selected
   // give hint to choose a device
   if let Some(current_playback_context) = &app.current_playback_context {
@@ -955,20 +1044,37 @@ where
         "Off"
       };
 
+      let behavior = &app.user_config.behavior;
       let repeat_text = match current_playback_context.repeat_state {
-        RepeatState::Off => "Off",
-        RepeatState::Track => "Track",
-        RepeatState::Context => "All",
+        RepeatState::Off => "Off".to_string(),
+        RepeatState::Track => format!("{} Track", behavior.repeat_track_icon),
+        RepeatState::Context => format!("{} All", behavior.repeat_context_icon),
       };
 
-      let title = format!(
-        "{:-7} ({} | Shuffle: {:-3} | Repeat: {:-5} | Volume: {:-2}%)",
-        play_title,
-        current_playback_context.device.name,
-        shuffle_text,
-        repeat_text,
-        current_playback_context.device.volume_percent
-      );
+      let progress_ms = match app.seek_ms {
+        Some(seek_ms) => seek_ms,
+        None => app.song_progress_ms,
+      };
+      let liked = match track_item {
+        PlayingItem::Track(track) => track
+          .id
+          .as_ref()
+          .is_some_and(|id| app.liked_song_ids_set.contains(id)),
+        PlayingItem::Episode(_) => false,
+      };
+      let values = Format::from_playback(current_playback_context, Some(progress_ms as u32), liked);
+
+      let title = match &behavior.playbar_format {
+        Some(format) => render(format, &values, &app.user_config),
+        None => format!(
+          "{:-7} ({} | Shuffle: {:-3} | Repeat: {:-5} | Volume: {:-2}%)",
+          play_title,
+          current_playback_context.device.name,
+          shuffle_text,
+          repeat_text,
+          current_playback_context.device.volume_percent
+        ),
+      };
 
       let current_route = app.get_current_route();
       let highlight_state = (
@@ -1005,9 +1111,12 @@ where
         name
       };
 
-      let play_bar_text = match track_item {
-        PlayingItem::Track(track) => create_artist_string(&track.artists),
-        PlayingItem::Episode(episode) => format!("{} - {}", episode.name, episode.show.name),
+      let play_bar_text = match (&behavior.playbar_text_format, track_item) {
+        (Some(format), _) => render(format, &values, &app.user_config),
+        (None, PlayingItem::Track(track)) => create_artist_string(&track.artists),
+        (None, PlayingItem::Episode(episode)) => {
+          format!("{} - {}", episode.name, episode.show.name)
+        }
       };
 
       let lines = Text::from(Span::styled(
@@ -1027,11 +1136,6 @@ where
         );
       f.render_widget(artist, chunks[0]);
 
-      let progress_ms = match app.seek_ms {
-        Some(seek_ms) => seek_ms,
-        None => app.song_progress_ms,
-      };
-
       let perc = get_track_progress_percentage(progress_ms, duration_ms);
 
       let song_progress_label = display_track_progress(progress_ms, duration_ms);
@@ -1053,10 +1157,36 @@ where
           Style::default().fg(app.user_config.theme.playbar_progress_text),
         ));
       f.render_widget(song_progress, chunks[2]);
+      register_click_area(app, ActiveBlock::PlayBar, layout_chunk, ClickTarget::Pane);
+      register_click_area(app, ActiveBlock::PlayBar, chunks[2], ClickTarget::Progress);
     }
   }
 }
 
+// In the top right corner, over the help block
+pub fn draw_toast<B>(f: &mut Frame<B>, app: &App)
+where
+  B: Backend,
+{
+  let toast = match &app.toast {
+    Some(toast) => toast,
+    None => return,
+  };
+  let size = f.size();
+  let width = std::cmp::min(toast.chars().count() as u16 + 4, size.width);
+  let area = Rect::new(size.width - width, 0, width, std::cmp::min(3, size.height));
+  let block = Block::default()
+    .borders(Borders::ALL)
+    .border_style(Style::default().fg(app.user_config.theme.hint));
+  let paragraph = Paragraph::new(Span::styled(
+    toast.as_str(),
+    Style::default().fg(app.user_config.theme.hint),
+  ))
+  .block(block);
+  f.render_widget(Clear, area);
+  f.render_widget(paragraph, area);
+}
+
 pub fn draw_error_screen<B>(f: &mut Frame<B>, app: &App)
 where
   B: Backend,
@@ -1177,110 +1307,158 @@ where
     .wrap(Wrap { trim: false })
     .scroll((app.home_scroll, 0));
   f.render_widget(bottom_text, chunks[1]);
+  register_click_area(app, ActiveBlock::Home, layout_chunk, ClickTarget::Pane);
 }
 
 fn draw_artist_albums<B>(f: &mut Frame<B>, app: &App, layout_chunk: Rect)
 where
   B: Backend,
 {
+  let artist = match &app.artist {
+    Some(artist) => artist,
+    None => return,
+  };
   let chunks = Layout::default()
-    .direction(Direction::Horizontal)
-    .constraints(
-      [
-        Constraint::Percentage(33),
-        Constraint::Percentage(33),
-        Constraint::Percentage(33),
-      ]
-      .as_ref(),
-    )
+    .direction(Direction::Vertical)
+    .constraints([Constraint::Length(3), Constraint::Min(1)].as_ref())
     .split(layout_chunk);
 
-  if let Some(artist) = &app.artist {
-    let top_tracks = artist
-      .top_tracks
-      .iter()
-      .map(|top_track| {
-        let mut name = String::new();
-        if let Some(context) = &app.current_playback_context {
-          let track_id = match &context.item {
-            Some(PlayingItem::Track(track)) => track.id.to_owned(),
-            Some(PlayingItem::Episode(episode)) => Some(episode.id.to_owned()),
-            _ => None,
-          };
-
-          if track_id == top_track.id {
-            name.push_str("▶ ");
-          }
-        };
-        name.push_str(&top_track.name);
-        name
-      })
-      .collect::<Vec<String>>();
-
-    draw_selectable_list(
-      f,
-      app,
-      chunks[0],
-      &format!("{} - Top Tracks", &artist.artist_name),
-      &top_tracks,
-      get_artist_highlight_state(app, ArtistBlock::TopTracks),
-      Some(artist.selected_top_track_index),
+  let group = match artist.album_group {
+    None => "all",
+    Some(AlbumType::Album) => "albums",
+    Some(AlbumType::Single) => "singles",
+    Some(AlbumType::Compilation) => "compilations",
+    Some(AlbumType::AppearsOn) => "appears on",
+  };
+  let tabs = [
+    (ArtistBlock::TopTracks, "Top Tracks".to_string()),
+    (
+      ArtistBlock::Albums,
+      format!("Albums ({}, {})", group, artist.albums.items.len()),
+    ),
+    (ArtistBlock::RelatedArtists, "Related Artists".to_string()),
+  ];
+  let tab = artist.artist_hovered_block;
+  let highlight_state = get_artist_highlight_state(app, tab);
+  let titles = tabs
+    .iter()
+    .map(|(_, title)| Spans::from(title.clone()))
+    .collect();
+  let mut name = String::new();
+  if app.followed_artist_ids_set.contains(&artist.artist_id) {
+    name.push_str(&app.user_config.padded_liked_icon());
+  }
+  name.push_str(&artist.artist_name);
+  let tab_bar = Tabs::new(titles)
+    .block(
+      Block::default()
+        .title(Span::styled(
+          name,
+          get_color(highlight_state, app.user_config.theme),
+        ))
+        .borders(Borders::ALL)
+        .border_style(get_color(highlight_state, app.user_config.theme)),
+    )
+    .style(Style::default().fg(app.user_config.theme.text))
+    .highlight_style(get_color((true, false), app.user_config.theme).add_modifier(Modifier::BOLD))
+    .select(
+      tabs
+        .iter()
+        .position(|(block, _)| *block == tab)
+        .unwrap_or(0),
     );
+  f.render_widget(tab_bar, chunks[0]);
 
-    let albums = &artist
-      .albums
-      .items
-      .iter()
-      .map(|item| {
-        let mut album_artist = String::new();
-        if let Some(album_id) = &item.id {
-          if app.saved_album_ids_set.contains(&album_id.to_owned()) {
-            album_artist.push_str(&app.user_config.padded_liked_icon());
+  match tab {
+    ArtistBlock::TopTracks => {
+      let top_tracks = artist
+        .top_tracks
+        .iter()
+        .map(|top_track| {
+          let mut name = String::new();
+          if let Some(context) = &app.current_playback_context {
+            let track_id = match &context.item {
+              Some(PlayingItem::Track(track)) => track.id.to_owned(),
+              Some(PlayingItem::Episode(episode)) => Some(episode.id.to_owned()),
+              _ => None,
+            };
+
+            if track_id == top_track.id {
+              name.push_str("▶ ");
+            }
+          };
+          name.push_str(&top_track.name);
+          name
+        })
+        .collect::<Vec<String>>();
+
+      draw_selectable_list(
+        f,
+        app,
+        chunks[1],
+        "Top Tracks",
+        &top_tracks,
+        highlight_state,
+        Some(artist.selected_top_track_index),
+      );
+    }
+    ArtistBlock::Albums => {
+      let albums = &artist
+        .albums
+        .items
+        .iter()
+        .map(|item| {
+          let mut album_artist = String::new();
+          if let Some(album_id) = &item.id {
+            if app.saved_album_ids_set.contains(&album_id.to_owned()) {
+              album_artist.push_str(&app.user_config.padded_liked_icon());
+            }
           }
-        }
-        album_artist.push_str(&format!(
-          "{} - {} ({})",
-          item.name.to_owned(),
-          create_artist_string(&item.artists),
-          item.album_type.as_deref().unwrap_or("unknown")
-        ));
-        album_artist
-      })
-      .collect::<Vec<String>>();
-
-    draw_selectable_list(
-      f,
-      app,
-      chunks[1],
-      "Albums",
-      albums,
-      get_artist_highlight_state(app, ArtistBlock::Albums),
-      Some(artist.selected_album_index),
-    );
-
-    let related_artists = artist
-      .related_artists
-      .iter()
-      .map(|item| {
-        let mut artist = String::new();
-        if app.followed_artist_ids_set.contains(&item.id.to_owned()) {
-          artist.push_str(&app.user_config.padded_liked_icon());
-        }
-        artist.push_str(&item.name.to_owned());
-        artist
-      })
-      .collect::<Vec<String>>();
-
-    draw_selectable_list(
-      f,
-      app,
-      chunks[2],
-      "Related artists",
-      &related_artists,
-      get_artist_highlight_state(app, ArtistBlock::RelatedArtists),
-      Some(artist.selected_related_artist_index),
-    );
-  };
+          album_artist.push_str(&format!(
+            "{} - {} ({})",
+            item.name.to_owned(),
+            create_artist_string(&item.artists),
+            item.album_type.as_deref().unwrap_or("unknown")
+          ));
+          album_artist
+        })
+        .collect::<Vec<String>>();
+
+      draw_selectable_list(
+        f,
+        app,
+        chunks[1],
+        "Albums",
+        albums,
+        highlight_state,
+        Some(artist.selected_album_index),
+      );
+    }
+    ArtistBlock::RelatedArtists | ArtistBlock::Empty => {
+      let related_artists = artist
+        .related_artists
+        .iter()
+        .map(|item| {
+          let mut artist = String::new();
+          if app.followed_artist_ids_set.contains(&item.id.to_owned()) {
+            artist.push_str(&app.user_config.padded_liked_icon());
+          }
+          artist.push_str(&item.name.to_owned());
+          artist
+        })
+        .collect::<Vec<String>>();
+
+      draw_selectable_list(
+        f,
+        app,
+        chunks[1],
+        "Related artists",
+        &related_artists,
+        highlight_state,
+        Some(artist.selected_related_artist_index),
+      );
+    }
+  }
 }
 
 pub fn draw_device_list<B>(f: &mut Frame<B>, app: &App)
@@ -1315,19 +1493,14 @@ where
 
   let no_device_message = Span::raw("No devices found: Make sure a device is active");
 
-  let items = match &app.devices {
-    Some(items) => {
-      if items.devices.is_empty() {
-        vec![ListItem::new(no_device_message)]
-      } else {
-        items
-          .devices
-          .iter()
-          .map(|device| ListItem::new(Span::raw(&device.name)))
-          .collect()
-      }
-    }
-    None => vec![ListItem::new(no_device_message)],
+  let names = app.device_names();
+  let items = if names.is_empty() {
+    vec![ListItem::new(no_device_message)]
+  } else {
+    names
+      .into_iter()
+      .map(|name| ListItem::new(Span::raw(name)))
+      .collect()
   };
 
   let mut state = ListState::default();
@@ -1403,6 +1576,13 @@ where
       })
       .collect::<Vec<TableItem>>();
 
+    register_table_rows(
+      app,
+      ActiveBlock::AlbumList,
+      layout_chunk,
+      selected_song_index,
+      items.len(),
+    );
     draw_table(
       f,
       app,
@@ -1463,8 +1643,12 @@ where
             fully_played,
             resume_position_ms,
           }) => (
+            // Finished and not yet started episodes are marked, started ones
+            // show how far they got
             if fully_played {
               " ✔".to_owned()
+            } else if resume_position_ms == 0 {
+              " •".to_owned()
             } else {
               "".to_owned()
             },
@@ -1514,6 +1698,13 @@ where
       },
     };
 
+    register_table_rows(
+      app,
+      ActiveBlock::EpisodeTable,
+      layout_chunk,
+      app.episode_list_index,
+      items.len(),
+    );
     draw_table(
       f,
       app,
@@ -1555,6 +1746,13 @@ where
       current_route.hovered_block == ActiveBlock::MadeForYou,
     );
 
+    register_table_rows(
+      app,
+      ActiveBlock::MadeForYou,
+      layout_chunk,
+      app.made_for_you_index,
+      items.len(),
+    );
     draw_table(
       f,
       app,
@@ -1622,6 +1820,13 @@ where
       })
       .collect::<Vec<TableItem>>();
 
+    register_table_rows(
+      app,
+      ActiveBlock::RecentlyPlayed,
+      layout_chunk,
+      selected_song_index,
+      items.len(),
+    );
     draw_table(
       f,
       app,
@@ -1634,6 +1839,156 @@ where
   };
 }
 
+pub fn draw_top_items_table<B>(f: &mut Frame<B>, app: &App, layout_chunk: Rect)
+where
+  B: Backend,
+{
+  let current_route = app.get_current_route();
+  let highlight_state = (
+    current_route.active_block == ActiveBlock::TopItems,
+    current_route.hovered_block == ActiveBlock::TopItems,
+  );
+
+  let (kind, header, items) = match app.top_items.kind {
+    TopItemsKind::Tracks => (
+      "Tracks",
+      TableHeader {
+        id: TableId::Song,
+        items: vec![
+          TableHeaderItem {
+            id: ColumnId::Liked,
+            text: "",
+            width: 2,
+          },
+          TableHeaderItem {
+            id: ColumnId::Title,
+            text: "Title",
+            width: get_percentage_width(layout_chunk.width, 2.0 / 5.0) - 2,
+          },
+          TableHeaderItem {
+            text: "Artist",
+            width: get_percentage_width(layout_chunk.width, 2.0 / 5.0),
+            ..Default::default()
+          },
+          TableHeaderItem {
+            text: "Length",
+            width: get_percentage_width(layout_chunk.width, 1.0 / 5.0),
+            ..Default::default()
+          },
+        ],
+      },
+      app
+        .top_items
+        .tracks
+        .iter()
+        .map(|track| TableItem {
+          id: track.id.clone().unwrap_or_default(),
+          format: vec![
+            "".to_string(),
+            track.name.to_owned(),
+            create_artist_string(&track.artists),
+            millis_to_minutes(u128::from(track.duration_ms)),
+          ],
+        })
+        .collect::<Vec<TableItem>>(),
+    ),
+    TopItemsKind::Artists => (
+      "Artists",
+      TableHeader {
+        id: TableId::Artist,
+        items: vec![
+          TableHeaderItem {
+            text: "Artist",
+            width: get_percentage_width(layout_chunk.width, 2.0 / 5.0),
+            ..Default::default()
+          },
+          TableHeaderItem {
+            text: "Genres",
+            width: get_percentage_width(layout_chunk.width, 3.0 / 5.0),
+            ..Default::default()
+          },
+        ],
+      },
+      app
+        .top_items
+        .artists
+        .iter()
+        .map(|artist| {
+          let mut name = String::new();
+          if app.followed_artist_ids_set.contains(&artist.id) {
+            name.push_str(&app.user_config.padded_liked_icon());
+          }
+          name.push_str(&artist.name);
+          TableItem {
+            id: artist.id.clone(),
+            format: vec![name, artist.genres.join(", ")],
+          }
+        })
+        .collect::<Vec<TableItem>>(),
+    ),
+  };
+
+  register_table_rows(
+    app,
+    ActiveBlock::TopItems,
+    layout_chunk,
+    app.top_items.index,
+    items.len(),
+  );
+  draw_table(
+    f,
+    app,
+    layout_chunk,
+    (
+      &format!("Top {} ({})", kind, app.top_items.time_range_label()),
+      &header,
+    ),
+    &items,
+    app.top_items.index,
+    highlight_state,
+  )
+}
+
+fn register_click_area(app: &App, block: ActiveBlock, rect: Rect, target: ClickTarget) {
+  app.click_areas.borrow_mut().push(ClickArea {
+    rect,
+    block,
+    target,
+  });
+}
+
+// Like `List`, which scrolls just far enough to show the selected item
+fn register_list_rows(app: &App, block: ActiveBlock, rect: Rect, selected: usize, count: usize) {
+  let height = rect.height.saturating_sub(2) as usize;
+  let target = ClickTarget::Rows {
+    first_row: rect.y + 1,
+    offset: (selected + 1).saturating_sub(height),
+    count,
+  };
+  register_click_area(app, block, rect, target);
+}
+
+fn register_table_rows(app: &App, block: ActiveBlock, rect: Rect, selected: usize, count: usize) {
+  let target = ClickTarget::Rows {
+    // Below the border and the header
+    first_row: rect.y + 2,
+    offset: table_offset(rect, selected),
+    count,
+  };
+  register_click_area(app, block, rect, target);
+}
+
+// Make sure that the selected item is visible on the page. Need to add some rows of padding
+// to chunk height for header and header space to get a true table height
+fn table_offset(layout_chunk: Rect, selected_index: usize) -> usize {
+  let padding = 5;
+  layout_chunk
+    .height
+    .checked_sub(padding)
+    .and_then(|height| selected_index.checked_sub(height as usize))
+    .unwrap_or(0)
+}
+
 fn draw_selectable_list<B, S>(
   f: &mut Frame<B>,
   app: &App,
@@ -1648,6 +2003,9 @@ fn draw_selectable_list<B, S>(
 {
   let mut state = ListState::default();
   state.select(selected_index);
+  // The borders
+  let visible = usize::from(layout_chunk.height.saturating_sub(2));
+  let title = list_title(title, selected_index, visible, items.len());
 
   let lst_items: Vec<ListItem> = items
     .iter()
@@ -1670,6 +2028,13 @@ fn draw_selectable_list<B, S>(
       get_color(highlight_state, app.user_config.theme).add_modifier(Modifier::BOLD),
     );
   f.render_stateful_widget(list, layout_chunk, &mut state);
+  draw_scrollbar(
+    f,
+    app,
+    layout_chunk,
+    (selected_index.unwrap_or(0), visible, items.len()),
+    highlight_state,
+  );
 }
 
 fn draw_dialog<B>(f: &mut Frame<B>, app: &App)
@@ -1759,6 +2124,34 @@ fn draw_table<B>(
   highlight_state: (bool, bool),
 ) where
   B: Backend,
+{
+  draw_table_with_highlight(
+    f,
+    app,
+    layout_chunk,
+    table_layout,
+    items,
+    selected_index,
+    highlight_state,
+    "",
+    None,
+  )
+}
+
+// Like `draw_table`, with the parts of the cells matching `query` highlighted
+#[allow(clippy::too_many_arguments)]
+fn draw_table_with_highlight<B>(
+  f: &mut Frame<B>,
+  app: &App,
+  layout_chunk: Rect,
+  table_layout: (&str, &TableHeader),
+  items: &[TableItem],
+  selected_index: usize,
+  highlight_state: (bool, bool),
+  query: &str,
+  position: Option<TablePosition>,
+) where
+  B: Backend,
 {
   let selected_style =
     get_color(highlight_state, app.user_config.theme).add_modifier(Modifier::BOLD);
@@ -1774,14 +2167,7 @@ fn draw_table<B>(
 
   let (title, header) = table_layout;
 
-  // Make sure that the selected item is visible on the page. Need to add some rows of padding
-  // to chunk height for header and header space to get a true table height
-  let padding = 5;
-  let offset = layout_chunk
-    .height
-    .checked_sub(padding)
-    .and_then(|height| selected_index.checked_sub(height as usize))
-    .unwrap_or(0);
+  let offset = table_offset(layout_chunk, selected_index);
 
   let rows = items.iter().skip(offset).enumerate().map(|(i, item)| {
     let mut formatted_row = item.format.clone();
@@ -1834,7 +2220,20 @@ fn draw_table<B>(
     }
 
     // Return row styled data
-    Row::new(formatted_row).style(style)
+    if query.is_empty() {
+      Row::new(formatted_row).style(style)
+    } else {
+      let matched = Style::default()
+        .fg(app.user_config.theme.hint)
+        .add_modifier(Modifier::UNDERLINED);
+      Row::new(
+        formatted_row
+          .iter()
+          .map(|cell| Cell::from(highlight_matches(cell, query, matched)))
+          .collect::<Vec<Cell>>(),
+      )
+      .style(style)
+    }
   });
 
   let widths = header
@@ -1861,4 +2260,65 @@ fn draw_table<B>(
     .style(Style::default().fg(app.user_config.theme.text))
     .widths(&widths);
   f.render_widget(table, layout_chunk);
+
+  // Of all tracks if the table has a page of them
+  let (position, total) = position
+    .map(|p| (p.selected, p.total))
+    .unwrap_or((selected_index, items.len()));
+  // The borders and the header
+  let visible = usize::from(layout_chunk.height.saturating_sub(3));
+  draw_scrollbar(
+    f,
+    app,
+    layout_chunk,
+    (position, visible, total),
+    highlight_state,
+  );
+}
+
+// Over the right border of a list or table whose items don't all fit, `scroll`
+// is the position, the visible items and all of them
+fn draw_scrollbar<B>(
+  f: &mut Frame<B>,
+  app: &App,
+  layout_chunk: Rect,
+  scroll: (usize, usize, usize),
+  highlight_state: (bool, bool),
+) where
+  B: Backend,
+{
+  let (position, visible, total) = scroll;
+  let track = layout_chunk.height.saturating_sub(2);
+  if layout_chunk.width == 0 {
+    return;
+  }
+  if let Some((start, len)) = scrollbar_thumb(track, position, visible, total) {
+    let area = Rect::new(
+      layout_chunk.x + layout_chunk.width - 1,
+      layout_chunk.y + 1 + start,
+      1,
+      len,
+    );
+    let thumb = Paragraph::new(vec![Spans::from("┃"); usize::from(len)])
+      .style(get_color(highlight_state, app.user_config.theme));
+    f.render_widget(thumb, area);
+  }
+}
+
+// The position in a list, only if the list needs scrolling
+fn list_title(title: &str, selected_index: Option<usize>, visible: usize, total: usize) -> String {
+  match selected_index {
+    Some(index) if total > visible => format!("{} {}/{}", title, index + 1, total),
+    _ => title.to_string(),
+  }
+}
+
+// How many results spotify has beyond the ones loaded
+fn results_title<T>(title: &str, results: &Option<Page<T>>) -> String {
+  match results {
+    Some(page) if page.total as usize > page.items.len() => {
+      format!("{} ({} of {} loaded)", title, page.items.len(), page.total)
+    }
+    _ => title.to_string(),
+  }
 }
@@ -0,0 +1,149 @@
+use crate::app::App;
+use crate::format::pitch_class_to_key;
+use rspotify::model::audio::AudioFeatures;
+use tui::{
+  backend::Backend,
+  layout::{Constraint, Direction, Layout, Rect},
+  style::Style,
+  text::Span,
+  widgets::{Block, Borders, Clear, Gauge, Paragraph},
+  Frame,
+};
+
+// Most tracks are slower, faster ones fill the bar
+const MAX_TEMPO: f32 = 220.0;
+// Loudness is in dB, almost every track is between this and 0
+const MIN_LOUDNESS: f32 = -60.0;
+
+// The name, how full the bar is and the value written on it
+fn feature_rows(features: &AudioFeatures) -> Vec<(&'static str, f64, String)> {
+  let percent = |value: f32| format!("{:.0}%", value * 100.0);
+  let key = pitch_class_to_key(features.key, features.mode);
+  vec![
+    (
+      "Tempo",
+      features.tempo / MAX_TEMPO,
+      format!("{:.0} bpm", features.tempo),
+    ),
+    (
+      "Key",
+      // A bar per pitch class, empty when no key was detected
+      (features.key + 1) as f32 / 12.0,
+      if key.is_empty() {
+        "unknown".to_string()
+      } else {
+        key
+      },
+    ),
+    ("Energy", features.energy, percent(features.energy)),
+    (
+      "Danceability",
+      features.danceability,
+      percent(features.danceability),
+    ),
+    ("Valence", features.valence, percent(features.valence)),
+    (
+      "Loudness",
+      1.0 - features.loudness / MIN_LOUDNESS,
+      format!("{:.1} dB", features.loudness),
+    ),
+  ]
+  .into_iter()
+  .map(|(name, ratio, label)| (name, f64::from(ratio.clamp(0.0, 1.0)), label))
+  .collect()
+}
+
+pub fn draw<B>(f: &mut Frame<B>, app: &App)
+where
+  B: Backend,
+{
+  let popup = match &app.track_features {
+    Some(popup) => popup,
+    None => return,
+  };
+  let theme = app.user_config.theme;
+
+  let bounds = f.size();
+  let width = std::cmp::min(bounds.width.saturating_sub(4), 60);
+  let height = std::cmp::min(bounds.height.saturating_sub(2), 8);
+  let rect = Rect::new((bounds.width - width) / 2, bounds.height / 6, width, height);
+  f.render_widget(Clear, rect);
+
+  let block = Block::default()
+    .borders(Borders::ALL)
+    .title(Span::styled(
+      format!("Audio features of {}", popup.name),
+      Style::default().fg(theme.active),
+    ))
+    .border_style(Style::default().fg(theme.active));
+  let inner = block.inner(rect);
+  f.render_widget(block, rect);
+
+  let features = match &popup.features {
+    Some(features) => features,
+    None => {
+      let loading = Paragraph::new("Loading...").style(Style::default().fg(theme.text));
+      f.render_widget(loading, inner);
+      return;
+    }
+  };
+
+  let rows = feature_rows(features);
+  let lines = Layout::default()
+    .direction(Direction::Vertical)
+    .constraints(vec![Constraint::Length(1); rows.len()])
+    .split(inner);
+  for ((name, ratio, label), line) in rows.into_iter().zip(lines) {
+    let columns = Layout::default()
+      .direction(Direction::Horizontal)
+      .constraints([Constraint::Length(14), Constraint::Min(1)].as_ref())
+      .split(line);
+    f.render_widget(
+      Paragraph::new(name).style(Style::default().fg(theme.text)),
+      columns[0],
+    );
+    let gauge = Gauge::default()
+      .gauge_style(Style::default().fg(theme.analysis_bar).bg(theme.inactive))
+      .ratio(ratio)
+      .label(Span::styled(
+        label,
+        Style::default().fg(theme.analysis_bar_text),
+      ));
+    f.render_widget(gauge, columns[1]);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn feature_rows_test() {
+    let features: AudioFeatures = serde_json::from_value(serde_json::json!({
+      "acousticness": 0.1,
+      "analysis_url": "",
+      "danceability": 0.5,
+      "duration_ms": 200_000,
+      "energy": 0.8,
+      "id": "1",
+      "instrumentalness": 0.0,
+      "key": -1,
+      "liveness": 0.1,
+      "loudness": -6.0,
+      "mode": 1.0,
+      "speechiness": 0.0,
+      "tempo": 250.0,
+      "time_signature": 4,
+      "track_href": "",
+      "type": "audio_features",
+      "uri": "spotify:track:1",
+      "valence": 0.25,
+    }))
+    .unwrap();
+    let rows = feature_rows(&features);
+    assert_eq!(rows[0], ("Tempo", 1.0, "250 bpm".to_string()));
+    assert_eq!(rows[1], ("Key", 0.0, "unknown".to_string()));
+    assert_eq!(rows[4].2, "25%");
+    assert!((rows[5].1 - 0.9).abs() < 1e-6);
+  }
+}
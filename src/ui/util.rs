@@ -1,12 +1,33 @@
 use super::super::app::{ActiveBlock, App, ArtistBlock, SearchResultBlock};
 use crate::user_config::Theme;
 use rspotify::model::artist::SimplifiedArtist;
-use tui::style::Style;
+use tui::{
+  style::Style,
+  text::{Span, Spans},
+};
 
 pub const BASIC_VIEW_HEIGHT: u16 = 6;
 pub const SMALL_TERMINAL_WIDTH: u16 = 150;
 pub const SMALL_TERMINAL_HEIGHT: u16 = 45;
 
+// The cells of a scrollbar `track` cells long which show `visible` of `total`
+// items around the one at `position`, `None` if they all fit
+pub fn scrollbar_thumb(
+  track: u16,
+  position: usize,
+  visible: usize,
+  total: usize,
+) -> Option<(u16, u16)> {
+  // Nothing fits into a table that's only borders and the header
+  if total <= visible || total <= 1 || visible == 0 || track == 0 {
+    return None;
+  }
+  let track = usize::from(track);
+  let len = (visible * track / total).clamp(1, track);
+  let start = position.min(total - 1) * (track - len) / (total - 1);
+  Some((start as u16, len as u16))
+}
+
 pub fn get_search_results_highlight_state(
   app: &App,
   block_to_match: SearchResultBlock,
@@ -86,6 +107,41 @@ pub fn get_track_progress_percentage(song_progress_ms: u128, track_duration_ms:
   min_perc.max(track_perc) as u16
 }
 
+// Splits `text` so the parts matching `query`, ignoring case, get `style`
+pub fn highlight_matches(text: &str, query: &str, style: Style) -> Spans<'static> {
+  let query = query.to_lowercase();
+  // How long the match starting at the beginning of `rest` is
+  let matched_len = |rest: &str| {
+    let mut lowered = String::new();
+    for (i, c) in rest.char_indices() {
+      lowered.extend(c.to_lowercase());
+      if lowered.len() >= query.len() {
+        return Some(i + c.len_utf8()).filter(|_| lowered == query);
+      }
+    }
+    None
+  };
+  let mut spans = Vec::new();
+  let (mut start, mut i) = (0, 0);
+  while i < text.len() {
+    match matched_len(&text[i..]).filter(|_| !query.is_empty()) {
+      Some(len) => {
+        if start < i {
+          spans.push(Span::raw(text[start..i].to_string()));
+        }
+        spans.push(Span::styled(text[i..i + len].to_string(), style));
+        i += len;
+        start = i;
+      }
+      None => i += text[i..].chars().next().map_or(1, |c| c.len_utf8()),
+    }
+  }
+  if start < text.len() {
+    spans.push(Span::raw(text[start..].to_string()));
+  }
+  Spans::from(spans)
+}
+
 // Make better use of space on small terminals
 pub fn get_main_layout_margin(app: &App) -> u16 {
   if app.size.height > SMALL_TERMINAL_HEIGHT {
@@ -99,6 +155,37 @@ pub fn get_main_layout_margin(app: &App) -> u16 {
 mod tests {
   use super::*;
 
+  #[test]
+  fn scrollbar_thumb_test() {
+    assert_eq!(scrollbar_thumb(10, 0, 20, 20), None);
+    assert_eq!(scrollbar_thumb(10, 0, 10, 100), Some((0, 1)));
+    assert_eq!(scrollbar_thumb(10, 99, 10, 100), Some((9, 1)));
+    assert_eq!(scrollbar_thumb(10, 50, 50, 100), Some((2, 5)));
+    // A 3000 track playlist still gets a thumb
+    assert_eq!(scrollbar_thumb(20, 1500, 30, 3000), Some((9, 1)));
+    // A table of one row, 3 rows tall
+    assert_eq!(scrollbar_thumb(1, 0, 0, 1), None);
+    assert_eq!(scrollbar_thumb(1, 0, 0, 5), None);
+  }
+
+  #[test]
+  fn highlight_matches_test() {
+    let style = Style::default();
+    let parts = |text: &str, query: &str| {
+      highlight_matches(text, query, style)
+        .0
+        .into_iter()
+        .map(|span| span.content.to_string())
+        .collect::<Vec<String>>()
+    };
+    assert_eq!(
+      parts("Hello yellow", "ELLO"),
+      vec!["H", "ello", " y", "ello", "w"]
+    );
+    assert_eq!(parts("Café", "é"), vec!["Caf", "é"]);
+    assert_eq!(parts("Song", "x"), vec!["Song"]);
+  }
+
   #[test]
   fn millis_to_minutes_test() {
     assert_eq!(millis_to_minutes(0), "0:00");
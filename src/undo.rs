@@ -0,0 +1,104 @@
+use crate::network::IoEvent;
+use std::collections::VecDeque;
+
+// Older actions are forgotten, nobody goes back this far
+const MAX_ACTIONS: usize = 20;
+
+// A destructive action that can be taken back with `undo`
+#[derive(Debug, Clone, PartialEq)]
+pub enum UndoAction {
+  UnlikeTracks(Vec<String>),
+  UnsaveAlbum(String),
+  UnfollowArtists(Vec<String>),
+  // The owner, id and name of a playlist that was unfollowed or deleted (a
+  // deleted playlist is only unfollowed by its owner, following brings it back)
+  UnfollowPlaylist(String, String, String),
+  UnsaveShow(String),
+}
+
+impl UndoAction {
+  // What undoing it does
+  pub fn reverse(&self) -> IoEvent {
+    match self {
+      UndoAction::UnlikeTracks(track_ids) => IoEvent::CurrentUserSavedTracksAdd(track_ids.clone()),
+      UndoAction::UnsaveAlbum(album_id) => IoEvent::CurrentUserSavedAlbumAdd(album_id.clone()),
+      UndoAction::UnfollowArtists(artist_ids) => IoEvent::UserFollowArtists(artist_ids.clone()),
+      UndoAction::UnfollowPlaylist(owner_id, playlist_id, _) => {
+        IoEvent::UserFollowPlaylist(owner_id.clone(), playlist_id.clone(), None)
+      }
+      UndoAction::UnsaveShow(show_id) => IoEvent::CurrentUserSavedShowAdd(show_id.clone()),
+    }
+  }
+
+  pub fn description(&self) -> String {
+    let plural = |ids: &[String], one: &str, many: &str| match ids.len() {
+      1 => one.to_string(),
+      n => format!("the {} {}", n, many),
+    };
+    match self {
+      UndoAction::UnlikeTracks(track_ids) => format!(
+        "Undone: liked {} again",
+        plural(track_ids, "the track", "tracks")
+      ),
+      UndoAction::UnsaveAlbum(_) => "Undone: saved the album again".to_string(),
+      UndoAction::UnfollowArtists(artist_ids) => format!(
+        "Undone: followed {} again",
+        plural(artist_ids, "the artist", "artists")
+      ),
+      UndoAction::UnfollowPlaylist(_, _, name) => format!("Undone: followed '{}' again", name),
+      UndoAction::UnsaveShow(_) => "Undone: saved the podcast again".to_string(),
+    }
+  }
+}
+
+#[derive(Default)]
+pub struct UndoStack {
+  actions: VecDeque<UndoAction>,
+}
+
+impl UndoStack {
+  pub fn push(&mut self, action: UndoAction) {
+    if self.actions.len() == MAX_ACTIONS {
+      self.actions.pop_front();
+    }
+    self.actions.push_back(action);
+  }
+
+  // The latest action
+  pub fn pop(&mut self) -> Option<UndoAction> {
+    self.actions.pop_back()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn undo_stack_test() {
+    let mut stack = UndoStack::default();
+    assert_eq!(stack.pop(), None);
+    for i in 0..=MAX_ACTIONS {
+      stack.push(UndoAction::UnsaveAlbum(i.to_string()));
+    }
+    stack.push(UndoAction::UnlikeTracks(vec![
+      "a".to_string(),
+      "b".to_string(),
+    ]));
+    let action = stack.pop().unwrap();
+    assert_eq!(action.description(), "Undone: liked the 2 tracks again");
+    assert!(matches!(
+      action.reverse(),
+      IoEvent::CurrentUserSavedTracksAdd(ids) if ids.len() == 2
+    ));
+    assert_eq!(
+      stack.pop(),
+      Some(UndoAction::UnsaveAlbum(MAX_ACTIONS.to_string()))
+    );
+    let mut left = 0;
+    while stack.pop().is_some() {
+      left += 1;
+    }
+    assert_eq!(left, MAX_ACTIONS - 2);
+  }
+}
@@ -1,7 +1,10 @@
 use crate::event::Key;
+use crate::listenbrainz;
+use crate::lyrics::LyricsProvider;
 use anyhow::{anyhow, Result};
 use serde::{Deserialize, Serialize};
 use std::{
+  collections::HashMap,
   fs,
   path::{Path, PathBuf},
 };
@@ -10,10 +13,22 @@ use tui::style::Color;
 const FILE_NAME: &str = "config.yml";
 const CONFIG_DIR: &str = ".config";
 const APP_CONFIG_DIR: &str = "spotify-tui";
+const THEMES_DIR: &str = "themes";
+
+// Themes that can be used by name without a file in the themes directory
+const PRESET_THEMES: [(&str, &str); 3] = [
+  ("dracula", include_str!("themes/dracula.yml")),
+  ("gruvbox", include_str!("themes/gruvbox.yml")),
+  ("nord", include_str!("themes/nord.yml")),
+];
 
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct UserTheme {
+  // A theme file or preset the other colors are applied on top of
+  pub name: Option<String>,
   pub active: Option<String>,
+  pub analysis_bar: Option<String>,
+  pub analysis_bar_text: Option<String>,
   pub banner: Option<String>,
   pub error_border: Option<String>,
   pub error_text: Option<String>,
@@ -106,12 +121,21 @@ fn parse_key(key: String) -> Result<Key> {
         "pageup" => Ok(Key::PageUp),
         "pagedown" => Ok(Key::PageDown),
         "space" => Ok(Key::Char(' ')),
+        "tab" => Ok(Key::Tab),
         _ => Err(anyhow!("The key \"{}\" is unknown.", sections[0])),
       }
     }
   }
 }
 
+// Bindings like `g g` or `space p` are sequences of keys
+fn parse_key_sequence(keys: &str) -> Result<Vec<Key>> {
+  keys
+    .split_whitespace()
+    .map(|k| parse_key(k.to_string()))
+    .collect()
+}
+
 fn check_reserved_keys(key: Key) -> Result<()> {
   let reserved = [
     Key::Char('h'),
@@ -173,6 +197,33 @@ pub struct KeyBindingsString {
   audio_analysis: Option<String>,
   basic_view: Option<String>,
   add_item_to_queue: Option<String>,
+  toggle_discord_presence: Option<String>,
+  lyrics: Option<String>,
+  command_palette: Option<String>,
+  library_finder: Option<String>,
+  add_to_playlist: Option<String>,
+  sort_tracks: Option<String>,
+  filter: Option<String>,
+  audio_features: Option<String>,
+  toggle_save_album: Option<String>,
+  seek_to_percent: Option<String>,
+  switch_profile: Option<String>,
+  toggle_sidebar: Option<String>,
+  undo: Option<String>,
+  save_item: Option<String>,
+  follow: Option<String>,
+  unfollow: Option<String>,
+  play_recommendations: Option<String>,
+  play_artist: Option<String>,
+  play_random_song: Option<String>,
+  sort_episodes: Option<String>,
+  cycle_range: Option<String>,
+  switch_tab: Option<String>,
+  mark_track: Option<String>,
+  visual_select: Option<String>,
+  clear_marks: Option<String>,
+  move_track_down: Option<String>,
+  move_track_up: Option<String>,
 }
 
 #[derive(Clone)]
@@ -203,6 +254,71 @@ pub struct KeyBindings {
   pub audio_analysis: Key,
   pub basic_view: Key,
   pub add_item_to_queue: Key,
+  pub toggle_discord_presence: Key,
+  pub lyrics: Key,
+  pub command_palette: Key,
+  pub library_finder: Key,
+  pub add_to_playlist: Key,
+  pub sort_tracks: Key,
+  pub filter: Key,
+  pub audio_features: Key,
+  pub toggle_save_album: Key,
+  // Followed by a digit, which seeks to that tenth of the track
+  pub seek_to_percent: Key,
+  pub switch_profile: Key,
+  pub toggle_sidebar: Key,
+  pub undo: Key,
+  // The keys of the selected list or table, they can repeat between views
+  pub save_item: Key,
+  pub follow: Key,
+  pub unfollow: Key,
+  pub play_recommendations: Key,
+  pub play_artist: Key,
+  pub play_random_song: Key,
+  pub sort_episodes: Key,
+  pub cycle_range: Key,
+  pub switch_tab: Key,
+  pub mark_track: Key,
+  pub visual_select: Key,
+  pub clear_marks: Key,
+  pub move_track_down: Key,
+  pub move_track_up: Key,
+  // An action bound to a sequence gets `Key::Sequence(index)` as its key
+  pub sequences: Vec<Vec<Key>>,
+}
+
+// Result of looking up the keys pressed so far
+#[derive(Debug, PartialEq)]
+pub enum SequenceMatch {
+  Complete(Key),
+  Prefix,
+  None,
+}
+
+impl KeyBindings {
+  pub fn match_sequence(&self, pressed: &[Key]) -> SequenceMatch {
+    let mut result = SequenceMatch::None;
+    for (i, sequence) in self.sequences.iter().enumerate() {
+      if sequence.as_slice() == pressed {
+        return SequenceMatch::Complete(Key::Sequence(i));
+      } else if sequence.starts_with(pressed) {
+        result = SequenceMatch::Prefix;
+      }
+    }
+    result
+  }
+
+  // How a key is shown in the help menu
+  pub fn describe(&self, key: Key) -> String {
+    match key {
+      Key::Sequence(i) => self.sequences[i]
+        .iter()
+        .map(|k| k.to_string())
+        .collect::<Vec<String>>()
+        .join(" "),
+      key => key.to_string(),
+    }
+  }
 }
 
 #[derive(Default, Clone, Debug, PartialEq, Serialize, Deserialize)]
@@ -220,6 +336,32 @@ pub struct BehaviorConfigString {
   pub playing_icon: Option<String>,
   pub paused_icon: Option<String>,
   pub set_window_title: Option<bool>,
+  pub device_retry_count: Option<u8>,
+  pub device_retry_delay_milliseconds: Option<u64>,
+  pub default_device: Option<String>,
+  pub device_aliases: Option<HashMap<String, String>>,
+  pub remember_device: Option<bool>,
+  pub remember_device_volume: Option<bool>,
+  pub key_sequence_timeout_milliseconds: Option<u64>,
+  pub show_notifications: Option<bool>,
+  pub enable_discord_presence: Option<bool>,
+  pub discord_client_id: Option<String>,
+  pub bar_paused_color: Option<String>,
+  pub lyrics_provider: Option<String>,
+  pub library_cache_ttl_seconds: Option<u64>,
+  pub catalog_cache_ttl_seconds: Option<u64>,
+  pub enable_mouse: Option<bool>,
+  pub playbar_format: Option<String>,
+  pub playbar_text_format: Option<String>,
+  pub use_keyring: Option<bool>,
+  pub proxy: Option<String>,
+  pub listenbrainz_token: Option<String>,
+  pub listenbrainz_url: Option<String>,
+  pub scrobble: Option<bool>,
+  pub lastfm_api_key: Option<String>,
+  pub lastfm_api_secret: Option<String>,
+  pub lastfm_username: Option<String>,
+  pub lastfm_password: Option<String>,
 }
 
 #[derive(Clone)]
@@ -237,6 +379,93 @@ pub struct BehaviorConfig {
   pub playing_icon: String,
   pub paused_icon: String,
   pub set_window_title: bool,
+  pub device_retry_count: u8,
+  pub device_retry_delay_milliseconds: u64,
+  pub default_device: Option<String>,
+  // Short names for devices, usable wherever a device name is
+  pub device_aliases: HashMap<String, String>,
+  pub remember_device: bool,
+  pub remember_device_volume: bool,
+  pub key_sequence_timeout_milliseconds: u64,
+  pub show_notifications: bool,
+  pub enable_discord_presence: bool,
+  pub discord_client_id: Option<String>,
+  pub bar_paused_color: String,
+  pub lyrics_provider: LyricsProvider,
+  pub library_cache_ttl_seconds: u64,
+  pub catalog_cache_ttl_seconds: u64,
+  pub enable_mouse: bool,
+  // Replace the title and the artist line of the playbar if set
+  pub playbar_format: Option<String>,
+  pub playbar_text_format: Option<String>,
+  // Keep the client secret and the login out of the config directory
+  pub use_keyring: bool,
+  // e.g. `socks5://localhost:1080`, see proxy.rs
+  pub proxy: Option<String>,
+  // Submits the played tracks to ListenBrainz if set, see listenbrainz.rs
+  pub listenbrainz_token: Option<String>,
+  pub listenbrainz_url: String,
+  // Scrobbles to Last.fm with the credentials below, see scrobbler.rs
+  pub scrobble: bool,
+  pub lastfm_api_key: Option<String>,
+  pub lastfm_api_secret: Option<String>,
+  pub lastfm_username: Option<String>,
+  pub lastfm_password: Option<String>,
+}
+
+impl BehaviorConfig {
+  // The device an alias stands for, anything else is returned as it is
+  pub fn unalias_device<'a>(&'a self, name: &'a str) -> &'a str {
+    self
+      .device_aliases
+      .iter()
+      .find(|(alias, _)| alias.eq_ignore_ascii_case(name))
+      .map_or(name, |(_, device)| device.as_str())
+  }
+
+  // The alias of a device, by its name or id
+  pub fn device_alias(&self, name: &str, id: &str) -> Option<&str> {
+    self
+      .device_aliases
+      .iter()
+      .find(|(_, device)| device.eq_ignore_ascii_case(name) || *device == id)
+      .map(|(alias, _)| alias.as_str())
+  }
+}
+
+// Shell commands run on playback changes, with the playing item in `SPT_*`
+// environment variables
+#[derive(Default, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Hooks {
+  pub on_track_change: Option<String>,
+  pub on_play: Option<String>,
+  pub on_pause: Option<String>,
+  pub on_volume_change: Option<String>,
+}
+
+impl Hooks {
+  pub fn is_empty(&self) -> bool {
+    *self == Hooks::default()
+  }
+}
+
+// spotifyd started and supervised by the TUI, only if there's a `path`
+#[derive(Default, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SpotifydConfig {
+  pub path: Option<String>,
+  pub args: Option<Vec<String>>,
+  pub restart_on_crash: Option<bool>,
+  // Which device it is, by default the `--device-name` in `args`
+  pub device_name: Option<String>,
+}
+
+// Sizes of the panes, the defaults and limits are in ui/layout.rs
+#[derive(Default, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LayoutConfig {
+  pub sidebar_width_percent: Option<u16>,
+  pub library_height_percent: Option<u16>,
+  pub playbar_height: Option<u16>,
+  pub sidebar_collapsed: Option<bool>,
 }
 
 #[derive(Default, Clone, Debug, PartialEq, Serialize, Deserialize)]
@@ -244,6 +473,9 @@ pub struct UserConfigString {
   keybindings: Option<KeyBindingsString>,
   behavior: Option<BehaviorConfigString>,
   theme: Option<UserTheme>,
+  hooks: Option<Hooks>,
+  spotifyd: Option<SpotifydConfig>,
+  layout: Option<LayoutConfig>,
 }
 
 #[derive(Clone)]
@@ -251,6 +483,9 @@ pub struct UserConfig {
   pub keys: KeyBindings,
   pub theme: Theme,
   pub behavior: BehaviorConfig,
+  pub hooks: Hooks,
+  pub spotifyd: SpotifydConfig,
+  pub layout: LayoutConfig,
   pub path_to_config: Option<UserConfigPaths>,
 }
 
@@ -285,6 +520,34 @@ impl UserConfig {
         audio_analysis: Key::Char('v'),
         basic_view: Key::Char('B'),
         add_item_to_queue: Key::Char('z'),
+        toggle_discord_presence: Key::Char('P'),
+        lyrics: Key::Char('y'),
+        command_palette: Key::Char(':'),
+        library_finder: Key::Char('F'),
+        add_to_playlist: Key::Char('W'),
+        sort_tracks: Key::Char('O'),
+        filter: Key::Char('f'),
+        audio_features: Key::Char('i'),
+        toggle_save_album: Key::Char('b'),
+        seek_to_percent: Key::Char('g'),
+        switch_profile: Key::Char('U'),
+        toggle_sidebar: Key::Char('T'),
+        undo: Key::Char('u'),
+        save_item: Key::Char('s'),
+        follow: Key::Char('w'),
+        unfollow: Key::Char('D'),
+        play_recommendations: Key::Char('r'),
+        play_artist: Key::Char('e'),
+        play_random_song: Key::Char('S'),
+        sort_episodes: Key::Char('S'),
+        cycle_range: Key::Char('t'),
+        switch_tab: Key::Tab,
+        mark_track: Key::Char('x'),
+        visual_select: Key::Char('V'),
+        clear_marks: Key::Char('X'),
+        move_track_down: Key::Char('J'),
+        move_track_up: Key::Char('K'),
+        sequences: Vec::new(),
       },
       behavior: BehaviorConfig {
         seek_milliseconds: 5 * 1000,
@@ -300,7 +563,36 @@ impl UserConfig {
         playing_icon: "▶".to_string(),
         paused_icon: "⏸".to_string(),
         set_window_title: true,
+        device_retry_count: 2,
+        device_retry_delay_milliseconds: 500,
+        default_device: None,
+        device_aliases: HashMap::new(),
+        remember_device: true,
+        remember_device_volume: false,
+        key_sequence_timeout_milliseconds: 1000,
+        show_notifications: false,
+        enable_discord_presence: false,
+        discord_client_id: None,
+        bar_paused_color: "#888888".to_string(),
+        lyrics_provider: LyricsProvider::Lrclib,
+        library_cache_ttl_seconds: 300,
+        catalog_cache_ttl_seconds: 86400,
+        enable_mouse: false,
+        playbar_format: None,
+        playbar_text_format: None,
+        use_keyring: false,
+        proxy: None,
+        listenbrainz_token: None,
+        listenbrainz_url: listenbrainz::DEFAULT_URL.to_string(),
+        scrobble: false,
+        lastfm_api_key: None,
+        lastfm_api_secret: None,
+        lastfm_username: None,
+        lastfm_password: None,
       },
+      hooks: Hooks::default(),
+      spotifyd: SpotifydConfig::default(),
+      layout: LayoutConfig::default(),
       path_to_config: None,
     }
   }
@@ -336,8 +628,16 @@ impl UserConfig {
     macro_rules! to_keys {
       ($name: ident) => {
         if let Some(key_string) = keybindings.$name {
-          self.keys.$name = parse_key(key_string)?;
-          check_reserved_keys(self.keys.$name)?;
+          if key_string.trim().contains(' ') {
+            let sequence = parse_key_sequence(&key_string)?;
+            // Only the first key is taken over, the others are free within the sequence
+            check_reserved_keys(sequence[0])?;
+            self.keys.$name = Key::Sequence(self.keys.sequences.len());
+            self.keys.sequences.push(sequence);
+          } else {
+            self.keys.$name = parse_key(key_string)?;
+            check_reserved_keys(self.keys.$name)?;
+          }
         }
       };
     }
@@ -368,11 +668,76 @@ impl UserConfig {
     to_keys!(audio_analysis);
     to_keys!(basic_view);
     to_keys!(add_item_to_queue);
+    to_keys!(toggle_discord_presence);
+    to_keys!(lyrics);
+    to_keys!(command_palette);
+    to_keys!(library_finder);
+    to_keys!(add_to_playlist);
+    to_keys!(sort_tracks);
+    to_keys!(filter);
+    to_keys!(audio_features);
+    to_keys!(toggle_save_album);
+    to_keys!(seek_to_percent);
+    to_keys!(switch_profile);
+    to_keys!(toggle_sidebar);
+    to_keys!(undo);
+    to_keys!(save_item);
+    to_keys!(follow);
+    to_keys!(unfollow);
+    to_keys!(play_recommendations);
+    to_keys!(play_artist);
+    to_keys!(play_random_song);
+    to_keys!(sort_episodes);
+    to_keys!(cycle_range);
+    to_keys!(switch_tab);
+    to_keys!(mark_track);
+    to_keys!(visual_select);
+    to_keys!(clear_marks);
+    to_keys!(move_track_down);
+    to_keys!(move_track_up);
 
     Ok(())
   }
 
   pub fn load_theme(&mut self, theme: UserTheme) -> Result<()> {
+    if let Some(name) = &theme.name {
+      let named = self.read_named_theme(name)?;
+      self.apply_theme(named)?;
+    }
+    self.apply_theme(theme)
+  }
+
+  // `themes/<name>.yml` next to the config file, or one of the presets
+  fn read_named_theme(&self, name: &str) -> Result<UserTheme> {
+    let dir = self
+      .path_to_config
+      .as_ref()
+      .and_then(|paths| paths.config_file_path.parent());
+    if let Some(dir) = dir {
+      let path = dir.join(THEMES_DIR).join(format!("{}.yml", name));
+      if path.exists() {
+        let theme_string = fs::read_to_string(&path)?;
+        return serde_yaml::from_str(&theme_string)
+          .map_err(|e| anyhow!("Invalid theme file {}: {}", path.display(), e));
+      }
+    }
+    match PRESET_THEMES.iter().find(|(preset, _)| *preset == name) {
+      Some((_, theme_string)) => Ok(serde_yaml::from_str(theme_string)?),
+      None => Err(anyhow!(
+        "Unknown theme '{}', add {}/{}.yml to the config directory or use one of: {}",
+        name,
+        THEMES_DIR,
+        name,
+        PRESET_THEMES
+          .iter()
+          .map(|(preset, _)| *preset)
+          .collect::<Vec<&str>>()
+          .join(", ")
+      )),
+    }
+  }
+
+  fn apply_theme(&mut self, theme: UserTheme) -> Result<()> {
     macro_rules! to_theme_item {
       ($name: ident) => {
         if let Some(theme_item) = theme.$name {
@@ -382,6 +747,8 @@ impl UserConfig {
     }
 
     to_theme_item!(active);
+    to_theme_item!(analysis_bar);
+    to_theme_item!(analysis_bar_text);
     to_theme_item!(banner);
     to_theme_item!(error_border);
     to_theme_item!(error_text);
@@ -461,6 +828,121 @@ impl UserConfig {
       self.behavior.set_window_title = set_window_title;
     }
 
+    if let Some(device_retry_count) = behavior_config.device_retry_count {
+      self.behavior.device_retry_count = device_retry_count;
+    }
+
+    if let Some(delay) = behavior_config.device_retry_delay_milliseconds {
+      self.behavior.device_retry_delay_milliseconds = delay;
+    }
+
+    if let Some(default_device) = behavior_config.default_device {
+      self.behavior.default_device = Some(default_device);
+    }
+
+    if let Some(device_aliases) = behavior_config.device_aliases {
+      self.behavior.device_aliases = device_aliases;
+    }
+
+    if let Some(remember_device) = behavior_config.remember_device {
+      self.behavior.remember_device = remember_device;
+    }
+
+    if let Some(remember_device_volume) = behavior_config.remember_device_volume {
+      self.behavior.remember_device_volume = remember_device_volume;
+    }
+
+    if let Some(timeout) = behavior_config.key_sequence_timeout_milliseconds {
+      self.behavior.key_sequence_timeout_milliseconds = timeout;
+    }
+
+    if let Some(show_notifications) = behavior_config.show_notifications {
+      self.behavior.show_notifications = show_notifications;
+    }
+
+    if let Some(enable_discord_presence) = behavior_config.enable_discord_presence {
+      self.behavior.enable_discord_presence = enable_discord_presence;
+    }
+
+    if let Some(discord_client_id) = behavior_config.discord_client_id {
+      self.behavior.discord_client_id = Some(discord_client_id);
+    }
+
+    if let Some(bar_paused_color) = behavior_config.bar_paused_color {
+      self.behavior.bar_paused_color = bar_paused_color;
+    }
+
+    if let Some(lyrics_provider) = behavior_config.lyrics_provider {
+      self.behavior.lyrics_provider = LyricsProvider::from_name(&lyrics_provider)?;
+    }
+
+    if let Some(library_cache_ttl_seconds) = behavior_config.library_cache_ttl_seconds {
+      self.behavior.library_cache_ttl_seconds = library_cache_ttl_seconds;
+    }
+
+    if let Some(catalog_cache_ttl_seconds) = behavior_config.catalog_cache_ttl_seconds {
+      self.behavior.catalog_cache_ttl_seconds = catalog_cache_ttl_seconds;
+    }
+
+    if let Some(enable_mouse) = behavior_config.enable_mouse {
+      self.behavior.enable_mouse = enable_mouse;
+    }
+
+    if let Some(playbar_format) = behavior_config.playbar_format {
+      self.behavior.playbar_format = Some(playbar_format);
+    }
+
+    if let Some(playbar_text_format) = behavior_config.playbar_text_format {
+      self.behavior.playbar_text_format = Some(playbar_text_format);
+    }
+
+    if let Some(use_keyring) = behavior_config.use_keyring {
+      self.behavior.use_keyring = use_keyring;
+    }
+
+    if let Some(proxy) = behavior_config.proxy {
+      self.behavior.proxy = Some(proxy);
+    }
+
+    if let Some(listenbrainz_token) = behavior_config.listenbrainz_token {
+      self.behavior.listenbrainz_token = Some(listenbrainz_token);
+    }
+
+    if let Some(listenbrainz_url) = behavior_config.listenbrainz_url {
+      self.behavior.listenbrainz_url = listenbrainz_url;
+    }
+
+    if let Some(lastfm_api_key) = behavior_config.lastfm_api_key {
+      self.behavior.lastfm_api_key = Some(lastfm_api_key);
+    }
+
+    if let Some(lastfm_api_secret) = behavior_config.lastfm_api_secret {
+      self.behavior.lastfm_api_secret = Some(lastfm_api_secret);
+    }
+
+    if let Some(lastfm_username) = behavior_config.lastfm_username {
+      self.behavior.lastfm_username = Some(lastfm_username);
+    }
+
+    if let Some(lastfm_password) = behavior_config.lastfm_password {
+      self.behavior.lastfm_password = Some(lastfm_password);
+    }
+
+    if let Some(scrobble) = behavior_config.scrobble {
+      let behavior = &self.behavior;
+      if scrobble
+        && (behavior.lastfm_api_key.is_none()
+          || behavior.lastfm_api_secret.is_none()
+          || behavior.lastfm_username.is_none()
+          || behavior.lastfm_password.is_none())
+      {
+        return Err(anyhow!(
+          "scrobble needs lastfm_api_key, lastfm_api_secret, lastfm_username and lastfm_password"
+        ));
+      }
+      self.behavior.scrobble = scrobble;
+    }
+
     Ok(())
   }
 
@@ -491,6 +973,15 @@ impl UserConfig {
       if let Some(theme) = config_yml.theme {
         self.load_theme(theme)?;
       }
+      if let Some(hooks) = config_yml.hooks {
+        self.hooks = hooks;
+      }
+      if let Some(spotifyd) = config_yml.spotifyd {
+        self.spotifyd = spotifyd;
+      }
+      if let Some(layout) = config_yml.layout {
+        self.layout = layout;
+      }
 
       Ok(())
     } else {
@@ -522,6 +1013,18 @@ fn parse_theme_item(theme_item: &str) -> Result<Color> {
     "LightMagenta" => Color::LightMagenta,
     "LightCyan" => Color::LightCyan,
     "White" => Color::White,
+    hex if hex.starts_with('#') => {
+      let digits = &hex[1..];
+      let channel = |i: usize| {
+        digits
+          .get(i..i + 2)
+          .and_then(|c| u8::from_str_radix(c, 16).ok())
+      };
+      match (channel(0), channel(2), channel(4)) {
+        (Some(r), Some(g), Some(b)) if digits.len() == 6 => Color::Rgb(r, g, b),
+        _ => return Err(anyhow!("Unexpected color {}, expected #rrggbb", hex)),
+      }
+    }
     _ => {
       let colors = theme_item.split(',').collect::<Vec<&str>>();
       if let (Some(r), Some(g), Some(b)) = (colors.get(0), colors.get(1), colors.get(2)) {
@@ -551,6 +1054,7 @@ mod tests {
     assert_eq!(parse_key(String::from("ctrl-j")).unwrap(), Key::Ctrl('j'));
     assert_eq!(parse_key(String::from("ctrl-J")).unwrap(), Key::Ctrl('J'));
     assert_eq!(parse_key(String::from("-")).unwrap(), Key::Char('-'));
+    assert_eq!(parse_key(String::from("tab")).unwrap(), Key::Tab);
     assert_eq!(parse_key(String::from("esc")).unwrap(), Key::Esc);
     assert_eq!(parse_key(String::from("del")).unwrap(), Key::Delete);
   }
@@ -583,6 +1087,89 @@ mod tests {
       parse_theme_item("23, 43, 45").unwrap(),
       Color::Rgb(23, 43, 45)
     );
+    assert_eq!(
+      parse_theme_item("#1DB954").unwrap(),
+      Color::Rgb(29, 185, 84)
+    );
+    assert!(parse_theme_item("#1DB95").is_err());
+    assert!(parse_theme_item("#zzzzzz").is_err());
+  }
+
+  #[test]
+  fn test_key_sequences() {
+    use super::{KeyBindingsString, SequenceMatch, UserConfig};
+    use crate::event::Key;
+
+    let mut config = UserConfig::new();
+    let bindings = KeyBindingsString {
+      jump_to_start: Some(String::from("g g")),
+      toggle_playback: Some(String::from("space p")),
+      next_track: Some(String::from("n")),
+      ..Default::default()
+    };
+    config.load_keybindings(bindings).unwrap();
+    let keys = &config.keys;
+
+    assert_eq!(keys.jump_to_start, Key::Sequence(0));
+    assert_eq!(keys.next_track, Key::Char('n'));
+    assert_eq!(
+      keys.match_sequence(&[Key::Char('g')]),
+      SequenceMatch::Prefix
+    );
+    assert_eq!(
+      keys.match_sequence(&[Key::Char(' '), Key::Char('p')]),
+      SequenceMatch::Complete(Key::Sequence(1))
+    );
+    assert_eq!(
+      keys.match_sequence(&[Key::Char('g'), Key::Char('x')]),
+      SequenceMatch::None
+    );
+    assert_eq!(keys.describe(keys.toggle_playback), "<Space> p");
+
+    // The first key of a sequence can't be a reserved one
+    let bindings = KeyBindingsString {
+      help: Some(String::from("j j")),
+      ..Default::default()
+    };
+    assert!(UserConfig::new().load_keybindings(bindings).is_err());
+  }
+
+  #[test]
+  fn named_theme_test() {
+    use super::{UserConfig, UserTheme, PRESET_THEMES};
+    use tui::style::Color;
+
+    for (name, _) in PRESET_THEMES {
+      let theme: UserTheme = serde_yaml::from_str(&format!("name: {}", name)).unwrap();
+      assert!(UserConfig::new().load_theme(theme).is_ok(), "{}", name);
+    }
+
+    // The other colors are applied on top of the named theme
+    let mut config = UserConfig::new();
+    let theme: UserTheme = serde_yaml::from_str("name: nord\nactive: Red").unwrap();
+    config.load_theme(theme).unwrap();
+    assert_eq!(config.theme.active, Color::Red);
+    assert_eq!(config.theme.text, Color::Rgb(0xd8, 0xde, 0xe9));
+
+    let theme: UserTheme = serde_yaml::from_str("name: missing").unwrap();
+    assert!(UserConfig::new().load_theme(theme).is_err());
+  }
+
+  #[test]
+  fn device_alias_test() {
+    use super::{BehaviorConfigString, UserConfig};
+    let mut config = UserConfig::new();
+    let behavior: BehaviorConfigString =
+      serde_yaml::from_str("device_aliases:\n  livingroom: Sonos Move ABC123").unwrap();
+    config.load_behaviorconfig(behavior).unwrap();
+    let behavior = config.behavior;
+    assert_eq!(behavior.unalias_device("LivingRoom"), "Sonos Move ABC123");
+    assert_eq!(behavior.unalias_device("Kitchen"), "Kitchen");
+    assert_eq!(
+      behavior.device_alias("Sonos Move ABC123", "1"),
+      Some("livingroom")
+    );
+    assert_eq!(behavior.device_alias("Kitchen", "2"), None);
   }
 
   #[test]